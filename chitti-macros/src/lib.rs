@@ -0,0 +1,180 @@
+//! `#[derive(ChittiTool)]`: generates a tool's `FunctionDeclaration` from a
+//! typed arguments struct, so the JSON schema the model sees is the same
+//! shape serde parses.
+//!
+//! ```ignore
+//! /// Read a range of lines from a file.
+//! #[derive(serde::Deserialize, ChittiTool)]
+//! #[tool(name = "read_lines")]
+//! struct ReadLines {
+//!     /// Path of the file to read.
+//!     path: String,
+//!     /// First line to return (1-based).
+//!     start: Option<u64>,
+//! }
+//! ```
+//!
+//! The struct's doc comment becomes the tool description and each field's
+//! doc comment its parameter description. `Option` fields are optional;
+//! everything else is required. The tool name defaults to the struct name in
+//! snake_case without an `Args` suffix.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, Lit, LitStr, PathArguments, Type};
+
+#[proc_macro_derive(ChittiTool, attributes(tool))]
+pub fn derive_chitti_tool(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(ident, "ChittiTool can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(ident, "ChittiTool needs a struct with named fields"));
+    };
+
+    let name = tool_name(&input.attrs)?.unwrap_or_else(|| default_name(&ident.to_string()));
+    let description = doc_comment(&input.attrs);
+
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+    for field in &fields.named {
+        let field_name = field.ident.as_ref().expect("named field").to_string();
+        let field_name = field_name.strip_prefix("r#").unwrap_or(&field_name).to_string();
+        let (ty, optional) = match option_inner(&field.ty) {
+            Some(inner) => (inner, true),
+            None => (&field.ty, false),
+        };
+        let mut schema = schema_for(ty);
+        let doc = doc_comment(&field.attrs);
+        if !doc.is_empty() {
+            schema = quote! {{
+                let mut schema = #schema;
+                schema["description"] = ::chitti::tools::args::serde_json::Value::String(#doc.to_string());
+                schema
+            }};
+        }
+        properties.push(quote! { properties.insert(#field_name.to_string(), #schema); });
+        if !optional {
+            required.push(field_name);
+        }
+    }
+
+    Ok(quote! {
+        impl ::chitti::tools::args::ToolArgs for #ident {
+            fn declaration() -> ::chitti::brains::gemini::types::FunctionDeclaration {
+                let mut properties = ::chitti::tools::args::serde_json::Map::new();
+                #(#properties)*
+                ::chitti::brains::gemini::types::FunctionDeclaration {
+                    name: #name.to_string(),
+                    description: #description.to_string(),
+                    parameters: Some(::chitti::tools::args::serde_json::json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": [#(#required),*],
+                    })),
+                }
+            }
+        }
+    })
+}
+
+/// The JSON schema for a Rust type, as an expression producing a `serde_json::Value`.
+fn schema_for(ty: &Type) -> TokenStream2 {
+    let Type::Path(path) = ty else {
+        return quote! { ::chitti::tools::args::serde_json::json!({}) };
+    };
+    let Some(last) = path.path.segments.last() else {
+        return quote! { ::chitti::tools::args::serde_json::json!({}) };
+    };
+    match last.ident.to_string().as_str() {
+        "String" | "str" | "PathBuf" | "char" => quote! { ::chitti::tools::args::serde_json::json!({ "type": "string" }) },
+        "bool" => quote! { ::chitti::tools::args::serde_json::json!({ "type": "boolean" }) },
+        "u8" | "u16" | "u32" | "u64" | "usize" | "i8" | "i16" | "i32" | "i64" | "isize" => {
+            quote! { ::chitti::tools::args::serde_json::json!({ "type": "integer" }) }
+        }
+        "f32" | "f64" => quote! { ::chitti::tools::args::serde_json::json!({ "type": "number" }) },
+        "Vec" => {
+            let items = first_generic(&last.arguments).map(schema_for).unwrap_or_else(|| quote! { ::chitti::tools::args::serde_json::json!({}) });
+            quote! { ::chitti::tools::args::serde_json::json!({ "type": "array", "items": #items }) }
+        }
+        "HashMap" | "BTreeMap" | "Map" => quote! { ::chitti::tools::args::serde_json::json!({ "type": "object" }) },
+        // serde_json::Value and anything else: accept any JSON.
+        _ => quote! { ::chitti::tools::args::serde_json::json!({}) },
+    }
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    first_generic(&last.arguments)
+}
+
+fn first_generic(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(s) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    lines.join(" ").trim().to_string()
+}
+
+fn tool_name(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tool")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported tool attribute; expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+fn default_name(ident: &str) -> String {
+    let ident = ident.strip_suffix("Args").unwrap_or(ident);
+    let mut out = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use futures_util::{stream::{self, BoxStream}, StreamExt};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+use crate::vault::Vault;
+
+/// Default lifetime of a cached response.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    created: u64,
+    /// SHA-256 of the rendered request, checked on lookup so a colliding or
+    /// misplaced file is never replayed for a different request.
+    request: String,
+    events: Vec<BrainEvent>,
+}
+
+/// Replays responses to repeated one-shot requests from disk.
+///
+/// The key is a SHA-256 of the fully rendered provider request, which includes the
+/// model, so any change to prompt, tools or settings is a miss. Only turns that
+/// start a fresh conversation and finish without errors are cached; follow-ups
+/// depend on server-side state a replay can't reproduce.
+pub struct CachedBrain {
+    inner: Box<dyn BrainEngine>,
+    dir: PathBuf,
+    ttl: Duration,
+    vault: Option<Arc<Vault>>,
+}
+
+impl CachedBrain {
+    pub fn new(inner: Box<dyn BrainEngine>, dir: PathBuf, ttl: Duration) -> Self {
+        Self { inner, dir, ttl, vault: None }
+    }
+
+    /// Encrypts new entries and decrypts existing encrypted ones.
+    pub fn with_vault(mut self, vault: Option<Arc<Vault>>) -> Self {
+        self.vault = vault;
+        self
+    }
+
+    /// `$XDG_CACHE_HOME/chitti/responses`, falling back to `~/.cache`.
+    pub fn default_dir() -> PathBuf {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+        base.join("chitti").join("responses")
+    }
+
+    fn path_for(&self, hash: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", hash))
+    }
+
+    fn lookup(&self, path: &Path, hash: &str) -> Option<Vec<BrainEvent>> {
+        let mut data = std::fs::read(path).ok()?;
+        if Vault::is_encrypted(&data) {
+            data = self.vault.as_ref()?.decrypt(&data).ok()?;
+        }
+        let cached: CachedResponse = serde_json::from_slice(&data).ok()?;
+        if cached.request != hash {
+            return None;
+        }
+        let age = now_secs().saturating_sub(cached.created);
+        (age < self.ttl.as_secs()).then_some(cached.events)
+    }
+}
+
+/// Hex SHA-256 of the request as sent.
+fn request_hash(request: &Value) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, request.to_string().as_bytes());
+    digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn store(path: &Path, request: String, events: Vec<BrainEvent>, vault: Option<&Vault>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let cached = CachedResponse { created: now_secs(), request, events };
+    let mut data = serde_json::to_vec(&cached)?;
+    if let Some(vault) = vault {
+        data = vault.encrypt(&data)?;
+    }
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+#[async_trait]
+impl BrainEngine for CachedBrain {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let one_shot = context.previous_interaction_id.is_none() && context.tool_results.is_empty();
+        let request = if one_shot { self.inner.render_request(&context)? } else { None };
+        let Some(request) = request else {
+            return self.inner.process_turn(context).await;
+        };
+
+        let hash = request_hash(&request);
+        let path = self.path_for(&hash);
+        if let Some(events) = self.lookup(&path, &hash) {
+            debug!(path = %path.display(), "Response cache hit");
+            return Ok(Box::pin(stream::iter(events.into_iter().map(Ok))));
+        }
+
+        let mut inner = self.inner.process_request(request).await?;
+        let vault = self.vault.clone();
+        let s = async_stream::try_stream! {
+            let mut recorded = Vec::new();
+            let mut failed = false;
+            while let Some(evt) = inner.next().await {
+                let evt = evt?;
+                failed |= matches!(evt, BrainEvent::Error(_));
+                // A replay doesn't answer that HTTP request.
+                if !matches!(evt, BrainEvent::RequestId(_)) {
+                    recorded.push(evt.clone());
+                }
+                yield evt;
+            }
+            if !failed {
+                if let Err(e) = store(&path, hash, recorded, vault.as_deref()) {
+                    warn!("Could not write response cache {}: {}", path.display(), e);
+                }
+            }
+        };
+        Ok(Box::pin(s))
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        self.inner.render_request(context)
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.inner.process_request(request).await
+    }
+
+    async fn count_tokens(&self, text: &str) -> Result<Option<u64>> {
+        self.inner.count_tokens(text).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        self.inner.delete_interactions(ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingBrain {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for CountingBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            self.process_request(serde_json::json!({ "input": context.prompt })).await
+        }
+
+        fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+            Ok(Some(serde_json::json!({ "model": "m", "input": context.prompt })))
+        }
+
+        async fn process_request(&self, _request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::pin(stream::iter(vec![
+                Ok(BrainEvent::TextDelta(format!("answer {}", n))),
+                Ok(BrainEvent::Complete { interaction_id: None }),
+            ])))
+        }
+    }
+
+    fn context(prompt: &str) -> TurnContext {
+        TurnContext {
+            prompt: prompt.to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        }
+    }
+
+    async fn text(brain: &CachedBrain, ctx: TurnContext) -> Result<String> {
+        let mut stream = brain.process_turn(ctx).await?;
+        let mut out = String::new();
+        while let Some(evt) = stream.next().await {
+            if let BrainEvent::TextDelta(t) = evt? {
+                out.push_str(&t);
+            }
+        }
+        Ok(out)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_one_shot_requests_hit_the_cache() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("chitti-response-cache-{}", uuid::Uuid::new_v4()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let brain = CachedBrain::new(Box::new(CountingBrain { calls: calls.clone() }), dir.clone(), DEFAULT_TTL);
+
+        assert_eq!(text(&brain, context("hi")).await?, "answer 0");
+        assert_eq!(text(&brain, context("hi")).await?, "answer 0");
+        assert_eq!(text(&brain, context("other")).await?, "answer 1");
+
+        let mut follow_up = context("hi");
+        follow_up.previous_interaction_id = Some("abc".to_string());
+        assert_eq!(text(&brain, follow_up).await?, "answer 2");
+
+        let expired = CachedBrain::new(Box::new(CountingBrain { calls: calls.clone() }), dir.clone(), Duration::ZERO);
+        assert_eq!(text(&expired, context("hi")).await?, "answer 3");
+
+        let vault = Some(Arc::new(Vault::from_key(&[1u8; 32])));
+        let sealed = CachedBrain::new(Box::new(CountingBrain { calls: calls.clone() }), dir.clone(), DEFAULT_TTL)
+            .with_vault(vault);
+        assert_eq!(text(&sealed, context("private")).await?, "answer 4");
+        assert_eq!(text(&sealed, context("private")).await?, "answer 4");
+        let path = sealed.path_for(&request_hash(&serde_json::json!({ "model": "m", "input": "private" })));
+        assert!(Vault::is_encrypted(&std::fs::read(&path)?));
+        // Without the key an encrypted entry is a miss, not an error.
+        assert_eq!(text(&brain, context("private")).await?, "answer 5");
+
+        // An entry stored under another request's name is never replayed.
+        let hi = brain.path_for(&request_hash(&serde_json::json!({ "model": "m", "input": "hi" })));
+        let misplaced = brain.path_for(&request_hash(&serde_json::json!({ "model": "m", "input": "misplaced" })));
+        std::fs::copy(&hi, &misplaced)?;
+        assert_eq!(text(&brain, context("misplaced")).await?, "answer 6");
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock, Mutex};
+use tracing::warn;
+use crate::brains::{BrainEngine, RateLimited};
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+static UNAVAILABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\(code: (429|5\d\d)\b|RESOURCE_EXHAUSTED|\bUNAVAILABLE\b").unwrap()
+});
+
+/// Whether `err` means the engine is out of quota or failing server-side,
+/// as opposed to rejecting this particular request.
+pub fn is_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<RateLimited>().is_some() || UNAVAILABLE.is_match(&format!("{:#}", err))
+}
+
+/// Tries `primary`, and answers the turn with `secondary` instead when the
+/// primary is out of quota or returns a 5xx (e.g. Gemini, then a local
+/// Ollama). The switch is announced with a `BrainEvent::Error` note.
+pub struct FallbackBrain {
+    primary: Box<dyn BrainEngine>,
+    secondary: Box<dyn BrainEngine>,
+    secondary_name: String,
+    /// Interaction ids the secondary issued. The primary can't resolve them,
+    /// so turns continuing one stay on the secondary.
+    secondary_ids: Arc<Mutex<HashSet<String>>>,
+}
+
+impl FallbackBrain {
+    pub fn new(primary: Box<dyn BrainEngine>, secondary: Box<dyn BrainEngine>, secondary_name: impl Into<String>) -> Self {
+        Self {
+            primary,
+            secondary,
+            secondary_name: secondary_name.into(),
+            secondary_ids: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    async fn on_secondary(&self, context: TurnContext, note: Option<String>) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let ids = self.secondary_ids.clone();
+        let events = self.secondary.process_turn(context).await?.inspect(move |event| {
+            if let Ok(BrainEvent::Complete { interaction_id: Some(id) }) = event {
+                ids.lock().unwrap().insert(id.clone());
+            }
+        });
+        match note {
+            Some(note) => Ok(Box::pin(stream::once(async move { Ok(BrainEvent::Error(note)) }).chain(events))),
+            None => Ok(Box::pin(events)),
+        }
+    }
+}
+
+/// The secondary can't see the primary's server-side conversation, and tool
+/// results only make sense next to the calls that asked for them, so those
+/// are inlined into the prompt.
+fn detach(context: &mut TurnContext) {
+    if context.previous_interaction_id.take().is_none() && context.tool_results.is_empty() {
+        return;
+    }
+    let mut prompt = String::from(
+        "[Earlier messages in this conversation are unavailable to you. Ask the user if you need something from them.]",
+    );
+    for res in std::mem::take(&mut context.tool_results) {
+        prompt.push_str(&format!("\n\nResult of your {} call:\n{}", res.name, res.result));
+    }
+    if !context.prompt.is_empty() {
+        prompt.push_str(&format!("\n\n{}", context.prompt));
+    }
+    context.prompt = prompt;
+}
+
+#[async_trait]
+impl BrainEngine for FallbackBrain {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let continues_secondary = context.previous_interaction_id.as_ref()
+            .is_some_and(|id| self.secondary_ids.lock().unwrap().contains(id));
+        if continues_secondary {
+            return self.on_secondary(context, None).await;
+        }
+        match self.primary.process_turn(context.clone()).await {
+            Err(e) if is_unavailable(&e) => {
+                warn!("Primary brain unavailable, falling back to {}: {:#}", self.secondary_name, e);
+                let mut context = context;
+                detach(&mut context);
+                let note = format!("Primary model unavailable ({}); answering with {} instead", e, self.secondary_name);
+                self.on_secondary(context, Some(note)).await
+            }
+            res => res,
+        }
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        self.primary.render_request(context)
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.primary.process_request(request).await
+    }
+
+    async fn count_tokens(&self, text: &str) -> Result<Option<u64>> {
+        self.primary.count_tokens(text).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        let (secondary, primary): (Vec<String>, Vec<String>) = {
+            let known = self.secondary_ids.lock().unwrap();
+            ids.iter().cloned().partition(|id| known.contains(id))
+        };
+        self.primary.delete_interactions(&primary).await?;
+        self.secondary.delete_interactions(&secondary).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+    use serde_json::json;
+
+    struct Engine {
+        error: Option<&'static str>,
+        id: &'static str,
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for Engine {
+        async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            self.calls.lock().unwrap().push(context);
+            if let Some(error) = self.error {
+                anyhow::bail!("{}", error);
+            }
+            Ok(Box::pin(stream::iter(vec![Ok(BrainEvent::Complete { interaction_id: Some(self.id.to_string()) })])))
+        }
+    }
+
+    fn context(previous: Option<&str>) -> TurnContext {
+        TurnContext {
+            prompt: "go on".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: previous.map(str::to_string),
+            tool_results: vec![ToolResult { call_id: "c1".into(), name: "execute_bash".into(), result: json!({ "stdout": "ok" }), is_error: false }],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fallback_on_unavailable_primary_and_stays_there() -> Result<()> {
+        let (primary_calls, secondary_calls) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+        let brain = FallbackBrain::new(
+            Box::new(Engine { error: Some("API Error: The model is overloaded. (code: 503 Service Unavailable)"), id: "p", calls: primary_calls.clone() }),
+            Box::new(Engine { error: None, id: "chat-1", calls: secondary_calls.clone() }),
+            "ollama",
+        );
+        let events: Vec<BrainEvent> = brain.process_turn(context(Some("v1_abc"))).await?.map(|e| e.unwrap()).collect().await;
+        assert!(matches!(&events[0], BrainEvent::Error(note) if note.contains("answering with ollama instead")));
+        assert!(matches!(&events[1], BrainEvent::Complete { interaction_id: Some(id) } if id == "chat-1"));
+        {
+            let detached = &secondary_calls.lock().unwrap()[0];
+            assert_eq!(detached.previous_interaction_id, None);
+            assert!(detached.tool_results.is_empty());
+            assert!(detached.prompt.contains("Result of your execute_bash call:\n{\"stdout\":\"ok\"}\n\ngo on"));
+        }
+
+        // Continuing the secondary's conversation doesn't go back to the primary.
+        let events: Vec<BrainEvent> = brain.process_turn(context(Some("chat-1"))).await?.map(|e| e.unwrap()).collect().await;
+        assert_eq!(events.len(), 1, "no fallback note when staying on the secondary");
+        assert!(matches!(&events[0], BrainEvent::Complete { interaction_id: Some(id) } if id == "chat-1"));
+        assert_eq!(primary_calls.lock().unwrap().len(), 1);
+        assert_eq!(secondary_calls.lock().unwrap()[1].previous_interaction_id.as_deref(), Some("chat-1"));
+
+        let rejecting = FallbackBrain::new(
+            Box::new(Engine { error: Some("API Error: API key not valid (code: 400 Bad Request)"), id: "p", calls: primary_calls.clone() }),
+            Box::new(Engine { error: None, id: "chat-2", calls: secondary_calls.clone() }),
+            "ollama",
+        );
+        assert!(rejecting.process_turn(context(None)).await.is_err());
+        assert_eq!(secondary_calls.lock().unwrap().len(), 2);
+        Ok(())
+    }
+}
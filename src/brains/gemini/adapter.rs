@@ -1,12 +1,15 @@
 use async_trait::async_trait;
-use futures_util::{stream::BoxStream, StreamExt};
-use anyhow::Result;
+use futures_util::{stream::{self, BoxStream}, StreamExt};
+use anyhow::{Context, Result};
 use std::sync::Arc;
 use crate::tools::ToolRegistry;
-use crate::brains::BrainEngine;
+use crate::brains::{BrainEngine, RateLimited};
 use crate::brains::gemini::Client;
-use crate::brains::gemini::types::{InteractionInput, InteractionPart, FunctionResponse};
-use crate::conductor::events::{BrainEvent, TurnContext};
+use crate::brains::gemini::error::GeminiError;
+use crate::brains::gemini::types::{InteractionEvent, InteractionInput, InteractionOutput, InteractionPart, InteractionContent, InteractionRequest, FunctionResponse, GenerationConfig, Tool};
+use serde_json::Value;
+use crate::brains::structured;
+use crate::conductor::events::{BrainEvent, TurnContext, Usage};
 
 pub struct GeminiEngine {
     client: Client,
@@ -19,10 +22,11 @@ impl GeminiEngine {
     }
 }
 
-#[async_trait]
-impl BrainEngine for GeminiEngine {
-    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
-        let input = if context.tool_results.is_empty() {
+impl GeminiEngine {
+    /// Renders the Interactions API request for a turn, with `attached` (the
+    /// turn's attachments, already turned into parts) ahead of the prompt.
+    fn build_request(&self, context: TurnContext, attached: Vec<InteractionPart>) -> InteractionRequest {
+        let input = if context.tool_results.is_empty() && attached.is_empty() {
             InteractionInput::Text(context.prompt)
         } else {
             let mut parts = Vec::new();
@@ -33,6 +37,7 @@ impl BrainEngine for GeminiEngine {
                     response: res.result,
                 }));
             }
+            parts.extend(attached);
             // If there's a steering prompt, add it as a text part
             if !context.prompt.is_empty() {
                 parts.push(InteractionPart::Text { text: context.prompt });
@@ -44,49 +49,214 @@ impl BrainEngine for GeminiEngine {
         if let Some(id) = context.previous_interaction_id {
             builder = builder.previous_interaction_id(id);
         }
+        if let Some(instruction) = context.system_instruction {
+            builder = builder.system_instruction(InteractionContent {
+                role: None,
+                parts: vec![InteractionPart::Text { text: instruction }],
+            });
+        }
+
+        if let Some(schema) = context.response_schema {
+            builder = builder.generation_config(GenerationConfig {
+                response_mime_type: Some("application/json".to_string()),
+                response_schema: Some(schema),
+                ..Default::default()
+            });
+        }
+
+        if let Some(level) = context.thinking_level {
+            builder = builder.thinking_level(level);
+        }
+        if let Some(temperature) = context.temperature {
+            builder = builder.temperature(temperature);
+        }
+        if let Some(model) = context.model {
+            builder = builder.model(model);
+        }
 
         // Add tool definitions
-        let tool_defs = self.tools.get_definitions();
+        let mut tool_defs = self.tools.get_definitions(context.allowed_tools.as_deref());
+        if context.search_grounding {
+            tool_defs.push(Tool::GoogleSearch);
+        }
         if !tool_defs.is_empty() {
             builder = builder.tools(tool_defs);
         }
 
-        let stream = builder.stream().await?;
-
-        let brain_stream = stream.map(|res| {
-            match res {
-                Ok(evt) => {
-                    match evt {
-                        crate::brains::gemini::types::InteractionEvent::ContentDelta { delta, .. } => {
-                            match delta {
-                                crate::brains::gemini::types::InteractionOutput::Text { text } => Ok(BrainEvent::TextDelta(text)),
-                                crate::brains::gemini::types::InteractionOutput::ContentDelta { text, thought } => {
-                                    if thought.unwrap_or(false) {
-                                        Ok(BrainEvent::ThoughtDelta(text))
-                                    } else {
-                                        Ok(BrainEvent::TextDelta(text))
-                                    }
-                                }
-                                crate::brains::gemini::types::InteractionOutput::FunctionCall(fc) => {
-                                    Ok(BrainEvent::ToolCall { 
-                                        name: fc.name, 
-                                        id: fc.id.unwrap_or_default(), 
-                                        args: serde_json::to_value(fc.args).unwrap_or_default() 
-                                    })
-                                }
-                                _ => Ok(BrainEvent::Complete { interaction_id: None }),
-                            }
-                        }
-                        crate::brains::gemini::types::InteractionEvent::InteractionComplete { interaction } => {
-                            Ok(BrainEvent::Complete { interaction_id: interaction.id })
-                        }
-                        _ => Ok(BrainEvent::Complete { interaction_id: None }),
+        builder.build()
+    }
+
+    /// Streams a rendered request; structured output is assembled when it sets a response schema.
+    async fn send_request(&self, mut request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let response_schema = request.pointer("/generation_config/response_schema").cloned();
+        request["stream"] = Value::Bool(true);
+        let (request_id, stream) = match self.client.stream_interaction(&request).await {
+            Err(GeminiError::RateLimited { message, retry_after }) => {
+                return Err(RateLimited { message, retry_after }.into());
+            }
+            res => res?,
+        };
+
+        let brain_stream = stream::once(async move { Ok(BrainEvent::RequestId(request_id)) })
+            .chain(stream.flat_map(|res| stream::iter(to_brain_events(res))));
+
+        match response_schema {
+            Some(schema) => Ok(structured::assemble(Box::pin(brain_stream), schema)),
+            None => Ok(Box::pin(brain_stream)),
+        }
+    }
+}
+
+#[async_trait]
+impl BrainEngine for GeminiEngine {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let mut attached = Vec::new();
+        for path in &context.attachments {
+            attached.push(self.client.attach(path).await
+                .with_context(|| format!("Failed to attach {}", path.display()))?);
+        }
+        let request = serde_json::to_value(self.build_request(context, attached))?;
+        self.send_request(request).await
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        // Attachments may need uploading first, which rendering can't do.
+        if !context.attachments.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::to_value(self.build_request(context.clone(), Vec::new()))?))
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.send_request(request).await
+    }
+
+    async fn count_tokens(&self, text: &str) -> Result<Option<u64>> {
+        Ok(Some(self.client.count_tokens(text).await?))
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        for id in ids {
+            self.client.delete_interaction(id).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps one SSE event to brain events; completion also reports token usage when present.
+fn to_brain_events(res: Result<InteractionEvent, GeminiError>) -> Vec<Result<BrainEvent>> {
+    let evt = match res {
+        Ok(evt) => evt,
+        Err(e) => return vec![Err(anyhow::anyhow!("Gemini stream error: {:?}", e))],
+    };
+    let event = match evt {
+        InteractionEvent::ContentDelta { delta, .. } => {
+            match delta {
+                InteractionOutput::Text { text } => Ok(BrainEvent::TextDelta(text)),
+                InteractionOutput::ContentDelta { text, thought } => {
+                    if thought.unwrap_or(false) {
+                        Ok(BrainEvent::ThoughtDelta(text))
+                    } else {
+                        Ok(BrainEvent::TextDelta(text))
                     }
                 }
-                Err(e) => Err(anyhow::anyhow!("Gemini stream error: {:?}", e)),
+                InteractionOutput::FunctionCall(fc) => {
+                    Ok(BrainEvent::ToolCall { 
+                        name: fc.name, 
+                        id: fc.id.unwrap_or_default(), 
+                        args: serde_json::to_value(fc.args).unwrap_or_default() 
+                    })
+                }
+                InteractionOutput::Image(media) => {
+                    Ok(BrainEvent::Image { mime_type: media.mime_type, data: media.data, uri: media.uri })
+                }
+                InteractionOutput::Document(media) | InteractionOutput::Audio(media) | InteractionOutput::Video(media) => {
+                    Ok(BrainEvent::File { mime_type: media.mime_type, data: media.data, uri: media.uri })
+                }
+                _ => Ok(BrainEvent::Complete { interaction_id: None }),
             }
-        });
+        }
+        InteractionEvent::InteractionComplete { interaction } => {
+            let complete = Ok(BrainEvent::Complete { interaction_id: interaction.id });
+            return match interaction.extra.get("usage").map(parse_usage) {
+                Some(usage) => vec![Ok(BrainEvent::Usage(usage)), complete],
+                None => vec![complete],
+            };
+        }
+        _ => Ok(BrainEvent::Complete { interaction_id: None }),
+    };
+    vec![event]
+}
 
-        Ok(Box::pin(brain_stream))
+/// Reads token counts from an interaction's `usage` object.
+fn parse_usage(usage: &Value) -> Usage {
+    let count = |keys: &[&str]| keys.iter().find_map(|k| usage.get(*k).and_then(Value::as_u64)).unwrap_or(0);
+    Usage {
+        input_tokens: count(&["total_input_tokens", "input_tokens"]),
+        output_tokens: count(&["total_output_tokens", "output_tokens"]),
+        thought_tokens: count(&["total_thought_tokens", "total_reasoning_tokens", "thought_tokens"]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_request_sends_steering_with_tool_results() -> Result<()> {
+        let engine = GeminiEngine::new(Client::new("key".into(), "gemini-test".into()), Arc::new(ToolRegistry::new()));
+        let request = engine.build_request(TurnContext {
+            prompt: "[User interjection] only the root volume".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: Some("id_1".to_string()),
+            tool_results: vec![ToolResult { call_id: "call_a".into(), name: "execute_bash".into(), result: json!({ "stdout": "12G" }), is_error: false }],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        }, Vec::new());
+        let request = serde_json::to_value(request)?;
+        assert_eq!(request["previous_interaction_id"], "id_1");
+        assert_eq!(request["input"][0]["type"], "function_result");
+        assert_eq!(request["input"][0]["call_id"], "call_a");
+        assert_eq!(request["input"][1], json!({ "type": "text", "text": "[User interjection] only the root volume" }));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_attachments_go_ahead_of_the_prompt() -> Result<()> {
+        let client = Client::new("key".into(), "gemini-test".into());
+        // Named .dat, but the bytes say PNG.
+        let path = std::env::temp_dir().join(format!("chitti-attach-{}.dat", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        let attached = vec![client.attach(&path).await?];
+        std::fs::remove_file(&path)?;
+
+        let engine = GeminiEngine::new(client, Arc::new(ToolRegistry::new()));
+        let mut context = TurnContext {
+            prompt: "what is this?".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: vec![path],
+        };
+        assert!(engine.render_request(&context)?.is_none());
+        context.attachments.clear();
+        let request = serde_json::to_value(engine.build_request(context, attached))?;
+        assert_eq!(request["input"][0]["type"], "image");
+        assert_eq!(request["input"][0]["mime_type"], "image/png");
+        assert_eq!(request["input"][1], json!({ "type": "text", "text": "what is this?" }));
+        Ok(())
     }
 }
@@ -0,0 +1,226 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, instrument};
+use crate::brains::gemini::client::Client;
+use crate::brains::gemini::types::*;
+use crate::brains::gemini::error::{GeminiError, Result};
+
+/// Requests larger than this must reference media through the File API
+/// instead of carrying it inline.
+pub const INLINE_LIMIT: u64 = 20 * 1024 * 1024;
+
+/// How a file reaches the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Inline,
+    Upload,
+}
+
+impl Delivery {
+    /// Inline for small files; uploads above the request limit and for video,
+    /// which the model only reads through the File API at useful lengths.
+    pub fn for_file(size: u64, mime_type: &str) -> Self {
+        if size >= INLINE_LIMIT || mime_type.starts_with("video/") {
+            Delivery::Upload
+        } else {
+            Delivery::Inline
+        }
+    }
+}
+
+/// Recognizes common media formats by their leading bytes.
+pub fn sniff(head: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+    let mime_type = if at(0, b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if at(0, b"\xff\xd8\xff") {
+        "image/jpeg"
+    } else if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        "image/gif"
+    } else if at(0, b"RIFF") && at(8, b"WEBP") {
+        "image/webp"
+    } else if at(0, b"RIFF") && at(8, b"WAVE") {
+        "audio/wav"
+    } else if at(0, b"%PDF-") {
+        "application/pdf"
+    } else if at(4, b"ftyp") {
+        match head.get(8..12)? {
+            b"heic" | b"heix" | b"heim" | b"heis" | b"mif1" | b"msf1" => "image/heic",
+            b"qt  " => "video/quicktime",
+            b"M4A " => "audio/mp4",
+            _ => "video/mp4",
+        }
+    } else if at(0, b"\x1a\x45\xdf\xa3") {
+        "video/webm"
+    } else if at(0, b"ID3") || at(0, b"\xff\xfb") || at(0, b"\xff\xf3") || at(0, b"\xff\xf2") {
+        "audio/mpeg"
+    } else if at(0, b"OggS") {
+        "audio/ogg"
+    } else if at(0, b"fLaC") {
+        "audio/flac"
+    } else {
+        return None;
+    };
+    Some(mime_type)
+}
+
+/// Works out a file's MIME type from its leading bytes, then its extension.
+/// Anything else is plain text when it reads as UTF-8, so source files in
+/// obscure languages still go as documents, and `application/octet-stream`
+/// otherwise.
+pub fn mime_type_for(path: &Path) -> String {
+    let mut head = Vec::new();
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(512).read_to_end(&mut head);
+    }
+    if let Some(mime_type) = sniff(&head) {
+        return mime_type.to_string();
+    }
+    if let Some("heic" | "heif") = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        return "image/heic".to_string();
+    }
+    if let Some(mime_type) = mime_guess::from_path(path).first_raw() {
+        return mime_type.to_string();
+    }
+    // A character cut off at the end of the sample doesn't make it binary.
+    let text = match std::str::from_utf8(&head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    if text { "text/plain" } else { "application/octet-stream" }.to_string()
+}
+
+/// Wraps a media part in the variant matching its MIME type.
+pub fn part_for(mime_type: &str, media: MediaPart) -> InteractionPart {
+    match mime_type.split('/').next().unwrap_or_default() {
+        "image" => InteractionPart::Image(media),
+        "audio" => InteractionPart::Audio(media),
+        "video" => InteractionPart::Video(media),
+        _ => InteractionPart::Document(media),
+    }
+}
+
+impl Client {
+    /// Turns a local file into a part ready for an Interactions request:
+    /// inline base64 when it fits in the request, a File API upload otherwise.
+    /// HEIC images (the iPhone and macOS screenshot default) are converted to
+    /// JPEG first, since the API does not accept them everywhere.
+    #[instrument(skip(self, path))]
+    pub async fn attach<P: AsRef<Path>>(&self, path: P) -> Result<InteractionPart> {
+        let path = path.as_ref();
+        let mut mime_type = mime_type_for(path);
+        let converted = if mime_type == "image/heic" {
+            mime_type = "image/jpeg".to_string();
+            Some(heic_to_jpeg(path).await?)
+        } else {
+            None
+        };
+        let source = converted.as_deref().unwrap_or(path);
+        let size = tokio::fs::metadata(source).await?.len();
+
+        let media = match Delivery::for_file(size, &mime_type) {
+            Delivery::Inline => {
+                let bytes = tokio::fs::read(source).await?;
+                MediaPart { data: Some(STANDARD.encode(bytes)), mime_type: mime_type.clone(), ..Default::default() }
+            }
+            Delivery::Upload => {
+                let display_name = path.file_name().and_then(|n| n.to_str()).map(str::to_string);
+                let file = self.upload_file_as(source, display_name, mime_type.clone()).await?;
+                let file = self.wait_until_active(file).await?;
+                MediaPart { uri: Some(file.uri), mime_type: mime_type.clone(), ..Default::default() }
+            }
+        };
+        if let Some(tmp) = converted {
+            let _ = tokio::fs::remove_file(tmp).await;
+        }
+        Ok(part_for(&mime_type, media))
+    }
+
+    /// Polls an uploaded file until the service has finished processing it;
+    /// video in particular can't be referenced while still `PROCESSING`.
+    async fn wait_until_active(&self, mut file: File) -> Result<File> {
+        for _ in 0..60 {
+            match file.state {
+                FileState::Processing => {
+                    debug!("Waiting for {} to finish processing", file.name);
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    file = self.get_file(&file.name).await?;
+                }
+                FileState::Failed => {
+                    return Err(GeminiError::Other(format!("Processing {} failed: {:?}", file.name, file.error)));
+                }
+                _ => return Ok(file),
+            }
+        }
+        Err(GeminiError::Other(format!("Timed out waiting for {} to be processed", file.name)))
+    }
+}
+
+/// Converts a HEIC image to a temporary JPEG with whichever converter the
+/// platform has: `sips` on macOS, libheif's `heif-convert` or ImageMagick elsewhere.
+async fn heic_to_jpeg(path: &Path) -> Result<PathBuf> {
+    let out = std::env::temp_dir().join(format!("chitti-{}.jpg", uuid::Uuid::new_v4()));
+    let (input, output) = (path.to_string_lossy().to_string(), out.to_string_lossy().to_string());
+    let candidates: [(&str, Vec<&str>); 3] = [
+        ("sips", vec!["-s", "format", "jpeg", &input, "--out", &output]),
+        ("heif-convert", vec![&input, &output]),
+        ("magick", vec![&input, &output]),
+    ];
+    for (program, args) in candidates {
+        let status = tokio::process::Command::new(program)
+            .args(&args)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .await;
+        if matches!(status, Ok(s) if s.success()) && out.exists() {
+            return Ok(out);
+        }
+    }
+    Err(GeminiError::Other(format!(
+        "Cannot convert {} to JPEG: install libheif (heif-convert) or ImageMagick",
+        path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_delivery_and_part_kind() -> std::io::Result<()> {
+        assert_eq!(Delivery::for_file(1024, "image/png"), Delivery::Inline);
+        assert_eq!(Delivery::for_file(INLINE_LIMIT, "application/pdf"), Delivery::Upload);
+        assert_eq!(Delivery::for_file(1024, "video/mp4"), Delivery::Upload);
+
+        assert_eq!(mime_type_for(Path::new("Screenshot.HEIC")), "image/heic");
+        assert_eq!(mime_type_for(Path::new("report.pdf")), "application/pdf");
+        assert_eq!(mime_type_for(Path::new("build.zig")), "text/plain");
+
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff(b"\0\0\0\x18ftypheic\0\0"), Some("image/heic"));
+        assert_eq!(sniff(b"\0\0\0\x18ftypisom\0\0"), Some("video/mp4"));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(sniff(b"fn main() {}"), None);
+
+        // The bytes win over a misleading extension; unknown binary isn't passed off as text.
+        let dir = std::env::temp_dir().join(format!("chitti-mime-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("photo.txt"), b"\xff\xd8\xff\xe0\0\x10JFIF")?;
+        assert_eq!(mime_type_for(&dir.join("photo.txt")), "image/jpeg");
+        std::fs::write(dir.join("blob.zzz"), b"\0\x01\x02\xfe\xff")?;
+        assert_eq!(mime_type_for(&dir.join("blob.zzz")), "application/octet-stream");
+        std::fs::write(dir.join("notes.zzz"), "தமிழ் notes")?;
+        assert_eq!(mime_type_for(&dir.join("notes.zzz")), "text/plain");
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(matches!(part_for("image/jpeg", MediaPart::default()), InteractionPart::Image(_)));
+        assert!(matches!(part_for("audio/mpeg", MediaPart::default()), InteractionPart::Audio(_)));
+        assert!(matches!(part_for("application/pdf", MediaPart::default()), InteractionPart::Document(_)));
+        Ok(())
+    }
+}
@@ -10,9 +10,11 @@ impl Client {
     #[allow(dead_code)]
     pub async fn create_batch(&self, display_name: String, file_name: String) -> Result<Operation> {
         let path = format!("/v1beta/models/{}:batchGenerateContent", self.model);
-        let request = BatchRequest {
-            display_name,
-            input_config: BatchInputConfig { file_name },
+        let request = CreateBatchRequest {
+            batch: BatchRequest {
+                display_name,
+                input_config: BatchInputConfig { file_name },
+            },
         };
 
         let response = self.request(Method::POST, &path)
@@ -1,6 +1,9 @@
 use reqwest::{Client as HttpClient, Method, RequestBuilder as ReqwestRequestBuilder, Response};
 use tracing::{debug, instrument, warn};
 use crate::brains::gemini::error::GeminiError;
+use crate::brains::gemini::keys::KeyRing;
+use crate::brains::gemini::vertex::Vertex;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -8,7 +11,9 @@ use tokio::time::sleep;
 #[derive(Clone)]
 pub struct Client {
     pub(crate) http_client: HttpClient,
-    pub api_key: String,
+    pub keys: Arc<KeyRing>,
+    /// Set when requests go to Vertex AI with OAuth instead of an API key.
+    pub vertex: Option<Arc<Vertex>>,
     pub model: String,
     pub base_url: String,
 }
@@ -18,12 +23,26 @@ impl Client {
     pub fn new(api_key: String, model: String) -> Self {
         Self {
             http_client: HttpClient::new(),
-            api_key,
+            keys: Arc::new(KeyRing::new(vec![api_key])),
+            vertex: None,
             model,
             base_url: "https://generativelanguage.googleapis.com".to_string(),
         }
     }
 
+    /// Rotates through `keys` on quota errors instead of using a single key.
+    pub fn with_keys(mut self, keys: Vec<String>) -> Self {
+        self.keys = Arc::new(KeyRing::new(keys));
+        self
+    }
+
+    /// Sends requests to Vertex AI in the given project and location.
+    pub fn with_vertex(mut self, vertex: Vertex) -> Self {
+        self.base_url = vertex.base_url();
+        self.vertex = Some(Arc::new(vertex));
+        self
+    }
+
     /// Sets a custom model for the client.
     #[allow(dead_code)]
     pub fn with_model(mut self, model: String) -> Self {
@@ -38,16 +57,19 @@ impl Client {
         self
     }
 
-    /// Builds a request with the necessary headers and API key.
+    /// Builds a request with the necessary headers. The API key or access
+    /// token is added when it is sent, so a retry can switch keys.
     #[instrument(skip(self))]
     pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
-        let url = format!("{}{}", self.base_url, path);
+        let url = match &self.vertex {
+            Some(vertex) => format!("{}{}", self.base_url, vertex.path(path)),
+            None => format!("{}{}", self.base_url, path),
+        };
         debug!("Building request: {} {}", method, url);
         
         let request_id = uuid::Uuid::new_v4();
         let inner = self.http_client
             .request(method.clone(), &url)
-            .header("x-goog-api-key", &self.api_key)
             .header("Content-Type", "application/json")
             .header("X-Request-ID", request_id.to_string());
         RequestBuilder { 
@@ -55,6 +77,8 @@ impl Client {
             method: method.to_string(), 
             url,
             request_id,
+            keys: self.keys.clone(),
+            vertex: self.vertex.clone(),
         }
     }
 }
@@ -65,9 +89,16 @@ pub struct RequestBuilder {
     method: String,
     url: String,
     request_id: uuid::Uuid,
+    keys: Arc<KeyRing>,
+    vertex: Option<Arc<Vertex>>,
 }
 
 impl RequestBuilder {
+    /// The `X-Request-ID` sent with every attempt of this request.
+    pub fn request_id(&self) -> uuid::Uuid {
+        self.request_id
+    }
+
     #[allow(dead_code)]
     pub fn header<K, V>(mut self, key: K, value: V) -> Self
     where
@@ -109,6 +140,8 @@ impl RequestBuilder {
         let mut attempt = 1;
         let max_retries = 3;
         let mut backoff = Duration::from_secs(1);
+        // Each other key gets one try before quota errors back off.
+        let mut rotations = 0;
         
         // We use Option to handle ownership of the source builder across retry loops
         let mut source = Some(self.inner);
@@ -135,6 +168,13 @@ impl RequestBuilder {
             };
 
             debug!(attempt, "Sending request");
+            let (request_to_send, key) = match &self.vertex {
+                Some(vertex) => (request_to_send.bearer_auth(vertex.access_token().await?), String::new()),
+                None => {
+                    let key = self.keys.take();
+                    (request_to_send.header("x-goog-api-key", &key), key)
+                }
+            };
             match request_to_send.send().await {
                 Ok(response) => {
                     let status = response.status();
@@ -148,6 +188,14 @@ impl RequestBuilder {
                         return Ok(response);
                     }
 
+                    if status == 429 && self.vertex.is_none() && source.is_some() && rotations + 1 < self.keys.count()
+                        && self.keys.rotate_after_quota_error(&key)
+                    {
+                        warn!(attempt, "API key hit its quota, retrying with the next key");
+                        rotations += 1;
+                        continue;
+                    }
+
                     // Check for retryable status codes
                     // We can only retry if we still have the source (i.e., we cloned it)
                     if source.is_some() && (status == 429 || status == 500 || status == 503) {
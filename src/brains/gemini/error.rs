@@ -9,6 +9,13 @@ pub enum GeminiError {
         code: String,
         message: String,
     },
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<std::time::Duration>,
+    },
+    #[error("Auth Error: {0}")]
+    Auth(String),
     #[error("HTTP Error: {0}")]
     Http(#[from] reqwest::Error),
     #[error("Serialization Error: {0}")]
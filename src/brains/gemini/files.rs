@@ -9,15 +9,22 @@ impl Client {
     #[instrument(skip(self, path))]
     #[allow(dead_code)]
     pub async fn upload_file<P: AsRef<Path>>(&self, path: P, display_name: Option<String>) -> Result<File> {
+        let mime_type = mime_guess::from_path(path.as_ref())
+            .first_raw()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        self.upload_file_as(path, display_name, mime_type).await
+    }
+
+    /// Uploads a file with an explicit MIME type (e.g. "jsonl" for batch input).
+    #[instrument(skip(self, path))]
+    #[allow(dead_code)]
+    pub async fn upload_file_as<P: AsRef<Path>>(&self, path: P, display_name: Option<String>, mime_type: String) -> Result<File> {
         let path = path.as_ref();
         let file_name = path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("file")
             .to_string();
-        let mime_type = mime_guess::from_path(path)
-            .first_raw()
-            .unwrap_or("application/octet-stream")
-            .to_string();
 
         let file_bytes = tokio::fs::read(path).await?;
         // 1. Initial metadata request
@@ -29,7 +36,7 @@ impl Client {
 
         let response = self.http_client
             .request(Method::POST, "https://generativelanguage.googleapis.com/upload/v1beta/files")
-            .header("x-goog-api-key", &self.api_key)
+            .header("x-goog-api-key", self.keys.take())
             .header("X-Goog-Upload-Protocol", "resumable")
             .header("X-Goog-Upload-Command", "start")
             .header("X-Goog-Upload-Header-Content-Length", file_bytes.len())
@@ -52,7 +59,7 @@ impl Client {
         // 2. Upload actual bytes
         let response = self.http_client
             .request(Method::POST, &upload_url)
-            .header("x-goog-api-key", &self.api_key)
+            .header("x-goog-api-key", self.keys.take())
             .header("Content-Length", file_bytes.len())
             .header("X-Goog-Upload-Offset", "0")
             .header("X-Goog-Upload-Command", "upload, finalize")
@@ -73,6 +80,28 @@ impl Client {
             })?;
         Ok(file)
     }
+    /// Downloads the contents of a file (e.g. a batch responses file).
+    #[instrument(skip(self))]
+    #[allow(dead_code)]
+    pub async fn download_file(&self, name: &str) -> Result<Vec<u8>> {
+        let name = if name.starts_with("files/") {
+            name.to_string()
+        } else {
+            format!("files/{}", name)
+        };
+        let response = self.request(Method::GET, &format!("/download/v1beta/{}:download", name))
+            .query(&[("alt", "media")])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let code = response.status().as_str().to_string();
+            let message = response.text().await.unwrap_or_default();
+            return Err(GeminiError::Api { code, message });
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
     /// Gets metadata for a file.
     #[instrument(skip(self))]
     #[allow(dead_code)]
@@ -93,6 +93,22 @@ impl<'a> InteractionRequestBuilder<'a> {
         self
     }
 
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        let mut config = self.request.generation_config.take().unwrap_or_default();
+        config.temperature = Some(temperature);
+        self.request.generation_config = Some(config);
+        self
+    }
+
+    /// Default detail level for every image, PDF and video part in the request.
+    #[allow(dead_code)]
+    pub fn media_resolution(mut self, resolution: MediaResolution) -> Self {
+        let mut config = self.request.generation_config.take().unwrap_or_default();
+        config.media_resolution = Some(resolution);
+        self.request.generation_config = Some(config);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn store(mut self, store: bool) -> Self {
         self.request.store = Some(store);
@@ -131,30 +147,17 @@ impl<'a> InteractionRequestBuilder<'a> {
         Ok(interaction_resp)
     }
 
+    /// Returns the request without sending it.
+    pub fn build(self) -> InteractionRequest {
+        self.request
+    }
+
     /// Starts a streaming interaction.
+    #[allow(dead_code)]
     #[instrument(skip(self), fields(model = ?self.request.model))]
     pub async fn stream(mut self) -> Result<impl Stream<Item = Result<InteractionEvent, GeminiError>>, GeminiError> {
         self.request.stream = Some(true);
-        let response = self.client
-            .request(Method::POST, "/v1beta/interactions")
-            .json(&self.request)
-            .send()
-            .await?;
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            let message = if let Ok(api_error) = serde_json::from_str::<ApiError>(&error_text) {
-                api_error.message
-            } else {
-                error_text
-            };
-
-            return Err(GeminiError::Api {
-                code: status.to_string(),
-                message,
-            });
-        }
-        Ok(parse_sse_stream(response))
+        Ok(self.client.stream_interaction(&self.request).await?.1)
     }
 }
 
@@ -190,4 +193,62 @@ impl Client {
     pub fn interaction(&self, input: InteractionInput) -> InteractionRequestBuilder<'_> {
         InteractionRequestBuilder::new(self, input)
     }
+
+    /// Posts an already rendered interaction request (which must set `stream`) and
+    /// parses the event stream, returned with the request's `X-Request-ID`.
+    /// Used to send hand-edited requests as-is.
+    pub async fn stream_interaction<T: serde::Serialize + ?Sized>(
+        &self,
+        request: &T,
+    ) -> Result<(String, impl Stream<Item = Result<InteractionEvent, GeminiError>>), GeminiError> {
+        let builder = self
+            .request(Method::POST, "/v1beta/interactions")
+            .json(request);
+        let request_id = builder.request_id().to_string();
+        let response = builder.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let headers = response.headers().clone();
+            let error_text = response.text().await.unwrap_or_default();
+            let retry_after = crate::brains::retry_after(&headers, &error_text);
+            let message = if let Ok(api_error) = serde_json::from_str::<ApiError>(&error_text) {
+                api_error.message
+            } else {
+                error_text
+            };
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(GeminiError::RateLimited { message, retry_after });
+            }
+
+            return Err(GeminiError::Api {
+                code: status.to_string(),
+                message: format!("{} [X-Request-ID {}]", message, request_id),
+            });
+        }
+        Ok((request_id, parse_sse_stream(response)))
+    }
+
+    /// Deletes a stored interaction. Interactions that were never stored (or
+    /// are already gone) count as deleted.
+    #[instrument(skip(self))]
+    pub async fn delete_interaction(&self, id: &str) -> Result<(), GeminiError> {
+        let response = self
+            .request(Method::DELETE, &format!("/v1beta/interactions/{}", id))
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() || status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        let error_text = response.text().await.unwrap_or_default();
+        let message = if let Ok(api_error) = serde_json::from_str::<ApiError>(&error_text) {
+            api_error.message
+        } else {
+            error_text
+        };
+        Err(GeminiError::Api {
+            code: status.to_string(),
+            message,
+        })
+    }
 }
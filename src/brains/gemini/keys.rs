@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+/// Requests made with one key, and how many of them hit a quota error.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyUsage {
+    pub requests: u64,
+    pub quota_errors: u64,
+}
+
+struct Ring {
+    keys: Vec<String>,
+    usage: Vec<KeyUsage>,
+    active: usize,
+}
+
+/// The API keys a client can use. Requests go out with the active key; when
+/// it hits a quota error the client moves on to the next one. Shared by all
+/// clones of a `Client`, so a rotation applies everywhere.
+pub struct KeyRing {
+    ring: Mutex<Ring>,
+}
+
+impl KeyRing {
+    pub fn new(keys: Vec<String>) -> Self {
+        let usage = vec![KeyUsage::default(); keys.len()];
+        Self { ring: Mutex::new(Ring { keys, usage, active: 0 }) }
+    }
+
+    pub fn count(&self) -> usize {
+        self.ring.lock().unwrap().keys.len()
+    }
+
+    /// The key to send, counted as one request against it.
+    pub fn take(&self) -> String {
+        let mut ring = self.ring.lock().unwrap();
+        let active = ring.active;
+        match ring.usage.get_mut(active) {
+            Some(usage) => usage.requests += 1,
+            None => return String::new(),
+        }
+        ring.keys[active].clone()
+    }
+
+    /// Records a quota error against `key` and, if it is still the active
+    /// key, switches to the next one. Returns whether another key is now
+    /// active; a request that raced with another rotation counts as rotated.
+    pub fn rotate_after_quota_error(&self, key: &str) -> bool {
+        let mut ring = self.ring.lock().unwrap();
+        let Some(index) = ring.keys.iter().position(|k| k == key) else {
+            return false;
+        };
+        ring.usage[index].quota_errors += 1;
+        if ring.keys.len() < 2 {
+            return false;
+        }
+        if ring.active == index {
+            ring.active = (index + 1) % ring.keys.len();
+        }
+        true
+    }
+
+    /// One line per key for `/keys`, with the key masked to its last four
+    /// characters.
+    pub fn listing(&self) -> String {
+        let ring = self.ring.lock().unwrap();
+        let mut out = format!("{} API key{}:", ring.keys.len(), if ring.keys.len() == 1 { "" } else { "s" });
+        for (i, (key, usage)) in ring.keys.iter().zip(&ring.usage).enumerate() {
+            let tail: String = key.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+            out.push_str(&format!(
+                "\n{} {}. …{}  {} request{}, {} quota error{}",
+                if i == ring.active { "*" } else { " " },
+                i + 1,
+                tail,
+                usage.requests,
+                if usage.requests == 1 { "" } else { "s" },
+                usage.quota_errors,
+                if usage.quota_errors == 1 { "" } else { "s" },
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_ring_rotates_on_quota_errors() {
+        let ring = KeyRing::new(vec!["key-aaaa".to_string(), "key-bbbb".to_string()]);
+        assert_eq!(ring.take(), "key-aaaa");
+        assert!(ring.rotate_after_quota_error("key-aaaa"));
+        assert_eq!(ring.take(), "key-bbbb");
+        // A second failure with the old key doesn't skip past the new one.
+        assert!(ring.rotate_after_quota_error("key-aaaa"));
+        assert_eq!(ring.take(), "key-bbbb");
+        assert_eq!(
+            ring.listing(),
+            "2 API keys:\n  1. …aaaa  1 request, 2 quota errors\n* 2. …bbbb  2 requests, 0 quota errors"
+        );
+
+        let single = KeyRing::new(vec!["only".to_string()]);
+        assert!(!single.rotate_after_quota_error("only"));
+        assert_eq!(single.take(), "only");
+    }
+}
@@ -4,8 +4,12 @@ pub mod interactions;
 pub mod files;
 pub mod batch;
 pub mod caching;
+pub mod models;
 pub mod error;
+pub mod keys;
 pub mod adapter;
+pub mod attachments;
+pub mod vertex;
 
 
 pub use client::Client;
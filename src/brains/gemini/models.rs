@@ -0,0 +1,55 @@
+use reqwest::Method;
+use tracing::instrument;
+use crate::brains::gemini::client::Client;
+use crate::brains::gemini::types::*;
+use crate::brains::gemini::error::{GeminiError, Result};
+
+impl Client {
+    /// Lists the models available to this API key, following pagination.
+    #[instrument(skip(self))]
+    pub async fn list_models(&self) -> Result<Vec<Model>> {
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut query = vec![("pageSize", "1000".to_string())];
+            if let Some(pt) = page_token.take() {
+                query.push(("pageToken", pt));
+            }
+            let response = self.request(Method::GET, "/v1beta/models")
+                .query(&query)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let code = response.status().as_str().to_string();
+                let message = response.text().await.unwrap_or_default();
+                return Err(GeminiError::Api { code, message });
+            }
+
+            let page: ListModelsResponse = response.json().await?;
+            models.extend(page.models);
+            match page.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => return Ok(models),
+            }
+        }
+    }
+
+    /// Counts the tokens `text` takes up for the client's model.
+    #[instrument(skip(self, text))]
+    pub async fn count_tokens(&self, text: &str) -> Result<u64> {
+        let path = format!("/v1beta/models/{}:countTokens", self.model);
+        let response = self.request(Method::POST, &path)
+            .json(&serde_json::json!({ "contents": [{ "parts": [{ "text": text }] }] }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let code = response.status().as_str().to_string();
+            let message = response.text().await.unwrap_or_default();
+            return Err(GeminiError::Api { code, message });
+        }
+        let counted: CountTokensResponse = response.json().await?;
+        Ok(counted.total_tokens)
+    }
+}
@@ -25,6 +25,44 @@ pub struct Part {
     pub function_call: Option<FunctionCall>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function_response: Option<FunctionResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_resolution: Option<MediaResolutionLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_metadata: Option<VideoMetadata>,
+}
+
+/// How many tokens the model may spend on an image, PDF page or video frame.
+/// Higher levels read finer detail (small text, dense documents) at more cost.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MediaResolution {
+    #[serde(rename = "media_resolution_low")]
+    Low,
+    #[serde(rename = "media_resolution_medium")]
+    Medium,
+    #[serde(rename = "media_resolution_high")]
+    High,
+    #[serde(rename = "media_resolution_ultra_high")]
+    UltraHigh,
+}
+
+/// Per-part resolution in generateContent requests: `{"level": "media_resolution_high"}`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MediaResolutionLevel {
+    pub level: MediaResolution,
+}
+
+/// Restricts a video part to a clip and/or sampling rate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoMetadata {
+    /// Clip start, e.g. "12s" or "1.5s".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<String>,
+    /// Frames sampled per second (default 1).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,7 +127,7 @@ pub struct InteractionTurn {
 
 // --- Shared Structs ---
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct MediaPart {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -97,6 +135,42 @@ pub struct MediaPart {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
     pub mime_type: String,
+    /// Detail level for this part, overriding the request-wide `media_resolution`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<MediaResolution>,
+    /// Video only: clip offsets and frame rate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_metadata: Option<InteractionVideoMetadata>,
+}
+
+/// Video clip settings in Interactions requests, which use snake_case fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InteractionVideoMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f32>,
+}
+
+#[allow(dead_code)]
+impl MediaPart {
+    /// Image detail: how closely the model should look at this part.
+    pub fn with_resolution(mut self, resolution: MediaResolution) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Limits a video part to `start..end` (e.g. "10s", "75s") sampled at `fps`.
+    pub fn with_clip(mut self, start: Option<&str>, end: Option<&str>, fps: Option<f32>) -> Self {
+        self.video_metadata = Some(InteractionVideoMetadata {
+            start_offset: start.map(str::to_string),
+            end_offset: end.map(str::to_string),
+            fps,
+        });
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,11 +253,13 @@ pub struct GenerationConfig {
     pub image_config: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speech_config: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_resolution: Option<MediaResolution>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ThinkingLevel {
     Minimal,
@@ -192,6 +268,27 @@ pub enum ThinkingLevel {
     High,
 }
 
+impl ThinkingLevel {
+    /// The next cheaper level, if any.
+    pub fn lower(self) -> Option<Self> {
+        match self {
+            ThinkingLevel::High => Some(ThinkingLevel::Medium),
+            ThinkingLevel::Medium => Some(ThinkingLevel::Low),
+            ThinkingLevel::Low => Some(ThinkingLevel::Minimal),
+            ThinkingLevel::Minimal => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThinkingLevel::Minimal => "minimal",
+            ThinkingLevel::Low => "low",
+            ThinkingLevel::Medium => "medium",
+            ThinkingLevel::High => "high",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct InteractionRequest {
@@ -386,10 +483,53 @@ pub enum FileSource {
 #[allow(dead_code)]
 #[serde(rename_all = "camelCase")]
 pub struct ListFilesResponse {
+    #[serde(default)]
     pub files: Vec<File>,
     pub next_page_token: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensResponse {
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+/// A model as returned by `models.list`.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    pub name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub supported_generation_methods: Vec<String>,
+}
+
+impl Model {
+    /// The model id without the `models/` prefix, as used in requests.
+    pub fn id(&self) -> &str {
+        self.name.strip_prefix("models/").unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModelsResponse {
+    #[serde(default)]
+    pub models: Vec<Model>,
+    pub next_page_token: Option<String>,
+}
+
+/// Body of `batchGenerateContent`; the API expects the batch under a `batch` key.
+#[derive(Debug, Clone, Serialize)]
+#[allow(dead_code)]
+pub struct CreateBatchRequest {
+    pub batch: BatchRequest,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[allow(dead_code)]
 #[serde(rename_all = "camelCase")]
@@ -436,7 +576,10 @@ pub enum BatchState {
 #[serde(rename_all = "camelCase")]
 pub struct Operation {
     pub name: String,
+    #[serde(default)]
     pub done: bool,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
     pub error: Option<serde_json::Value>,
     pub response: Option<serde_json::Value>,
 }
@@ -483,6 +626,37 @@ mod tests {
         assert!(json.get("agent").is_none());
     }
 
+    #[test]
+    fn test_media_resolution_and_video_metadata_serialization() {
+        let config = GenerationConfig { media_resolution: Some(MediaResolution::High), ..Default::default() };
+        assert_eq!(serde_json::to_value(&config).unwrap(), serde_json::json!({ "media_resolution": "media_resolution_high" }));
+
+        let video = InteractionPart::Video(MediaPart {
+            uri: Some("files/abc".to_string()),
+            mime_type: "video/mp4".to_string(),
+            ..Default::default()
+        }.with_resolution(MediaResolution::Low).with_clip(Some("10s"), Some("40s"), Some(2.0)));
+        assert_eq!(serde_json::to_value(&video).unwrap(), serde_json::json!({
+            "type": "video",
+            "uri": "files/abc",
+            "mime_type": "video/mp4",
+            "resolution": "media_resolution_low",
+            "video_metadata": { "start_offset": "10s", "end_offset": "40s", "fps": 2.0 },
+        }));
+
+        let part = Part {
+            file_data: Some(FileData { mime_type: "video/mp4".to_string(), file_uri: "files/abc".to_string() }),
+            media_resolution: Some(MediaResolutionLevel { level: MediaResolution::UltraHigh }),
+            video_metadata: Some(VideoMetadata { start_offset: Some("5s".to_string()), fps: Some(0.5), ..Default::default() }),
+            ..Default::default()
+        };
+        assert_eq!(serde_json::to_value(&part).unwrap(), serde_json::json!({
+            "fileData": { "mimeType": "video/mp4", "fileUri": "files/abc" },
+            "mediaResolution": { "level": "media_resolution_ultra_high" },
+            "videoMetadata": { "startOffset": "5s", "fps": 0.5 },
+        }));
+    }
+
     #[test]
     fn test_function_response_serialization() {
         let resp = FunctionResponse {
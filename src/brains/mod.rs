@@ -2,10 +2,71 @@ use async_trait::async_trait;
 use futures_util::stream::BoxStream;
 use crate::conductor::events::{BrainEvent, TurnContext};
 use anyhow::Result;
+use serde_json::Value;
+use std::time::Duration;
 
+pub mod cache;
+pub mod fallback;
 pub mod gemini;
+pub mod ollama;
+pub mod openai;
+pub mod privacy;
+pub mod router;
+pub mod structured;
+#[cfg(feature = "scripted-brain")]
+#[allow(dead_code)]
+pub mod scripted;
+
+/// The provider turned a request down for rate limiting. Brains return it
+/// (inside `anyhow::Error`) so the conductor can wait and retry the turn.
+#[derive(Debug, thiserror::Error)]
+#[error("Rate limited: {message}")]
+pub struct RateLimited {
+    pub message: String,
+    /// How long the provider asked us to wait, when it said.
+    pub retry_after: Option<Duration>,
+}
+
+/// Reads the wait from a `Retry-After: <seconds>` header, or else from a
+/// Google `RetryInfo` detail (`"retryDelay": "31s"`) in the error body.
+pub fn retry_after(headers: &reqwest::header::HeaderMap, body: &str) -> Option<Duration> {
+    let from_header = headers.get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok());
+    let from_body = || {
+        let body: Value = serde_json::from_str(body).ok()?;
+        body.pointer("/error/details")?.as_array()?.iter()
+            .find_map(|d| d.get("retryDelay")?.as_str()?.trim_end_matches('s').parse::<f64>().ok())
+    };
+    from_header.or_else(from_body)
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
 
 #[async_trait]
 pub trait BrainEngine: Send + Sync {
     async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>>;
+
+    /// Renders the provider request `process_turn` would send, for inspection.
+    /// Engines that can't expose one return `None`.
+    fn render_request(&self, _context: &TurnContext) -> Result<Option<Value>> {
+        Ok(None)
+    }
+
+    /// Sends a request produced by `render_request`, possibly hand-edited.
+    async fn process_request(&self, _request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        anyhow::bail!("This brain does not support sending raw requests")
+    }
+
+    /// Counts the tokens `text` would use with this engine's model. Engines
+    /// without a tokenizer endpoint return `None`, and callers estimate.
+    async fn count_tokens(&self, _text: &str) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Deletes interactions the provider stored server-side. Engines without
+    /// server-side state have nothing to do.
+    async fn delete_interactions(&self, _ids: &[String]) -> Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,371 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
+use tracing::warn;
+use crate::brains::openai::Conversations;
+use crate::brains::{structured, BrainEngine};
+use crate::conductor::events::{BrainEvent, TurnContext, Usage};
+use crate::tools::ToolRegistry;
+use crate::brains::gemini::types::Tool;
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+const CALL_OPEN: &str = "<tool_call>";
+
+static TOOL_CALL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<tool_call>\s*(.*?)\s*(?:</tool_call>|$)").unwrap()
+});
+
+/// A `BrainEngine` for a local Ollama server's `/api/chat`, so chitti can
+/// run without any network access. Most local models have no tool support
+/// in their template, so by default tools are described in the system
+/// prompt and calls are parsed out of `<tool_call>` blocks in the answer.
+pub struct OllamaEngine {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+    native_tools: bool,
+    tools: Arc<ToolRegistry>,
+    conversations: Arc<Mutex<Conversations>>,
+}
+
+impl OllamaEngine {
+    pub fn new(base_url: String, model: String, native_tools: bool, tools: Arc<ToolRegistry>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            native_tools,
+            tools,
+            conversations: Arc::new(Mutex::new(Conversations::default())),
+        }
+    }
+
+    /// Renders the `/api/chat` request for a turn: the stored conversation,
+    /// then tool results and the new prompt.
+    fn build_request(&self, context: TurnContext) -> Value {
+        let tools: Vec<Value> = self.tools.get_definitions(context.allowed_tools.as_deref())
+            .into_iter()
+            .filter_map(|tool| match tool {
+                Tool::Function { declaration } => Some(json!({
+                    "type": "function",
+                    "function": {
+                        "name": declaration.name,
+                        "description": declaration.description,
+                        "parameters": declaration.parameters.unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+                    }
+                })),
+                _ => None,
+            })
+            .collect();
+
+        let mut messages = Vec::new();
+        let mut system: Vec<String> = context.system_instruction.into_iter().collect();
+        if !self.native_tools && !tools.is_empty() {
+            system.push(tool_prompt(&tools));
+        }
+        if !system.is_empty() {
+            messages.push(json!({ "role": "system", "content": system.join("\n\n") }));
+        }
+        if let Some(id) = &context.previous_interaction_id {
+            match self.conversations.lock().unwrap().get(id) {
+                Some(history) => messages.extend(history),
+                None => warn!(id = %id, "Unknown conversation; starting a new one"),
+            }
+        }
+        if self.native_tools {
+            for res in context.tool_results {
+                messages.push(json!({ "role": "tool", "tool_name": res.name, "content": res.result.to_string() }));
+            }
+        } else if !context.tool_results.is_empty() {
+            let results: Vec<String> = context.tool_results.iter()
+                .map(|res| format!("<tool_result name=\"{}\">\n{}\n</tool_result>", res.name, res.result))
+                .collect();
+            messages.push(json!({ "role": "user", "content": results.join("\n") }));
+        }
+        if !context.prompt.is_empty() {
+            messages.push(json!({ "role": "user", "content": context.prompt }));
+        }
+
+        let mut request = json!({
+            "model": context.model.unwrap_or_else(|| self.model.clone()),
+            "messages": messages,
+            "stream": true,
+        });
+        if self.native_tools && !tools.is_empty() {
+            request["tools"] = Value::Array(tools);
+        }
+        if let Some(schema) = context.response_schema {
+            request["format"] = schema;
+        }
+        if let Some(temperature) = context.temperature {
+            request["options"] = json!({ "temperature": temperature });
+        }
+        request
+    }
+
+    /// Streams a rendered request. When it finishes, the conversation so
+    /// far (without the system message) is stored under a new interaction id.
+    async fn send_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let response_schema = request.get("format").cloned();
+        let response = self.http
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Could not reach Ollama at {}; is `ollama serve` running?", self.base_url))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&text).ok()
+                .and_then(|v| v.get("error").and_then(Value::as_str).map(str::to_string))
+                .unwrap_or(text);
+            anyhow::bail!("API Error: {} (code: {})", message, status);
+        }
+
+        let history: Vec<Value> = request["messages"].as_array().cloned().unwrap_or_default()
+            .into_iter()
+            .filter(|m| m["role"] != "system")
+            .collect();
+        let emulated = request.get("tools").is_none();
+        let conversations = self.conversations.clone();
+        let bytes = response.bytes_stream().map_err(std::io::Error::other);
+        let mut lines = FramedRead::new(StreamReader::new(bytes), LinesCodec::new());
+        let events = async_stream::try_stream! {
+            let mut chat = OllamaStream::new(emulated);
+            while let Some(line) = lines.next().await {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Value>(&line) {
+                    Ok(chunk) => {
+                        if let Some(err) = chunk.get("error").and_then(Value::as_str) {
+                            Err(anyhow::anyhow!("API Error: {}", err))?;
+                        }
+                        for event in chat.push(&chunk) {
+                            yield event;
+                        }
+                    }
+                    Err(e) => warn!("Failed to parse Ollama chunk: {} | Data: {}", e, line),
+                }
+            }
+            let (events, reply) = chat.finish();
+            let mut history = history;
+            history.push(reply);
+            let id = conversations.lock().unwrap().insert(history);
+            for event in events {
+                yield event;
+            }
+            yield BrainEvent::Complete { interaction_id: Some(id) };
+        };
+
+        match response_schema {
+            Some(schema) => Ok(structured::assemble(Box::pin(events), schema)),
+            None => Ok(Box::pin(events)),
+        }
+    }
+}
+
+#[async_trait]
+impl BrainEngine for OllamaEngine {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let request = self.build_request(context);
+        self.send_request(request).await
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        Ok(Some(self.build_request(context.clone())))
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.send_request(request).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        let mut conversations = self.conversations.lock().unwrap();
+        for id in ids {
+            conversations.remove(id);
+        }
+        Ok(())
+    }
+}
+
+/// Describes the tools for models that can't take them natively.
+fn tool_prompt(tools: &[Value]) -> String {
+    let mut out = String::from(
+        "You can call tools. To call one, reply with a block like\n\
+         <tool_call>{\"name\": \"tool_name\", \"arguments\": {\"arg\": \"value\"}}</tool_call>\n\
+         and stop; use one block per call. Results come back in <tool_result> blocks. \
+         When no tool is needed, answer normally.\n\nTools:",
+    );
+    for tool in tools {
+        let function = &tool["function"];
+        out.push_str(&format!(
+            "\n- {}: {}\n  parameters: {}",
+            function["name"].as_str().unwrap_or_default(),
+            function["description"].as_str().unwrap_or_default(),
+            function["parameters"],
+        ));
+    }
+    out
+}
+
+/// Turns streamed `/api/chat` chunks into brain events. With emulated tool
+/// calling, text from the first `<tool_call>` on is held back and parsed
+/// into calls at the end.
+struct OllamaStream {
+    emulated: bool,
+    text: String,
+    /// Bytes of `text` already streamed as `TextDelta`s.
+    shown: usize,
+    native_calls: Vec<Value>,
+    usage: Option<Usage>,
+}
+
+impl OllamaStream {
+    fn new(emulated: bool) -> Self {
+        Self { emulated, text: String::new(), shown: 0, native_calls: Vec::new(), usage: None }
+    }
+
+    fn push(&mut self, chunk: &Value) -> Vec<BrainEvent> {
+        let mut events = Vec::new();
+        let message = &chunk["message"];
+        if let Some(thought) = message.get("thinking").and_then(Value::as_str).filter(|t| !t.is_empty()) {
+            events.push(BrainEvent::ThoughtDelta(thought.to_string()));
+        }
+        if let Some(text) = message.get("content").and_then(Value::as_str).filter(|t| !t.is_empty()) {
+            self.text.push_str(text);
+            let end = if self.emulated { self.visible_end() } else { self.text.len() };
+            if end > self.shown {
+                events.push(BrainEvent::TextDelta(self.text[self.shown..end].to_string()));
+                self.shown = end;
+            }
+        }
+        if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+            self.native_calls.extend(calls.iter().cloned());
+        }
+        if chunk["done"] == true {
+            let count = |key: &str| chunk.get(key).and_then(Value::as_u64).unwrap_or(0);
+            self.usage = Some(Usage {
+                input_tokens: count("prompt_eval_count"),
+                output_tokens: count("eval_count"),
+                thought_tokens: 0,
+            });
+        }
+        events
+    }
+
+    /// Where the text that is safe to show ends: before a `<tool_call>`, or
+    /// before a trailing fragment that may turn out to start one.
+    fn visible_end(&self) -> usize {
+        if let Some(at) = self.text.find(CALL_OPEN) {
+            return at;
+        }
+        (1..CALL_OPEN.len())
+            .rev()
+            .find(|&n| self.text.ends_with(&CALL_OPEN[..n]))
+            .map_or(self.text.len(), |n| self.text.len() - n)
+    }
+
+    /// The held-back text, tool calls and usage, plus the assistant message
+    /// to store.
+    fn finish(self) -> (Vec<BrainEvent>, Value) {
+        let mut events = Vec::new();
+        let mut reply = json!({ "role": "assistant", "content": self.text });
+        if self.emulated {
+            if !self.text.contains(CALL_OPEN) && self.shown < self.text.len() {
+                events.push(BrainEvent::TextDelta(self.text[self.shown..].to_string()));
+            }
+            for (i, block) in TOOL_CALL.captures_iter(&self.text).enumerate() {
+                match serde_json::from_str::<Value>(&block[1]) {
+                    Ok(call) if call["name"].is_string() => events.push(BrainEvent::ToolCall {
+                        name: call["name"].as_str().unwrap_or_default().to_string(),
+                        id: format!("call_{}", i),
+                        args: call.get("arguments").cloned().unwrap_or_else(|| json!({})),
+                    }),
+                    _ => warn!("Ignoring malformed tool call from the model: {}", &block[1]),
+                }
+            }
+        } else {
+            for (i, call) in self.native_calls.iter().enumerate() {
+                events.push(BrainEvent::ToolCall {
+                    name: call.pointer("/function/name").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    id: format!("call_{}", i),
+                    args: call.pointer("/function/arguments").cloned().unwrap_or_else(|| json!({})),
+                });
+            }
+            if !self.native_calls.is_empty() {
+                reply["tool_calls"] = Value::Array(self.native_calls);
+            }
+        }
+        events.extend(self.usage.map(BrainEvent::Usage));
+        (events, reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+
+    #[test]
+    fn test_emulated_tool_calls_are_held_back_and_parsed() {
+        let mut chat = OllamaStream::new(true);
+        let mut events = Vec::new();
+        for content in ["Let me check.", " <tool", "_call>{\"name\": \"execute_bash\", ", "\"arguments\": {\"command\": \"df -h\"}}</tool_call>"] {
+            events.extend(chat.push(&json!({ "message": { "role": "assistant", "content": content }, "done": false })));
+        }
+        events.extend(chat.push(&json!({ "message": { "role": "assistant", "content": "" }, "done": true, "prompt_eval_count": 120, "eval_count": 30 })));
+        let (end, reply) = chat.finish();
+        events.extend(end);
+
+        let text: String = events.iter().filter_map(|e| match e {
+            BrainEvent::TextDelta(t) => Some(t.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(text, "Let me check. ");
+        assert!(events.iter().any(|e| matches!(e, BrainEvent::ToolCall { name, id, args }
+            if name == "execute_bash" && id == "call_0" && args["command"] == "df -h")));
+        assert!(matches!(events.last(), Some(BrainEvent::Usage(u)) if u.input_tokens == 120 && u.output_tokens == 30));
+        assert!(reply["content"].as_str().unwrap().ends_with("</tool_call>"));
+
+        // A trailing "<" that never becomes a call is shown at the end.
+        let mut chat = OllamaStream::new(true);
+        assert!(matches!(&chat.push(&json!({ "message": { "content": "1 <" } }))[0], BrainEvent::TextDelta(t) if t == "1 "));
+        assert!(matches!(&chat.finish().0[0], BrainEvent::TextDelta(t) if t == "<"));
+    }
+
+    #[test]
+    fn test_build_request_describes_tools_and_inlines_results() {
+        let engine = OllamaEngine::new(DEFAULT_BASE_URL.into(), "llama-test".into(), false, Arc::new(ToolRegistry::new()));
+        let request = engine.build_request(TurnContext {
+            prompt: String::new(),
+            system_instruction: Some("Be brief.".to_string()),
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: vec![ToolResult { call_id: "call_0".into(), name: "execute_bash".into(), result: json!({ "stdout": "12G" }), is_error: false }],
+            thinking_level: None,
+            temperature: Some(0.5),
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        });
+        assert_eq!(request["model"], "llama-test");
+        assert_eq!(request["messages"][0]["content"], "Be brief.");
+        assert_eq!(request["messages"][1]["role"], "user");
+        assert!(request["messages"][1]["content"].as_str().unwrap().starts_with("<tool_result name=\"execute_bash\">"));
+        assert_eq!(request["options"]["temperature"], 0.5);
+        assert!(request.get("tools").is_none());
+
+        let tools = vec![json!({ "type": "function", "function": { "name": "execute_bash", "description": "Run a command", "parameters": { "type": "object" } } })];
+        assert!(tool_prompt(&tools).ends_with("\n- execute_bash: Run a command\n  parameters: {\"type\":\"object\"}"));
+    }
+}
@@ -0,0 +1,389 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
+use tracing::warn;
+use crate::brains::{structured, BrainEngine, RateLimited};
+use crate::conductor::events::{BrainEvent, TurnContext, Usage};
+use crate::tools::ToolRegistry;
+use crate::brains::gemini::types::Tool;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Conversations kept in memory; the oldest are forgotten first.
+const MAX_CONVERSATIONS: usize = 64;
+
+/// Chat completions are stateless, so the engine keeps each conversation's
+/// messages itself, keyed by the interaction id it hands out. The conductor
+/// threads those ids exactly as it does Gemini's server-side ones.
+#[derive(Default)]
+pub(crate) struct Conversations {
+    messages: HashMap<String, Vec<Value>>,
+    order: VecDeque<String>,
+}
+
+impl Conversations {
+    pub(crate) fn get(&self, id: &str) -> Option<Vec<Value>> {
+        self.messages.get(id).cloned()
+    }
+
+    pub(crate) fn insert(&mut self, messages: Vec<Value>) -> String {
+        let id = format!("chat-{}", uuid::Uuid::new_v4().simple());
+        self.messages.insert(id.clone(), messages);
+        self.order.push_back(id.clone());
+        while self.order.len() > MAX_CONVERSATIONS {
+            if let Some(old) = self.order.pop_front() {
+                self.messages.remove(&old);
+            }
+        }
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: &str) {
+        self.messages.remove(id);
+        self.order.retain(|o| o != id);
+    }
+}
+
+/// A `BrainEngine` for the OpenAI chat completions API and compatible
+/// endpoints (OpenRouter, local servers) selected by `base_url`.
+pub struct OpenAiEngine {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    tools: Arc<ToolRegistry>,
+    conversations: Arc<Mutex<Conversations>>,
+}
+
+impl OpenAiEngine {
+    pub fn new(api_key: String, base_url: String, model: String, tools: Arc<ToolRegistry>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+            tools,
+            conversations: Arc::new(Mutex::new(Conversations::default())),
+        }
+    }
+
+    /// Renders the chat completions request for a turn: the stored
+    /// conversation, then tool results and the new prompt.
+    fn build_request(&self, context: TurnContext) -> Value {
+        let mut messages = Vec::new();
+        if let Some(instruction) = context.system_instruction {
+            messages.push(json!({ "role": "system", "content": instruction }));
+        }
+        if let Some(id) = &context.previous_interaction_id {
+            match self.conversations.lock().unwrap().get(id) {
+                Some(history) => messages.extend(history),
+                None => warn!(id = %id, "Unknown conversation; starting a new one"),
+            }
+        }
+        for res in context.tool_results {
+            messages.push(json!({ "role": "tool", "tool_call_id": res.call_id, "content": res.result.to_string() }));
+        }
+        if !context.prompt.is_empty() {
+            messages.push(json!({ "role": "user", "content": context.prompt }));
+        }
+
+        let mut request = json!({
+            "model": context.model.unwrap_or_else(|| self.model.clone()),
+            "messages": messages,
+            "stream": true,
+            "stream_options": { "include_usage": true },
+        });
+        let tools: Vec<Value> = self.tools.get_definitions(context.allowed_tools.as_deref())
+            .into_iter()
+            .filter_map(|tool| match tool {
+                Tool::Function { declaration } => Some(json!({
+                    "type": "function",
+                    "function": {
+                        "name": declaration.name,
+                        "description": declaration.description,
+                        "parameters": declaration.parameters.unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+                    }
+                })),
+                _ => None,
+            })
+            .collect();
+        if !tools.is_empty() {
+            request["tools"] = Value::Array(tools);
+        }
+        if let Some(schema) = context.response_schema {
+            request["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema },
+            });
+        }
+        if let Some(temperature) = context.temperature {
+            request["temperature"] = json!(temperature);
+        }
+        // Only reasoning models accept this, so it is sent only when asked for.
+        if let Some(level) = context.thinking_level {
+            request["reasoning_effort"] = json!(level.as_str());
+        }
+        request
+    }
+
+    /// Streams a rendered request. When it finishes, the conversation so
+    /// far (without the system message) is stored under a new interaction id.
+    async fn send_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let response_schema = request.pointer("/response_format/json_schema/schema").cloned();
+        let response = self.http
+            .post(format!("{}/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&request)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let text = response.text().await.unwrap_or_default();
+            let retry_after = crate::brains::retry_after(&headers, &text);
+            let message = serde_json::from_str::<Value>(&text).ok()
+                .and_then(|v| v.pointer("/error/message").and_then(Value::as_str).map(str::to_string))
+                .unwrap_or(text);
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(RateLimited { message, retry_after }.into());
+            }
+            anyhow::bail!("API Error: {} (code: {})", message, status);
+        }
+
+        let history: Vec<Value> = request["messages"].as_array().cloned().unwrap_or_default()
+            .into_iter()
+            .filter(|m| m["role"] != "system")
+            .collect();
+        let conversations = self.conversations.clone();
+        let bytes = response.bytes_stream().map_err(std::io::Error::other);
+        let mut lines = FramedRead::new(StreamReader::new(bytes), LinesCodec::new());
+        let events = async_stream::try_stream! {
+            let mut chat = ChatStream::default();
+            while let Some(line) = lines.next().await {
+                let line = line?;
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    break;
+                }
+                match serde_json::from_str::<Value>(data) {
+                    Ok(chunk) => {
+                        for event in chat.push(&chunk) {
+                            yield event;
+                        }
+                    }
+                    Err(e) => warn!("Failed to parse chat completion chunk: {} | Data: {}", e, data),
+                }
+            }
+            let (events, reply) = chat.finish();
+            let mut history = history;
+            history.push(reply);
+            let id = conversations.lock().unwrap().insert(history);
+            for event in events {
+                yield event;
+            }
+            yield BrainEvent::Complete { interaction_id: Some(id) };
+        };
+
+        match response_schema {
+            Some(schema) => Ok(structured::assemble(Box::pin(events), schema)),
+            None => Ok(Box::pin(events)),
+        }
+    }
+}
+
+#[async_trait]
+impl BrainEngine for OpenAiEngine {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let request = self.build_request(context);
+        self.send_request(request).await
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        Ok(Some(self.build_request(context.clone())))
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.send_request(request).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        let mut conversations = self.conversations.lock().unwrap();
+        for id in ids {
+            conversations.remove(id);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct PendingCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Turns streamed chat completion chunks into brain events. Text and
+/// reasoning stream through; tool calls arrive in fragments and are only
+/// complete at the end.
+#[derive(Default)]
+struct ChatStream {
+    text: String,
+    calls: Vec<PendingCall>,
+    usage: Option<Usage>,
+}
+
+impl ChatStream {
+    fn push(&mut self, chunk: &Value) -> Vec<BrainEvent> {
+        let mut events = Vec::new();
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            let count = |pointer: &str| usage.pointer(pointer).and_then(Value::as_u64).unwrap_or(0);
+            self.usage = Some(Usage {
+                input_tokens: count("/prompt_tokens"),
+                output_tokens: count("/completion_tokens"),
+                thought_tokens: count("/completion_tokens_details/reasoning_tokens"),
+            });
+        }
+        let Some(delta) = chunk.pointer("/choices/0/delta") else {
+            return events;
+        };
+        // DeepSeek and OpenRouter stream reasoning under different names.
+        for key in ["reasoning_content", "reasoning"] {
+            if let Some(thought) = delta.get(key).and_then(Value::as_str).filter(|t| !t.is_empty()) {
+                events.push(BrainEvent::ThoughtDelta(thought.to_string()));
+            }
+        }
+        if let Some(text) = delta.get("content").and_then(Value::as_str).filter(|t| !t.is_empty()) {
+            self.text.push_str(text);
+            events.push(BrainEvent::TextDelta(text.to_string()));
+        }
+        for call in delta.get("tool_calls").and_then(Value::as_array).into_iter().flatten() {
+            let index = call.get("index").and_then(Value::as_u64).unwrap_or(self.calls.len() as u64) as usize;
+            while self.calls.len() <= index {
+                self.calls.push(PendingCall::default());
+            }
+            let pending = &mut self.calls[index];
+            if let Some(id) = call.get("id").and_then(Value::as_str) {
+                pending.id = id.to_string();
+            }
+            if let Some(name) = call.pointer("/function/name").and_then(Value::as_str) {
+                pending.name.push_str(name);
+            }
+            if let Some(arguments) = call.pointer("/function/arguments").and_then(Value::as_str) {
+                pending.arguments.push_str(arguments);
+            }
+        }
+        events
+    }
+
+    /// The tool calls and usage, plus the assistant message to store.
+    fn finish(self) -> (Vec<BrainEvent>, Value) {
+        let mut events = Vec::new();
+        let mut tool_calls = Vec::new();
+        for (i, call) in self.calls.into_iter().enumerate() {
+            let id = if call.id.is_empty() { format!("call_{}", i) } else { call.id };
+            let args = serde_json::from_str(&call.arguments).unwrap_or_else(|_| json!({}));
+            tool_calls.push(json!({
+                "id": id,
+                "type": "function",
+                "function": { "name": call.name, "arguments": call.arguments },
+            }));
+            events.push(BrainEvent::ToolCall { name: call.name, id, args });
+        }
+        events.extend(self.usage.map(BrainEvent::Usage));
+        let mut reply = json!({ "role": "assistant", "content": self.text });
+        if !tool_calls.is_empty() {
+            reply["tool_calls"] = Value::Array(tool_calls);
+        }
+        (events, reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+
+    #[test]
+    fn test_chat_stream_assembles_text_reasoning_and_tool_calls() {
+        let mut chat = ChatStream::default();
+        let mut events = Vec::new();
+        for chunk in [
+            json!({ "choices": [{ "delta": { "reasoning_content": "Need the disk usage." } }] }),
+            json!({ "choices": [{ "delta": { "content": "Checking" } }] }),
+            json!({ "choices": [{ "delta": { "tool_calls": [{ "index": 0, "id": "call_a", "function": { "name": "execute_bash", "arguments": "{\"comm" } }] } }] }),
+            json!({ "choices": [{ "delta": { "tool_calls": [{ "index": 0, "function": { "arguments": "and\":\"df -h\"}" } }] } }] }),
+            json!({ "choices": [], "usage": { "prompt_tokens": 40, "completion_tokens": 12, "completion_tokens_details": { "reasoning_tokens": 5 } } }),
+        ] {
+            events.extend(chat.push(&chunk));
+        }
+        let (end, reply) = chat.finish();
+        events.extend(end);
+
+        assert!(matches!(&events[0], BrainEvent::ThoughtDelta(t) if t == "Need the disk usage."));
+        assert!(matches!(&events[1], BrainEvent::TextDelta(t) if t == "Checking"));
+        assert!(matches!(&events[2], BrainEvent::ToolCall { name, id, args }
+            if name == "execute_bash" && id == "call_a" && args["command"] == "df -h"));
+        assert!(matches!(&events[3], BrainEvent::Usage(u) if u.input_tokens == 40 && u.thought_tokens == 5));
+        assert_eq!(reply["tool_calls"][0]["function"]["arguments"], "{\"command\":\"df -h\"}");
+    }
+
+    #[test]
+    fn test_build_request_replays_the_stored_conversation() {
+        let engine = OpenAiEngine::new("key".into(), DEFAULT_BASE_URL.into(), "gpt-test".into(), Arc::new(ToolRegistry::new()));
+        let id = engine.conversations.lock().unwrap().insert(vec![
+            json!({ "role": "user", "content": "free space?" }),
+            json!({ "role": "assistant", "content": "", "tool_calls": [{ "id": "call_a", "type": "function", "function": { "name": "execute_bash", "arguments": "{}" } }] }),
+        ]);
+        let request = engine.build_request(TurnContext {
+            prompt: String::new(),
+            system_instruction: Some("Be brief.".to_string()),
+            response_schema: None,
+            previous_interaction_id: Some(id),
+            tool_results: vec![ToolResult { call_id: "call_a".into(), name: "execute_bash".into(), result: json!({ "stdout": "12G" }), is_error: false }],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        });
+        let roles: Vec<&str> = request["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "tool"]);
+        assert_eq!(request["messages"][3]["tool_call_id"], "call_a");
+        assert_eq!(request["model"], "gpt-test");
+        assert!(request.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_build_request_sends_steering_after_tool_results() {
+        let engine = OpenAiEngine::new("key".into(), DEFAULT_BASE_URL.into(), "gpt-test".into(), Arc::new(ToolRegistry::new()));
+        let id = engine.conversations.lock().unwrap().insert(vec![
+            json!({ "role": "user", "content": "free space?" }),
+            json!({ "role": "assistant", "content": "", "tool_calls": [{ "id": "call_a", "type": "function", "function": { "name": "execute_bash", "arguments": "{}" } }] }),
+        ]);
+        let request = engine.build_request(TurnContext {
+            prompt: "[User interjection] only the root volume".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: Some(id),
+            tool_results: vec![ToolResult { call_id: "call_a".into(), name: "execute_bash".into(), result: json!({ "stdout": "12G" }), is_error: false }],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        });
+        let roles: Vec<&str> = request["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "tool", "user"]);
+        assert_eq!(request["messages"][3]["content"], "[User interjection] only the root volume");
+    }
+}
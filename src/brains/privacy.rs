@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap()
+});
+
+/// Digit groups that may be a phone number; see `MIN_PHONE_DIGITS`.
+static PHONE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,5}\)|\b\d{2,5})[\s.-]\d{3,5}(?:[\s.-]?\d{3,5})?\b").unwrap()
+});
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[(?:EMAIL|PHONE|NAME|PII)_\d+\]").unwrap());
+
+/// A trailing fragment that may be the start of a placeholder split across deltas.
+static PARTIAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[[A-Z]{0,5}(?:_\d{0,6})?$").unwrap());
+
+/// Fewer digits than this is more likely a date, version or amount.
+const MIN_PHONE_DIGITS: usize = 8;
+
+const INSTRUCTION: &str = "Some personal details in this conversation were replaced with placeholders such as \
+    [NAME_1] or [EMAIL_2]. Use the placeholders exactly as written wherever you refer to those details.";
+
+/// Replaces emails, phone numbers, configured names and patterns with
+/// placeholders, and maps placeholders back. The mapping lasts for the
+/// session, so the same value always gets the same placeholder.
+pub struct PrivacyFilter {
+    names: Option<Regex>,
+    patterns: Vec<Regex>,
+    mapping: Mutex<Mapping>,
+}
+
+#[derive(Default)]
+struct Mapping {
+    placeholders: HashMap<String, String>,
+    values: HashMap<String, String>,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl PrivacyFilter {
+    /// `names` are matched as whole words, ignoring case; `patterns` are extra regexes.
+    pub fn new(names: &[String], patterns: &[String]) -> Result<Self> {
+        let mut names: Vec<&String> = names.iter().filter(|n| !n.trim().is_empty()).collect();
+        // Longest first, so "Ada Lovelace" wins over "Ada".
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+        let names = (!names.is_empty()).then(|| {
+            let alternatives: Vec<String> = names.iter().map(|n| regex::escape(n.trim())).collect();
+            Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|")))
+        }).transpose()?;
+        let patterns = patterns.iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid privacy pattern '{}'", p)))
+            .collect::<Result<_>>()?;
+        Ok(Self { names, patterns, mapping: Mutex::new(Mapping::default()) })
+    }
+
+    /// `text` with personal data swapped for placeholders.
+    pub fn mask(&self, text: &str) -> String {
+        let mut spans: Vec<(usize, usize, &'static str)> = Vec::new();
+        spans.extend(EMAIL.find_iter(text).map(|m| (m.start(), m.end(), "EMAIL")));
+        spans.extend(PHONE.find_iter(text)
+            .filter(|m| m.as_str().chars().filter(char::is_ascii_digit).count() >= MIN_PHONE_DIGITS)
+            .map(|m| (m.start(), m.end(), "PHONE")));
+        if let Some(names) = &self.names {
+            spans.extend(names.find_iter(text).map(|m| (m.start(), m.end(), "NAME")));
+        }
+        for pattern in &self.patterns {
+            spans.extend(pattern.find_iter(text).filter(|m| !m.is_empty()).map(|m| (m.start(), m.end(), "PII")));
+        }
+        spans.sort_by_key(|&(start, end, _)| (start, std::cmp::Reverse(end)));
+
+        let mut mapping = self.mapping.lock().unwrap();
+        let mut out = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for (start, end, kind) in spans {
+            if start < cursor {
+                continue;
+            }
+            out.push_str(&text[cursor..start]);
+            out.push_str(&mapping.placeholder(kind, &text[start..end]));
+            cursor = end;
+        }
+        out.push_str(&text[cursor..]);
+        out
+    }
+
+    /// `text` with known placeholders replaced by the original values.
+    pub fn restore(&self, text: &str) -> String {
+        let mapping = self.mapping.lock().unwrap();
+        PLACEHOLDER.replace_all(text, |caps: &regex::Captures| {
+            mapping.values.get(&caps[0]).cloned().unwrap_or_else(|| caps[0].to_string())
+        }).into_owned()
+    }
+
+    fn map_strings(&self, value: Value, f: &dyn Fn(&Self, &str) -> String) -> Value {
+        match value {
+            Value::String(s) => Value::String(f(self, &s)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.map_strings(v, f)).collect()),
+            Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, self.map_strings(v, f))).collect()),
+            other => other,
+        }
+    }
+
+    fn mask_context(&self, mut context: TurnContext) -> TurnContext {
+        context.prompt = self.mask(&context.prompt);
+        context.system_instruction = Some(match context.system_instruction {
+            Some(instruction) => format!("{}\n\n{}", self.mask(&instruction), INSTRUCTION),
+            None => INSTRUCTION.to_string(),
+        });
+        for result in &mut context.tool_results {
+            result.result = self.map_strings(std::mem::take(&mut result.result), &Self::mask);
+        }
+        context
+    }
+}
+
+impl Mapping {
+    fn placeholder(&mut self, kind: &'static str, value: &str) -> String {
+        if let Some(existing) = self.placeholders.get(value) {
+            return existing.clone();
+        }
+        let n = self.counts.entry(kind).or_default();
+        *n += 1;
+        let placeholder = format!("[{}_{}]", kind, n);
+        self.placeholders.insert(value.to_string(), placeholder.clone());
+        self.values.insert(placeholder.clone(), value.to_string());
+        placeholder
+    }
+}
+
+/// Restores streamed text and thoughts, holding back a trailing fragment
+/// that may be the start of a placeholder until the next delta completes it.
+#[derive(Default)]
+struct Restorer {
+    text: String,
+    thought: String,
+}
+
+impl Restorer {
+    fn push(&mut self, filter: &PrivacyFilter, event: BrainEvent) -> Vec<BrainEvent> {
+        match event {
+            BrainEvent::TextDelta(delta) => {
+                self.text.push_str(&delta);
+                Self::release(filter, &mut self.text).map(BrainEvent::TextDelta).into_iter().collect()
+            }
+            BrainEvent::ThoughtDelta(delta) => {
+                self.thought.push_str(&delta);
+                Self::release(filter, &mut self.thought).map(BrainEvent::ThoughtDelta).into_iter().collect()
+            }
+            other => {
+                let mut events = self.flush(filter);
+                events.push(match other {
+                    BrainEvent::ToolCall { name, id, args } => {
+                        BrainEvent::ToolCall { name, id, args: filter.map_strings(args, &PrivacyFilter::restore) }
+                    }
+                    BrainEvent::StructuredChunk { value, complete, errors } => {
+                        BrainEvent::StructuredChunk { value: filter.map_strings(value, &PrivacyFilter::restore), complete, errors }
+                    }
+                    BrainEvent::Error(message) => BrainEvent::Error(filter.restore(&message)),
+                    other => other,
+                });
+                events
+            }
+        }
+    }
+
+    fn release(filter: &PrivacyFilter, buffer: &mut String) -> Option<String> {
+        let keep = PARTIAL.find(buffer).map_or(buffer.len(), |m| m.start());
+        let ready: String = buffer.drain(..keep).collect();
+        (!ready.is_empty()).then(|| filter.restore(&ready))
+    }
+
+    fn flush(&mut self, filter: &PrivacyFilter) -> Vec<BrainEvent> {
+        let mut events = Vec::new();
+        if !self.thought.is_empty() {
+            events.push(BrainEvent::ThoughtDelta(filter.restore(&std::mem::take(&mut self.thought))));
+        }
+        if !self.text.is_empty() {
+            events.push(BrainEvent::TextDelta(filter.restore(&std::mem::take(&mut self.text))));
+        }
+        events
+    }
+}
+
+/// Keeps personal data off the wire: turns are masked before they reach
+/// `inner`, and its output is restored before the user sees it or tools run.
+pub struct PrivacyBrain {
+    inner: Box<dyn BrainEngine>,
+    filter: Arc<PrivacyFilter>,
+}
+
+impl PrivacyBrain {
+    pub fn new(inner: Box<dyn BrainEngine>, filter: PrivacyFilter) -> Self {
+        Self { inner, filter: Arc::new(filter) }
+    }
+
+    fn restoring(&self, events: BoxStream<'static, Result<BrainEvent>>) -> BoxStream<'static, Result<BrainEvent>> {
+        let restorer = Arc::new(Mutex::new(Restorer::default()));
+        let (filter, tail_filter, tail) = (self.filter.clone(), self.filter.clone(), restorer.clone());
+        let restored = events.flat_map(move |event| {
+            let events: Vec<Result<BrainEvent>> = match event {
+                Ok(event) => restorer.lock().unwrap().push(&filter, event).into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            stream::iter(events)
+        });
+        let rest = stream::once(async move { tail.lock().unwrap().flush(&tail_filter) })
+            .flat_map(|events| stream::iter(events.into_iter().map(Ok)));
+        Box::pin(restored.chain(rest))
+    }
+}
+
+#[async_trait]
+impl BrainEngine for PrivacyBrain {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let events = self.inner.process_turn(self.filter.mask_context(context)).await?;
+        Ok(self.restoring(events))
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        self.inner.render_request(&self.filter.mask_context(context.clone()))
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        // A hand-edited request may have new personal data typed into it.
+        let request = self.filter.map_strings(request, &PrivacyFilter::mask);
+        let events = self.inner.process_request(request).await?;
+        Ok(self.restoring(events))
+    }
+
+    async fn count_tokens(&self, text: &str) -> Result<Option<u64>> {
+        self.inner.count_tokens(&self.filter.mask(text)).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        self.inner.delete_interactions(ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+    use serde_json::json;
+
+    struct Echo {
+        seen: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for Echo {
+        async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            self.seen.lock().unwrap().push(context);
+            Ok(Box::pin(stream::iter(vec![
+                Ok(BrainEvent::TextDelta("Sure, I'll write to [EMA".to_string())),
+                Ok(BrainEvent::TextDelta("IL_1] and cc [NAME_1] about [".to_string())),
+                Ok(BrainEvent::ToolCall { name: "send_email".into(), id: "c1".into(), args: json!({ "to": ["[EMAIL_1]"] }) }),
+                Ok(BrainEvent::TextDelta("NAME_9]".to_string())),
+            ])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_privacy_brain_masks_requests_and_restores_stream() -> Result<()> {
+        let filter = PrivacyFilter::new(&["Ada Lovelace".to_string(), "Ada".to_string()], &[r"EMP-\d{5}".to_string()])?;
+        assert_eq!(
+            filter.mask("Call Ada Lovelace on +44 20 7946 0958 or ada@example.com about EMP-12345 before 2024-05-01, v10 2024."),
+            "Call [NAME_1] on [PHONE_1] or [EMAIL_1] about [PII_1] before 2024-05-01, v10 2024."
+        );
+        assert_eq!(filter.mask("ada@example.com again"), "[EMAIL_1] again");
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let brain = PrivacyBrain::new(Box::new(Echo { seen: seen.clone() }), PrivacyFilter::new(&["Grace Hopper".to_string()], &[])?);
+        let context = TurnContext {
+            prompt: "Email grace@navy.mil, cc Grace Hopper".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: vec![ToolResult { call_id: "c0".into(), name: "contacts".into(), result: json!({ "phone": "555-123-4567" }), is_error: false }],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let events: Vec<BrainEvent> = brain.process_turn(context).await?.map(|e| e.unwrap()).collect().await;
+        {
+            let sent = &seen.lock().unwrap()[0];
+            assert_eq!(sent.prompt, "Email [EMAIL_1], cc [NAME_1]");
+            assert_eq!(sent.tool_results[0].result, json!({ "phone": "[PHONE_1]" }));
+            assert!(sent.system_instruction.as_deref().unwrap().contains("placeholders"));
+        }
+        let text: String = events.iter().filter_map(|e| match e { BrainEvent::TextDelta(t) => Some(t.as_str()), _ => None }).collect();
+        assert_eq!(text, "Sure, I'll write to grace@navy.mil and cc Grace Hopper about [NAME_9]");
+        assert!(events.iter().any(|e| matches!(e, BrainEvent::ToolCall { args, .. } if args == &json!({ "to": ["grace@navy.mil"] }))));
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use tracing::debug;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+use crate::conductor::thinking;
+
+/// Image files or inline image data referenced by the prompt or a tool result.
+static IMAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)data:image/|\.(png|jpe?g|gif|webp|heic)\b").unwrap()
+});
+
+/// Name of the engine turns go to when no route names one.
+pub const DEFAULT_ENGINE: &str = "default";
+
+/// A `[[routes]]` entry in `~/.chitti/config.toml`. Every condition given
+/// must hold; the first matching route picks the turn's engine and model.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Route {
+    /// Thinking levels this route applies to; "default" is a turn without one.
+    pub thinking: Option<Vec<String>>,
+    pub min_prompt_chars: Option<usize>,
+    pub max_prompt_chars: Option<usize>,
+    /// Whether the prompt contains source code, stack traces or compiler output.
+    pub code: Option<bool>,
+    pub images: Option<bool>,
+    /// `gemini`, `openai` or `ollama`; the configured brain when unset.
+    pub brain: Option<String>,
+    pub model: Option<String>,
+}
+
+impl Route {
+    pub fn matches(&self, context: &TurnContext) -> bool {
+        let chars = context.prompt.chars().count();
+        let level = context.thinking_level.map(|l| l.as_str()).unwrap_or("default");
+        let images = IMAGE.is_match(&context.prompt)
+            || context.tool_results.iter().any(|r| IMAGE.is_match(&r.result.to_string()));
+        self.thinking.as_ref().is_none_or(|levels| levels.iter().any(|l| l.eq_ignore_ascii_case(level)))
+            && self.min_prompt_chars.is_none_or(|min| chars >= min)
+            && self.max_prompt_chars.is_none_or(|max| chars <= max)
+            && self.code.is_none_or(|code| code == thinking::looks_like_code(&context.prompt))
+            && self.images.is_none_or(|wanted| wanted == images)
+    }
+}
+
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    routes: Vec<Route>,
+}
+
+/// Reads the routing rules; a missing file means none.
+pub fn load_routes(path: Option<&Path>) -> Result<Vec<Route>> {
+    let Some(text) = path.and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return Ok(Vec::new());
+    };
+    let file: ConfigFile = toml::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.unwrap_or(Path::new("")).display()))?;
+    Ok(file.routes)
+}
+
+/// Sends each turn to the engine and model of the first matching route, e.g.
+/// a cheap model for short chat and a big one for code. Interaction ids
+/// belong to the engine that issued them, so a conversation stays on the
+/// engine it started on; routes naming another engine only apply to fresh
+/// conversations, while their model still applies.
+pub struct RouterBrain {
+    engines: BTreeMap<String, Box<dyn BrainEngine>>,
+    routes: Vec<Route>,
+    /// Which engine issued each interaction id.
+    owners: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl RouterBrain {
+    /// `engines` must contain `DEFAULT_ENGINE` and every engine the routes name.
+    pub fn new(engines: BTreeMap<String, Box<dyn BrainEngine>>, routes: Vec<Route>) -> Result<Self> {
+        anyhow::ensure!(engines.contains_key(DEFAULT_ENGINE), "Router has no default engine");
+        for route in &routes {
+            if let Some(brain) = &route.brain {
+                anyhow::ensure!(engines.contains_key(brain), "Route names unknown brain '{}'", brain);
+            }
+        }
+        Ok(Self { engines, routes, owners: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    fn default_engine(&self) -> &dyn BrainEngine {
+        &*self.engines[DEFAULT_ENGINE]
+    }
+}
+
+#[async_trait]
+impl BrainEngine for RouterBrain {
+    async fn process_turn(&self, mut context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let route = self.routes.iter().find(|r| r.matches(&context));
+        let owner = context.previous_interaction_id.as_ref()
+            .and_then(|id| self.owners.lock().unwrap().get(id).cloned());
+        let name = owner
+            .or_else(|| route.and_then(|r| r.brain.clone()))
+            .unwrap_or_else(|| DEFAULT_ENGINE.to_string());
+        // A per-turn model (from /compare or a tag) wins, and a route's model
+        // is only meant for the engine the route picked.
+        let route_engine = route.and_then(|r| r.brain.as_deref()).unwrap_or(DEFAULT_ENGINE);
+        if context.model.is_none() && route_engine == name {
+            context.model = route.and_then(|r| r.model.clone());
+        }
+        debug!(engine = %name, model = ?context.model, "Routed turn");
+
+        let owners = self.owners.clone();
+        let events = self.engines[&name].process_turn(context).await?.inspect(move |event| {
+            if let Ok(BrainEvent::Complete { interaction_id: Some(id) }) = event {
+                owners.lock().unwrap().insert(id.clone(), name.clone());
+            }
+        });
+        Ok(Box::pin(events))
+    }
+
+    fn render_request(&self, context: &TurnContext) -> Result<Option<Value>> {
+        self.default_engine().render_request(context)
+    }
+
+    async fn process_request(&self, request: Value) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        self.default_engine().process_request(request).await
+    }
+
+    async fn count_tokens(&self, text: &str) -> Result<Option<u64>> {
+        self.default_engine().count_tokens(text).await
+    }
+
+    async fn delete_interactions(&self, ids: &[String]) -> Result<()> {
+        let mut by_engine: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        {
+            let owners = self.owners.lock().unwrap();
+            for id in ids {
+                let owner = owners.get(id).cloned().unwrap_or_else(|| DEFAULT_ENGINE.to_string());
+                by_engine.entry(owner).or_default().push(id.clone());
+            }
+        }
+        for (name, ids) in by_engine {
+            self.engines[&name].delete_interactions(&ids).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brains::gemini::types::ThinkingLevel;
+    use futures_util::stream;
+
+    struct Engine {
+        id: &'static str,
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for Engine {
+        async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            let n = {
+                let mut calls = self.calls.lock().unwrap();
+                calls.push(context);
+                calls.len()
+            };
+            let id = format!("{}-{}", self.id, n);
+            Ok(Box::pin(stream::iter(vec![Ok(BrainEvent::Complete { interaction_id: Some(id) })])))
+        }
+    }
+
+    fn context(prompt: &str, thinking_level: Option<ThinkingLevel>, previous: Option<&str>) -> TurnContext {
+        TurnContext {
+            prompt: prompt.to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: previous.map(str::to_string),
+            tool_results: Vec::new(),
+            thinking_level,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        }
+    }
+
+    /// The interaction id that ended the routed turn; it names the engine.
+    async fn answered_by(router: &RouterBrain, context: TurnContext) -> Result<String> {
+        let events: Vec<BrainEvent> = router.process_turn(context).await?.map(|e| e.unwrap()).collect().await;
+        match events.last() {
+            Some(BrainEvent::Complete { interaction_id: Some(id) }) => Ok(id.clone()),
+            other => anyhow::bail!("Turn ended without an interaction id: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_picks_engine_and_model_by_rule() -> Result<()> {
+        let config = r#"
+            [[routes]]
+            code = true
+            brain = "ollama"
+            model = "qwen2.5-coder"
+
+            [[routes]]
+            max_prompt_chars = 40
+            thinking = ["default", "minimal"]
+            model = "gemini-2.5-flash-lite"
+
+            [[routes]]
+            thinking = ["high"]
+            model = "gemini-2.5-pro"
+        "#;
+        let routes: Vec<Route> = toml::from_str::<ConfigFile>(config)?.routes;
+        let (gemini, ollama) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+        let mut engines: BTreeMap<String, Box<dyn BrainEngine>> = BTreeMap::new();
+        engines.insert(DEFAULT_ENGINE.into(), Box::new(Engine { id: "gemini", calls: gemini.clone() }));
+        engines.insert("ollama".into(), Box::new(Engine { id: "ollama", calls: ollama.clone() }));
+        let router = RouterBrain::new(engines, routes.clone())?;
+
+        assert_eq!(answered_by(&router, context("hi there", None, None)).await?, "gemini-1");
+        let deep = context("Why do B-trees beat binary trees on disk? Walk me through it.", Some(ThinkingLevel::High), None);
+        assert_eq!(answered_by(&router, deep).await?, "gemini-2");
+        assert_eq!(answered_by(&router, context("fn main() {\n    let x = 1;\n}", None, None)).await?, "ollama-1");
+        // The follow-up stays on ollama even though it no longer matches the code route.
+        assert_eq!(answered_by(&router, context("thanks", None, Some("ollama-1"))).await?, "ollama-2");
+
+        let models: Vec<Option<String>> = gemini.lock().unwrap().iter().map(|c| c.model.clone()).collect();
+        assert_eq!(models, [Some("gemini-2.5-flash-lite".to_string()), Some("gemini-2.5-pro".to_string())]);
+        let models: Vec<Option<String>> = ollama.lock().unwrap().iter().map(|c| c.model.clone()).collect();
+        assert_eq!(models, [Some("qwen2.5-coder".to_string()), None]);
+
+        assert!(routes[0].matches(&context("see ```rust\nlet a = 1;\n```", None, None)));
+        assert!(Route { images: Some(true), ..Default::default() }.matches(&context("what's in @shot.PNG?", None, None)));
+        let unknown = RouterBrain::new(BTreeMap::new(), Vec::new());
+        assert!(unknown.is_err());
+        Ok(())
+    }
+}
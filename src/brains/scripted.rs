@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream};
+use anyhow::Result;
+use std::sync::Mutex;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+/// How a scripted rule selects the turn it answers.
+#[derive(Debug, Clone)]
+pub enum PromptMatch {
+    Exact(String),
+    Contains(String),
+    /// A follow-up turn carrying a result for the named tool.
+    ToolResult(String),
+    Any,
+}
+
+impl PromptMatch {
+    fn matches(&self, context: &TurnContext) -> bool {
+        match self {
+            PromptMatch::Exact(p) => context.prompt == *p,
+            PromptMatch::Contains(p) => context.prompt.contains(p.as_str()),
+            PromptMatch::ToolResult(name) => context.tool_results.iter().any(|r| r.name == *name),
+            PromptMatch::Any => true,
+        }
+    }
+}
+
+struct ScriptRule {
+    matcher: PromptMatch,
+    events: Vec<BrainEvent>,
+    once: bool,
+    used: bool,
+}
+
+/// A network-free `BrainEngine` that replays scripted `BrainEvent` sequences.
+/// Rules are checked in order; the first match answers the turn. Every turn
+/// context is recorded so tests can assert on what the Conductor sent.
+pub struct ScriptedBrain {
+    rules: Mutex<Vec<ScriptRule>>,
+    turns: Mutex<Vec<TurnContext>>,
+}
+
+impl Default for ScriptedBrain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptedBrain {
+    pub fn new() -> Self {
+        Self { rules: Mutex::new(Vec::new()), turns: Mutex::new(Vec::new()) }
+    }
+
+    /// Adds a rule that answers every matching turn.
+    pub fn on(self, matcher: PromptMatch, events: Vec<BrainEvent>) -> Self {
+        self.push_rule(matcher, events, false)
+    }
+
+    /// Adds a rule that answers only the first matching turn.
+    pub fn once(self, matcher: PromptMatch, events: Vec<BrainEvent>) -> Self {
+        self.push_rule(matcher, events, true)
+    }
+
+    /// Shorthand for a rule replying with plain text to prompts containing `needle`.
+    pub fn reply(self, needle: &str, text: &str) -> Self {
+        self.on(PromptMatch::Contains(needle.to_string()), vec![BrainEvent::TextDelta(text.to_string())])
+    }
+
+    fn push_rule(self, matcher: PromptMatch, events: Vec<BrainEvent>, once: bool) -> Self {
+        self.rules.lock().unwrap().push(ScriptRule { matcher, events, once, used: false });
+        self
+    }
+
+    /// All turn contexts received so far.
+    pub fn turns(&self) -> Vec<TurnContext> {
+        self.turns.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl BrainEngine for ScriptedBrain {
+    async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+        let turn_number = {
+            let mut turns = self.turns.lock().unwrap();
+            turns.push(context.clone());
+            turns.len()
+        };
+
+        let mut rules = self.rules.lock().unwrap();
+        let rule = rules.iter_mut()
+            .find(|r| !(r.once && r.used) && r.matcher.matches(&context))
+            .ok_or_else(|| anyhow::anyhow!("ScriptedBrain has no rule for prompt {:?}", context.prompt))?;
+        rule.used = true;
+
+        let mut events = rule.events.clone();
+        if !events.iter().any(|e| matches!(e, BrainEvent::Complete { .. })) {
+            events.push(BrainEvent::Complete { interaction_id: Some(format!("scripted-{}", turn_number)) });
+        }
+        Ok(Box::pin(stream::iter(events.into_iter().map(Ok))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridges::CommBridge;
+    use crate::conductor::events::{SystemEvent, UserEvent};
+    use crate::conductor::Conductor;
+    use crate::tools::{ToolExecutor, ToolRegistry, ToolResult};
+    use crate::brains::gemini::types::FunctionDeclaration;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl ToolExecutor for EchoTool {
+        fn name(&self) -> String {
+            "echo".to_string()
+        }
+        fn definition(&self) -> FunctionDeclaration {
+            FunctionDeclaration { name: self.name(), description: "Echo".to_string(), parameters: None }
+        }
+        async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+            Ok(ToolResult { output: json!(args), is_error: false })
+        }
+    }
+
+    struct ApprovingBridge {
+        tx: mpsc::Sender<UserEvent>,
+        text: Mutex<String>,
+    }
+
+    #[async_trait]
+    impl CommBridge for ApprovingBridge {
+        async fn send(&self, event: SystemEvent) -> Result<()> {
+            match event {
+                SystemEvent::Text(t) => self.text.lock().unwrap().push_str(&t),
+                SystemEvent::RequestApproval { .. } => self.tx.send(UserEvent::Approve).await?,
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scripted_tool_flow_end_to_end() -> Result<()> {
+        let brain = Arc::new(ScriptedBrain::new()
+            .once(PromptMatch::Contains("echo".to_string()), vec![
+                BrainEvent::ToolCall { name: "echo".to_string(), id: "c1".to_string(), args: json!({ "msg": "hi" }) },
+            ])
+            .on(PromptMatch::ToolResult("echo".to_string()), vec![BrainEvent::TextDelta("done".to_string())]));
+
+        struct Shared(Arc<ScriptedBrain>);
+        #[async_trait]
+        impl BrainEngine for Shared {
+            async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+                self.0.process_turn(context).await
+            }
+        }
+
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+        let (tx, rx) = mpsc::channel(10);
+        let bridge = Arc::new(ApprovingBridge { tx, text: Mutex::new(String::new()) });
+        let mut conductor = Conductor::new(Box::new(Shared(brain.clone())), bridge.clone(), rx, Arc::new(registry));
+
+        conductor.handle_conversation("please echo".to_string()).await?;
+
+        let turns = brain.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1].tool_results[0].result["msg"], "hi");
+        assert!(bridge.text.lock().unwrap().contains("done"));
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use futures_util::{stream::BoxStream, StreamExt};
+use jsonschema::error::ValidationErrorKind;
+use serde_json::Value;
+use crate::conductor::events::BrainEvent;
+
+/// Accumulates streamed JSON text and produces best-effort partial values,
+/// validated against the response schema as they fill in.
+pub struct StructuredAssembler {
+    buffer: String,
+    validator: Option<jsonschema::Validator>,
+    last: Option<Value>,
+}
+
+impl StructuredAssembler {
+    pub fn new(schema: &Value) -> Self {
+        let validator = match jsonschema::validator_for(schema) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                tracing::warn!("Invalid response schema, structured chunks will not be validated: {}", e);
+                None
+            }
+        };
+        Self { buffer: String::new(), validator, last: None }
+    }
+
+    /// Appends a text delta; returns a chunk when the partial value changed.
+    pub fn push(&mut self, delta: &str) -> Option<BrainEvent> {
+        self.buffer.push_str(delta);
+        let partial = parse_partial(&self.buffer)?;
+        if self.last.as_ref() == Some(&partial) {
+            return None;
+        }
+        self.last = Some(partial.clone());
+        let errors = self.validate(&partial, false);
+        Some(BrainEvent::StructuredChunk { value: partial, complete: false, errors })
+    }
+
+    /// Produces the final chunk once the stream is done.
+    pub fn finish(&mut self) -> Option<BrainEvent> {
+        if self.buffer.trim().is_empty() {
+            return None;
+        }
+        let (value, mut errors) = match serde_json::from_str::<Value>(self.buffer.trim()) {
+            Ok(v) => (v, Vec::new()),
+            Err(e) => (
+                parse_partial(&self.buffer).unwrap_or(Value::Null),
+                vec![format!("Response is not valid JSON: {}", e)],
+            ),
+        };
+        errors.extend(self.validate(&value, true));
+        Some(BrainEvent::StructuredChunk { value, complete: true, errors })
+    }
+
+    /// Validates a value; missing required properties are only reported once complete.
+    fn validate(&self, value: &Value, complete: bool) -> Vec<String> {
+        let Some(validator) = &self.validator else {
+            return Vec::new();
+        };
+        validator
+            .iter_errors(value)
+            .filter(|e| complete || !matches!(e.kind(), ValidationErrorKind::Required { .. }))
+            .map(|e| format!("{} (at '{}')", e, e.instance_path()))
+            .collect()
+    }
+}
+
+/// Wraps a brain stream so text deltas of a structured turn also yield `StructuredChunk`s.
+pub fn assemble(
+    stream: BoxStream<'static, Result<BrainEvent>>,
+    schema: Value,
+) -> BoxStream<'static, Result<BrainEvent>> {
+    let mut stream = stream;
+    let s = async_stream::try_stream! {
+        let mut assembler = StructuredAssembler::new(&schema);
+        while let Some(evt) = stream.next().await {
+            let evt = evt?;
+            match &evt {
+                BrainEvent::TextDelta(text) => {
+                    let chunk = assembler.push(text);
+                    yield evt;
+                    if let Some(chunk) = chunk {
+                        yield chunk;
+                    }
+                }
+                BrainEvent::Complete { .. } => {
+                    if let Some(chunk) = assembler.finish() {
+                        yield chunk;
+                    }
+                    yield evt;
+                }
+                _ => yield evt,
+            }
+        }
+    };
+    Box::pin(s)
+}
+
+/// Closes an incomplete JSON document (open strings, objects, arrays) so it parses,
+/// backing off to the last complete member when the tail is mid-token.
+pub fn parse_partial(text: &str) -> Option<Value> {
+    let text = text.trim_start();
+    let start = text.find(['{', '['])?;
+    let text = &text[start..];
+
+    // Cut points: (byte offset to keep, open containers at that point).
+    let mut candidates: Vec<(usize, Vec<char>)> = Vec::new();
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                stack.push(if c == '{' { '}' } else { ']' });
+                candidates.push((i + 1, stack.clone()));
+            }
+            '}' | ']' => {
+                stack.pop();
+                candidates.push((i + 1, stack.clone()));
+                if stack.is_empty() {
+                    break;
+                }
+            }
+            ',' => candidates.push((i, stack.clone())),
+            _ => {}
+        }
+    }
+
+    // First try the whole buffer, closing an open string if needed.
+    let mut whole = text.to_string();
+    if in_string {
+        if escaped {
+            whole.pop();
+        }
+        whole.push('"');
+    }
+    if let Some(v) = try_close(&whole, &stack) {
+        return Some(v);
+    }
+
+    for (cut, open) in candidates.iter().rev() {
+        if let Some(v) = try_close(&text[..*cut], open) {
+            return Some(v);
+        }
+    }
+    None
+}
+
+fn try_close(prefix: &str, open: &[char]) -> Option<Value> {
+    let mut candidate = prefix.trim_end().trim_end_matches(',').to_string();
+    if candidate.ends_with(':') {
+        return None;
+    }
+    for closer in open.iter().rev() {
+        candidate.push(*closer);
+    }
+    serde_json::from_str(&candidate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_partial_closes_open_structures() {
+        assert_eq!(parse_partial(r#"{"rows": [{"name": "Al"#), Some(json!({"rows": [{"name": "Al"}]})));
+        assert_eq!(parse_partial(r#"{"a": 1, "b": tr"#), Some(json!({"a": 1})));
+        assert_eq!(parse_partial(r#"{"a": 1, "b":"#), Some(json!({"a": 1})));
+        assert_eq!(parse_partial(r#"[1, 2, 3"#), Some(json!([1, 2, 3])));
+        assert_eq!(parse_partial("no json yet"), None);
+    }
+
+    #[test]
+    fn test_assembler_validates_incrementally() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "count": { "type": "integer" }, "name": { "type": "string" } },
+            "required": ["count", "name"]
+        });
+        let mut assembler = StructuredAssembler::new(&schema);
+        match assembler.push(r#"{"name": "x""#) {
+            Some(BrainEvent::StructuredChunk { errors, complete, .. }) => {
+                assert!(!complete);
+                assert!(errors.is_empty(), "missing required fields are fine mid-stream");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match assembler.push(r#", "count": "three"}"#) {
+            Some(BrainEvent::StructuredChunk { errors, .. }) => assert_eq!(errors.len(), 1),
+            other => panic!("unexpected {:?}", other),
+        }
+        match assembler.finish() {
+            Some(BrainEvent::StructuredChunk { complete, errors, .. }) => {
+                assert!(complete);
+                assert_eq!(errors.len(), 1);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
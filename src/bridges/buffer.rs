@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, Notify};
+use tracing::warn;
+use crate::bridges::{CommBridge, FlushPolicy};
+use crate::conductor::events::SystemEvent;
+
+/// Events a bridge may fall behind by before the buffer starts coalescing.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 1024;
+
+/// Counters describing how a bridge has kept up.
+#[derive(Default)]
+pub struct BufferStats {
+    pub delivered: AtomicU64,
+    pub coalesced: AtomicU64,
+    pub dropped: AtomicU64,
+    pub blocked: AtomicU64,
+    pub failed: AtomicU64,
+}
+
+impl BufferStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "delivered {}, coalesced {}, dropped {}, blocked {}, failed {}",
+            self.delivered.load(Ordering::Relaxed),
+            self.coalesced.load(Ordering::Relaxed),
+            self.dropped.load(Ordering::Relaxed),
+            self.blocked.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Decouples the Conductor from a slow bridge. Events are queued and
+/// delivered in order by a background task. When `capacity` events are
+/// waiting, text deltas are merged into queued text first, debug events are
+/// dropped, and anything else makes `send` wait for room (backpressure).
+pub struct BufferedBridge {
+    inner: Arc<dyn CommBridge>,
+    capacity: usize,
+    queue: Mutex<VecDeque<SystemEvent>>,
+    ready: Notify,
+    // Events queued or being delivered.
+    pending: watch::Sender<usize>,
+    stats: BufferStats,
+}
+
+impl BufferedBridge {
+    /// Wraps `inner` and starts the delivery task on the current runtime.
+    pub fn spawn(inner: Arc<dyn CommBridge>, capacity: usize) -> Arc<Self> {
+        let bridge = Arc::new(Self {
+            inner,
+            capacity: capacity.max(1),
+            queue: Mutex::new(VecDeque::new()),
+            ready: Notify::new(),
+            pending: watch::channel(0).0,
+            stats: BufferStats::default(),
+        });
+        let worker = Arc::downgrade(&bridge);
+        tokio::spawn(async move {
+            loop {
+                let Some(bridge) = worker.upgrade() else {
+                    break;
+                };
+                let next = bridge.queue.lock().unwrap().pop_front();
+                match next {
+                    Some(event) => {
+                        match bridge.inner.send(event).await {
+                            Ok(()) => bridge.stats.delivered.fetch_add(1, Ordering::Relaxed),
+                            Err(e) => {
+                                warn!("Bridge failed to deliver an event: {}", e);
+                                bridge.stats.failed.fetch_add(1, Ordering::Relaxed)
+                            }
+                        };
+                        bridge.pending.send_modify(|n| *n -= 1);
+                    }
+                    None => {
+                        // Wakes up periodically so the task ends once the bridge is dropped.
+                        let _ = tokio::time::timeout(std::time::Duration::from_secs(1), bridge.ready.notified()).await;
+                    }
+                }
+            }
+        });
+        bridge
+    }
+
+    pub fn stats(&self) -> &BufferStats {
+        &self.stats
+    }
+
+    /// Waits until every queued event has been delivered.
+    pub async fn drain(&self) {
+        let _ = self.pending.subscribe().wait_for(|n| *n == 0).await;
+    }
+
+    fn enqueue(&self, queue: &mut VecDeque<SystemEvent>, event: SystemEvent) {
+        queue.push_back(event);
+        self.pending.send_modify(|n| *n += 1);
+        self.ready.notify_one();
+    }
+}
+
+/// Merges runs of adjacent text events; returns how many events were folded away.
+fn coalesce_text(queue: &mut VecDeque<SystemEvent>) -> usize {
+    let before = queue.len();
+    let mut merged: VecDeque<SystemEvent> = VecDeque::with_capacity(before);
+    for event in queue.drain(..) {
+        match (merged.back_mut(), event) {
+            (Some(SystemEvent::Text(last)), SystemEvent::Text(text)) => last.push_str(&text),
+            (_, event) => merged.push_back(event),
+        }
+    }
+    *queue = merged;
+    before - queue.len()
+}
+
+#[async_trait]
+impl CommBridge for BufferedBridge {
+    async fn send(&self, event: SystemEvent) -> Result<()> {
+        let mut event = Some(event);
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if queue.len() < self.capacity {
+                    self.enqueue(&mut queue, event.take().unwrap());
+                    return Ok(());
+                }
+                match event.as_ref() {
+                    Some(SystemEvent::Text(text)) => {
+                        if let Some(SystemEvent::Text(last)) = queue.back_mut() {
+                            last.push_str(text);
+                            self.stats.coalesced.fetch_add(1, Ordering::Relaxed);
+                            return Ok(());
+                        }
+                    }
+                    Some(SystemEvent::Debug { .. }) => {
+                        self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+                let folded = coalesce_text(&mut queue);
+                if folded > 0 {
+                    self.stats.coalesced.fetch_add(folded as u64, Ordering::Relaxed);
+                    self.pending.send_modify(|n| *n -= folded);
+                    continue;
+                }
+            }
+            self.stats.blocked.fetch_add(1, Ordering::Relaxed);
+            let capacity = self.capacity;
+            let _ = self.pending.subscribe().wait_for(|n| *n <= capacity).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn flush_policy(&self) -> Option<FlushPolicy> {
+        self.inner.flush_policy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Semaphore;
+
+    /// Delivers one event per permit so tests control how fast it keeps up.
+    struct Gated {
+        permits: Semaphore,
+        seen: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl CommBridge for Gated {
+        async fn send(&self, event: SystemEvent) -> Result<()> {
+            self.permits.acquire().await?.forget();
+            self.seen.lock().unwrap().push(format!("{:?}", event));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overflow_coalesces_text_and_drops_debug() -> Result<()> {
+        let inner = Arc::new(Gated { permits: Semaphore::new(0), seen: Mutex::new(Vec::new()) });
+        let bridge = BufferedBridge::spawn(inner.clone(), 2);
+        bridge.send(SystemEvent::Info("start".into())).await?;
+        bridge.send(SystemEvent::Text("a".into())).await?;
+        bridge.send(SystemEvent::Text("b".into())).await?;
+        bridge.send(SystemEvent::Text("c".into())).await?;
+        bridge.send(SystemEvent::Debug { message: "noise".into(), request_id: None }).await?;
+        assert_eq!(bridge.stats().coalesced.load(Ordering::Relaxed), 2);
+        assert_eq!(bridge.stats().dropped.load(Ordering::Relaxed), 1);
+
+        inner.permits.add_permits(10);
+        bridge.drain().await;
+        let seen = inner.seen.lock().unwrap().clone();
+        assert_eq!(seen, vec!["Info(\"start\")", "Text(\"abc\")"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use tokio::sync::{mpsc, Mutex};
+use anyhow::Result;
+use crate::bridges::CommBridge;
+use crate::conductor::events::{UserEvent, SystemEvent};
+
+/// A non-interactive bridge that collects model output in memory and
+/// auto-approves tool calls. Only give it a registry restricted to tools
+/// the user has explicitly allowed.
+pub struct HeadlessBridge {
+    tx: mpsc::Sender<UserEvent>,
+    output: Mutex<String>,
+    errors: Mutex<Vec<String>>,
+}
+
+impl HeadlessBridge {
+    pub fn new() -> (Self, mpsc::Receiver<UserEvent>) {
+        let (tx, rx) = mpsc::channel(100);
+        (Self { tx, output: Mutex::new(String::new()), errors: Mutex::new(Vec::new()) }, rx)
+    }
+
+    pub async fn output(&self) -> String {
+        self.output.lock().await.clone()
+    }
+
+    pub async fn errors(&self) -> Vec<String> {
+        self.errors.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl CommBridge for HeadlessBridge {
+    fn name(&self) -> &'static str {
+        "headless"
+    }
+
+    async fn send(&self, event: SystemEvent) -> Result<()> {
+        match event {
+            SystemEvent::Text(text) => {
+                self.output.lock().await.push_str(&text);
+            }
+            SystemEvent::Error(err) => {
+                self.errors.lock().await.push(err);
+            }
+            SystemEvent::RequestApproval { .. } => {
+                self.tx.send(UserEvent::Approve).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
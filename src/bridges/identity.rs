@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::tools::ToolRegistry;
+
+/// Per-user overrides of the daemon's settings; unset fields keep the defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserPolicy {
+    /// Tools this user may see; all registered tools when unset.
+    pub tools: Option<Vec<String>>,
+    pub auto_approve: Option<Vec<String>>,
+    pub read_only: Option<bool>,
+    pub language: Option<String>,
+}
+
+impl UserPolicy {
+    /// The registry a session for this user should get.
+    pub fn registry(&self, tools: &ToolRegistry) -> ToolRegistry {
+        let names = self.tools.clone().unwrap_or_else(|| tools.names());
+        let registry = tools.subset(&names);
+        if let Some(read_only) = self.read_only {
+            registry.set_read_only(read_only);
+        }
+        registry
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    token: Option<String>,
+    #[serde(flatten)]
+    policy: UserPolicy,
+}
+
+/// Who may talk to a networked bridge, loaded from YAML:
+///
+/// ```yaml
+/// alice: { token: s3cret }
+/// bob: { token: hunter2, read_only: true, tools: [execute_bash] }
+/// ```
+///
+/// Bridges that already authenticate users (e.g. a chat service's user id)
+/// may omit tokens; bridges that can't must require one.
+#[derive(Debug, Default)]
+pub struct Identities {
+    users: HashMap<String, User>,
+}
+
+impl Identities {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read users file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid users file {}", path.display()))
+    }
+
+    /// Users who need no token and keep the default policy, e.g. chat user
+    /// ids the chat service has already authenticated.
+    pub fn from_ids(ids: &[String]) -> Self {
+        let users = ids.iter()
+            .map(|id| (id.clone(), User { token: None, policy: UserPolicy::default() }))
+            .collect();
+        Self { users }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let users: HashMap<String, User> = serde_yaml::from_str(text)?;
+        for user in users.values() {
+            if let Some(token) = &user.token {
+                crate::redact::register_secret(token);
+            }
+        }
+        Ok(Self { users })
+    }
+
+    /// Returns the user's policy if they are on the allow-list and, when they
+    /// have a token configured, presented it.
+    pub fn authorize(&self, user_id: &str, token: Option<&str>) -> Result<&UserPolicy> {
+        let user = self.users.get(user_id).context("Unknown user")?;
+        if let Some(expected) = &user.token {
+            let presented = token.unwrap_or_default();
+            anyhow::ensure!(constant_time_eq(expected.as_bytes(), presented.as_bytes()), "Invalid token");
+        }
+        Ok(&user.policy)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorize_users_and_apply_policy() -> Result<()> {
+        let identities = Identities::parse("alice: { token: s3cret }\nbob: { read_only: true, language: ta }\n")?;
+        assert!(identities.authorize("alice", Some("s3cret")).is_ok());
+        assert!(identities.authorize("alice", Some("wrong")).is_err());
+        assert!(identities.authorize("alice", None).is_err());
+        assert!(identities.authorize("mallory", None).is_err());
+        let bob = identities.authorize("bob", None)?;
+        assert_eq!(bob.read_only, Some(true));
+        assert_eq!(bob.language.as_deref(), Some("ta"));
+
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(crate::tools::bash::BashTool::default()));
+        tools.register(Box::new(crate::tools::truncate::ReadToolOutputTool::new(Default::default())));
+        let registry = bob.registry(&tools);
+        assert!(registry.is_read_only());
+        assert!(!tools.is_read_only());
+        let carol = UserPolicy { tools: Some(vec!["read_tool_output".to_string()]), ..Default::default() };
+        assert_eq!(carol.registry(&tools).names(), ["read_tool_output"]);
+
+        let ids = Identities::from_ids(&["U1".to_string()]);
+        assert!(ids.authorize("U1", None).is_ok());
+        assert!(ids.authorize("U2", None).is_err());
+        assert!(Identities::from_ids(&[]).is_empty());
+        Ok(())
+    }
+}
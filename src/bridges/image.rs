@@ -0,0 +1,118 @@
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::imageops::FilterType;
+use std::env;
+
+/// How the terminal can display images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    Kitty,
+    Iterm2,
+    /// Unicode half blocks with 24-bit colour; works in any modern terminal.
+    Blocks,
+}
+
+impl ImageProtocol {
+    /// Picks the best protocol from the environment the terminal exports.
+    pub fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+        if env::var("KITTY_WINDOW_ID").is_ok() || term.contains("kitty") || term_program == "ghostty" {
+            ImageProtocol::Kitty
+        } else if matches!(term_program.as_str(), "iTerm.app" | "WezTerm") {
+            ImageProtocol::Iterm2
+        } else {
+            ImageProtocol::Blocks
+        }
+    }
+}
+
+/// Renders image bytes as a string of terminal escapes, at most `max_cols` wide.
+pub fn render(protocol: ImageProtocol, bytes: &[u8], max_cols: u32) -> Result<String> {
+    match protocol {
+        ImageProtocol::Kitty => {
+            // Kitty only takes PNG directly, so normalise other formats first.
+            let png = if bytes.starts_with(b"\x89PNG") {
+                bytes.to_vec()
+            } else {
+                let mut out = std::io::Cursor::new(Vec::new());
+                image::load_from_memory(bytes)?.write_to(&mut out, image::ImageFormat::Png)?;
+                out.into_inner()
+            };
+            Ok(kitty(&png, max_cols))
+        }
+        ImageProtocol::Iterm2 => Ok(format!(
+            "\x1b]1337;File=inline=1;size={};width={};preserveAspectRatio=1:{}\x07\n",
+            bytes.len(),
+            max_cols,
+            STANDARD.encode(bytes)
+        )),
+        ImageProtocol::Blocks => blocks(bytes, max_cols),
+    }
+}
+
+fn kitty(png: &[u8], max_cols: u32) -> String {
+    let encoded = STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!("\x1b_Gf=100,a=T,c={},m={};{}\x1b\\", max_cols, more, chunk));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Two pixels per cell: the upper one as foreground of '▀', the lower as background.
+fn blocks(bytes: &[u8], max_cols: u32) -> Result<String> {
+    let img = image::load_from_memory(bytes)?;
+    let cols = img.width().min(max_cols).max(1);
+    let rows = ((img.height() as f64 * cols as f64 / img.width().max(1) as f64) as u32).max(2);
+    let img = img.resize_exact(cols, rows, FilterType::Triangle).to_rgb8();
+
+    let mut out = String::new();
+    for y in (0..rows - 1).step_by(2) {
+        for x in 0..cols {
+            let top = img.get_pixel(x, y);
+            let bottom = img.get_pixel(x, y + 1);
+            out.push_str(&format!(
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀",
+                top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+            ));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_png() -> Vec<u8> {
+        let img = image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]));
+        let mut out = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut out, image::ImageFormat::Png).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_render_protocols() {
+        let png = tiny_png();
+        let blocks = render(ImageProtocol::Blocks, &png, 40).unwrap();
+        assert_eq!(blocks.lines().count(), 2);
+        assert!(blocks.contains("\x1b[38;2;255;0;0m"));
+
+        let kitty = render(ImageProtocol::Kitty, &png, 40).unwrap();
+        assert!(kitty.starts_with("\x1b_Gf=100,a=T,c=40,m=0;"));
+
+        let iterm = render(ImageProtocol::Iterm2, &png, 40).unwrap();
+        assert!(iterm.contains(&format!("size={}", png.len())));
+    }
+}
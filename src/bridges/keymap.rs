@@ -0,0 +1,149 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What a typed line can trigger in the TUI before it is treated as a
+/// message or command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Approve,
+    Reject,
+    Cancel,
+    Exit,
+    Palette,
+    Panic,
+    Thoughts,
+}
+
+/// The TUI reads whole lines, so a binding is what the user types and
+/// submits: a word (matched whole, ignoring case) or a `/command` (matched
+/// as the first word, the rest passed along). Nothing exits on a single
+/// keystroke, and exit is only bound to explicit commands by default.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keymap {
+    pub approve: Vec<String>,
+    pub reject: Vec<String>,
+    pub cancel: Vec<String>,
+    pub exit: Vec<String>,
+    pub palette: Vec<String>,
+    pub panic: Vec<String>,
+    pub thoughts: Vec<String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect();
+        Self {
+            approve: keys(&["y", "yes"]),
+            reject: keys(&["n", "no"]),
+            cancel: keys(&["/cancel"]),
+            exit: keys(&["/exit", "/quit"]),
+            palette: keys(&["/palette", "/p"]),
+            panic: keys(&["/panic"]),
+            thoughts: keys(&["/thoughts"]),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    keys: Option<Keymap>,
+}
+
+impl Keymap {
+    /// Reads `[keys]` from the config file; actions it leaves out keep their
+    /// defaults. A missing file or section means all defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let Some(text) = path.and_then(|p| std::fs::read_to_string(p).ok()) else {
+            return Ok(Self::default());
+        };
+        let file: ConfigFile = toml::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.unwrap_or(Path::new("")).display()))?;
+        let keymap = file.keys.unwrap_or_default();
+        keymap.check()?;
+        Ok(keymap)
+    }
+
+    fn bindings(&self) -> [(Action, &[String]); 7] {
+        [
+            (Action::Approve, &self.approve),
+            (Action::Reject, &self.reject),
+            (Action::Cancel, &self.cancel),
+            (Action::Exit, &self.exit),
+            (Action::Palette, &self.palette),
+            (Action::Panic, &self.panic),
+            (Action::Thoughts, &self.thoughts),
+        ]
+    }
+
+    /// Rejects empty bindings and one binding used for two actions.
+    fn check(&self) -> Result<()> {
+        let mut seen: HashMap<String, Action> = HashMap::new();
+        for (action, keys) in self.bindings() {
+            for key in keys {
+                let key = key.trim().to_lowercase();
+                anyhow::ensure!(!key.is_empty() && !key.contains(char::is_whitespace), "Invalid key binding '{}' for {:?}", key, action);
+                if let Some(other) = seen.insert(key.clone(), action) {
+                    anyhow::ensure!(other == action, "'{}' is bound to both {:?} and {:?}", key, other, action);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The action `line` triggers, with the text after a `/command` binding.
+    pub fn action<'a>(&self, line: &'a str) -> Option<(Action, &'a str)> {
+        let line = line.trim();
+        let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        self.bindings().into_iter().find_map(|(action, keys)| {
+            keys.iter().find_map(|key| {
+                let key = key.trim();
+                if key.starts_with('/') {
+                    first.eq_ignore_ascii_case(key).then(|| (action, rest.trim()))
+                } else {
+                    line.eq_ignore_ascii_case(key).then_some((action, ""))
+                }
+            })
+        })
+    }
+
+    /// The first binding of each action, for the help text.
+    pub fn summary(&self) -> String {
+        self.bindings().iter()
+            .filter_map(|(action, keys)| keys.first().map(|k| format!("{:?}: {}", action, k).to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_defaults_and_overrides() -> Result<()> {
+        let defaults = Keymap::default();
+        assert_eq!(defaults.action(" Yes "), Some((Action::Approve, "")));
+        assert_eq!(defaults.action("/p deploy"), Some((Action::Palette, "deploy")));
+        assert_eq!(defaults.action("yes please"), None);
+        assert_eq!(defaults.action("/panicky"), None);
+
+        let path = std::env::temp_dir().join(format!("chitti-keys-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "[keys]\napprove = [\"a\"]\nexit = [\"/q\"]\npalette = [\"/k\"]\n")?;
+        let keymap = Keymap::load(Some(&path))?;
+        assert_eq!(keymap.action("a"), Some((Action::Approve, "")));
+        assert_eq!(keymap.action("y"), None);
+        assert_eq!(keymap.action("/exit"), None);
+        assert_eq!(keymap.action("/q"), Some((Action::Exit, "")));
+        assert_eq!(keymap.action("n"), Some((Action::Reject, "")));
+
+        std::fs::write(&path, "[keys]\ncancel = [\"n\"]\n")?;
+        assert!(Keymap::load(Some(&path)).unwrap_err().to_string().contains("bound to both"));
+        std::fs::remove_file(&path)?;
+        assert_eq!(Keymap::load(None)?, defaults);
+        Ok(())
+    }
+}
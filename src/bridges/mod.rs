@@ -1,12 +1,50 @@
 use async_trait::async_trait;
 use anyhow::Result;
-use crate::conductor::events::SystemEvent;
+use crate::conductor::events::{Sequenced, SystemEvent};
+use std::time::Duration;
 
 pub mod tui;
+pub mod buffer;
 pub mod mock;
+pub mod headless;
+pub mod identity;
+pub mod image;
+pub mod keymap;
+pub mod sequence;
+pub mod slack;
+pub mod thoughts;
+pub mod wrap;
 
 #[async_trait]
 pub trait CommBridge: Send + Sync {
     // Sends a message/update back to the user
     async fn send(&self, event: SystemEvent) -> Result<()>;
+
+    // Identifies the bridge in configuration such as CHITTI_BRIDGE_TOOLS.
+    fn name(&self) -> &'static str {
+        "unknown"
+    }
+
+    // How streamed text should be batched before it reaches this bridge.
+    // `None` delivers every delta as it arrives.
+    fn flush_policy(&self) -> Option<FlushPolicy> {
+        None
+    }
+
+    // Receives events with their sequence and turn ids. Bridges on unreliable
+    // transports override this to detect gaps and deduplicate; the rest keep
+    // the plain `send`.
+    async fn send_sequenced(&self, event: Sequenced) -> Result<()> {
+        self.send(event.event).await
+    }
+}
+
+/// Batches streamed text for bridges that rate-limit updates (e.g. chat
+/// services where each delta becomes a message edit). Buffered text is sent
+/// once `interval` has passed since the first buffered delta or once it
+/// reaches `max_chars`, whichever comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlushPolicy {
+    pub interval: Duration,
+    pub max_chars: usize,
 }
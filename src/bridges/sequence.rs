@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use crate::bridges::{CommBridge, FlushPolicy};
+use crate::conductor::events::{Sequenced, SystemEvent};
+
+/// How many recent events are kept for `replay`.
+pub const DEFAULT_REPLAY_CAPACITY: usize = 256;
+
+struct State {
+    next_seq: u64,
+    turn: u64,
+    buffer: VecDeque<Sequenced>,
+}
+
+/// Wraps a bridge, numbering every event it sends and keeping the most
+/// recent ones so a bridge that lost some (e.g. over a reconnect) can ask
+/// for them again.
+pub struct SequencedBridge {
+    inner: Arc<dyn CommBridge>,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+impl SequencedBridge {
+    pub fn new(inner: Arc<dyn CommBridge>, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            state: Mutex::new(State { next_seq: 1, turn: 0, buffer: VecDeque::new() }),
+        }
+    }
+
+    /// Starts a new turn; events sent from now on carry the next turn id.
+    pub fn begin_turn(&self) {
+        self.state.lock().unwrap().turn += 1;
+    }
+
+    /// Re-sends buffered events with `seq > after`. If some of the requested
+    /// events have already left the buffer, a warning goes out first.
+    pub async fn replay(&self, after: u64) -> Result<()> {
+        let (missed, events) = {
+            let state = self.state.lock().unwrap();
+            let oldest = state.buffer.front().map_or(state.next_seq, |e| e.seq);
+            let events: Vec<Sequenced> = state.buffer.iter().filter(|e| e.seq > after).cloned().collect();
+            (oldest > after + 1, events)
+        };
+        if missed {
+            self.send(SystemEvent::Warning(format!(
+                "Some events after #{} are no longer available for replay",
+                after
+            ))).await?;
+        }
+        for event in events {
+            self.inner.send_sequenced(event).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommBridge for SequencedBridge {
+    async fn send(&self, event: SystemEvent) -> Result<()> {
+        let sequenced = {
+            let mut state = self.state.lock().unwrap();
+            let sequenced = Sequenced { seq: state.next_seq, turn: state.turn, event };
+            state.next_seq += 1;
+            state.buffer.push_back(sequenced.clone());
+            if state.buffer.len() > self.capacity {
+                state.buffer.pop_front();
+            }
+            sequenced
+        };
+        self.inner.send_sequenced(sequenced).await
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn flush_policy(&self) -> Option<FlushPolicy> {
+        self.inner.flush_policy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder(Mutex<Vec<(u64, u64)>>);
+
+    #[async_trait]
+    impl CommBridge for Recorder {
+        async fn send(&self, _event: SystemEvent) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_sequenced(&self, event: Sequenced) -> Result<()> {
+            self.0.lock().unwrap().push((event.seq, event.turn));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sequence_ids_and_replay() -> Result<()> {
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let bridge = SequencedBridge::new(recorder.clone(), 2);
+        bridge.send(SystemEvent::Info("a".into())).await?;
+        bridge.begin_turn();
+        bridge.send(SystemEvent::Text("b".into())).await?;
+        bridge.send(SystemEvent::Text("c".into())).await?;
+        assert_eq!(*recorder.0.lock().unwrap(), vec![(1, 0), (2, 1), (3, 1)]);
+
+        recorder.0.lock().unwrap().clear();
+        bridge.replay(2).await?;
+        assert_eq!(*recorder.0.lock().unwrap(), vec![(3, 1)]);
+
+        // Event 1 fell out of the two-event buffer, so a warning (seq 4) precedes the replay.
+        recorder.0.lock().unwrap().clear();
+        bridge.replay(0).await?;
+        assert_eq!(*recorder.0.lock().unwrap(), vec![(4, 1), (2, 1), (3, 1)]);
+        Ok(())
+    }
+}
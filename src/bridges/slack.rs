@@ -0,0 +1,375 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
+use crate::bridges::{CommBridge, FlushPolicy};
+use crate::conductor::events::{SystemEvent, UserEvent};
+
+const API: &str = "https://slack.com/api";
+/// Longest reply kept in one Slack message; longer replies continue in a new one.
+const MESSAGE_CHARS: usize = 3500;
+
+/// The Slack Web API methods the bridge needs.
+#[derive(Clone)]
+pub struct SlackApi {
+    http: reqwest::Client,
+    bot_token: String,
+}
+
+impl SlackApi {
+    pub fn new(bot_token: String) -> Self {
+        Self { http: reqwest::Client::new(), bot_token }
+    }
+
+    async fn call(&self, method: &str, token: &str, body: Value) -> Result<Value> {
+        let reply: Value = self.http
+            .post(format!("{}/{}", API, method))
+            .bearer_auth(token)
+            .timeout(Duration::from_secs(20))
+            .json(&body)
+            .send().await?
+            .error_for_status()?
+            .json().await?;
+        if reply.get("ok").and_then(Value::as_bool) != Some(true) {
+            let error = reply.get("error").and_then(Value::as_str).unwrap_or("unknown error");
+            anyhow::bail!("Slack {} failed: {}", method, error);
+        }
+        Ok(reply)
+    }
+
+    /// A fresh Socket Mode WebSocket URL; each one is good for a single connection.
+    pub async fn open_connection(&self, app_token: &str) -> Result<String> {
+        let reply = self.call("apps.connections.open", app_token, json!({})).await?;
+        reply.get("url").and_then(Value::as_str).map(str::to_string)
+            .context("apps.connections.open returned no URL")
+    }
+
+    /// The bot's own user id, to recognise mentions and skip its own messages.
+    pub async fn bot_user_id(&self) -> Result<String> {
+        let reply = self.call("auth.test", &self.bot_token, json!({})).await?;
+        reply.get("user_id").and_then(Value::as_str).map(str::to_string)
+            .context("auth.test returned no user id")
+    }
+
+    /// Posts in the thread and returns the new message's timestamp.
+    pub async fn post(&self, thread: &ThreadKey, text: &str, blocks: Option<Value>) -> Result<String> {
+        let mut body = json!({ "channel": thread.channel, "thread_ts": thread.thread_ts, "text": text });
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks;
+        }
+        let reply = self.call("chat.postMessage", &self.bot_token, body).await?;
+        reply.get("ts").and_then(Value::as_str).map(str::to_string)
+            .context("chat.postMessage returned no timestamp")
+    }
+
+    pub async fn update(&self, channel: &str, ts: &str, text: &str, blocks: Option<Value>) -> Result<()> {
+        let body = json!({ "channel": channel, "ts": ts, "text": text, "blocks": blocks.unwrap_or(json!([])) });
+        self.call("chat.update", &self.bot_token, body).await?;
+        Ok(())
+    }
+}
+
+/// A Slack thread, identified by its channel and the timestamp of its first message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadKey {
+    pub channel: String,
+    pub thread_ts: String,
+}
+
+/// What a Socket Mode envelope asks of the bot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming {
+    /// `mention` is true for @-mentions and direct messages, which may start
+    /// a session; other thread replies only reach a session already running.
+    Message { thread: ThreadKey, user: String, text: String, mention: bool },
+    /// A click on an approval button.
+    Decision { thread: ThreadKey, user: String, approve: bool },
+}
+
+/// A Socket Mode envelope: `id` must be acknowledged, `kind` is e.g.
+/// `events_api`, `interactive`, `hello` or `disconnect`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub id: Option<String>,
+    pub kind: String,
+    pub incoming: Option<Incoming>,
+}
+
+pub fn parse_envelope(envelope: &Value, bot_user: &str) -> Envelope {
+    let str_at = |value: &Value, pointer: &str| value.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+    let kind = str_at(envelope, "/type").unwrap_or_default();
+    let payload = envelope.get("payload").cloned().unwrap_or(Value::Null);
+    let incoming = match kind.as_str() {
+        "events_api" => parse_event(payload.get("event").unwrap_or(&Value::Null), bot_user),
+        "interactive" if str_at(&payload, "/type").as_deref() == Some("block_actions") => {
+            let approve = match str_at(&payload, "/actions/0/action_id").as_deref() {
+                Some("approve") => Some(true),
+                Some("reject") => Some(false),
+                _ => None,
+            };
+            let thread_ts = str_at(&payload, "/message/thread_ts").or_else(|| str_at(&payload, "/container/thread_ts"));
+            match (approve, str_at(&payload, "/channel/id"), thread_ts, str_at(&payload, "/user/id")) {
+                (Some(approve), Some(channel), Some(thread_ts), Some(user)) => {
+                    Some(Incoming::Decision { thread: ThreadKey { channel, thread_ts }, user, approve })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    Envelope { id: str_at(envelope, "/envelope_id"), kind, incoming }
+}
+
+fn parse_event(event: &Value, bot_user: &str) -> Option<Incoming> {
+    let field = |name: &str| event.get(name).and_then(Value::as_str);
+    let user = field("user")?;
+    if user == bot_user || event.get("bot_id").is_some() || event.get("subtype").is_some() {
+        return None;
+    }
+    let mention_tag = format!("<@{}>", bot_user);
+    let text = field("text").unwrap_or_default();
+    let mention = match field("type")? {
+        "app_mention" => true,
+        "message" if field("channel_type") == Some("im") => true,
+        // Mentions in channels also arrive as app_mention; answer those once.
+        "message" if text.contains(&mention_tag) => return None,
+        "message" => false,
+        _ => return None,
+    };
+    let text = text.replace(&mention_tag, " ").trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    let thread_ts = field("thread_ts").or(field("ts"))?.to_string();
+    Some(Incoming::Message {
+        thread: ThreadKey { channel: field("channel")?.to_string(), thread_ts },
+        user: user.to_string(),
+        text,
+        mention,
+    })
+}
+
+/// Splits `text` into pieces of at most `max` characters, preferring to
+/// break after a newline.
+fn split_message(text: &str, max: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max {
+        let limit = rest.char_indices().nth(max).map(|(i, _)| i).unwrap_or(rest.len());
+        let cut = rest[..limit].rfind('\n').map(|i| i + 1).filter(|&i| i > limit / 2).unwrap_or(limit);
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// The message a reply is being streamed into.
+struct Reply {
+    ts: String,
+    text: String,
+}
+
+/// One Slack thread's side of a conversation. Streamed text is posted once
+/// and then edited in place as it grows; tool approvals become Approve and
+/// Reject buttons that only the thread's owner can use.
+pub struct SlackBridge {
+    api: SlackApi,
+    thread: ThreadKey,
+    /// The user who started the thread's session: the only one whose
+    /// messages start turns and whose clicks decide approvals.
+    owner: String,
+    tx: mpsc::Sender<UserEvent>,
+    reply: Mutex<Option<Reply>>,
+    /// The approval message waiting for a click, with its description.
+    approval: Mutex<Option<(String, String)>>,
+}
+
+impl SlackBridge {
+    pub fn new(api: SlackApi, thread: ThreadKey, owner: String) -> (Self, mpsc::Receiver<UserEvent>) {
+        let (tx, rx) = mpsc::channel(100);
+        (Self { api, thread, owner, tx, reply: Mutex::new(None), approval: Mutex::new(None) }, rx)
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Whether the thread's conductor has stopped (e.g. after `/exit`).
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Hands a message from the thread to the conductor. The answer starts a
+    /// new Slack message rather than extending the previous one.
+    pub async fn deliver(&self, text: String) -> Result<()> {
+        *self.reply.lock().await = None;
+        let event = if text.starts_with('/') { UserEvent::Command(text) } else { UserEvent::Message(text) };
+        self.tx.send(event).await?;
+        Ok(())
+    }
+
+    /// Applies a button click, replacing the buttons with who decided.
+    /// Clicks by anyone but the owner, and on an approval that was already
+    /// answered, are ignored.
+    pub async fn decide(&self, approve: bool, user: &str) -> Result<()> {
+        if user != self.owner {
+            debug!(user = %user, owner = %self.owner, "Ignoring a Slack approval click from someone other than the thread's owner");
+            return Ok(());
+        }
+        let Some((ts, description)) = self.approval.lock().await.take() else {
+            return Ok(());
+        };
+        self.tx.send(if approve { UserEvent::Approve } else { UserEvent::Reject }).await?;
+        let verdict = format!("{} by <@{}>", if approve { "Approved" } else { "Rejected" }, user);
+        let blocks = json!([
+            { "type": "section", "text": { "type": "mrkdwn", "text": description } },
+            { "type": "context", "elements": [{ "type": "mrkdwn", "text": verdict }] },
+        ]);
+        self.api.update(&self.thread.channel, &ts, &verdict, Some(blocks)).await
+    }
+
+    async fn append_text(&self, text: &str) -> Result<()> {
+        let mut reply = self.reply.lock().await;
+        let mut rest = text;
+        if let Some(current) = reply.as_mut() {
+            let room = MESSAGE_CHARS.saturating_sub(current.text.chars().count());
+            let split = rest.char_indices().nth(room).map(|(i, _)| i).unwrap_or(rest.len());
+            if split > 0 {
+                current.text.push_str(&rest[..split]);
+                self.api.update(&self.thread.channel, &current.ts, &current.text, None).await?;
+                rest = &rest[split..];
+            }
+        }
+        for piece in split_message(rest, MESSAGE_CHARS) {
+            let ts = self.api.post(&self.thread, piece, None).await?;
+            *reply = Some(Reply { ts, text: piece.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Posts a standalone message; the next text starts below it.
+    async fn post(&self, text: &str) -> Result<()> {
+        *self.reply.lock().await = None;
+        self.api.post(&self.thread, text, None).await?;
+        Ok(())
+    }
+
+    async fn request_approval(&self, description: String) -> Result<()> {
+        *self.reply.lock().await = None;
+        let blocks = json!([
+            { "type": "section", "text": { "type": "mrkdwn", "text": description } },
+            { "type": "actions", "elements": [
+                { "type": "button", "action_id": "approve", "style": "primary", "text": { "type": "plain_text", "text": "Approve" } },
+                { "type": "button", "action_id": "reject", "style": "danger", "text": { "type": "plain_text", "text": "Reject" } },
+            ] },
+        ]);
+        let ts = self.api.post(&self.thread, &description, Some(blocks)).await?;
+        *self.approval.lock().await = Some((ts, description));
+        Ok(())
+    }
+
+    async fn post_event(&self, event: SystemEvent) -> Result<()> {
+        match event {
+            SystemEvent::Text(text) => self.append_text(&text).await,
+            SystemEvent::ToolCall { name, .. } => self.post(&format!(":hammer_and_wrench: `{}`", name)).await,
+            SystemEvent::RequestApproval { description } => self.request_approval(description).await,
+            SystemEvent::Error(err) => self.post(&format!(":x: {}", err)).await,
+            SystemEvent::Warning(msg) => self.post(&format!(":warning: {}", msg)).await,
+            SystemEvent::Info(msg) => self.post(&msg).await,
+            SystemEvent::StructuredChunk { value, complete: true, .. } => {
+                self.post(&format!("```{}```", serde_json::to_string_pretty(&value)?)).await
+            }
+            SystemEvent::Artifact { path, .. } => self.post(&format!("Saved {}", path.display())).await,
+            SystemEvent::Shutdown { reason } => self.post(&format!("Session ended: {}", reason)).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommBridge for SlackBridge {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    // Each flush is a chat.update, which Slack rate-limits to about one per second.
+    fn flush_policy(&self) -> Option<FlushPolicy> {
+        Some(FlushPolicy { interval: Duration::from_millis(1200), max_chars: 1500 })
+    }
+
+    async fn send(&self, event: SystemEvent) -> Result<()> {
+        // A failed post shouldn't end the thread's session.
+        if let Err(e) = self.post_event(event).await {
+            warn!(channel = %self.thread.channel, thread = %self.thread.thread_ts, "Slack post failed: {:#}", e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slack_envelopes() {
+        let thread = |ts: &str| ThreadKey { channel: "C1".to_string(), thread_ts: ts.to_string() };
+        let mention = json!({
+            "envelope_id": "e1", "type": "events_api",
+            "payload": { "event": { "type": "app_mention", "user": "U1", "channel": "C1", "ts": "100.1", "text": "<@UBOT> check disk usage" } }
+        });
+        let envelope = parse_envelope(&mention, "UBOT");
+        assert_eq!(envelope.id.as_deref(), Some("e1"));
+        assert_eq!(envelope.incoming, Some(Incoming::Message {
+            thread: thread("100.1"), user: "U1".to_string(), text: "check disk usage".to_string(), mention: true,
+        }));
+
+        let reply = json!({ "type": "events_api", "payload": { "event": {
+            "type": "message", "channel_type": "channel", "user": "U2", "channel": "C1", "ts": "101.0", "thread_ts": "100.1", "text": "and memory?"
+        } } });
+        assert_eq!(parse_envelope(&reply, "UBOT").incoming, Some(Incoming::Message {
+            thread: thread("100.1"), user: "U2".to_string(), text: "and memory?".to_string(), mention: false,
+        }));
+        // The same mention also arrives as a message event, and the bot's own posts come back too.
+        let duplicate = json!({ "type": "events_api", "payload": { "event": {
+            "type": "message", "channel_type": "channel", "user": "U1", "channel": "C1", "ts": "100.1", "text": "<@UBOT> check disk usage"
+        } } });
+        assert_eq!(parse_envelope(&duplicate, "UBOT").incoming, None);
+        let own = json!({ "type": "events_api", "payload": { "event": { "type": "message", "user": "UBOT", "channel": "C1", "ts": "102.0", "text": "Done" } } });
+        assert_eq!(parse_envelope(&own, "UBOT").incoming, None);
+
+        let click = json!({
+            "envelope_id": "e2", "type": "interactive",
+            "payload": {
+                "type": "block_actions", "user": { "id": "U2" }, "channel": { "id": "C1" },
+                "message": { "ts": "103.0", "thread_ts": "100.1" },
+                "actions": [{ "action_id": "reject" }]
+            }
+        });
+        assert_eq!(parse_envelope(&click, "UBOT").incoming, Some(Incoming::Decision { thread: thread("100.1"), user: "U2".to_string(), approve: false }));
+        assert_eq!(parse_envelope(&json!({ "type": "disconnect" }), "UBOT").kind, "disconnect");
+    }
+
+    #[tokio::test]
+    async fn test_only_the_owner_decides_approvals() -> Result<()> {
+        let thread = ThreadKey { channel: "C1".to_string(), thread_ts: "100.1".to_string() };
+        let (bridge, mut rx) = SlackBridge::new(SlackApi::new("xoxb-test".to_string()), thread, "U1".to_string());
+        *bridge.approval.lock().await = Some(("101.0".to_string(), "Execute tool 'execute_bash'".to_string()));
+        bridge.decide(true, "U2").await?;
+        assert!(rx.try_recv().is_err());
+        assert!(bridge.approval.lock().await.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_long_replies() {
+        assert_eq!(split_message("short", 10), ["short"]);
+        assert_eq!(split_message("first line\nsecond", 14), ["first line\n", "second"]);
+        assert_eq!(split_message("ααααα", 2), ["αα", "αα", "α"]);
+        assert!(split_message("", 10).is_empty());
+    }
+}
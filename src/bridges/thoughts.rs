@@ -0,0 +1,121 @@
+use crate::bridges::wrap;
+
+/// Below this terminal width there is no room for a side pane.
+const MIN_TERMINAL_WIDTH: usize = 80;
+const MAX_PANE_WIDTH: usize = 60;
+const MAX_PANE_HEIGHT: usize = 16;
+
+/// The model's thought summary for the current turn, drawn in a pane at the
+/// top right of the terminal instead of interleaved with the answer. The
+/// pane is redrawn in place as thoughts stream in and emptied per turn.
+pub struct ThoughtPane {
+    text: String,
+    expanded: bool,
+    /// Whether the collapsed hint was printed for this turn.
+    hinted: bool,
+}
+
+impl Default for ThoughtPane {
+    fn default() -> Self {
+        Self { text: String::new(), expanded: true, hinted: false }
+    }
+}
+
+impl ThoughtPane {
+    pub fn push(&mut self, delta: &str) {
+        self.text.push_str(delta);
+    }
+
+    /// Starts a new turn.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.hinted = false;
+    }
+
+    /// Expands or collapses the pane; returns whether it is now expanded.
+    pub fn toggle(&mut self) -> bool {
+        self.expanded = !self.expanded;
+        self.expanded
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    /// Cells taken from the right of the terminal; 0 when nothing is drawn.
+    pub fn width(&self, terminal_width: usize) -> usize {
+        if !self.expanded || self.is_empty() || terminal_width < MIN_TERMINAL_WIDTH {
+            return 0;
+        }
+        (terminal_width / 3).min(MAX_PANE_WIDTH)
+    }
+
+    /// A one-off dim line for when thoughts arrive but no pane is drawn.
+    pub fn hint(&mut self) -> Option<&'static str> {
+        if self.hinted || self.is_empty() {
+            return None;
+        }
+        self.hinted = true;
+        Some("[thinking... /thoughts to show the thought pane]")
+    }
+
+    /// The pane's rows: a header, then the newest wrapped thought lines that
+    /// fit, each padded to `width` cells including the left border.
+    pub fn lines(&self, width: usize, height: usize) -> Vec<String> {
+        let inner = width.saturating_sub(2).max(1);
+        let rows = height.clamp(2, MAX_PANE_HEIGHT);
+        let wrapped: Vec<String> = wrap::wrap_lines(self.text.trim(), inner)
+            .into_iter()
+            .filter(|l| !l.is_empty())
+            .collect();
+        let shown = &wrapped[wrapped.len().saturating_sub(rows - 1)..];
+        std::iter::once(wrap::pad("Thinking (/thoughts hides)", inner))
+            .chain(shown.iter().map(|l| wrap::pad(l, inner)))
+            .map(|l| format!("│ {}", l))
+            .collect()
+    }
+
+    /// Escape sequences that draw the pane without moving the cursor.
+    pub fn render(&self, terminal_width: usize, terminal_height: usize) -> String {
+        let width = self.width(terminal_width);
+        if width == 0 {
+            return String::new();
+        }
+        let column = terminal_width - width + 1;
+        let mut out = String::from("\x1b7");
+        for (row, line) in self.lines(width, terminal_height.saturating_sub(2)).iter().enumerate() {
+            out.push_str(&format!("\x1b[{};{}H\x1b[2m{}\x1b[0m", row + 1, column, line));
+        }
+        out.push_str("\x1b8");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thought_pane_keeps_newest_lines_and_collapses() {
+        let mut pane = ThoughtPane::default();
+        assert_eq!(pane.width(120), 0);
+        pane.push("**Reading the logs.** The service restarted at noon.\n\n");
+        pane.push("**Checking memory.** Usage climbed before each restart, so it was probably killed by the OOM killer.");
+        assert_eq!(pane.width(120), 40);
+        assert_eq!(pane.width(60), 0);
+
+        let lines = pane.lines(30, 4);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("│ Thinking"));
+        assert_eq!(lines[3].trim_end(), "│ the OOM killer.");
+        assert_eq!(lines[3].chars().count(), 30);
+        assert!(pane.render(120, 40).starts_with("\x1b7\x1b[1;81H"));
+
+        assert!(!pane.toggle());
+        assert_eq!(pane.width(120), 0);
+        assert!(pane.hint().is_some());
+        assert!(pane.hint().is_none());
+        pane.clear();
+        assert!(pane.is_empty());
+    }
+}
@@ -2,53 +2,142 @@ use async_trait::async_trait;
 use tokio::sync::mpsc;
 use anyhow::Result;
 use std::io::{self, Write};
+use std::sync::{Mutex, RwLock};
 use crate::bridges::CommBridge;
+use crate::bridges::image::{self, ImageProtocol};
+use crate::bridges::keymap::{Action, Keymap};
+use crate::bridges::thoughts::ThoughtPane;
+use crate::bridges::wrap::{self, LineWrapper};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use crate::conductor::events::{UserEvent, SystemEvent};
+use crate::i18n::{self, Msg};
 
 pub struct TuiBridge {
     tx: mpsc::Sender<UserEvent>,
+    language: RwLock<String>,
+    wrapper: Mutex<LineWrapper>,
+    thoughts: Mutex<ThoughtPane>,
+    /// Whether a status line is drawn under the cursor.
+    status: Mutex<bool>,
+    keymap: Keymap,
 }
 
 impl TuiBridge {
     pub fn new() -> (Self, mpsc::Receiver<UserEvent>) {
         let (tx, rx) = mpsc::channel(100);
-        (Self { tx }, rx)
+        let bridge = Self {
+            tx,
+            language: RwLock::new("en".to_string()),
+            wrapper: Mutex::new(LineWrapper::for_terminal()),
+            thoughts: Mutex::new(ThoughtPane::default()),
+            status: Mutex::new(false),
+            keymap: Keymap::default(),
+        };
+        (bridge, rx)
+    }
+
+    /// A handle for injecting events from outside the input loop, e.g. file watchers.
+    pub fn sender(&self) -> mpsc::Sender<UserEvent> {
+        self.tx.clone()
+    }
+
+    /// Sets the language used for built-in TUI strings.
+    pub fn with_language(self, language: Option<String>) -> Self {
+        *self.language.write().unwrap() = language.unwrap_or_else(|| "en".to_string());
+        self
+    }
+
+    /// Replaces the default bindings for approval, cancel, exit and the rest.
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    fn toggle_thoughts(&self) -> Result<()> {
+        let mut thoughts = self.thoughts.lock().unwrap();
+        let expanded = thoughts.toggle();
+        println!("\x1b[36m[Thought pane {}]\x1b[0m", if expanded { "shown" } else { "hidden" });
+        print!("{}", self.draw_thoughts(&thoughts));
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    /// The thought pane for the current terminal size, or nothing when hidden.
+    fn draw_thoughts(&self, thoughts: &ThoughtPane) -> String {
+        match crossterm::terminal::size() {
+            Ok((width, height)) => thoughts.render(width as usize, height as usize),
+            Err(_) => String::new(),
+        }
+    }
+
+    fn tr(&self, msg: Msg) -> &'static str {
+        i18n::tr(&self.language.read().unwrap(), msg)
     }
 
     pub async fn run_input_loop(&self) -> Result<()> {
         let stdin = io::stdin();
         loop {
             let mut user_input = String::new();
-            stdin.read_line(&mut user_input)?;
+            // End of input (Ctrl-D) exits without confirmation.
+            if stdin.read_line(&mut user_input)? == 0 {
+                self.tx.send(UserEvent::EndOfInput).await?;
+                break;
+            }
             let prompt = user_input.trim();
             if prompt.is_empty() { continue; }
 
-            match prompt.to_lowercase().as_str() {
-                "y" | "yes" => {
-                    self.tx.send(UserEvent::Approve).await?;
-                }
-                "n" | "no" => {
-                    self.tx.send(UserEvent::Reject).await?;
+            if let Some((action, rest)) = self.keymap.action(prompt) {
+                match action {
+                    Action::Approve => self.tx.send(UserEvent::Approve).await?,
+                    Action::Reject => self.tx.send(UserEvent::Reject).await?,
+                    Action::Cancel => self.tx.send(UserEvent::Command("/cancel".to_string())).await?,
+                    // The Conductor may ask for confirmation first; the
+                    // process exits once it stops.
+                    Action::Exit => self.tx.send(UserEvent::Command("/exit".to_string())).await?,
+                    Action::Palette => {
+                        let command = format!("/palette {}", rest);
+                        self.tx.send(UserEvent::Command(command.trim_end().to_string())).await?;
+                    }
+                    Action::Panic => self.tx.send(UserEvent::Command("/panic".to_string())).await?,
+                    Action::Thoughts => self.toggle_thoughts()?,
                 }
+                continue;
+            }
+
+            match prompt {
                 _ if prompt.starts_with('/') => {
                     let parts: Vec<&str> = prompt.split_whitespace().collect();
                     match parts[0] {
-                        "/exit" | "/quit" => {
-                            self.tx.send(UserEvent::Command("/exit".to_string())).await?;
-                            break;
-                        }
                         "/clear" => {
                             self.tx.send(UserEvent::Command("/clear".to_string())).await?;
                         }
+                        "/steer" => {
+                            let text = prompt["/steer".len()..].trim();
+                            if !text.is_empty() {
+                                self.tx.send(UserEvent::Steer(text.to_string())).await?;
+                            }
+                        }
+                        "/help" => {
+                            println!("{}", self.tr(Msg::Help));
+                            if self.keymap != Keymap::default() {
+                                println!("Key bindings: {}", self.keymap.summary());
+                            }
+                        }
+                        "/lang" => {
+                            let code = parts.get(1).copied().unwrap_or("off");
+                            *self.language.write().unwrap() = if code == "off" { "en".to_string() } else { code.to_string() };
+                            self.tx.send(UserEvent::Command(prompt.to_string())).await?;
+                        }
                         _ => {
                             self.tx.send(UserEvent::Command(prompt.to_string())).await?;
                         }
                     }
                 }
                 _ => {
-                    // We treat normal messages as either Message or Steer 
-                    // depending on Conductor state, but TuiBridge just sends Message.
-                    // Conductor will decide how to handle it.
+                    // Messages sent while a turn is running are queued by the
+                    // Conductor; /steer redirects the running turn instead.
+                    self.thoughts.lock().unwrap().clear();
                     self.tx.send(UserEvent::Message(prompt.to_string())).await?;
                 }
             }
@@ -57,24 +146,172 @@ impl TuiBridge {
     }
 }
 
+/// Resets colours and shows the cursor again, leaving the terminal usable.
+pub fn restore_terminal() {
+    let mut stdout = io::stdout();
+    let _ = write!(stdout, "\x1b[0m\x1b[?25h");
+    let _ = stdout.flush();
+}
+
+/// Restores the terminal before the default hook prints a panic, so the
+/// message and the shell afterwards aren't left in a half-written style.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        previous(info);
+    }));
+}
+
 #[async_trait]
 impl CommBridge for TuiBridge {
+    fn name(&self) -> &'static str {
+        "tui"
+    }
+
     async fn send(&self, event: SystemEvent) -> Result<()> {
         let mut stdout = io::stdout();
-        match event {
-            SystemEvent::Text(text) => {
-                print!("{}", text);
-                stdout.flush()?;
+        if let SystemEvent::Text(text) = &event {
+            let mut wrapper = self.wrapper.lock().unwrap();
+            let thoughts = self.thoughts.lock().unwrap();
+            if let Ok((width, _)) = crossterm::terminal::size() {
+                // Keep the answer clear of the thought pane.
+                let width = width as usize;
+                wrapper.set_width(width - thoughts.width(width));
+            }
+            print!("{}{}", wrapper.push(text), self.draw_thoughts(&thoughts));
+            stdout.flush()?;
+            return Ok(());
+        }
+        if let SystemEvent::Thought(delta) = &event {
+            let mut thoughts = self.thoughts.lock().unwrap();
+            thoughts.push(delta);
+            let drawn = self.draw_thoughts(&thoughts);
+            if !drawn.is_empty() {
+                print!("{}", drawn);
+            } else if let Some(hint) = thoughts.hint() {
+                self.wrapper.lock().unwrap().reset();
+                println!("\x1b[2m\n{}\x1b[0m", hint);
+            }
+            stdout.flush()?;
+            return Ok(());
+        }
+        if let SystemEvent::Status(status) = &event {
+            let mut shown = self.status.lock().unwrap();
+            match status {
+                Some(msg) => {
+                    if !*shown {
+                        self.wrapper.lock().unwrap().reset();
+                        println!();
+                    }
+                    print!("\r\x1b[2K\x1b[33m[{}]\x1b[0m", msg);
+                }
+                None if *shown => print!("\r\x1b[2K"),
+                None => {}
             }
+            *shown = status.is_some();
+            stdout.flush()?;
+            return Ok(());
+        }
+        // The terminal's title bar doubles as the status bar.
+        if let SystemEvent::Mode(mode) = &event {
+            print!("\x1b]0;chitti · {}\x07", mode);
+            stdout.flush()?;
+            return Ok(());
+        }
+        // Everything else is printed on its own lines, replacing any status.
+        if std::mem::take(&mut *self.status.lock().unwrap()) {
+            print!("\r\x1b[2K");
+        }
+        self.wrapper.lock().unwrap().reset();
+        match event {
+            SystemEvent::Text(_) | SystemEvent::Thought(_) | SystemEvent::Status(_) | SystemEvent::Mode(_) => {}
             SystemEvent::ToolCall { name, args } => {
                 // Dimmed output for tool calls
-                println!("\x1b[34m\n[Chitti calling tool: {} with args: {}]\x1b[0m", name, args);
+                println!("\x1b[34m\n[{}: {} with args: {}]\x1b[0m", self.tr(Msg::CallingTool), name, args);
             }
             SystemEvent::Error(err) => {
-                eprintln!("\x1b[31m\n[Error: {}]\x1b[31m", err);
+                eprintln!("\x1b[31m\n[{}: {}]\x1b[31m", self.tr(Msg::Error), err);
+            }
+            SystemEvent::Warning(msg) => {
+                println!("\x1b[33m\n[{}: {}]\x1b[0m", self.tr(Msg::Warning), msg);
+            }
+            SystemEvent::Info(msg) => {
+                println!("\x1b[36m\n[{}]\x1b[0m", msg);
+            }
+            SystemEvent::StructuredChunk { complete, errors, .. } => {
+                // Raw JSON already streams as text; report validation once complete.
+                if complete {
+                    if errors.is_empty() {
+                        println!("\x1b[32m\n[structured output valid]\x1b[0m");
+                    } else {
+                        for err in errors {
+                            println!("\x1b[33m\n[{}: {}]\x1b[0m", self.tr(Msg::Warning), err);
+                        }
+                    }
+                }
+            }
+            SystemEvent::Debug { message, request_id } => {
+                let request = request_id.map(|id| format!(" {}", id)).unwrap_or_default();
+                println!("\x1b[2m\n[debug{}] {}\x1b[0m", request, message);
+            }
+            SystemEvent::Image { mime_type, data, uri } => {
+                let preview = data.as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("no inline data"))
+                    .and_then(|d| Ok(STANDARD.decode(d)?))
+                    .and_then(|bytes| {
+                        let cols = crossterm::terminal::size().map(|(w, _)| (w as u32).min(80)).unwrap_or(60);
+                        image::render(ImageProtocol::detect(), &bytes, cols)
+                    });
+                match preview {
+                    Ok(rendered) => print!("\n{}", rendered),
+                    Err(_) => println!("\x1b[2m\n[image: {}{}]\x1b[0m", mime_type, uri.map(|u| format!(" {}", u)).unwrap_or_default()),
+                }
+                stdout.flush()?;
+            }
+            SystemEvent::Comparison { columns } => {
+                let cells: Vec<(String, String, String)> = columns.into_iter().map(|c| {
+                    let stats = match (&c.error, &c.usage) {
+                        (Some(err), _) => format!("{}: {}", self.tr(Msg::Error), err),
+                        (None, Some(u)) => format!(
+                            "{} ms · {} in / {} out / {} thought tokens",
+                            c.latency_ms, u.input_tokens, u.output_tokens, u.thought_tokens
+                        ),
+                        (None, None) => format!("{} ms", c.latency_ms),
+                    };
+                    (c.label, c.text, stats)
+                }).collect();
+                let width = crossterm::terminal::size().map(|(w, _)| w as usize).unwrap_or(100);
+                print!("\n{}", wrap::side_by_side(&cells, width));
+                stdout.flush()?;
+            }
+            SystemEvent::Artifact { path, mime_type } => {
+                println!("\x1b[2m\n[saved {}: {}]\x1b[0m", mime_type, path.display());
+            }
+            SystemEvent::FileRefs { files } => {
+                let list = |changed: bool| files.iter()
+                    .filter(|f| f.changed == changed)
+                    .map(|f| match f.lines {
+                        Some(n) => format!("{} ({} lines)", f.path, n),
+                        None => f.path.clone(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let (read, changed) = (list(false), list(true));
+                if !read.is_empty() {
+                    println!("\x1b[2m[Files referenced: {}]\x1b[0m", read);
+                }
+                if !changed.is_empty() {
+                    println!("\x1b[2m[Files changed: {}]\x1b[0m", changed);
+                }
+            }
+            SystemEvent::Shutdown { reason } => {
+                // Reset any colour left active by an interrupted line.
+                println!("\x1b[0m\n[Shutting down: {}]", reason);
+                stdout.flush()?;
             }
             SystemEvent::RequestApproval { description } => {
-                print!("\n\x1b[33m[Approval required: {}]\x1b[0m\nConfirm? (y/n): ", description);
+                print!("\n\x1b[33m[{}: {}]\x1b[0m\n{}", self.tr(Msg::ApprovalRequired), description, self.tr(Msg::ConfirmPrompt));
                 stdout.flush()?;
             }
         }
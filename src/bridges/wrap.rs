@@ -0,0 +1,193 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Word-wraps streamed text to the terminal width as it arrives.
+/// Widths are measured in terminal cells, so CJK and emoji count as two.
+/// When a word overflows the line it is erased and re-printed on the next
+/// one, which keeps output unbuffered while never splitting words.
+pub struct LineWrapper {
+    width: usize,
+    column: usize,
+    word: String,
+    word_width: usize,
+}
+
+impl LineWrapper {
+    pub fn new(width: usize) -> Self {
+        Self { width: width.max(10), column: 0, word: String::new(), word_width: 0 }
+    }
+
+    /// Uses the current terminal width, falling back to 80 columns.
+    pub fn for_terminal() -> Self {
+        let width = crossterm::terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
+        Self::new(width)
+    }
+
+    /// Forgets the cursor position, e.g. after other output ended with a newline.
+    pub fn reset(&mut self) {
+        self.column = 0;
+        self.word.clear();
+        self.word_width = 0;
+    }
+
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width.max(10);
+    }
+
+    /// Returns the text to print for a delta, with line breaks inserted.
+    pub fn push(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c == '\n' {
+                out.push('\n');
+                self.reset();
+                continue;
+            }
+            if c.is_whitespace() {
+                self.word.clear();
+                self.word_width = 0;
+                if self.column + 1 > self.width {
+                    out.push('\n');
+                    self.column = 0;
+                } else {
+                    out.push(c);
+                    self.column += 1;
+                }
+                continue;
+            }
+
+            let w = c.width().unwrap_or(0);
+            if self.column + w > self.width {
+                if !self.word.is_empty() && self.word_width + w <= self.width {
+                    // Move the partial word down to the next line.
+                    out.push_str(&format!("\x1b[{}D\x1b[K\n", self.word_width));
+                    out.push_str(&self.word);
+                    self.column = self.word_width;
+                } else {
+                    out.push('\n');
+                    self.column = 0;
+                    self.word.clear();
+                    self.word_width = 0;
+                }
+            }
+            out.push(c);
+            self.column += w;
+            self.word.push(c);
+            self.word_width += w;
+        }
+        out
+    }
+}
+
+/// Word-wraps finished text into lines at most `width` cells wide.
+/// Words longer than a line are split.
+pub fn wrap_lines(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = word.width();
+            let needed = if line.is_empty() { word_width } else { line_width + 1 + word_width };
+            if needed <= width {
+                if !line.is_empty() {
+                    line.push(' ');
+                    line_width += 1;
+                }
+                line.push_str(word);
+                line_width += word_width;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            for c in word.chars() {
+                let w = c.width().unwrap_or(0);
+                if line_width + w > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push(c);
+                line_width += w;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Pads `text` with spaces to `width` cells.
+pub fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+/// Lays out columns of (header, body, footer) next to each other in
+/// `total_width` cells, with rules between the sections.
+pub fn side_by_side(columns: &[(String, String, String)], total_width: usize) -> String {
+    const GAP: &str = " │ ";
+    let n = columns.len().max(1);
+    let col_width = (total_width.saturating_sub(3 * (n - 1)) / n).max(10);
+    let rule = vec!["─".repeat(col_width); n].join("─┼─");
+
+    let mut out = String::new();
+    let sections = [
+        columns.iter().map(|c| c.0.as_str()).collect::<Vec<_>>(),
+        columns.iter().map(|c| c.1.as_str()).collect(),
+        columns.iter().map(|c| c.2.as_str()).collect(),
+    ];
+    for (i, section) in sections.iter().enumerate() {
+        if i > 0 {
+            out.push_str(&rule);
+            out.push('\n');
+        }
+        let cells: Vec<Vec<String>> = section.iter().map(|text| wrap_lines(text, col_width)).collect();
+        let rows = cells.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..rows {
+            let line: Vec<String> = cells
+                .iter()
+                .map(|c| pad(c.get(row).map(String::as_str).unwrap_or(""), col_width))
+                .collect();
+            out.push_str(line.join(GAP).trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraps_at_word_boundaries() {
+        let mut wrapper = LineWrapper::new(10);
+        assert_eq!(wrapper.push("hello big "), "hello big ");
+        assert_eq!(wrapper.push("world"), "\nworld");
+        let mut wrapper = LineWrapper::new(10);
+        let out = wrapper.push("hello wor") + &wrapper.push("ld");
+        assert_eq!(out, "hello worl\x1b[4D\x1b[K\nworld");
+    }
+
+    #[test]
+    fn test_wrap_lines_and_columns() {
+        assert_eq!(wrap_lines("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap_lines("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        let columns = vec![
+            ("a".to_string(), "left side".to_string(), "1s".to_string()),
+            ("b".to_string(), "right".to_string(), "2s".to_string()),
+        ];
+        let rendered = side_by_side(&columns, 23);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "a          │ b");
+        assert_eq!(lines[2], "left side  │ right");
+        assert_eq!(lines[4], "1s         │ 2s");
+    }
+
+    #[test]
+    fn test_counts_wide_characters() {
+        let mut wrapper = LineWrapper::new(10);
+        // Six CJK characters are twelve cells wide.
+        assert_eq!(wrapper.push("你好你好你好"), "你好你好你\n好");
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use futures_util::StreamExt;
+use std::io::Write;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+/// Runs a single prompt without tools and streams the answer to stdout.
+pub async fn ask(brain: &dyn BrainEngine, prompt: String) -> Result<()> {
+    let context = TurnContext {
+        prompt,
+        system_instruction: None,
+        response_schema: None,
+        previous_interaction_id: None,
+        tool_results: Vec::new(),
+        thinking_level: None,
+        temperature: None,
+        model: None,
+        allowed_tools: None,
+        search_grounding: false,
+        attachments: Vec::new(),
+    };
+    let mut stream = brain.process_turn(context).await?;
+    let mut stdout = std::io::stdout();
+    while let Some(evt) = stream.next().await {
+        match evt? {
+            BrainEvent::TextDelta(text) => {
+                write!(stdout, "{}", text)?;
+                stdout.flush()?;
+            }
+            BrainEvent::Error(err) => anyhow::bail!(err),
+            _ => {}
+        }
+    }
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// Joins the non-flag arguments after the subcommand into the prompt.
+pub fn prompt_from_args(args: &[String]) -> String {
+    args.iter()
+        .skip(2)
+        .filter(|a| !a.starts_with("--"))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
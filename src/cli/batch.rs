@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::info;
+use crate::brains::gemini::Client;
+
+/// One line of the `--input` file: either a bare JSON string or an object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PromptLine {
+    Prompt(String),
+    Keyed {
+        #[serde(default)]
+        key: Option<String>,
+        prompt: String,
+    },
+}
+
+const TERMINAL_STATES: &[&str] = &[
+    "JOB_STATE_SUCCEEDED", "JOB_STATE_FAILED", "JOB_STATE_CANCELLED", "JOB_STATE_EXPIRED",
+    "BATCH_STATE_SUCCEEDED", "BATCH_STATE_FAILED", "BATCH_STATE_CANCELLED", "BATCH_STATE_EXPIRED",
+];
+
+/// `chitti batch ask --input prompts.jsonl --out results.jsonl [--poll-secs N]`
+pub async fn ask(client: &Client, input: &str, out: &str, poll_secs: u64) -> Result<()> {
+    let text = tokio::fs::read_to_string(input).await
+        .with_context(|| format!("Failed to read {}", input))?;
+    let prompts = parse_prompts(&text)?;
+    if prompts.is_empty() {
+        anyhow::bail!("{} contains no prompts", input);
+    }
+
+    // 1. Build and upload the batch JSONL
+    let request_file = std::env::temp_dir().join(format!("chitti-batch-{}.jsonl", uuid::Uuid::new_v4()));
+    tokio::fs::write(&request_file, build_requests(&prompts)).await?;
+    let uploaded = client.upload_file_as(&request_file, Some("chitti-batch-input".to_string()), "jsonl".to_string()).await;
+    let _ = tokio::fs::remove_file(&request_file).await;
+    let uploaded = uploaded.context("Failed to upload batch input")?;
+    info!(file = %uploaded.name, prompts = prompts.len(), "Uploaded batch input");
+
+    // 2. Create the batch and poll until it settles
+    let mut operation = client.create_batch("chitti-batch-ask".to_string(), uploaded.name.clone()).await
+        .context("Failed to create batch")?;
+    println!("Created batch {} with {} prompts", operation.name, prompts.len());
+    loop {
+        let state = batch_state(&operation);
+        if operation.done || TERMINAL_STATES.contains(&state.as_str()) {
+            break;
+        }
+        println!("Batch state: {} (next check in {}s)", state, poll_secs);
+        tokio::time::sleep(Duration::from_secs(poll_secs)).await;
+        operation = client.get_batch_operation(&operation.name).await?;
+    }
+    let _ = client.delete_file(&uploaded.name).await;
+
+    if let Some(err) = &operation.error {
+        anyhow::bail!("Batch failed: {}", err);
+    }
+    let state = batch_state(&operation);
+    if !state.ends_with("SUCCEEDED") && !state.is_empty() {
+        anyhow::bail!("Batch finished in state {}", state);
+    }
+
+    // 3. Download and flatten results
+    let responses_file = operation.response.as_ref()
+        .and_then(|r| r.get("responsesFile").or_else(|| r.pointer("/output/responsesFile")))
+        .and_then(|v| v.as_str())
+        .context("Batch response has no responsesFile")?
+        .to_string();
+    let raw = client.download_file(&responses_file).await?;
+    let by_key: HashMap<String, String> = prompts.into_iter().collect();
+    let flattened = flatten_results(&String::from_utf8_lossy(&raw), &by_key);
+    tokio::fs::write(out, flattened).await
+        .with_context(|| format!("Failed to write {}", out))?;
+    let _ = client.delete_file(&responses_file).await;
+    println!("Wrote results to {}", out);
+    Ok(())
+}
+
+fn batch_state(operation: &crate::brains::gemini::Operation) -> String {
+    operation.metadata.as_ref()
+        .and_then(|m| m.get("state"))
+        .and_then(|s| s.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Parses the input file into (key, prompt) pairs, numbering unkeyed prompts.
+fn parse_prompts(text: &str) -> Result<Vec<(String, String)>> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let parsed: PromptLine = serde_json::from_str(line)
+                .with_context(|| format!("Invalid prompt on line {}", i + 1))?;
+            Ok(match parsed {
+                PromptLine::Prompt(prompt) => (format!("request-{}", i + 1), prompt),
+                PromptLine::Keyed { key, prompt } => (key.unwrap_or_else(|| format!("request-{}", i + 1)), prompt),
+            })
+        })
+        .collect()
+}
+
+fn build_requests(prompts: &[(String, String)]) -> String {
+    prompts.iter()
+        .map(|(key, prompt)| json!({
+            "key": key,
+            "request": { "contents": [{ "role": "user", "parts": [{ "text": prompt }] }] },
+        }).to_string() + "\n")
+        .collect()
+}
+
+/// Turns raw `GenerateContentResponse` lines into `{key, prompt, text | error}` lines.
+fn flatten_results(raw: &str, prompts: &HashMap<String, String>) -> String {
+    raw.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let value: Value = serde_json::from_str(line).unwrap_or_else(|_| json!({ "error": line }));
+            let key = value.get("key").and_then(|k| k.as_str()).unwrap_or_default().to_string();
+            let mut flat = json!({ "key": key, "prompt": prompts.get(&key) });
+            if let Some(err) = value.get("error").or_else(|| value.get("status")) {
+                flat["error"] = err.clone();
+            } else {
+                let text: String = value.pointer("/response/candidates/0/content/parts")
+                    .and_then(|p| p.as_array())
+                    .map(|parts| parts.iter().filter_map(|p| p.get("text").and_then(|t| t.as_str())).collect())
+                    .unwrap_or_default();
+                flat["text"] = json!(text);
+            }
+            flat.to_string() + "\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_round_trip() {
+        let prompts = parse_prompts("\"hello\"\n{\"key\": \"k2\", \"prompt\": \"bye\"}\n").unwrap();
+        assert_eq!(prompts, vec![
+            ("request-1".to_string(), "hello".to_string()),
+            ("k2".to_string(), "bye".to_string()),
+        ]);
+        assert!(build_requests(&prompts).contains(r#""key":"k2""#));
+
+        let raw = r#"{"key":"k2","response":{"candidates":[{"content":{"parts":[{"text":"good"},{"text":"bye"}]}}]}}"#;
+        let by_key: HashMap<String, String> = prompts.into_iter().collect();
+        let flat: Value = serde_json::from_str(flatten_results(raw, &by_key).trim()).unwrap();
+        assert_eq!(flat["text"], "goodbye");
+        assert_eq!(flat["prompt"], "bye");
+    }
+}
@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::brains::gemini::{Client, GeminiError};
+use crate::config::Config;
+
+/// Binaries the built-in tools and common workflows shell out to.
+const BINARIES: &[(&str, bool)] = &[("bash", true), ("git", false), ("rg", false)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+/// Runs every check. Configuration errors are reported rather than returned
+/// so a broken setup still gets a full report.
+pub async fn run(env_file: Option<&Path>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    checks.push(match env_file {
+        Some(path) => Check::new("env file", Status::Pass, path.display().to_string()),
+        None => Check::new("env file", Status::Warn, "no .env found; using the process environment"),
+    });
+    checks.push(secret_storage(env_file));
+
+    let config = match Config::from_env() {
+        Ok(config) => {
+            checks.push(Check::new("config", Status::Pass, format!("model {}", config.gemini_model)));
+            Some(config)
+        }
+        Err(e) => {
+            checks.push(Check::new("config", Status::Fail, e.to_string()));
+            None
+        }
+    };
+
+    if config.as_ref().is_some_and(|c| c.vault) {
+        checks.push(match crate::vault::Vault::open() {
+            Ok(_) => Check::new("keychain", Status::Pass, "vault key available"),
+            Err(e) => Check::new("keychain", Status::Fail, format!("{:#}", e)),
+        });
+    }
+
+    let client = Client::new(
+        config.as_ref().map(|c| c.gemini_api_key.clone()).unwrap_or_default(),
+        config.as_ref().map(|c| c.gemini_model.clone()).unwrap_or_default(),
+    );
+    let reachable = network(&client).await;
+    let network_ok = reachable.status == Status::Pass;
+    checks.push(reachable);
+    if let Some(config) = &config {
+        if let Some(vertex) = &config.vertex {
+            checks.push(vertex_auth(&vertex.project, &vertex.location).await);
+        } else if network_ok {
+            checks.push(api_key(&client, &config.gemini_model).await);
+        }
+    }
+
+    let mut binaries = BINARIES.to_vec();
+    if let Some(remote) = config.as_ref().and_then(|c| c.remote.as_ref()) {
+        checks.push(Check::new("remote", Status::Pass, format!("shell tools run on {}", remote.destination())));
+        binaries.push(("ssh", true));
+    }
+    for (binary, required) in &binaries {
+        checks.push(match find_in_path(binary) {
+            Some(path) => Check::new(binary, Status::Pass, path.display().to_string()),
+            None if *required => Check::new(binary, Status::Fail, "not found in PATH"),
+            None => Check::new(binary, Status::Warn, "not found in PATH"),
+        });
+    }
+    checks
+}
+
+/// Times an unauthenticated request to the API endpoint; any HTTP response counts as reachable.
+async fn network(client: &Client) -> Check {
+    let started = Instant::now();
+    let request = client.http_client.get(&client.base_url).timeout(Duration::from_secs(10)).send();
+    match request.await {
+        Ok(_) => {
+            let ms = started.elapsed().as_millis();
+            let status = if ms > 2000 { Status::Warn } else { Status::Pass };
+            Check::new("network", status, format!("{} reachable in {} ms", client.base_url, ms))
+        }
+        Err(e) => Check::new("network", Status::Fail, format!("{} unreachable: {}", client.base_url, e)),
+    }
+}
+
+/// Validates the key by listing models and confirms the configured model is among them.
+async fn api_key(client: &Client, model: &str) -> Check {
+    match client.list_models().await {
+        Ok(models) if models.iter().any(|m| m.id() == model) => {
+            Check::new("api key", Status::Pass, format!("valid; {} models available", models.len()))
+        }
+        Ok(_) => Check::new("api key", Status::Warn, format!("valid, but model '{}' is not listed", model)),
+        Err(GeminiError::Api { code, message }) => {
+            let first = message.lines().next().unwrap_or_default().to_string();
+            Check::new("api key", Status::Fail, format!("rejected ({}): {}", code, first))
+        }
+        Err(e) => Check::new("api key", Status::Fail, e.to_string()),
+    }
+}
+
+/// Confirms Application Default Credentials yield an access token for Vertex AI.
+async fn vertex_auth(project: &str, location: &str) -> Check {
+    let credentials = match crate::brains::gemini::vertex::Credentials::discover() {
+        Ok(credentials) => credentials,
+        Err(e) => return Check::new("vertex auth", Status::Fail, e.to_string()),
+    };
+    let vertex = crate::brains::gemini::vertex::Vertex::new(project.to_string(), location.to_string(), credentials);
+    match vertex.access_token().await {
+        Ok(_) => Check::new("vertex auth", Status::Pass, format!("token issued for project {} ({})", project, location)),
+        Err(e) => Check::new("vertex auth", Status::Fail, e.to_string()),
+    }
+}
+
+/// The setup wizard keeps the key in the OS keychain. A key set in the env
+/// file instead should at least be unreadable by other users.
+fn secret_storage(env_file: Option<&Path>) -> Check {
+    if std::env::var("GEMINI_API_KEY").is_err() && crate::vault::stored_api_key().is_some() {
+        return Check::new("secret storage", Status::Pass, "API key kept in the OS keychain");
+    }
+    let Some(path) = env_file else {
+        return Check::new("secret storage", Status::Pass, "API key read from the environment");
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(path) {
+            let mode = meta.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return Check::new(
+                    "secret storage",
+                    Status::Warn,
+                    format!("{} is readable by other users (mode {:o}); run chmod 600", path.display(), mode),
+                );
+            }
+        }
+    }
+    Check::new("secret storage", Status::Pass, format!("API key kept in {}", path.display()))
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Formats the checks as an aligned report with a summary line.
+pub fn report(checks: &[Check]) -> String {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for c in checks {
+        let mark = match c.status {
+            Status::Pass => "ok  ",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        out.push_str(&format!("[{}] {:width$}  {}\n", mark, c.name, c.detail, width = width));
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    out.push_str(&format!("\n{} checks, {} failed, {} warnings\n", checks.len(), failed, warned));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_alignment_and_summary() {
+        let checks = vec![
+            Check::new("bash", Status::Pass, "/bin/bash"),
+            Check::new("api key", Status::Fail, "rejected"),
+            Check::new("rg", Status::Warn, "not found in PATH"),
+        ];
+        assert_eq!(
+            report(&checks),
+            "[ok  ] bash     /bin/bash\n[FAIL] api key  rejected\n[warn] rg       not found in PATH\n\n3 checks, 1 failed, 1 warnings\n"
+        );
+        assert!(find_in_path("definitely-not-a-real-binary").is_none());
+    }
+}
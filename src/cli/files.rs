@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::brains::gemini::types::File;
+use crate::brains::gemini::Client;
+
+pub const USAGE: &str = "Usage: chitti files [list | rm <name>... | gc [--hours N]]";
+
+/// Every uploaded file, following `next_page_token` through all pages.
+pub async fn list_all(client: &Client) -> Result<Vec<File>> {
+    let mut files = Vec::new();
+    let mut page_token = None;
+    loop {
+        let page = client.list_files(Some(100), page_token).await
+            .context("Failed to list files")?;
+        files.extend(page.files);
+        match page.next_page_token.filter(|t| !t.is_empty()) {
+            Some(token) => page_token = Some(token),
+            None => return Ok(files),
+        }
+    }
+}
+
+/// Deletes uploads created more than `max_age` ago; returns how many were removed.
+pub async fn gc(client: &Client, max_age: Duration) -> Result<usize> {
+    let now = unix_now();
+    let mut removed = 0;
+    for file in list_all(client).await? {
+        let age = parse_timestamp(&file.create_time).map(|created| now.saturating_sub(created));
+        if age.is_some_and(|age| age > max_age.as_secs()) {
+            client.delete_file(&file.name).await
+                .with_context(|| format!("Failed to delete {}", file.name))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Runs `list`, `rm` or `gc` and returns the text to show, so `chitti files`
+/// and `/files` share one implementation. `default_gc_age` applies when `gc`
+/// has no `--hours`.
+pub async fn command(client: &Client, args: &[String], default_gc_age: Duration) -> Result<String> {
+    match args.first().map(|s| s.as_str()) {
+        None | Some("list") | Some("ls") => Ok(listing(&list_all(client).await?, unix_now())),
+        Some("rm") if args.len() > 1 => {
+            for name in &args[1..] {
+                client.delete_file(name).await
+                    .with_context(|| format!("Failed to delete {}", name))?;
+            }
+            Ok(format!("Deleted {} file(s)", args.len() - 1))
+        }
+        Some("gc") => {
+            let max_age = match super::flag(args, "--hours") {
+                Some(h) => Duration::from_secs(h.parse::<u64>().map_err(|_| anyhow::anyhow!("Invalid number of hours: {}", h))? * 3600),
+                None => default_gc_age,
+            };
+            let removed = gc(client, max_age).await?;
+            Ok(format!("Deleted {} file(s) older than {}", removed, human_duration(max_age.as_secs())))
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+}
+
+pub fn listing(files: &[File], now: u64) -> String {
+    if files.is_empty() {
+        return "No uploaded files.".to_string();
+    }
+    let mut out = format!("{:<22} {:>9} {:>6} {:>10}  {}", "NAME", "SIZE", "AGE", "EXPIRES IN", "DISPLAY NAME");
+    for file in files {
+        let size = file.size_bytes.parse().map(human_size).unwrap_or_else(|_| file.size_bytes.clone());
+        let age = parse_timestamp(&file.create_time)
+            .map(|t| human_duration(now.saturating_sub(t)))
+            .unwrap_or_else(|| "?".to_string());
+        let expires = file.expiration_time.as_deref()
+            .and_then(parse_timestamp)
+            .map(|t| if t > now { human_duration(t - now) } else { "expired".to_string() })
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "\n{:<22} {:>9} {:>6} {:>10}  {}",
+            file.name, size, age, expires, file.display_name.as_deref().unwrap_or("")
+        ));
+    }
+    out.push_str(&format!("\n{} file(s)", files.len()));
+    out
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Seconds since the epoch for an RFC 3339 UTC timestamp such as
+/// `2025-01-31T09:15:00.123456Z`, the form the File API returns.
+pub fn parse_timestamp(s: &str) -> Option<u64> {
+    let (date, time) = s.trim_end_matches('Z').split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+
+    // Days from civil date (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    u64::try_from(days * 86400 + hour * 3600 + minute * 60 + second).ok()
+}
+
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.1} GB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1u64 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KB", b as f64 / (1u64 << 10) as f64),
+        b => format!("{} B", b),
+    }
+}
+
+fn human_duration(secs: u64) -> String {
+    match secs {
+        s if s >= 86400 => format!("{}d", s / 86400),
+        s if s >= 3600 => format!("{}h", s / 3600),
+        s if s >= 60 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brains::gemini::types::FileState;
+
+    #[test]
+    fn test_files_listing_shows_size_age_and_expiry() {
+        assert_eq!(parse_timestamp("1970-01-02T00:00:00Z"), Some(86400));
+        assert_eq!(parse_timestamp("2025-03-01T12:30:15.123456Z"), Some(1740832215));
+        assert_eq!(parse_timestamp("not a time"), None);
+
+        let now = parse_timestamp("2025-03-02T12:00:00Z").unwrap();
+        let file = File {
+            name: "files/abc123".to_string(),
+            display_name: Some("plot.png".to_string()),
+            mime_type: "image/png".to_string(),
+            size_bytes: "2621440".to_string(),
+            create_time: "2025-03-01T12:00:00Z".to_string(),
+            update_time: "2025-03-01T12:00:00Z".to_string(),
+            expiration_time: Some("2025-03-03T12:00:00Z".to_string()),
+            sha256_hash: String::new(),
+            uri: "https://example.invalid/files/abc123".to_string(),
+            download_uri: None,
+            state: FileState::Active,
+            source: None,
+            error: None,
+        };
+        let out = listing(&[file], now);
+        let row = out.lines().nth(1).unwrap();
+        assert!(row.starts_with("files/abc123"));
+        assert!(row.contains("2.5 MB") && row.contains("1d") && row.ends_with("plot.png"), "{}", row);
+        assert!(out.ends_with("1 file(s)"));
+        assert_eq!(listing(&[], now), "No uploaded files.");
+    }
+}
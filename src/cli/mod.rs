@@ -0,0 +1,19 @@
+//! Non-interactive subcommands (`chitti <command> ...`).
+
+pub mod ask;
+pub mod batch;
+pub mod digest;
+pub mod doctor;
+pub mod files;
+pub mod run;
+pub mod setup;
+pub mod slack;
+pub mod watch;
+
+/// Returns the value following `--name` in the argument list.
+pub fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+use crate::brains::gemini::adapter::GeminiEngine;
+use crate::brains::gemini::Client;
+use crate::bridges::headless::HeadlessBridge;
+use crate::conductor::Conductor;
+use crate::tools::ToolRegistry;
+
+/// A `tasks.yaml` file for `chitti run`.
+#[derive(Debug, Deserialize)]
+pub struct TaskFile {
+    /// Maximum number of tasks running at once.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Directory receiving `<task name>.md` outputs.
+    #[serde(default = "default_output_dir")]
+    pub output_dir: PathBuf,
+    /// Tools every task may use unless it overrides the list. Calls are auto-approved.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    pub tasks: Vec<TaskSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskSpec {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+fn default_output_dir() -> PathBuf {
+    PathBuf::from("chitti-results")
+}
+
+#[derive(Debug)]
+struct TaskOutcome {
+    name: String,
+    output: PathBuf,
+    error: Option<String>,
+}
+
+/// Runs every task in the file concurrently, each in its own Conductor.
+pub async fn run_tasks(path: &str, client: Client, registry: Arc<ToolRegistry>) -> Result<()> {
+    let text = tokio::fs::read_to_string(path).await
+        .with_context(|| format!("Failed to read task file {}", path))?;
+    let file: TaskFile = serde_yaml::from_str(&text)
+        .with_context(|| format!("Failed to parse task file {}", path))?;
+
+    tokio::fs::create_dir_all(&file.output_dir).await
+        .with_context(|| format!("Failed to create output directory {:?}", file.output_dir))?;
+
+    let total = file.tasks.len();
+    info!(tasks = total, concurrency = file.concurrency, "Running task file");
+    let semaphore = Arc::new(Semaphore::new(file.concurrency.max(1)));
+    let mut set = JoinSet::new();
+
+    for task in file.tasks {
+        let tool_names = task.tools.clone().unwrap_or_else(|| file.tools.clone());
+        let tools = Arc::new(registry.subset(&tool_names));
+        let output = task.output.clone()
+            .unwrap_or_else(|| file.output_dir.join(format!("{}.md", file_stem(&task.name))));
+        let client = match &task.model {
+            Some(model) => client.clone().with_model(model.clone()),
+            None => client.clone(),
+        };
+        let semaphore = semaphore.clone();
+
+        set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let error = run_task(&task, client, tools, &output).await.err().map(|e| format!("{:#}", e));
+            TaskOutcome { name: task.name, output, error }
+        });
+    }
+
+    let mut failed = 0;
+    while let Some(joined) = set.join_next().await {
+        let outcome = joined.context("Task panicked")?;
+        match &outcome.error {
+            None => println!("\u{2713} {} -> {}", outcome.name, outcome.output.display()),
+            Some(err) => {
+                failed += 1;
+                println!("\u{2717} {}: {}", outcome.name, err);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} tasks failed", failed, total);
+    }
+    Ok(())
+}
+
+async fn run_task(task: &TaskSpec, client: Client, tools: Arc<ToolRegistry>, output: &Path) -> Result<()> {
+    let brain = Box::new(GeminiEngine::new(client, tools.clone()));
+    let (bridge, rx) = HeadlessBridge::new();
+    let bridge = Arc::new(bridge);
+    let mut conductor = Conductor::new(brain, bridge.clone(), rx, tools);
+
+    conductor.handle_conversation(task.prompt.clone()).await?;
+
+    let errors = bridge.errors().await;
+    for err in &errors {
+        warn!(task = %task.name, "Task reported error: {}", err);
+    }
+    if let Some(parent) = output.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(output, bridge.output().await).await
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    if let Some(err) = errors.first() {
+        anyhow::bail!("{}", err);
+    }
+    Ok(())
+}
+
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_task_file_defaults() {
+        let file: TaskFile = serde_yaml::from_str(r#"
+tools: [execute_bash]
+tasks:
+  - name: label one
+    prompt: "Classify this"
+  - name: two
+    prompt: "Refactor"
+    tools: []
+    model: gemini-3-flash-preview
+"#).unwrap();
+        assert_eq!(file.concurrency, 4);
+        assert_eq!(file.output_dir, PathBuf::from("chitti-results"));
+        assert_eq!(file.tasks.len(), 2);
+        assert_eq!(file.tasks[1].tools, Some(vec![]));
+        assert_eq!(file_stem(&file.tasks[0].name), "label_one");
+    }
+}
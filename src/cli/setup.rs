@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use crate::brains::gemini::{Client, Model};
+
+const DEFAULT_MODEL: &str = "gemini-1.5-flash";
+
+/// Interactive first-run setup, used when no API key is configured.
+///
+/// Asks for the API key and checks it by listing models, lets the user pick a
+/// default model from that list and choose which tools may run without
+/// approval. The key goes to the OS keychain, where `Config::from_env` finds
+/// it; the other answers are merged into `env_file`, which is kept readable
+/// by its owner only.
+pub async fn run(env_file: &Path) -> Result<()> {
+    println!("Welcome to Chitti! No GEMINI_API_KEY is configured, so let's set one up.");
+    println!("Create a key at https://aistudio.google.com/apikey\n");
+
+    let (api_key, models) = loop {
+        let key = read_secret("Gemini API key: ")?;
+        if key.is_empty() {
+            continue;
+        }
+        print!("Checking the key... ");
+        io::stdout().flush()?;
+        match Client::new(key.clone(), DEFAULT_MODEL.to_string()).list_models().await {
+            Ok(models) => {
+                println!("ok");
+                break (key, chat_models(&models));
+            }
+            Err(e) => println!("failed: {}\nPlease try again.", e),
+        }
+    };
+
+    let model = if models.is_empty() {
+        DEFAULT_MODEL.to_string()
+    } else {
+        let default = models.iter().position(|m| m == DEFAULT_MODEL).unwrap_or(0);
+        println!("\nAvailable models:");
+        for (i, m) in models.iter().enumerate() {
+            println!("  {:>2}. {}", i + 1, m);
+        }
+        let answer = read_line(&format!("Default model [{}]: ", default + 1))?;
+        pick(&models, &answer).unwrap_or_else(|| models[default].clone())
+    };
+
+    println!("\nEvery tool call asks for approval unless the tool is listed here.");
+    let auto_approve = read_line("Tools to run without asking (comma-separated, e.g. bash; empty to always ask): ")?;
+
+    crate::vault::store_api_key(&api_key)
+        .context("Set GEMINI_API_KEY in the environment instead")?;
+    let existing = match std::fs::read_to_string(env_file) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", env_file.display())),
+    };
+    write_env(env_file, &merge_env(&existing, &render_env(&model, &auto_approve)))?;
+    println!("\nSaved the API key to the OS keychain and settings to {}.", env_file.display());
+    println!("Edit the settings any time; see .env.example for more options.\n");
+    Ok(())
+}
+
+/// Ids of models that support text generation, sorted.
+pub fn chat_models(models: &[Model]) -> Vec<String> {
+    let mut ids: Vec<String> = models.iter()
+        .filter(|m| m.supported_generation_methods.iter().any(|g| g == "generateContent"))
+        .map(|m| m.id().to_string())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Resolves an answer given as a list number or a model id.
+fn pick(models: &[String], answer: &str) -> Option<String> {
+    let answer = answer.trim();
+    match answer.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).and_then(|i| models.get(i)).cloned(),
+        Err(_) => models.iter().find(|m| *m == answer).cloned(),
+    }
+}
+
+/// The wizard's settings as env file lines. The API key is not among them.
+pub fn render_env(model: &str, auto_approve: &str) -> String {
+    let tools: Vec<&str> = auto_approve.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+    format!(
+        "# Written by the Chitti setup wizard\nGEMINI_MODEL={}\nCHITTI_AUTO_APPROVE_TOOLS={}\n",
+        model,
+        tools.join(",")
+    )
+}
+
+/// `existing` with `rendered` appended, dropping the lines it replaces (and
+/// any plaintext `GEMINI_API_KEY`) so each setting appears once.
+pub fn merge_env(existing: &str, rendered: &str) -> String {
+    let name = |line: &str| line.split_once('=').map(|(name, _)| name.trim().to_string());
+    let replaced: Vec<String> = rendered.lines().filter_map(name)
+        .chain(std::iter::once("GEMINI_API_KEY".to_string()))
+        .collect();
+    let mut merged: String = existing.lines()
+        .filter(|line| !rendered.lines().any(|r| r == *line))
+        .filter(|line| line.trim_start().starts_with('#') || name(line).is_none_or(|n| !replaced.contains(&n)))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    if !merged.is_empty() && !merged.ends_with("\n\n") {
+        merged.push('\n');
+    }
+    merged.push_str(rendered);
+    merged
+}
+
+/// Replaces `path` with `contents`, readable by its owner only, whether or not it existed.
+fn write_env(path: &Path, contents: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    // `mode` only applies when the file is created.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn read_line(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        anyhow::bail!("Setup cancelled");
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Reads a line with terminal echo turned off where `stty` is available.
+fn read_secret(prompt: &str) -> Result<String> {
+    let stty = |arg: &str| {
+        std::process::Command::new("stty")
+            .arg(arg)
+            .stdin(std::process::Stdio::inherit())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    };
+    let hidden = stty("-echo");
+    let line = read_line(prompt);
+    if hidden {
+        stty("echo");
+        println!();
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(name: &str, methods: &[&str]) -> Model {
+        Model {
+            name: name.to_string(),
+            display_name: None,
+            supported_generation_methods: methods.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_model_choices_and_env_output() {
+        let models = vec![
+            model("models/gemini-2.0-flash", &["generateContent", "countTokens"]),
+            model("models/text-embedding-004", &["embedContent"]),
+            model("models/gemini-1.5-flash", &["generateContent"]),
+        ];
+        let choices = chat_models(&models);
+        assert_eq!(choices, vec!["gemini-1.5-flash", "gemini-2.0-flash"]);
+        assert_eq!(pick(&choices, "2").as_deref(), Some("gemini-2.0-flash"));
+        assert_eq!(pick(&choices, "gemini-1.5-flash").as_deref(), Some("gemini-1.5-flash"));
+        assert_eq!(pick(&choices, "9"), None);
+
+        let env = render_env("gemini-2.0-flash", " bash, ,read_tool_output ");
+        assert!(!env.contains("GEMINI_API_KEY"));
+        assert!(env.contains("GEMINI_MODEL=gemini-2.0-flash\n"));
+        assert!(env.contains("CHITTI_AUTO_APPROVE_TOOLS=bash,read_tool_output\n"));
+
+        // Other settings survive; the wizard's own (and a stale key) are replaced.
+        let merged = merge_env("# mine\nGEMINI_API_KEY=\nGEMINI_MODEL=old\nCHITTI_LANGUAGE=ta\n", &env);
+        assert_eq!(merged, format!("# mine\nCHITTI_LANGUAGE=ta\n\n{}", env));
+        assert_eq!(merge_env("", &env), env);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_env_replaces_the_file_owner_only() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("chitti-setup-{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "OLD=1\n")?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        write_env(&path, "NEW=1\n")?;
+        assert_eq!(std::fs::read_to_string(&path)?, "NEW=1\n");
+        assert_eq!(std::fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+use crate::brains::gemini::adapter::GeminiEngine;
+use crate::brains::gemini::Client;
+use crate::bridges::identity::{Identities, UserPolicy};
+use crate::bridges::slack::{self, Incoming, SlackApi, SlackBridge, ThreadKey};
+use crate::conductor::events::UserEvent;
+use crate::conductor::Conductor;
+use crate::config::Config;
+use crate::tools::ToolRegistry;
+use crate::vault::Vault;
+
+pub const USAGE: &str = "Usage: chitti slack (set SLACK_APP_TOKEN and SLACK_BOT_TOKEN)";
+
+/// `chitti slack`: answers @-mentions and direct messages over Socket Mode.
+/// Every thread gets its own Conductor, started by the first mention and fed
+/// by later replies in the thread from the same user, with that user's tools
+/// and policy. Tool calls wait for the user's click on the approval buttons
+/// unless listed in `CHITTI_AUTO_APPROVE_TOOLS`. Only users listed in
+/// `CHITTI_USERS_FILE` or, without one, `CHITTI_SLACK_USERS` are answered.
+pub async fn run(client: Client, tools: Arc<ToolRegistry>, config: &Config, vault: Option<Arc<Vault>>) -> Result<()> {
+    let app_token = config.slack_app_token.clone().context(USAGE)?;
+    let api = SlackApi::new(config.slack_bot_token.clone().context(USAGE)?);
+    let identities = match &config.users_file {
+        Some(path) => Identities::load(path)?,
+        None => Identities::from_ids(&config.slack_users),
+    };
+    if identities.is_empty() {
+        warn!("No Slack users are allowed; set CHITTI_SLACK_USERS or CHITTI_USERS_FILE to answer anyone");
+    }
+    let bot_user = api.bot_user_id().await.context("Failed to check SLACK_BOT_TOKEN")?;
+    let mut daemon = Daemon { api, identities, client, tools, config, vault, sessions: HashMap::new() };
+    println!("Listening on Slack as <@{}> (Ctrl+C to stop)", bot_user);
+
+    loop {
+        let url = daemon.api.open_connection(&app_token).await.context("Failed to open a Socket Mode connection")?;
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str()).await
+            .context("Failed to connect to Slack")?;
+        let (mut sink, mut stream) = socket.split();
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            };
+            let text = match message {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            };
+            let Ok(value) = serde_json::from_str::<Value>(text.as_str()) else { continue };
+            let envelope = slack::parse_envelope(&value, &bot_user);
+            // Slack redelivers anything not acknowledged within a few seconds.
+            if let Some(id) = &envelope.id {
+                sink.send(Message::Text(json!({ "envelope_id": id }).to_string().into())).await?;
+            }
+            if envelope.kind == "disconnect" {
+                break;
+            }
+            let Some(incoming) = envelope.incoming else { continue };
+            if let Err(e) = daemon.dispatch(incoming).await {
+                warn!("Slack event failed: {:#}", e);
+            }
+        }
+        info!("Slack connection closed, reconnecting");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// What every thread's session is started from, and the running sessions.
+struct Daemon<'a> {
+    api: SlackApi,
+    identities: Identities,
+    client: Client,
+    tools: Arc<ToolRegistry>,
+    config: &'a Config,
+    vault: Option<Arc<Vault>>,
+    sessions: HashMap<ThreadKey, Arc<SlackBridge>>,
+}
+
+impl Daemon<'_> {
+    async fn dispatch(&mut self, incoming: Incoming) -> Result<()> {
+        self.sessions.retain(|_, bridge| !bridge.is_closed());
+        match incoming {
+            Incoming::Message { thread, user, text, mention } => {
+                let Ok(policy) = self.identities.authorize(&user, None) else {
+                    debug!(user = %user, "Ignoring Slack message from a user who isn't allowed");
+                    return Ok(());
+                };
+                let bridge = match self.sessions.get(&thread) {
+                    Some(bridge) if bridge.owner() == user => bridge.clone(),
+                    Some(_) => {
+                        debug!(user = %user, "Ignoring a reply in another user's Slack thread");
+                        return Ok(());
+                    }
+                    None if mention => {
+                        info!(channel = %thread.channel, thread = %thread.thread_ts, user = %user, "Starting Slack session");
+                        let (bridge, rx) = SlackBridge::new(self.api.clone(), thread.clone(), user);
+                        let bridge = Arc::new(bridge);
+                        self.start_session(bridge.clone(), rx, policy);
+                        self.sessions.insert(thread, bridge.clone());
+                        bridge
+                    }
+                    None => return Ok(()),
+                };
+                bridge.deliver(text).await
+            }
+            Incoming::Decision { thread, user, approve } => match self.sessions.get(&thread) {
+                Some(bridge) => bridge.decide(approve, &user).await,
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Runs the thread owner's Conductor. It sees the tools `CHITTI_BRIDGE_TOOLS`
+    /// allows for Slack, narrowed by the owner's policy.
+    fn start_session(&self, bridge: Arc<SlackBridge>, rx: mpsc::Receiver<UserEvent>, policy: &UserPolicy) {
+        let config = self.config;
+        let bridge_tools = config.bridge_tools.get("slack").cloned().unwrap_or_else(|| self.tools.names());
+        let tools = Arc::new(policy.registry(&self.tools.subset(&bridge_tools)));
+        let brain = Box::new(GeminiEngine::new(self.client.clone(), tools.clone()));
+        let mut conductor = Conductor::new(brain, bridge, rx, tools)
+            .with_artifacts_dir(crate::conductor::artifacts::root(), self.vault.clone())
+            .with_bridge_tools(&config.bridge_tools)
+            .with_language(policy.language.clone().or_else(|| config.language.clone()))
+            .with_auto_approve(policy.auto_approve.clone().unwrap_or_else(|| config.auto_approve_tools.clone()));
+        tokio::spawn(async move {
+            if let Err(e) = conductor.run().await {
+                warn!("Slack session ended with an error: {:#}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::time::Instant;
+use tracing::warn;
+use crate::brains::gemini::adapter::GeminiEngine;
+use crate::brains::gemini::Client;
+use crate::bridges::headless::HeadlessBridge;
+use crate::conductor::Conductor;
+use crate::tools::ToolRegistry;
+
+pub const USAGE: &str = "Usage: chitti watch <glob> --prompt \"...\" [--tools a,b] [--debounce-ms N] [--max-concurrent N]";
+
+/// `chitti watch <glob> --prompt "..."`: runs a conversation for each file
+/// matching the glob whenever it changes. Bursts of writes within the debounce
+/// window count as one change. Tools are off unless listed with `--tools`;
+/// listed tools are auto-approved.
+pub async fn run(args: &[String], client: Client, registry: Arc<ToolRegistry>) -> Result<()> {
+    let pattern = args.first().filter(|a| !a.starts_with("--")).context(USAGE)?;
+    let template = super::flag(args, "--prompt").context(USAGE)?.to_string();
+    let tool_names: Vec<String> = super::flag(args, "--tools")
+        .map(|t| t.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let debounce = Duration::from_millis(super::flag(args, "--debounce-ms").and_then(|n| n.parse().ok()).unwrap_or(500));
+    let max_concurrent = super::flag(args, "--max-concurrent").and_then(|n| n.parse().ok()).unwrap_or(2usize);
+
+    let cwd = std::env::current_dir()?;
+    let (base, matcher) = compile(pattern, &cwd)?;
+    let tools = Arc::new(registry.subset(&tool_names));
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    })?;
+    watcher.watch(&base, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", base.display()))?;
+    println!("Watching {} (Ctrl+C to stop)", pattern);
+
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    loop {
+        let next_due = pending.values().min().map(|last| *last + debounce);
+        tokio::select! {
+            Some(path) = rx.recv() => {
+                let relative = path.strip_prefix(&cwd).unwrap_or(&path).to_path_buf();
+                if path.is_file() && (matcher.is_match(&relative) || matcher.is_match(&path)) {
+                    pending.insert(relative, Instant::now());
+                }
+            }
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                let now = Instant::now();
+                let due: Vec<PathBuf> = pending.iter()
+                    .filter(|(_, last)| **last + debounce <= now)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                for path in due {
+                    pending.remove(&path);
+                    let prompt = render_prompt(&template, &path);
+                    let (client, tools, semaphore) = (client.clone(), tools.clone(), semaphore.clone());
+                    tokio::spawn(async move {
+                        let _permit = semaphore.acquire_owned().await;
+                        match converse(client, tools, prompt).await {
+                            Ok(output) => println!("\n── {} ──\n{}", path.display(), output.trim_end()),
+                            Err(e) => warn!(file = %path.display(), "Watch run failed: {:#}", e),
+                        }
+                    });
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    Ok(())
+}
+
+async fn converse(client: Client, tools: Arc<ToolRegistry>, prompt: String) -> Result<String> {
+    let brain = Box::new(GeminiEngine::new(client, tools.clone()));
+    let (bridge, rx) = HeadlessBridge::new();
+    let bridge = Arc::new(bridge);
+    let mut conductor = Conductor::new(brain, bridge.clone(), rx, tools);
+    conductor.handle_conversation(prompt).await?;
+    if let Some(err) = bridge.errors().await.first() {
+        anyhow::bail!("{}", err);
+    }
+    Ok(bridge.output().await)
+}
+
+/// The directory to watch (the glob's leading literal components) and a
+/// matcher for paths relative to `cwd`.
+pub fn compile(pattern: &str, cwd: &Path) -> Result<(PathBuf, GlobMatcher)> {
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob '{}'", pattern))?
+        .compile_matcher();
+    let literal: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[', '{']))
+        .collect();
+    // A pattern without wildcards names a single file; watch its directory.
+    let base = if literal.as_os_str() == pattern {
+        literal.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        literal
+    };
+    let base = if base.as_os_str().is_empty() { cwd.to_path_buf() } else { cwd.join(base) };
+    Ok((base, matcher))
+}
+
+/// Fills `{file}` in the prompt, or appends the path when there's no placeholder.
+pub fn render_prompt(template: &str, path: &Path) -> String {
+    let file = path.display().to_string();
+    if template.contains("{file}") {
+        template.replace("{file}", &file)
+    } else {
+        format!("{}\n\nChanged file: {}", template, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_glob_base_and_prompt() -> Result<()> {
+        let cwd = Path::new("/work");
+        let (base, matcher) = compile("logs/**/*.log", cwd)?;
+        assert_eq!(base, PathBuf::from("/work/logs"));
+        assert!(matcher.is_match("logs/app/today.log"));
+        assert!(!matcher.is_match("logs/app/today.txt"));
+
+        let (base, matcher) = compile("*.md", cwd)?;
+        assert_eq!(base, PathBuf::from("/work"));
+        assert!(!matcher.is_match("docs/readme.md"));
+
+        let (base, _) = compile("src/main.rs", cwd)?;
+        assert_eq!(base, PathBuf::from("/work/src"));
+
+        assert_eq!(render_prompt("Review {file} for bugs", Path::new("src/a.rs")), "Review src/a.rs for bugs");
+        assert_eq!(render_prompt("Summarize new errors", Path::new("app.log")), "Summarize new errors\n\nChanged file: app.log");
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::path::{Path, PathBuf};
+use crate::brains::gemini::Client;
+
+/// Files the model produced during a session (plots, CSVs, generated images),
+/// saved under one directory so they outlive the scrollback.
+pub struct Artifacts {
+    dir: PathBuf,
+    saved: Vec<PathBuf>,
+}
+
+impl Artifacts {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, saved: Vec::new() }
+    }
+
+    /// A fresh `<root>/<session>` directory, named so sessions sort by start time.
+    /// Nothing is created until the first file is saved.
+    pub fn for_new_session(root: &Path) -> Self {
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        Self::new(root.join(format!("{}-{}", started, &id[..8])))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    #[allow(dead_code)]
+    pub fn saved(&self) -> &[PathBuf] {
+        &self.saved
+    }
+
+    /// Copies an existing file (a transcript, an export) into the session.
+    pub fn keep(&mut self, source: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let name = source.file_name().ok_or_else(|| anyhow::anyhow!("{} is not a file", source.display()))?;
+        let path = self.dir.join(name);
+        std::fs::copy(source, &path)?;
+        self.saved.push(path.clone());
+        Ok(path)
+    }
+
+    /// What `/artifacts` shows: every file in the session directory with its size.
+    pub fn listing(&self) -> String {
+        let mut entries: Vec<(String, u64)> = std::fs::read_dir(&self.dir)
+            .map(|dir| dir.filter_map(|e| e.ok())
+                .filter_map(|e| Some((e.file_name().to_string_lossy().to_string(), e.metadata().ok()?.len())))
+                .collect())
+            .unwrap_or_default();
+        if entries.is_empty() {
+            return format!("No artifacts yet. They will be saved to {}", self.dir.display());
+        }
+        entries.sort();
+        let mut out = format!("Artifacts in {}:", self.dir.display());
+        for (name, size) in entries {
+            out.push_str(&format!("\n  {:<24} {:>8} bytes", name, size));
+        }
+        out
+    }
+
+    /// Saves inline base64 `data`, or downloads `uri` through the File API
+    /// when it names an uploaded file. Returns `None` for remote URIs that
+    /// aren't File API references.
+    pub async fn save(&mut self, client: Option<&Client>, mime_type: &str, data: Option<&str>, uri: Option<&str>) -> Result<Option<PathBuf>> {
+        let bytes = match (data, uri.and_then(file_ref), client) {
+            (Some(data), _, _) => STANDARD.decode(data)?,
+            (None, Some(name), Some(client)) => client.download_file(&name).await?,
+            _ => return Ok(None),
+        };
+        self.write(mime_type, &bytes).map(Some)
+    }
+
+    pub fn write(&mut self, mime_type: &str, bytes: &[u8]) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(file_name(self.saved.len() + 1, mime_type));
+        std::fs::write(&path, bytes)?;
+        self.saved.push(path.clone());
+        Ok(path)
+    }
+}
+
+/// `~/.chitti/artifacts`, holding one directory per session.
+pub fn root() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".chitti"))
+        .unwrap_or_else(|| std::env::temp_dir().join("chitti"))
+        .join("artifacts")
+}
+
+/// The File API resource name (`files/abc123`) referenced by a URI such as
+/// `https://generativelanguage.googleapis.com/v1beta/files/abc123`.
+pub fn file_ref(uri: &str) -> Option<String> {
+    let id = uri.split("files/").nth(1)?;
+    let id: String = id.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    (!id.is_empty()).then(|| format!("files/{}", id))
+}
+
+/// `artifact-3.png` for the third artifact of a session.
+fn file_name(index: usize, mime_type: &str) -> String {
+    let ext = match mime_type {
+        "text/csv" => "csv",
+        "text/plain" => "txt",
+        "application/json" => "json",
+        "image/jpeg" => "jpg",
+        _ => mime_guess::get_mime_extensions_str(mime_type)
+            .and_then(|exts| exts.first().copied())
+            .unwrap_or("bin"),
+    };
+    format!("artifact-{}.{}", index, ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_artifacts_are_saved_with_typed_names() -> Result<()> {
+        assert_eq!(file_ref("https://generativelanguage.googleapis.com/v1beta/files/abc-123:download?alt=media"), Some("files/abc-123".to_string()));
+        assert_eq!(file_ref("https://example.com/plot.png"), None);
+
+        let dir = std::env::temp_dir().join(format!("chitti-artifacts-test-{}", uuid::Uuid::new_v4()));
+        let mut artifacts = Artifacts::new(dir.clone());
+        let png = artifacts.save(None, "image/png", Some(&STANDARD.encode(b"\x89PNG")), None).await?.unwrap();
+        assert_eq!(png, dir.join("artifact-1.png"));
+        assert_eq!(std::fs::read(&png)?, b"\x89PNG");
+        let csv = artifacts.write("text/csv", b"a,b\n1,2\n")?;
+        assert_eq!(csv.file_name().unwrap(), "artifact-2.csv");
+        // A File API reference can't be fetched without a client.
+        assert!(artifacts.save(None, "text/csv", None, Some("https://x/v1beta/files/abc")).await?.is_none());
+        assert_eq!(artifacts.saved().len(), 2);
+        let listing = artifacts.listing();
+        assert!(listing.contains("artifact-1.png") && listing.contains("artifact-2.csv"), "{}", listing);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+pub const MAX_CANDIDATES: usize = 8;
+
+/// One generation's text and the interaction it produced.
+#[derive(Debug, Clone, Default)]
+pub struct Candidate {
+    pub temperature: f32,
+    pub text: String,
+    pub interaction_id: Option<String>,
+}
+
+/// Spreads `n` temperatures evenly between 0.3 and 1.2 so candidates differ.
+pub fn temperatures(n: usize) -> Vec<f32> {
+    (0..n)
+        .map(|i| 0.3 + 0.9 * i as f32 / (n.max(2) - 1) as f32)
+        .collect()
+}
+
+/// Runs a turn to completion, keeping only its text. Tool calls are ignored.
+pub async fn generate(brain: &dyn BrainEngine, context: TurnContext) -> Result<Candidate> {
+    let temperature = context.temperature.unwrap_or_default();
+    let stream = brain.process_turn(context).await?;
+    collect(stream, temperature).await
+}
+
+/// Drains an already started turn into a candidate.
+pub async fn collect(mut stream: BoxStream<'static, Result<BrainEvent>>, temperature: f32) -> Result<Candidate> {
+    let mut candidate = Candidate { temperature, ..Default::default() };
+    while let Some(evt) = stream.next().await {
+        match evt? {
+            BrainEvent::TextDelta(text) => candidate.text.push_str(&text),
+            BrainEvent::Complete { interaction_id: Some(id) } => candidate.interaction_id = Some(id),
+            BrainEvent::Error(err) => anyhow::bail!(err),
+            _ => {}
+        }
+    }
+    Ok(candidate)
+}
+
+/// Asks the model to choose or merge the best of several answers.
+pub fn judge_prompt(prompt: &str, candidates: &[Candidate]) -> String {
+    let mut out = format!(
+        "You were asked:\n{}\n\nSeveral candidate answers were generated. Pick the best one, or merge their \
+         strongest parts into a single better answer. Reply with the final answer only, without \
+         mentioning the candidates.\n",
+        prompt
+    );
+    for (i, c) in candidates.iter().enumerate() {
+        out.push_str(&format!("\n<candidate {}>\n{}\n</candidate {}>\n", i + 1, c.text.trim(), i + 1));
+    }
+    out
+}
+
+/// A one-line preview of a candidate for the collapsed view.
+pub fn summary(index: usize, candidate: &Candidate) -> String {
+    let first = candidate.text.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    let preview: String = first.chars().take(70).collect();
+    let ellipsis = if first.chars().count() > 70 { "…" } else { "" };
+    format!(
+        "#{} (temperature {:.1}, {} chars): {}{}",
+        index + 1,
+        candidate.temperature,
+        candidate.text.len(),
+        preview,
+        ellipsis
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temperatures_and_judge_prompt() {
+        assert_eq!(temperatures(1), vec![0.3]);
+        let temps = temperatures(4);
+        assert_eq!(temps.len(), 4);
+        assert!((temps[3] - 1.2).abs() < 1e-6);
+
+        let candidates = vec![
+            Candidate { temperature: 0.3, text: "A".to_string(), interaction_id: None },
+            Candidate { temperature: 1.2, text: "B".to_string(), interaction_id: None },
+        ];
+        let prompt = judge_prompt("why?", &candidates);
+        assert!(prompt.contains("<candidate 1>\nA\n</candidate 1>"));
+        assert!(prompt.contains("<candidate 2>\nB\n</candidate 2>"));
+        assert_eq!(summary(1, &candidates[1]), "#2 (temperature 1.2, 1 chars): B");
+    }
+}
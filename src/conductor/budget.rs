@@ -0,0 +1,111 @@
+use crate::conductor::events::TurnContext;
+use crate::tools::truncate::{self, OutputStore};
+
+/// Rough bytes per token for text, used before (or instead of) countTokens.
+const BYTES_PER_TOKEN: usize = 4;
+
+/// One part of the input a turn sends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub label: String,
+    pub bytes: usize,
+    pub tokens: u64,
+    /// Whether `tokens` came from the API rather than an estimate.
+    pub counted: bool,
+}
+
+pub fn estimate(bytes: usize) -> u64 {
+    bytes.div_ceil(BYTES_PER_TOKEN) as u64
+}
+
+/// The labelled texts making up a turn's new input. Earlier turns live on
+/// the server and aren't resent, so they don't count.
+pub fn sources(context: &TurnContext) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    if let Some(instruction) = &context.system_instruction {
+        out.push(("system instruction".to_string(), instruction.clone()));
+    }
+    if !context.prompt.is_empty() {
+        out.push(("prompt".to_string(), context.prompt.clone()));
+    }
+    for res in &context.tool_results {
+        out.push((format!("tool result {}", res.name), res.result.to_string()));
+    }
+    out
+}
+
+/// The pre-send table, largest source first.
+pub fn summary(sources: &[Source], limit: u64) -> String {
+    let mut sorted: Vec<&Source> = sources.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.tokens));
+    let total: u64 = sources.iter().map(|s| s.tokens).sum();
+    let mut out = format!("This request is about {} tokens (limit {}):", total, limit);
+    for s in sorted {
+        let approx = if s.counted { "" } else { "~" };
+        out.push_str(&format!("\n  {:<28} {}{:>8} tokens  {:>10} bytes", s.label, approx, s.tokens, s.bytes));
+    }
+    out
+}
+
+/// Shrinks the prompt and tool results to share `limit` tokens between
+/// them. Cut text is kept in `store` so the model can page it back in.
+pub fn trim(context: &mut TurnContext, limit: u64, store: &OutputStore) {
+    let parts = context.tool_results.len() + usize::from(!context.prompt.is_empty());
+    let instruction = context.system_instruction.as_ref().map_or(0, |s| s.len());
+    let budget = (limit as usize * BYTES_PER_TOKEN).saturating_sub(instruction) / parts.max(1);
+    if context.prompt.len() > budget {
+        let prompt = serde_json::Value::String(std::mem::take(&mut context.prompt));
+        if let serde_json::Value::String(trimmed) = truncate::truncate_output(prompt, budget, store) {
+            context.prompt = trimmed;
+        }
+    }
+    for res in &mut context.tool_results {
+        res.result = truncate::truncate_output(std::mem::take(&mut res.result), budget, store);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+
+    #[test]
+    fn test_sources_summary_and_trim() {
+        let log: String = (0..2000).map(|i| format!("line {}\n", i)).collect();
+        let mut context = TurnContext {
+            prompt: "summarise the log".to_string(),
+            tool_results: vec![ToolResult {
+                call_id: "1".to_string(),
+                name: "read_file".to_string(),
+                result: serde_json::json!({ "content": log }),
+                is_error: false,
+            }],
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let found = sources(&context);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].0, "tool result read_file");
+
+        let table = summary(&[
+            Source { label: "prompt".to_string(), bytes: 17, tokens: 4, counted: true },
+            Source { label: "tool result read_file".to_string(), bytes: 17780, tokens: 4445, counted: false },
+        ], 1000);
+        assert!(table.starts_with("This request is about 4449 tokens (limit 1000):"));
+        assert!(table.lines().nth(1).unwrap().contains("~    4445"));
+
+        let store = OutputStore::new();
+        trim(&mut context, 1000, &store);
+        assert_eq!(context.prompt, "summarise the log");
+        let trimmed = context.tool_results[0].result.to_string();
+        assert!(trimmed.len() < 2500);
+        assert!(trimmed.contains("read_tool_output"));
+    }
+}
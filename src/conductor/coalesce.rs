@@ -0,0 +1,61 @@
+use crate::bridges::FlushPolicy;
+use tokio::time::Instant;
+
+/// Buffers streamed text according to a bridge's `FlushPolicy`.
+pub struct Coalescer {
+    policy: Option<FlushPolicy>,
+    buffer: String,
+    started: Option<Instant>,
+}
+
+impl Coalescer {
+    pub fn new(policy: Option<FlushPolicy>) -> Self {
+        Self { policy, buffer: String::new(), started: None }
+    }
+
+    /// Adds `text`; returns what should be sent now, if anything.
+    pub fn push(&mut self, text: &str) -> Option<String> {
+        let Some(policy) = self.policy else {
+            return Some(text.to_string());
+        };
+        self.buffer.push_str(text);
+        let started = *self.started.get_or_insert_with(Instant::now);
+        if self.buffer.chars().count() >= policy.max_chars || started.elapsed() >= policy.interval {
+            return self.flush();
+        }
+        None
+    }
+
+    /// Takes everything buffered so far.
+    pub fn flush(&mut self) -> Option<String> {
+        self.started = None;
+        (!self.buffer.is_empty()).then(|| std::mem::take(&mut self.buffer))
+    }
+
+    /// When buffered text is due even if no more deltas arrive.
+    pub fn deadline(&self) -> Option<Instant> {
+        Some(self.started? + self.policy?.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_coalescer_flushes_on_size_and_passes_through_without_policy() {
+        let mut direct = Coalescer::new(None);
+        assert_eq!(direct.push("a").as_deref(), Some("a"));
+        assert_eq!(direct.deadline(), None);
+
+        let mut batched = Coalescer::new(Some(FlushPolicy { interval: Duration::from_secs(60), max_chars: 5 }));
+        assert_eq!(batched.push("ab"), None);
+        assert!(batched.deadline().is_some());
+        assert_eq!(batched.push("cde").as_deref(), Some("abcde"));
+        assert_eq!(batched.deadline(), None);
+        assert_eq!(batched.push("f"), None);
+        assert_eq!(batched.flush().as_deref(), Some("f"));
+        assert_eq!(batched.flush(), None);
+    }
+}
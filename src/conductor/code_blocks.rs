@@ -0,0 +1,66 @@
+/// A fenced code block from a model message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// Extracts fenced (``` or ~~~) code blocks in order of appearance.
+/// An unterminated final block is included, since output may have been cut off.
+pub fn extract(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, Option<String>, Vec<&str>)> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match &mut current {
+            None => {
+                if let Some(fence) = fence_of(trimmed) {
+                    let info = trimmed[fence.len()..].trim();
+                    let language = info.split_whitespace().next().map(str::to_string);
+                    current = Some((fence, language, Vec::new()));
+                }
+            }
+            Some((fence, _, lines)) => {
+                if trimmed.starts_with(fence.as_str()) && trimmed[fence.len()..].trim().is_empty() {
+                    let (_, language, lines) = current.take().unwrap();
+                    blocks.push(CodeBlock { language, code: join(&lines) });
+                } else {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    if let Some((_, language, lines)) = current {
+        blocks.push(CodeBlock { language, code: join(&lines) });
+    }
+    blocks
+}
+
+fn fence_of(line: &str) -> Option<String> {
+    let ch = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then(|| ch.to_string().repeat(len))
+}
+
+fn join(lines: &[&str]) -> String {
+    let mut code = lines.join("\n");
+    code.push('\n');
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_blocks_with_language() {
+        let text = "Here:\n```rust\nfn main() {}\n```\nand\n~~~\necho hi\n~~~\n```py\nprint(1)";
+        let blocks = extract(text);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0], CodeBlock { language: Some("rust".to_string()), code: "fn main() {}\n".to_string() });
+        assert_eq!(blocks[1].language, None);
+        assert_eq!(blocks[1].code, "echo hi\n");
+        assert_eq!(blocks[2].code, "print(1)\n");
+    }
+}
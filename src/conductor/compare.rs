@@ -0,0 +1,89 @@
+use futures_util::StreamExt;
+use std::time::Instant;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, ComparisonColumn, TurnContext};
+
+/// Runs a turn against the model named in `context.model`, collecting its text,
+/// token usage and wall-clock latency. Failures are reported in the column
+/// rather than returned so the other side of the comparison still renders.
+pub async fn run(brain: &dyn BrainEngine, context: TurnContext) -> ComparisonColumn {
+    let started = Instant::now();
+    let mut column = ComparisonColumn {
+        label: context.model.clone().unwrap_or_default(),
+        text: String::new(),
+        latency_ms: 0,
+        usage: None,
+        error: None,
+    };
+    match brain.process_turn(context).await {
+        Ok(mut stream) => {
+            while let Some(evt) = stream.next().await {
+                match evt {
+                    Ok(BrainEvent::TextDelta(text)) => column.text.push_str(&text),
+                    Ok(BrainEvent::Usage(usage)) => column.usage = Some(usage),
+                    Ok(BrainEvent::Error(err)) => column.error = Some(err),
+                    Err(e) => column.error = Some(e.to_string()),
+                    Ok(_) => {}
+                }
+            }
+        }
+        Err(e) => column.error = Some(e.to_string()),
+    }
+    column.latency_ms = started.elapsed().as_millis();
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use futures_util::stream::{self, BoxStream};
+    use crate::conductor::events::Usage;
+
+    struct EchoModelBrain;
+
+    #[async_trait]
+    impl BrainEngine for EchoModelBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<BoxStream<'static, Result<BrainEvent>>> {
+            let model = context.model.unwrap_or_default();
+            if model == "broken" {
+                anyhow::bail!("unknown model");
+            }
+            Ok(Box::pin(stream::iter(vec![
+                Ok(BrainEvent::TextDelta(format!("{} says {}", model, context.prompt))),
+                Ok(BrainEvent::Usage(Usage { input_tokens: 3, output_tokens: 4, thought_tokens: 0 })),
+                Ok(BrainEvent::Complete { interaction_id: None }),
+            ])))
+        }
+    }
+
+    fn context(model: &str) -> TurnContext {
+        TurnContext {
+            prompt: "hi".to_string(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: Some(model.to_string()),
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_columns_collect_text_usage_and_errors() {
+        let ok = run(&EchoModelBrain, context("flash")).await;
+        assert_eq!(ok.label, "flash");
+        assert_eq!(ok.text, "flash says hi");
+        assert_eq!(ok.usage.map(|u| u.output_tokens), Some(4));
+        assert!(ok.error.is_none());
+
+        let failed = run(&EchoModelBrain, context("broken")).await;
+        assert_eq!(failed.error.as_deref(), Some("unknown model"));
+        assert!(failed.text.is_empty());
+    }
+}
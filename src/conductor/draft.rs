@@ -0,0 +1,49 @@
+use anyhow::Result;
+use tokio::task::JoinHandle;
+use crate::conductor::best_of::Candidate;
+
+/// A High-thinking rewrite of a quick Minimal-thinking answer, running in
+/// the background while the user reads the draft.
+pub struct Refinement {
+    pub handle: JoinHandle<Result<Candidate>>,
+    /// The draft's interaction. The refined answer only becomes the
+    /// conversation state if nothing has moved past the draft meanwhile.
+    pub draft_id: Option<String>,
+}
+
+impl Drop for Refinement {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Asks the model to revisit its draft, which is already in the context.
+pub fn refine_prompt(prompt: &str) -> String {
+    format!(
+        "Your previous answer was a quick draft. Think the question through carefully and write an \
+         improved, complete answer to it, fixing any mistakes or omissions in the draft. Reply with \
+         the final answer only, without mentioning the draft.\n\nThe question was:\n{}",
+        prompt
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_refinement_is_aborted_when_dropped() {
+        let refinement = Refinement {
+            handle: tokio::spawn(async {
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                Ok(Candidate::default())
+            }),
+            draft_id: None,
+        };
+        let abort = refinement.handle.abort_handle();
+        drop(refinement);
+        tokio::task::yield_now().await;
+        assert!(abort.is_finished());
+        assert!(refine_prompt("why?").ends_with("The question was:\nwhy?"));
+    }
+}
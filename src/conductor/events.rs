@@ -1,4 +1,7 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::PathBuf;
+use crate::brains::gemini::types::ThinkingLevel;
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -8,32 +11,104 @@ pub enum UserEvent {
     Steer(String),   // Steering instruction
     Approve,         // "y"
     Reject,          // "n"
+    Replay { after: u64 }, // Re-send buffered events with a higher sequence id
+    EndOfInput,      // The input stream closed (Ctrl-D); exit without confirmation
 }
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum SystemEvent {
     Text(String),
+    Thought(String), // Thought summary delta, kept apart from the answer text
     ToolCall { name: String, args: Value },
     Error(String),
+    Warning(String),
+    Info(String),
+    Status(Option<String>), // Transient line redrawn in place; `None` clears it
+    Mode(String), // The active /mode preset's name, for the status bar
     RequestApproval { description: String },
+    Debug { message: String, request_id: Option<String> }, // request_id: the model request in flight, if any
+    StructuredChunk { value: Value, complete: bool, errors: Vec<String> },
+    Shutdown { reason: String }, // Last event before the process exits
+    Image { mime_type: String, data: Option<String>, uri: Option<String> }, // data is base64
+    Comparison { columns: Vec<ComparisonColumn> },
+    Artifact { path: std::path::PathBuf, mime_type: String }, // A generated file saved to disk
+    FileRefs { files: Vec<FileRef> }, // Files tools read or changed during the turn
 }
 
+/// A file a tool touched during the turn; `lines` is `None` when it can't be
+/// read locally (e.g. deleted, or on a remote host).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRef {
+    pub path: String,
+    pub changed: bool,
+    pub lines: Option<usize>,
+}
+
+/// A `SystemEvent` stamped with its position in the stream. `seq` increases
+/// by one per event; `turn` increases with each user message.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct Sequenced {
+    pub seq: u64,
+    pub turn: u64,
+    pub event: SystemEvent,
+}
+
+/// One side of a `/compare` run.
 #[derive(Debug, Clone)]
+pub struct ComparisonColumn {
+    pub label: String,
+    pub text: String,
+    pub latency_ms: u128,
+    pub usage: Option<Usage>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub enum BrainEvent {
     TextDelta(String),
     ThoughtDelta(String),
     ToolCall { name: String, id: String, args: Value },
+    StructuredChunk { value: Value, complete: bool, errors: Vec<String> },
+    Image { mime_type: String, data: Option<String>, uri: Option<String> },
+    File { mime_type: String, data: Option<String>, uri: Option<String> }, // Generated documents, audio, video
+    Usage(Usage),
     Complete { interaction_id: Option<String> },
     Error(String),
+    RequestId(String), // X-Request-ID of the HTTP request the following events answer
+}
+
+/// Token counts reported for one model request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub thought_tokens: u64,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.thought_tokens += other.thought_tokens;
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TurnContext {
     pub prompt: String,
+    pub system_instruction: Option<String>,
+    pub response_schema: Option<Value>,
     pub previous_interaction_id: Option<String>,
     pub tool_results: Vec<ToolResult>,
+    pub thinking_level: Option<ThinkingLevel>, // None uses the model default
+    pub temperature: Option<f32>,
+    pub model: Option<String>, // Overrides the brain's default model for this turn
+    pub allowed_tools: Option<Vec<String>>, // Tools the model may see; None means all
+    pub search_grounding: bool, // Lets the model use Google Search; engines without it ignore this
+    pub attachments: Vec<PathBuf>, // Local files sent with the prompt; engines without file input ignore them
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,53 @@
+use serde_json::{json, Value};
+
+/// Suggestions shown after an answer.
+pub const MAX_SUGGESTIONS: usize = 3;
+/// Characters of the answer the suggestions are based on.
+const ANSWER_EXCERPT: usize = 4000;
+
+/// Asks for short follow-up prompts the user might send next.
+pub fn prompt(question: &str, answer: &str) -> String {
+    let answer: String = answer.chars().take(ANSWER_EXCERPT).collect();
+    format!(
+        "Suggest 2 or 3 short follow-up messages the user might send next, written as the user \
+         would type them (under 12 words each, no numbering). Skip anything the answer already covers.\n\n\
+         User:\n{}\n\nAssistant:\n{}",
+        question, answer
+    )
+}
+
+pub fn schema() -> Value {
+    json!({
+        "type": "array",
+        "items": { "type": "string" },
+        "maxItems": MAX_SUGGESTIONS,
+    })
+}
+
+/// The usable suggestions in the model's reply: trimmed, single-line,
+/// de-duplicated and capped.
+pub fn parse(reply: &str) -> Vec<String> {
+    let items: Vec<String> = serde_json::from_str(reply.trim()).unwrap_or_default();
+    let mut out: Vec<String> = Vec::new();
+    for item in items {
+        let item = item.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !item.is_empty() && !out.iter().any(|o| o.eq_ignore_ascii_case(&item)) {
+            out.push(item);
+        }
+    }
+    out.truncate(MAX_SUGGESTIONS);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_follow_ups() {
+        let reply = r#"["Show the failing unit", " show the failing unit ", "Restart\nnginx", "", "Tail the logs", "One more"]"#;
+        assert_eq!(parse(reply), vec!["Show the failing unit", "Restart nginx", "Tail the logs"]);
+        assert!(parse("not json").is_empty());
+        assert!(prompt("why?", "because").ends_with("User:\nwhy?\n\nAssistant:\nbecause"));
+    }
+}
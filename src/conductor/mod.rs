@@ -2,23 +2,160 @@ use anyhow::Result;
 use tokio::sync::mpsc;
 use futures_util::StreamExt;
 use std::sync::Arc;
-use std::collections::VecDeque;
-use crate::brains::BrainEngine;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use crate::brains::{BrainEngine, RateLimited};
 use crate::bridges::CommBridge;
+use crate::bridges::buffer::{BufferedBridge, DEFAULT_BUFFER_CAPACITY};
+use crate::bridges::sequence::{SequencedBridge, DEFAULT_REPLAY_CAPACITY};
 use crate::conductor::events::{UserEvent, SystemEvent, BrainEvent, TurnContext, ToolResult};
-use crate::tools::ToolRegistry;
+use crate::tools::{FileAccess, ToolRegistry};
+use crate::brains::gemini::types::ThinkingLevel;
+use crate::brains::gemini::Client;
+use crate::brains::gemini::keys::KeyRing;
+use crate::i18n::{self, Msg};
+use crate::notifier::Notifier;
+use crate::turn_log::{TurnLog, TurnRecord};
+use crate::redact;
+use crate::reload::{self, Settings};
+use coalesce::Coalescer;
+use tee::Tee;
+use crate::tools::{hints, sanitize};
+use crate::tools::truncate::{self, OutputStore};
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
 
 pub mod events;
+pub mod follow_ups;
+pub mod mode;
+pub mod allow_list;
+pub mod artifacts;
+pub mod best_of;
+pub mod budget;
+pub mod coalesce;
+pub mod code_blocks;
+pub mod compare;
+pub mod draft;
+pub mod overflow;
+pub mod palette;
+pub mod pipeline;
+pub mod quick_actions;
+pub mod review;
+pub mod sensitive;
 pub mod session;
+pub mod stars;
+pub mod tee;
+pub mod thinking;
+pub mod timings;
+pub mod translate;
+pub mod vars;
 
 
+/// How a single model request ended.
+enum TurnOutcome {
+    Done(Vec<(String, String, serde_json::Value)>),
+    Aborted,
+    TimedOut,
+    /// The request didn't fit in the model's context window; carries the API error.
+    ContextTooLong(String),
+    /// The provider rate-limited the request; carries how long it asked us to wait.
+    RateLimited(Option<Duration>),
+}
+
+/// Rate-limit waits within one turn before giving up.
+const MAX_RATE_LIMIT_WAITS: u32 = 5;
+/// How long to wait when the provider doesn't say.
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(30);
+
+/// Awaits `fut`, or returns `None` if `deadline` passes first.
+async fn with_deadline<F: std::future::Future>(deadline: Option<Instant>, fut: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, fut).await.ok(),
+        None => Some(fut.await),
+    }
+}
+
 pub struct Conductor {
     brain: Box<dyn BrainEngine>,
     bridge: Arc<dyn CommBridge>,
+    sequencer: Arc<SequencedBridge>,
+    buffer: Arc<BufferedBridge>,
     events_rx: mpsc::Receiver<UserEvent>,
     tools: Arc<ToolRegistry>,
     previous_interaction_id: Option<String>,
+    interaction_ids: Vec<String>,
+    purge_on_clear: bool,
+    files: Option<(Client, Duration)>,
+    artifacts: artifacts::Artifacts,
     pending_steering: VecDeque<String>,
+    /// Function results the stored conversation still waits for after a
+    /// cancelled turn; they go out with the next message.
+    unanswered: Vec<ToolResult>,
+    /// Binary files mentioned with `@path`, sent with the next request.
+    attachments: Vec<std::path::PathBuf>,
+    language: Option<String>,
+    dev_mode: bool,
+    injection_classifier: bool,
+    max_tool_result_bytes: usize,
+    output_store: Arc<OutputStore>,
+    response_schema: Option<serde_json::Value>,
+    notifier: Option<Notifier>,
+    turn_log: Option<TurnLog>,
+    turn_usage: events::Usage,
+    /// `X-Request-ID`s of this turn's model requests, latest last.
+    request_ids: Vec<String>,
+    timings: timings::TurnTimings,
+    timing_stats: timings::TimingStats,
+    turn_files: std::collections::BTreeMap<String, FileAccess>,
+    snapshots: review::Snapshots,
+    tee: Option<Tee>,
+    last_response: String,
+    last_prompt: String,
+    stars: stars::Stars,
+    session: session::Session,
+    /// Where sessions are saved, for `/sessions` and `/search`; `None` keeps them in memory.
+    artifacts_root: Option<std::path::PathBuf>,
+    vault: Option<Arc<crate::vault::Vault>>,
+    tag_defaults: BTreeMap<String, session::TagDefaults>,
+    /// Model for conversation turns, set by tag defaults; `None` is the brain's default.
+    model: Option<String>,
+    graph: Option<Arc<crate::tools::graph::GraphStore>>,
+    api_keys: Option<Arc<KeyRing>>,
+    persona: Option<String>,
+    settings: Option<Settings>,
+    env_file: Option<std::path::PathBuf>,
+    preview_requests: bool,
+    turn_deadline: Option<Duration>,
+    alternatives: Vec<best_of::Candidate>,
+    auto_approve: Vec<String>,
+    allowed_tools: Option<Vec<String>>,
+    search_grounding: bool,
+    /// The active `/mode`, and the settings from before the first switch.
+    mode: Option<(mode::Mode, mode::Baseline)>,
+    quick_actions: std::collections::BTreeMap<String, quick_actions::QuickAction>,
+    glossary: Option<std::path::PathBuf>,
+    palette: Vec<palette::Entry>,
+    follow_ups: bool,
+    recent_files: VecDeque<String>,
+    queue: VecDeque<UserEvent>,
+    turn_cancelled: bool,
+    turn_completed: bool,
+    /// A turn is streaming or running tools.
+    in_turn: bool,
+    /// `/exit` was sent once while work was in flight; the next one exits.
+    exit_requested: bool,
+    fast_draft: bool,
+    thinking: thinking::ThinkingMode,
+    thinking_classifier: Option<String>,
+    confirm_tokens: Option<u64>,
+    secrets: sensitive::Scanner,
+    redact_secrets: bool,
+    error_hints: bool,
+    vars: vars::SessionVars,
+    allow_list: allow_list::AllowList,
+    refinement: Option<draft::Refinement>,
+    coalescer: Coalescer,
+    /// Kills Chitti's child processes on /panic; returns how many were signalled.
+    process_killer: Arc<dyn Fn() -> usize + Send + Sync>,
 }
 
 impl Conductor {
@@ -28,321 +165,3304 @@ impl Conductor {
         events_rx: mpsc::Receiver<UserEvent>,
         tools: Arc<ToolRegistry>,
     ) -> Self {
+        let coalescer = Coalescer::new(bridge.flush_policy());
+        let sequencer = Arc::new(SequencedBridge::new(bridge, DEFAULT_REPLAY_CAPACITY));
+        let buffer = BufferedBridge::spawn(sequencer.clone(), DEFAULT_BUFFER_CAPACITY);
+        let artifacts = artifacts::Artifacts::for_new_session(&std::env::temp_dir().join("chitti-artifacts"));
         Self {
             brain,
-            bridge,
+            bridge: buffer.clone(),
+            sequencer,
+            buffer,
             events_rx,
             tools,
             previous_interaction_id: None,
+            interaction_ids: Vec::new(),
+            purge_on_clear: false,
+            files: None,
+            artifacts,
             pending_steering: VecDeque::new(),
+            unanswered: Vec::new(),
+            attachments: Vec::new(),
+            language: None,
+            dev_mode: false,
+            injection_classifier: false,
+            max_tool_result_bytes: truncate::DEFAULT_MAX_TOOL_RESULT_BYTES,
+            output_store: Arc::new(OutputStore::new()),
+            response_schema: None,
+            notifier: None,
+            turn_log: None,
+            turn_usage: events::Usage::default(),
+            request_ids: Vec::new(),
+            timings: timings::TurnTimings::default(),
+            timing_stats: timings::TimingStats::default(),
+            turn_files: Default::default(),
+            snapshots: review::Snapshots::default(),
+            tee: None,
+            last_response: String::new(),
+            last_prompt: String::new(),
+            stars: stars::Stars::default(),
+            session: session::Session::default(),
+            artifacts_root: None,
+            vault: None,
+            tag_defaults: BTreeMap::new(),
+            model: None,
+            graph: None,
+            api_keys: None,
+            persona: None,
+            settings: None,
+            env_file: None,
+            preview_requests: false,
+            turn_deadline: None,
+            alternatives: Vec::new(),
+            auto_approve: Vec::new(),
+            allowed_tools: None,
+            search_grounding: false,
+            mode: None,
+            quick_actions: quick_actions::builtin(),
+            glossary: None,
+            palette: Vec::new(),
+            follow_ups: false,
+            recent_files: VecDeque::new(),
+            queue: VecDeque::new(),
+            turn_cancelled: false,
+            turn_completed: false,
+            in_turn: false,
+            exit_requested: false,
+            fast_draft: false,
+            thinking: thinking::ThinkingMode::Default,
+            thinking_classifier: None,
+            confirm_tokens: None,
+            secrets: sensitive::Scanner::default(),
+            redact_secrets: false,
+            error_hints: false,
+            vars: vars::SessionVars::default(),
+            allow_list: allow_list::AllowList::default(),
+            refinement: None,
+            coalescer,
+            process_killer: Arc::new(crate::tools::sysinfo::kill_descendants),
+        }
+    }
+
+    /// Caps tool result size; the full text of truncated results is kept in `store`
+    /// so the model can page through it with `read_tool_output`.
+    pub fn with_tool_output_limit(mut self, max_bytes: usize, store: Arc<OutputStore>) -> Self {
+        self.max_tool_result_bytes = max_bytes;
+        self.output_store = store;
+        self
+    }
+
+    /// Runs an extra classifier turn over untrusted tool output to flag prompt injection.
+    pub fn with_injection_classifier(mut self, enabled: bool) -> Self {
+        self.injection_classifier = enabled;
+        self
+    }
+
+    /// Alerts the user when a turn takes longer than the notifier's threshold.
+    pub fn with_notifier(mut self, notifier: Option<Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Persona text sent as the system instruction on every turn.
+    pub fn with_persona(mut self, persona: Option<String>) -> Self {
+        self.persona = persona;
+        self
+    }
+
+    /// Records every completed turn (prompt, answer, usage, request IDs) to
+    /// the configured webhook or JSONL file.
+    pub fn with_turn_log(mut self, turn_log: Option<TurnLog>) -> Self {
+        self.turn_log = turn_log;
+        self
+    }
+
+    /// Enables `/reload`: `settings` is what the Conductor was built with and
+    /// `env_file` is re-read on each reload.
+    pub fn with_hot_reload(mut self, env_file: Option<std::path::PathBuf>, settings: Settings) -> Self {
+        self.env_file = env_file;
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Emits redacted `SystemEvent::Debug` events for turn contexts and tool payloads.
+    pub fn with_dev_mode(mut self, dev_mode: bool) -> Self {
+        self.dev_mode = dev_mode;
+        self
+    }
+
+    /// Cancels model requests and tool runs that take longer than `deadline`.
+    pub fn with_turn_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.turn_deadline = deadline;
+        self
+    }
+
+    /// Tools that run without asking for approval; `*` approves every tool.
+    pub fn with_auto_approve(mut self, tools: Vec<String>) -> Self {
+        self.auto_approve = tools;
+        self
+    }
+
+    /// Restricts the tools offered to the model, keyed by bridge name (see
+    /// `CommBridge::name`). Bridges without an entry get every tool.
+    pub fn with_bridge_tools(mut self, bridge_tools: &HashMap<String, Vec<String>>) -> Self {
+        self.allowed_tools = bridge_tools.get(self.bridge.name()).cloned();
+        self
+    }
+
+    /// An untrusted directory gets no tools at all.
+    pub fn with_trust(mut self, trust: crate::trust::Trust) -> Self {
+        if trust == crate::trust::Trust::NoTools {
+            self.allowed_tools = Some(Vec::new());
+        }
+        self
+    }
+
+    /// Bash command prefixes that run without approval, and where to keep
+    /// the ones learned this session.
+    pub fn with_allow_list(mut self, allow_list: allow_list::AllowList) -> Self {
+        self.allow_list = allow_list;
+        self
+    }
+
+    /// Prompt templates available through `/qa`.
+    pub fn with_quick_actions(mut self, actions: std::collections::BTreeMap<String, quick_actions::QuickAction>) -> Self {
+        self.quick_actions = actions;
+        self
+    }
+
+    /// Terminology file enforced by `/translate`; re-read on every use.
+    pub fn with_glossary(mut self, path: Option<std::path::PathBuf>) -> Self {
+        self.glossary = path;
+        self
+    }
+
+    /// Deletes the conversation's server-side stored interactions on `/clear`.
+    pub fn with_purge_on_clear(mut self, enabled: bool) -> Self {
+        self.purge_on_clear = enabled;
+        self
+    }
+
+    /// Answers with Minimal thinking first, then refines in the background.
+    pub fn with_fast_draft(mut self, enabled: bool) -> Self {
+        self.fast_draft = enabled;
+        self
+    }
+
+    /// Sets the thinking level per turn; `Auto` classifies each prompt, asking
+    /// `classifier_model` when given and falling back to a heuristic.
+    pub fn with_thinking(mut self, mode: thinking::ThinkingMode, classifier_model: Option<String>) -> Self {
+        self.thinking = mode;
+        self.thinking_classifier = classifier_model;
+        self
+    }
+
+    /// Shows a per-source summary and asks before sending a turn whose new
+    /// input exceeds `tokens`.
+    pub fn with_request_confirmation(mut self, tokens: Option<u64>) -> Self {
+        self.confirm_tokens = tokens;
+        self
+    }
+
+    /// Secret patterns checked in attached files and replayed content; with
+    /// `auto_redact` matches are masked instead of asking first.
+    pub fn with_secret_guard(mut self, scanner: sensitive::Scanner, auto_redact: bool) -> Self {
+        self.secrets = scanner;
+        self.redact_secrets = auto_redact;
+        self
+    }
+
+    /// Saves this session (its log, stars and artifacts) in a new directory
+    /// under `root`, encrypting the log and stars with `vault` when given.
+    /// Nothing is written until there is something to save.
+    pub fn with_artifacts_dir(mut self, root: std::path::PathBuf, vault: Option<Arc<crate::vault::Vault>>) -> Self {
+        self.artifacts = artifacts::Artifacts::for_new_session(&root);
+        let in_context = self.stars.in_context;
+        self.stars = stars::Stars::load(Some(self.artifacts.dir().join("starred.json")), vault.clone());
+        self.stars.in_context = in_context;
+        self.session = session::Session::load(self.artifacts.dir().join("session.json"), vault.clone());
+        self.artifacts_root = Some(root);
+        self.vault = vault;
+        self
+    }
+
+    /// Repeats `/star`red exchanges to the model in the system instruction.
+    pub fn with_starred_context(mut self, enabled: bool) -> Self {
+        self.stars.in_context = enabled;
+        self
+    }
+
+    /// Replaces how /panic kills child processes.
+    #[allow(dead_code)]
+    pub fn with_process_killer(mut self, killer: Arc<dyn Fn() -> usize + Send + Sync>) -> Self {
+        self.process_killer = killer;
+        self
+    }
+
+    /// Extracts entities and relations from every completed exchange into
+    /// `graph` in the background, for the `who_is`/`what_is` tools.
+    pub fn with_knowledge_graph(mut self, graph: Option<Arc<crate::tools::graph::GraphStore>>) -> Self {
+        self.graph = graph;
+        self
+    }
+
+    /// Annotates failed tool results with locally computed hints, such as
+    /// similar paths when a file is missing.
+    pub fn with_error_hints(mut self, enabled: bool) -> Self {
+        self.error_hints = enabled;
+        self
+    }
+
+    /// Shares `/set` variables with the tools that export them.
+    pub fn with_session_vars(mut self, vars: vars::SessionVars) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Suggests follow-up prompts after each answer, picked by number.
+    pub fn with_follow_ups(mut self, enabled: bool) -> Self {
+        self.follow_ups = enabled;
+        self
+    }
+
+    /// Defaults (model, read-only, auto-approved tools) applied by `/tag`.
+    pub fn with_tag_defaults(mut self, defaults: BTreeMap<String, session::TagDefaults>) -> Self {
+        self.tag_defaults = defaults;
+        self
+    }
+
+    /// The Gemini client's keys, for `/keys`.
+    pub fn with_api_keys(mut self, keys: Arc<KeyRing>) -> Self {
+        self.api_keys = Some(keys);
+        self
+    }
+
+    /// In dev mode, shows each rendered provider request for approval or editing before it is sent.
+    pub fn with_request_preview(mut self, enabled: bool) -> Self {
+        self.preview_requests = enabled;
+        self
+    }
+
+    /// Enables `/files`, which lists and cleans up File API uploads with `client`;
+    /// `/files gc` without `--hours` removes uploads older than `gc_age`.
+    pub fn with_files(mut self, client: Client, gc_age: Duration) -> Self {
+        self.files = Some((client, gc_age));
+        self
+    }
+
+    /// Tagged with the latest model request's id, so it can be matched to
+    /// that HTTP exchange in the turn log.
+    async fn send_debug(&self, msg: String) -> Result<()> {
+        if self.dev_mode {
+            let request_id = self.request_ids.last().cloned();
+            self.bridge.send(SystemEvent::Debug { message: redact::redact(&msg), request_id }).await?;
         }
+        Ok(())
+    }
+
+    /// Sets the language the model should respond in (e.g. "ta").
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Persona followed by the response-language instruction, if either is set.
+    fn system_instruction(&self) -> Option<String> {
+        let parts: Vec<String> = self.persona.iter().cloned()
+            .chain(self.language.as_deref().map(i18n::response_instruction))
+            .chain(self.vars.instruction())
+            .chain(self.stars.instruction())
+            .chain(self.mode.as_ref().map(|(mode, _)| mode.preset().instruction.to_string()))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+
+    fn lang(&self) -> &str {
+        self.language.as_deref().unwrap_or("en")
     }
 
+    /// Processes input strictly one turn at a time. Input that arrives while a
+    /// turn is running is queued (see `triage`) and handled here afterwards.
     pub async fn run(&mut self) -> Result<()> {
-        while let Some(evt) = self.events_rx.recv().await {
+        loop {
+            let evt = match self.queue.pop_front() {
+                Some(evt) => evt,
+                None => {
+                    let next = match &mut self.refinement {
+                        Some(refinement) => tokio::select! {
+                            res = &mut refinement.handle => Err(res),
+                            evt = self.events_rx.recv() => Ok(evt),
+                        },
+                        None => Ok(self.events_rx.recv().await),
+                    };
+                    match next {
+                        Ok(Some(evt)) => evt,
+                        Ok(None) => break,
+                        Err(res) => {
+                            self.finish_refinement(res).await?;
+                            continue;
+                        }
+                    }
+                }
+            };
             match evt {
                 UserEvent::Message(prompt) => {
-                    self.handle_conversation(prompt).await?;
+                    // A number right after the palette was shown picks an entry.
+                    let choices = std::mem::take(&mut self.palette);
+                    if let Some(entry) = prompt.trim().parse::<usize>().ok()
+                        .and_then(|n| n.checked_sub(1))
+                        .and_then(|i| choices.get(i))
+                    {
+                        match entry.action() {
+                            Ok(evt) => self.queue.push_front(evt),
+                            Err(usage) => self.bridge.send(SystemEvent::Info(usage)).await?,
+                        }
+                        continue;
+                    }
+                    let prompt = self.vars.substitute(&prompt);
+                    // A new question supersedes any refinement still running.
+                    self.refinement = None;
+                    self.sequencer.begin_turn();
+                    let started = std::time::Instant::now();
+                    self.handle_conversation(prompt.clone()).await?;
+                    if self.fast_draft && self.turn_completed {
+                        self.start_refinement(&prompt).await?;
+                    }
+                    if self.turn_completed {
+                        if let Err(e) = self.session.record(&prompt, &self.last_response) {
+                            warn!("Failed to save the session log: {:#}", e);
+                        }
+                        self.start_extraction(&prompt).await;
+                        self.suggest_follow_ups(&prompt).await?;
+                    } else if self.exit_requested && !self.last_response.trim().is_empty() {
+                        // Keep what arrived of an answer cut short by /exit.
+                        let partial = format!("{}\n\n[Interrupted by /exit]", self.last_response.trim_end());
+                        if let Err(e) = self.session.record(&prompt, &partial) {
+                            warn!("Failed to save the session log: {:#}", e);
+                        }
+                    }
+                    if let Some(notifier) = &self.notifier {
+                        notifier.turn_finished(started.elapsed(), &prompt).await;
+                    }
+                    if let Some(turn_log) = &self.turn_log {
+                        turn_log.record(&TurnRecord::new(
+                            &prompt,
+                            &self.last_response,
+                            self.turn_usage,
+                            started.elapsed(),
+                            self.previous_interaction_id.clone(),
+                            self.request_ids.clone(),
+                        ));
+                    }
                 }
                 UserEvent::Command(cmd) => {
-                    if cmd == "/exit" {
+                    let keep_running = match pipeline::parse(&cmd) {
+                        Some(stages) => self.run_pipeline(stages).await?,
+                        None => self.command(&cmd).await?,
+                    };
+                    if !keep_running {
                         break;
                     }
-                    if cmd == "/clear" {
-                        self.previous_interaction_id = None;
-                        self.bridge.send(SystemEvent::Text("Context cleared.".to_string())).await?;
-                    }
                 }
+                UserEvent::Replay { after } => {
+                    self.buffer.drain().await;
+                    self.sequencer.replay(after).await?;
+                }
+                UserEvent::EndOfInput => break,
                 _ => {}
             }
         }
+        self.buffer.drain().await;
         Ok(())
     }
 
-    async fn handle_conversation(&mut self, initial_prompt: String) -> Result<()> {
-        let mut current_prompt = initial_prompt;
-        let mut current_tool_results = Vec::new();
-
-        loop {
-            // Process any buffered steering
-            while let Some(steer) = self.pending_steering.pop_front() {
-                if !current_prompt.is_empty() {
-                    current_prompt.push_str("\n");
-                }
-                current_prompt.push_str(&steer);
+    /// Runs one slash command. Returns false for `/exit`.
+    async fn command(&mut self, cmd: &str) -> Result<bool> {
+        let (name, arg) = cmd.split_once(' ').unwrap_or((cmd, ""));
+        if name != "/exit" {
+            self.exit_requested = false;
+        }
+        match name {
+            "/exit" if self.refinement.is_some() && !self.exit_requested => {
+                self.exit_requested = true;
+                self.bridge.send(SystemEvent::Warning(i18n::tr(self.lang(), Msg::ExitWhileRefining).to_string())).await?;
             }
-
-            let context = TurnContext {
-                prompt: current_prompt.clone(),
-                previous_interaction_id: self.previous_interaction_id.clone(),
-                tool_results: current_tool_results,
-            };
-
-            current_prompt = String::new();
-            current_tool_results = Vec::new();
-
-            let mut brain_stream = self.brain.process_turn(context).await?;
-            let mut tool_calls = Vec::new();
-
-            while let Some(brain_res) = brain_stream.next().await {
-                match brain_res? {
-                    BrainEvent::TextDelta(text) => {
-                        self.bridge.send(SystemEvent::Text(text)).await?;
+            "/exit" => return Ok(false),
+            "/clear" => {
+                self.refinement = None;
+                self.previous_interaction_id = None;
+                self.unanswered.clear();
+                let ids = std::mem::take(&mut self.interaction_ids);
+                if self.purge_on_clear && !ids.is_empty() {
+                    if let Err(e) = self.brain.delete_interactions(&ids).await {
+                        self.bridge.send(SystemEvent::Warning(format!("Failed to delete stored interactions: {}", e))).await?;
                     }
-                    BrainEvent::ThoughtDelta(thought) => {
-                        self.bridge.send(SystemEvent::Text(format!("\x1b[2m{}\x1b[0m", thought))).await?;
+                }
+                self.tools.clear_cache();
+                self.bridge.send(SystemEvent::Text(i18n::tr(self.lang(), Msg::ContextCleared).to_string())).await?;
+            }
+            "/lang" => {
+                self.set_language(arg.trim()).await?;
+            }
+            "/schema" => {
+                self.set_response_schema(arg.trim()).await?;
+            }
+            "/tee" => {
+                self.set_tee(arg.trim()).await?;
+            }
+            "/save-code" => {
+                self.save_code(arg.trim()).await?;
+            }
+            "/reload" => {
+                self.reload().await?;
+            }
+            "/best-of" => {
+                self.best_of(arg.trim()).await?;
+            }
+            "/compare" => {
+                self.compare(arg.trim()).await?;
+            }
+            "/prompt" => {
+                self.prompt_command(arg.trim(), None).await?;
+            }
+            "/qa" => {
+                self.quick_action(arg.trim()).await?;
+            }
+            "/translate" => {
+                self.translate(arg.trim()).await?;
+            }
+            "/think" => {
+                self.set_thinking(arg.trim()).await?;
+            }
+            "/set" => match arg.trim() {
+                "" => self.bridge.send(SystemEvent::Info(self.vars.listing())).await?,
+                assignment => match self.vars.assign(assignment) {
+                    Ok(()) => self.bridge.send(SystemEvent::Info(self.vars.listing())).await?,
+                    Err(e) => self.bridge.send(SystemEvent::Error(e.to_string())).await?,
+                },
+            },
+            "/draft" => {
+                self.set_fast_draft(arg.trim()).await?;
+            }
+            "/star" => {
+                self.star(arg.trim()).await?;
+            }
+            "/unstar" => {
+                self.unstar(arg.trim()).await?;
+            }
+            "/tag" => {
+                self.tag(arg.trim()).await?;
+            }
+            "/untag" => {
+                let event = match session::parse_tag(arg) {
+                    Some(tag) => match self.session.untag(&tag) {
+                        Ok(true) => SystemEvent::Info(format!("Removed tag '{}' (its defaults stay in effect)", tag)),
+                        Ok(false) => SystemEvent::Info(format!("This session isn't tagged '{}'", tag)),
+                        Err(e) => SystemEvent::Error(format!("Removed tag '{}', but saving the session failed: {:#}", tag, e)),
+                    },
+                    None => SystemEvent::Info("Usage: /untag <tag>".to_string()),
+                };
+                self.bridge.send(event).await?;
+            }
+            "/sessions" => {
+                let tag = session::parse_tag(arg);
+                let listing = match &self.artifacts_root {
+                    Some(root) => session::listing(root, tag.as_deref(), self.vault.as_deref()),
+                    None => "Sessions aren't saved in this mode".to_string(),
+                };
+                self.bridge.send(SystemEvent::Info(listing)).await?;
+            }
+            "/search" => {
+                let (tag, query) = match arg.trim().strip_prefix('#') {
+                    Some(rest) => {
+                        let (tag, query) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                        (session::parse_tag(tag), query.trim())
                     }
-                    BrainEvent::ToolCall { name, id, args } => {
-                        tool_calls.push((name, id, args));
+                    None => (None, arg.trim()),
+                };
+                let msg = match &self.artifacts_root {
+                    _ if query.is_empty() => "Usage: /search [#tag] <text>".to_string(),
+                    Some(root) => session::search(root, query, tag.as_deref(), self.vault.as_deref()),
+                    None => "Sessions aren't saved in this mode".to_string(),
+                };
+                self.bridge.send(SystemEvent::Info(msg)).await?;
+            }
+            "/keys" => {
+                let msg = match &self.api_keys {
+                    Some(keys) => keys.listing(),
+                    None => "API key rotation is only available with the Gemini backend".to_string(),
+                };
+                self.bridge.send(SystemEvent::Info(msg)).await?;
+            }
+            "/starred" => match arg.trim() {
+                "" => self.bridge.send(SystemEvent::Info(self.stars.listing())).await?,
+                "context on" | "context off" => {
+                    self.stars.in_context = arg.trim() == "context on";
+                    let msg = if self.stars.in_context {
+                        "Starred answers will be repeated to the model as prior decisions"
+                    } else {
+                        "Starred answers are no longer sent to the model"
+                    };
+                    self.bridge.send(SystemEvent::Info(msg.to_string())).await?;
+                }
+                _ => self.bridge.send(SystemEvent::Error("Usage: /starred [context on|off]".to_string())).await?,
+            },
+            "/palette" | "/p" => {
+                self.show_palette(arg.trim()).await?;
+            }
+            "/review" => {
+                self.review().await?;
+            }
+            "/artifacts" => {
+                self.bridge.send(SystemEvent::Info(self.artifacts.listing())).await?;
+            }
+            "/files" => {
+                self.files_command(arg.trim()).await?;
+            }
+            "/help" => {
+                self.bridge.send(SystemEvent::Info(i18n::tr(self.lang(), Msg::Help).to_string())).await?;
+            }
+            "/cancel" => {
+                self.bridge.send(SystemEvent::Info("Nothing to cancel".to_string())).await?;
+            }
+            "/trust" => {
+                self.trust_command(arg.trim()).await?;
+            }
+            "/panic" => {
+                self.panic().await?;
+            }
+            "/readonly" => {
+                self.set_read_only(arg.trim()).await?;
+            }
+            "/mode" => {
+                self.set_mode(arg.trim()).await?;
+            }
+            "/stats" => match arg.trim() {
+                "tools" => {
+                    let table = self.tools.stats().table();
+                    self.bridge.send(SystemEvent::Info(table.trim_end().to_string())).await?;
+                }
+                "bridge" => {
+                    let summary = format!("Bridge events: {}", self.buffer.stats().summary());
+                    self.bridge.send(SystemEvent::Info(summary)).await?;
+                }
+                "timings" => {
+                    self.bridge.send(SystemEvent::Info(self.timing_stats.table())).await?;
+                }
+                _ => self.bridge.send(SystemEvent::Error("Usage: /stats tools|bridge|timings".to_string())).await?,
+            },
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// Runs `/a | /b | ...`: each stage's output (the answer it produced)
+    /// feeds the next. A `/prompt` stage gets it appended as input, `/tee
+    /// <path>` writes it to a file, and other commands see it as the last
+    /// answer, e.g. `/translate ta` or `/save-code`.
+    async fn run_pipeline(&mut self, stages: Vec<String>) -> Result<bool> {
+        let mut input: Option<String> = None;
+        for stage in stages {
+            let (name, arg) = stage.split_once(' ').unwrap_or((stage.as_str(), ""));
+            if !name.starts_with('/') {
+                self.bridge.send(SystemEvent::Error(format!("Pipeline stages must be commands: '{}'", stage))).await?;
+                return Ok(true);
+            }
+            match (name, &input) {
+                ("/prompt", _) => self.prompt_command(arg.trim(), input.as_deref()).await?,
+                ("/tee", Some(text)) => {
+                    let path = arg.trim();
+                    if let Err(e) = tokio::fs::write(path, text).await {
+                        self.bridge.send(SystemEvent::Error(format!("Could not write {}: {}", path, e))).await?;
+                        return Ok(true);
                     }
-                    BrainEvent::Complete { interaction_id } => {
-                        if let Some(id) = interaction_id {
-                            self.previous_interaction_id = Some(id);
-                        }
+                    self.remember_file(path);
+                    self.bridge.send(SystemEvent::Info(format!("Wrote {} bytes to {}", text.len(), path))).await?;
+                }
+                _ => {
+                    if let Some(text) = &input {
+                        self.last_response = text.clone();
                     }
-                    BrainEvent::Error(err) => {
-                        self.bridge.send(SystemEvent::Error(err)).await?;
+                    if !self.command(&stage).await? {
+                        return Ok(false);
                     }
                 }
             }
+            if self.turn_cancelled || self.last_response.trim().is_empty() {
+                self.bridge.send(SystemEvent::Error(format!("Pipeline stopped: '{}' produced no output", name))).await?;
+                return Ok(true);
+            }
+            input = Some(self.last_response.clone());
+        }
+        Ok(true)
+    }
 
-            if tool_calls.is_empty() {
-                self.bridge.send(SystemEvent::Text("\n".to_string())).await?;
-                break;
+    /// `/prompt <text>` sends text as a message, inlining `@path` mentions of
+    /// local files. In a pipeline, the previous stage's output is appended.
+    async fn prompt_command(&mut self, text: &str, input: Option<&str>) -> Result<()> {
+        if text.is_empty() && input.is_none() {
+            return self.bridge.send(SystemEvent::Error("Usage: /prompt <text>".to_string())).await;
+        }
+        let (prompt, files, attachments) = match pipeline::expand_mentions(&self.vars.substitute(text)) {
+            Ok(expanded) => expanded,
+            Err(e) => return self.bridge.send(SystemEvent::Error(format!("{:#}", e))).await,
+        };
+        for file in &files {
+            self.remember_file(file);
+        }
+        let prompt = match input {
+            Some(input) => pipeline::with_input(&prompt, input),
+            None => prompt,
+        };
+        let Some(prompt) = self.guard_secrets(prompt).await? else {
+            return self.bridge.send(SystemEvent::Text("Not sent.\n".to_string())).await;
+        };
+        self.attachments = attachments;
+        self.sequencer.begin_turn();
+        self.handle_conversation(prompt).await
+    }
+
+    /// Announces shutdown to the bridge. Called once the run loop has been
+    /// dropped, which aborts any in-flight brain request or tool process.
+    pub async fn shutdown(&self, reason: &str) -> Result<()> {
+        info!(reason, "Conductor shutting down");
+        self.bridge.send(SystemEvent::Shutdown { reason: reason.to_string() }).await?;
+        let _ = tokio::time::timeout(Duration::from_secs(2), self.buffer.drain()).await;
+        Ok(())
+    }
+
+    /// Re-reads settings and applies the ones that changed since the last load,
+    /// so runtime changes such as `/lang` survive reloads that don't touch them.
+    async fn reload(&mut self) -> Result<()> {
+        let Some(old) = self.settings.clone() else {
+            return self.bridge.send(SystemEvent::Error("Hot reload is not enabled".to_string())).await;
+        };
+        let new = match reload::load(self.env_file.as_deref()) {
+            Ok(new) => new,
+            Err(e) => return self.bridge.send(SystemEvent::Error(format!("Reload failed: {:#}", e))).await,
+        };
+        let changed = old.diff(&new);
+        for name in &changed {
+            match *name {
+                "language" => self.language = new.language.clone(),
+                "dev mode" => self.dev_mode = new.dev_mode,
+                "injection classifier" => self.injection_classifier = new.injection_classifier,
+                "tool output limit" => self.max_tool_result_bytes = new.max_tool_result_bytes,
+                "notifications" => {
+                    self.notifier = new.notify_after_secs
+                        .map(|secs| Notifier::new(std::time::Duration::from_secs(secs), new.notify_bell));
+                }
+                "persona" => self.persona = new.persona.clone(),
+                _ => {}
             }
+        }
+        self.settings = Some(new);
+        if !changed.is_empty() {
+            info!(?changed, "Settings reloaded");
+            self.bridge.send(SystemEvent::Info(format!("Reloaded: {}", changed.join(", ")))).await?;
+        }
+        Ok(())
+    }
 
-            // GATING: Ask for approval for all tool calls in this turn
-            for (name, id, args) in tool_calls {
-                let description = format!("Execute tool '{}' with args: {}", name, args);
-                self.bridge.send(SystemEvent::RequestApproval { description }).await?;
-
-                // Wait for Approve, Reject, or Steering
-                let mut approved = false;
-                while let Some(user_evt) = self.events_rx.recv().await {
-                    match user_evt {
-                        UserEvent::Approve => {
-                            approved = true;
-                            break;
-                        }
-                        UserEvent::Reject => {
-                            approved = false;
-                            break;
-                        }
-                        UserEvent::Message(msg) | UserEvent::Steer(msg) => {
-                            self.pending_steering.push_back(msg);
-                            // We keep waiting for approval/rejection of the tool, 
-                            // but we've noted the steering for the next turn.
-                            self.bridge.send(SystemEvent::Text("[Steering noted. Waiting for tool approval/rejection...]".to_string())).await?;
-                        }
-                        _ => {}
-                    }
+    async fn set_language(&mut self, code: &str) -> Result<()> {
+        if code.is_empty() || code == "off" {
+            self.language = None;
+            self.bridge.send(SystemEvent::Text(format!("{}\n", i18n::tr(self.lang(), Msg::LanguageReset)))).await?;
+        } else {
+            self.language = Some(code.to_string());
+            let msg = format!("{}: {}\n", i18n::tr(code, Msg::LanguageSet), i18n::language_name(code));
+            self.bridge.send(SystemEvent::Text(msg)).await?;
+        }
+        Ok(())
+    }
+
+    /// Loads a JSON schema file for structured-output turns ("off" disables it).
+    async fn set_response_schema(&mut self, path: &str) -> Result<()> {
+        if path.is_empty() || path == "off" {
+            self.response_schema = None;
+            self.bridge.send(SystemEvent::Text("Structured output disabled.\n".to_string())).await?;
+            return Ok(());
+        }
+        let loaded = tokio::fs::read_to_string(path).await
+            .map_err(anyhow::Error::from)
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).map_err(anyhow::Error::from));
+        match loaded {
+            Ok(schema) => {
+                self.response_schema = Some(schema);
+                self.remember_file(path);
+                self.bridge.send(SystemEvent::Text(format!("Structured output enabled with schema {}\n", path))).await?;
+            }
+            Err(e) => {
+                self.bridge.send(SystemEvent::Error(format!("Could not load schema {}: {}", path, e))).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts or stops mirroring output to a file: `/tee <path> [--tools]`, `/tee off`.
+    async fn set_tee(&mut self, arg: &str) -> Result<()> {
+        if arg.is_empty() {
+            let status = match &self.tee {
+                Some(tee) => format!("Mirroring output to {}\n", tee.path().display()),
+                None => "Not mirroring output. Usage: /tee <path> [--tools], /tee off\n".to_string(),
+            };
+            return self.bridge.send(SystemEvent::Text(status)).await;
+        }
+        if arg == "off" {
+            if let Some(tee) = self.tee.take() {
+                self.bridge.send(SystemEvent::Text(format!("Stopped mirroring to {}\n", tee.path().display()))).await?;
+                // Keep a copy of the transcript with the session's other artifacts.
+                match self.artifacts.keep(tee.path()) {
+                    Ok(path) => self.bridge.send(SystemEvent::Artifact { path, mime_type: "text/plain".to_string() }).await?,
+                    Err(e) => warn!("Failed to copy {} to artifacts: {}", tee.path().display(), e),
                 }
+            }
+            return Ok(());
+        }
+        let include_tools = arg.split_whitespace().any(|a| a == "--tools");
+        let path: Vec<&str> = arg.split_whitespace().filter(|a| *a != "--tools").collect();
+        let path = path.join(" ");
+        match Tee::open(&path, include_tools).await {
+            Ok(tee) => {
+                let what = if include_tools { "model output and tool results" } else { "model output" };
+                self.bridge.send(SystemEvent::Text(format!("Mirroring {} to {}\n", what, path))).await?;
+                self.tee = Some(tee);
+            }
+            Err(e) => {
+                self.bridge.send(SystemEvent::Error(format!("Could not open {}: {}", path, e))).await?;
+            }
+        }
+        Ok(())
+    }
 
-                if approved {
-                    let args_map: std::collections::HashMap<String, serde_json::Value> = 
-                        serde_json::from_value(args).unwrap_or_default();
-                    
-                    match self.tools.execute(&name, args_map).await {
-                        Ok(res) => {
-                            current_tool_results.push(ToolResult {
-                                call_id: id,
-                                name,
-                                result: res.output,
-                                is_error: res.is_error,
-                            });
-                        }
-                        Err(e) => {
-                            current_tool_results.push(ToolResult {
-                                call_id: id,
-                                name,
-                                result: serde_json::json!({ "error": e.to_string() }),
-                                is_error: true,
-                            });
-                        }
-                    }
-                } else {
-                    current_tool_results.push(ToolResult {
-                        call_id: id,
-                        name,
-                        result: serde_json::json!({ "error": "User rejected tool execution." }),
-                        is_error: true,
-                    });
+    /// Writes to the active tee; a failing tee is reported and switched off.
+    async fn tee_text(&mut self, text: &str) -> Result<()> {
+        if let Some(tee) = &mut self.tee {
+            if let Err(e) = tee.write_text(text).await {
+                let path = tee.path().display().to_string();
+                self.tee = None;
+                self.bridge.send(SystemEvent::Error(format!("Stopped mirroring to {}: {}", path, e))).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn tee_tool_result(&mut self, name: &str, output: &serde_json::Value) -> Result<()> {
+        if let Some(tee) = &mut self.tee {
+            if let Err(e) = tee.write_tool_result(name, output).await {
+                let path = tee.path().display().to_string();
+                self.tee = None;
+                self.bridge.send(SystemEvent::Error(format!("Stopped mirroring to {}: {}", path, e))).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sanitizes output from tools that return outside content, warning the user
+    /// when it looks like it carries instructions aimed at the model.
+    async fn screen_tool_output(&self, name: &str, output: serde_json::Value) -> Result<serde_json::Value> {
+        if !self.tools.is_untrusted(name) {
+            return Ok(output);
+        }
+
+        let markers = sanitize::find_injection_markers(&output);
+        let flagged = if !markers.is_empty() {
+            Some(markers.join(", "))
+        } else if self.injection_classifier && self.classify_injection(name, &output).await {
+            Some("classifier".to_string())
+        } else {
+            None
+        };
+
+        if let Some(reason) = flagged {
+            warn!(tool = name, reason = %reason, "Possible prompt injection in tool output");
+            let msg = format!("{} '{}' ({})", i18n::tr(self.lang(), Msg::InjectionWarning), name, reason);
+            self.bridge.send(SystemEvent::Warning(msg)).await?;
+        }
+
+        Ok(sanitize::wrap_untrusted(name, output))
+    }
+
+    /// Runs a one-off, context-free and tool-free turn asking the brain whether
+    /// the output contains injected instructions. A failed check only logs a
+    /// warning: the marker scan has already run, and the tool result still
+    /// needs to reach the model.
+    async fn classify_injection(&self, name: &str, output: &serde_json::Value) -> bool {
+        match self.ask_classifier(name, output).await {
+            Ok(flagged) => flagged,
+            Err(e) => {
+                warn!(tool = name, "Injection classifier failed: {:#}", e);
+                false
+            }
+        }
+    }
+
+    async fn ask_classifier(&self, name: &str, output: &serde_json::Value) -> Result<bool> {
+        let context = TurnContext {
+            prompt: sanitize::classifier_prompt(name, output),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let mut stream = self.brain.process_turn(context).await?;
+        let mut answer = String::new();
+        while let Some(evt) = stream.next().await {
+            if let BrainEvent::TextDelta(text) = evt? {
+                answer.push_str(&text);
+            }
+        }
+        Ok(answer.trim().to_uppercase().starts_with("YES"))
+    }
+
+    /// Shows a rendered request and waits for approval. The JSON is also written to a
+    /// temp file; edits made there before approving are what gets sent.
+    /// Returns `None` if the user aborts.
+    async fn review_request(&mut self, request: serde_json::Value) -> Result<Option<serde_json::Value>> {
+        let pretty = serde_json::to_string_pretty(&request)?;
+        let path = std::env::temp_dir().join(format!("chitti-request-{}.json", uuid::Uuid::new_v4().simple()));
+        tokio::fs::write(&path, &pretty).await?;
+        self.send_debug(format!("Request:\n{}", pretty)).await?;
+
+        let result = loop {
+            let description = format!("Send this request? Edit {} first to change it", path.display());
+            self.bridge.send(SystemEvent::RequestApproval { description }).await?;
+            if !self.wait_for_approval().await? {
+                break None;
+            }
+            let edited = tokio::fs::read_to_string(&path).await?;
+            match serde_json::from_str(&edited) {
+                Ok(request) => break Some(request),
+                Err(e) => {
+                    self.bridge.send(SystemEvent::Error(format!("Edited request is not valid JSON: {}", e))).await?;
                 }
             }
+        };
+        let _ = tokio::fs::remove_file(&path).await;
+        Ok(result)
+    }
+
+    /// `/tag <name>` tags the session and applies the tag's defaults from
+    /// config.toml; `/tag` alone lists the session's tags.
+    async fn tag(&mut self, arg: &str) -> Result<()> {
+        if arg.is_empty() {
+            let msg = match self.session.tags() {
+                [] => "This session has no tags; /tag <name> adds one".to_string(),
+                tags => format!("Tags: {}", tags.join(", ")),
+            };
+            return self.bridge.send(SystemEvent::Info(msg)).await;
+        }
+        let Some(tag) = session::parse_tag(arg) else {
+            return self.bridge.send(SystemEvent::Error("Usage: /tag <name> (no spaces)".to_string())).await;
+        };
+        match self.session.tag(&tag) {
+            Ok(true) => {}
+            Ok(false) => return self.bridge.send(SystemEvent::Info(format!("Already tagged '{}'", tag))).await,
+            // The tag still holds for this session and is saved with the next exchange.
+            Err(e) => {
+                let msg = format!("Tagged '{}', but saving the session failed: {:#}", tag, e);
+                self.bridge.send(SystemEvent::Error(msg)).await?;
+            }
+        }
+        let mut applied = Vec::new();
+        if let Some(defaults) = self.tag_defaults.get(&tag).cloned() {
+            if let Some(model) = defaults.model {
+                applied.push(format!("model {}", model));
+                self.model = Some(model);
+            }
+            if let Some(read_only) = defaults.read_only {
+                self.tools.set_read_only(read_only);
+                applied.push(format!("read-only {}", if read_only { "on" } else { "off" }));
+            }
+            if let Some(tools) = defaults.auto_approve {
+                applied.push(format!("auto-approve {}", if tools.is_empty() { "none".to_string() } else { tools.join(", ") }));
+                self.auto_approve = tools;
+            }
+        }
+        let msg = if applied.is_empty() {
+            format!("Tagged this session '{}'", tag)
+        } else {
+            format!("Tagged this session '{}'; applied {}", tag, applied.join(", "))
+        };
+        self.bridge.send(SystemEvent::Info(msg)).await
+    }
+
+    /// Switches tools, thinking, search grounding, approvals and the system
+    /// instruction's flavor together. Each mode starts from the settings in
+    /// place before the first switch, so it never widens what trust or
+    /// bridge configuration allows.
+    async fn set_mode(&mut self, arg: &str) -> Result<()> {
+        if arg.is_empty() {
+            let msg = match &self.mode {
+                Some((mode, _)) => mode.summary(),
+                None => "No mode set. Usage: /mode chat|agent|research".to_string(),
+            };
+            return self.bridge.send(SystemEvent::Info(msg)).await;
+        }
+        let Some(mode) = mode::Mode::parse(arg) else {
+            return self.bridge.send(SystemEvent::Error("Usage: /mode chat|agent|research".to_string())).await;
+        };
+        let baseline = match self.mode.take() {
+            Some((_, baseline)) => baseline,
+            None => mode::Baseline {
+                allowed_tools: self.allowed_tools.clone(),
+                auto_approve: self.auto_approve.clone(),
+                read_only: self.tools.is_read_only(),
+            },
+        };
+        let preset = mode.preset();
+        self.allowed_tools = baseline.allowed_tools(mode);
+        self.auto_approve = baseline.auto_approve(mode);
+        self.tools.set_read_only(baseline.read_only(mode));
+        self.thinking = preset.thinking;
+        self.search_grounding = preset.search_grounding;
+        self.mode = Some((mode, baseline));
+        self.bridge.send(SystemEvent::Mode(mode.as_str().to_string())).await?;
+        self.bridge.send(SystemEvent::Info(mode.summary())).await
+    }
+
+    /// `/readonly [on|off]` toggles refusing mutating tool calls; no argument flips the mode.
+    async fn set_read_only(&mut self, arg: &str) -> Result<()> {
+        let enabled = match arg {
+            "" => !self.tools.is_read_only(),
+            "on" => true,
+            "off" => false,
+            _ => return self.bridge.send(SystemEvent::Error("Usage: /readonly [on|off]".to_string())).await,
+        };
+        self.tools.set_read_only(enabled);
+        let msg = if enabled {
+            "Read-only mode on: tools that may modify the system are refused"
+        } else {
+            "Read-only mode off"
+        };
+        self.bridge.send(SystemEvent::Info(msg.to_string())).await
+    }
+
+    /// Stops everything: cancels the turn and queued input, kills child
+    /// processes and switches to read-only mode until `/readonly off`.
+    /// Dropping the turn's futures afterwards ends any pending tool calls.
+    async fn panic(&mut self) -> Result<()> {
+        self.turn_cancelled = true;
+        self.pending_steering.clear();
+        self.queue.clear();
+        self.refinement = None;
+        self.tools.set_read_only(true);
+        let killed = (self.process_killer)();
+        warn!(killed, "Panic: turn cancelled and child processes killed");
+        let msg = format!(
+            "Stopped: turn cancelled, {} child process(es) killed. Read-only mode is on until /readonly off",
+            killed
+        );
+        self.bridge.send(SystemEvent::Warning(msg)).await
+    }
+
+    /// Offers to stop asking for a bash command prefix approved repeatedly.
+    async fn offer_allow(&mut self, prefix: &str) -> Result<()> {
+        let description = format!(
+            "You approved '{}' {} times. Always run commands starting with it without asking?",
+            prefix, allow_list::OFFER_AFTER
+        );
+        self.bridge.send(SystemEvent::RequestApproval { description }).await?;
+        if !self.wait_for_approval().await? {
+            self.allow_list.decline(prefix);
+            return Ok(());
+        }
+        match self.allow_list.add(prefix) {
+            Ok(()) => self.bridge.send(SystemEvent::Info(format!("'{}' added; /trust list shows the allow-list", prefix))).await,
+            Err(e) => self.bridge.send(SystemEvent::Error(format!("{:#}", e))).await,
+        }
+    }
+
+    /// `/trust list|remove <prefix>` manages the learned bash allow-list.
+    async fn trust_command(&mut self, arg: &str) -> Result<()> {
+        let (action, prefix) = arg.split_once(' ').unwrap_or((arg, ""));
+        match (action, prefix.trim()) {
+            ("" | "list", _) => self.bridge.send(SystemEvent::Info(self.allow_list.listing())).await,
+            ("remove", prefix) if !prefix.is_empty() => match self.allow_list.remove(prefix) {
+                Ok(true) => self.bridge.send(SystemEvent::Info(format!("'{}' will ask for approval again", prefix))).await,
+                Ok(false) => self.bridge.send(SystemEvent::Error(format!("'{}' is not in the allow-list", prefix))).await,
+                Err(e) => self.bridge.send(SystemEvent::Error(format!("{:#}", e))).await,
+            },
+            _ => self.bridge.send(SystemEvent::Error("Usage: /trust list|remove <prefix>".to_string())).await,
+        }
+    }
+
+    async fn set_thinking(&mut self, arg: &str) -> Result<()> {
+        if !arg.is_empty() {
+            match thinking::ThinkingMode::parse(arg) {
+                Some(mode) => self.thinking = mode,
+                None => {
+                    let usage = "Usage: /think [minimal|low|medium|high|auto|default]";
+                    return self.bridge.send(SystemEvent::Error(usage.to_string())).await;
+                }
+            }
+        }
+        self.bridge.send(SystemEvent::Info(format!("Thinking level: {}", self.thinking.as_str()))).await
+    }
+
+    /// The thinking level for a new prompt. In auto mode the choice is shown
+    /// to the user.
+    async fn thinking_level_for(&self, prompt: &str) -> Result<Option<ThinkingLevel>> {
+        let level = match self.thinking {
+            thinking::ThinkingMode::Default => return Ok(None),
+            thinking::ThinkingMode::Fixed(level) => return Ok(Some(level)),
+            thinking::ThinkingMode::Auto => match &self.thinking_classifier {
+                Some(model) => match self.classify_thinking(model, prompt).await {
+                    Ok(Some(level)) => level,
+                    Ok(None) => thinking::classify(prompt),
+                    Err(e) => {
+                        warn!("Thinking classifier failed: {}", e);
+                        thinking::classify(prompt)
+                    }
+                },
+                None => thinking::classify(prompt),
+            },
+        };
+        self.bridge.send(SystemEvent::Info(format!("Thinking: {} (auto)", level.as_str()))).await?;
+        Ok(Some(level))
+    }
+
+    /// Asks a small model how hard the prompt is, without conversation context.
+    async fn classify_thinking(&self, model: &str, prompt: &str) -> Result<Option<ThinkingLevel>> {
+        let context = TurnContext {
+            prompt: thinking::classifier_prompt(prompt),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: Some(ThinkingLevel::Minimal),
+            temperature: Some(0.0),
+            model: Some(model.to_string()),
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let answer = best_of::generate(&*self.brain, context).await?;
+        Ok(thinking::parse_answer(&answer.text))
+    }
+
+    /// `/star [note]` marks the last exchange; the note, when given, is what
+    /// the model is reminded of instead of the whole answer.
+    async fn star(&mut self, note: &str) -> Result<()> {
+        if self.last_response.trim().is_empty() {
+            return self.bridge.send(SystemEvent::Error("Nothing to star yet".to_string())).await;
+        }
+        let star = stars::Star {
+            prompt: self.last_prompt.clone(),
+            response: self.last_response.clone(),
+            note: (!note.is_empty()).then(|| note.to_string()),
+        };
+        match self.stars.add(star) {
+            Ok(n) => self.bridge.send(SystemEvent::Info(format!("Starred as #{}; /starred lists them", n))).await,
+            Err(e) => self.bridge.send(SystemEvent::Error(format!("Failed to save star: {}", e))).await,
+        }
+    }
+
+    async fn unstar(&mut self, arg: &str) -> Result<()> {
+        let Ok(n) = arg.parse::<usize>() else {
+            return self.bridge.send(SystemEvent::Error("Usage: /unstar <n>".to_string())).await;
+        };
+        let event = match self.stars.remove(n) {
+            Ok(Some(_)) => SystemEvent::Info(format!("Removed star #{}", n)),
+            Ok(None) => SystemEvent::Error(format!("No star #{}", n)),
+            Err(e) => SystemEvent::Error(format!("Failed to save stars: {}", e)),
+        };
+        self.bridge.send(event).await
+    }
+
+    async fn set_fast_draft(&mut self, arg: &str) -> Result<()> {
+        self.fast_draft = match arg {
+            "on" => true,
+            "off" => false,
+            "" => !self.fast_draft,
+            _ => return self.bridge.send(SystemEvent::Error("Usage: /draft [on|off]".to_string())).await,
+        };
+        if !self.fast_draft {
+            self.refinement = None;
+        }
+        let msg = if self.fast_draft {
+            "Fast draft on: quick answers first, refined in the background"
+        } else {
+            "Fast draft off"
+        };
+        self.bridge.send(SystemEvent::Info(msg.to_string())).await
+    }
+
+    /// Starts the High-thinking pass over the draft just shown. The request
+    /// is sent here; its stream is drained by a task `run` polls.
+    async fn start_refinement(&mut self, prompt: &str) -> Result<()> {
+        let context = TurnContext {
+            prompt: draft::refine_prompt(prompt),
+            system_instruction: self.system_instruction(),
+            response_schema: self.response_schema.clone(),
+            previous_interaction_id: self.previous_interaction_id.clone(),
+            tool_results: Vec::new(),
+            thinking_level: Some(ThinkingLevel::High),
+            temperature: None,
+            model: None,
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        match self.brain.process_turn(context).await {
+            Ok(stream) => {
+                self.refinement = Some(draft::Refinement {
+                    handle: tokio::spawn(best_of::collect(stream, 0.0)),
+                    draft_id: self.previous_interaction_id.clone(),
+                });
+                self.bridge.send(SystemEvent::Info("Refining the draft in the background...".to_string())).await
+            }
+            Err(e) => self.bridge.send(SystemEvent::Warning(format!("Could not start refinement: {}", e))).await,
+        }
+    }
+
+    /// Sends the finished exchange to the entity extractor and merges its
+    /// answer into the knowledge graph once it arrives. Failures are only
+    /// logged: the graph is a convenience, not part of the conversation.
+    async fn start_extraction(&mut self, prompt: &str) {
+        let Some(graph) = self.graph.clone() else {
+            return;
+        };
+        let context = TurnContext {
+            prompt: crate::tools::graph::extraction_prompt(prompt, &self.last_response),
+            system_instruction: None,
+            response_schema: Some(crate::tools::graph::extraction_schema()),
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: Some(ThinkingLevel::Minimal),
+            temperature: None,
+            model: None,
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let stream = match self.brain.process_turn(context).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Could not start entity extraction: {}", e);
+                return;
+            }
+        };
+        tokio::spawn(async move {
+            let merged = best_of::collect(stream, 0.0).await
+                .and_then(|c| Ok(serde_json::from_str(&c.text)?))
+                .and_then(|extraction| graph.merge(extraction));
+            match merged {
+                Ok(added) => info!(added, "Updated the knowledge graph"),
+                Err(e) => warn!("Entity extraction failed: {}", e),
+            }
+        });
+    }
+
+    /// Asks for a few follow-up prompts with minimal thinking and lists them
+    /// like palette entries, so entering a number sends one. Failures are
+    /// only logged; suggestions are a nicety.
+    async fn suggest_follow_ups(&mut self, prompt: &str) -> Result<()> {
+        if !self.follow_ups || self.last_response.trim().is_empty() {
+            return Ok(());
+        }
+        let context = TurnContext {
+            prompt: follow_ups::prompt(prompt, &self.last_response),
+            system_instruction: None,
+            response_schema: Some(follow_ups::schema()),
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: Some(ThinkingLevel::Minimal),
+            temperature: None,
+            model: None,
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let reply = match self.brain.process_turn(context).await {
+            Ok(stream) => best_of::collect(stream, 0.0).await,
+            Err(e) => Err(e),
+        };
+        let suggestions = match reply {
+            Ok(candidate) => follow_ups::parse(&candidate.text),
+            Err(e) => {
+                warn!("Could not suggest follow-ups: {}", e);
+                return Ok(());
+            }
+        };
+        if suggestions.is_empty() {
+            return Ok(());
+        }
+        let mut listing = String::from("Follow-ups (enter a number to send one):");
+        for (i, suggestion) in suggestions.iter().enumerate() {
+            listing.push_str(&format!("\n  {}. {}", i + 1, suggestion));
+        }
+        self.palette = suggestions.into_iter().map(palette::Entry::FollowUp).collect();
+        self.bridge.send(SystemEvent::Info(listing)).await
+    }
+
+    /// Shows the refined answer, marked as replacing the draft, and makes it
+    /// the conversation state unless another turn has happened since.
+    async fn finish_refinement(&mut self, res: Result<Result<best_of::Candidate>, tokio::task::JoinError>) -> Result<()> {
+        let Some(refinement) = self.refinement.take() else {
+            return Ok(());
+        };
+        let candidate = match res {
+            Ok(Ok(c)) if !c.text.trim().is_empty() => c,
+            Ok(Ok(_)) => return Ok(()),
+            Ok(Err(e)) => return self.bridge.send(SystemEvent::Warning(format!("Refinement failed: {}", e))).await,
+            Err(e) => return self.bridge.send(SystemEvent::Warning(format!("Refinement failed: {}", e))).await,
+        };
+        if let Some(id) = &candidate.interaction_id {
+            self.interaction_ids.push(id.clone());
+            if self.previous_interaction_id == refinement.draft_id {
+                self.previous_interaction_id = Some(id.clone());
+            }
+        }
+        self.bridge.send(SystemEvent::Info("Refined answer (replaces the draft above):".to_string())).await?;
+        self.tee_text(&candidate.text).await?;
+        self.bridge.send(SystemEvent::Text(format!("{}\n", candidate.text.trim_end()))).await?;
+        self.last_response = candidate.text;
+        Ok(())
+    }
+
+    /// `/best-of <n> <prompt>` generates n answers concurrently at different
+    /// temperatures, then a judge turn picks or merges the best one.
+    /// `/best-of show [i]` lists the alternatives or expands one.
+    async fn best_of(&mut self, arg: &str) -> Result<()> {
+        let (first, rest) = arg.split_once(' ').unwrap_or((arg, ""));
+        if first == "show" {
+            return self.show_alternative(rest.trim()).await;
+        }
+        let n = match first.parse::<usize>() {
+            Ok(n) if (2..=best_of::MAX_CANDIDATES).contains(&n) && !rest.trim().is_empty() => n,
+            _ => {
+                let usage = format!("Usage: /best-of <2-{}> <prompt>, /best-of show [i]", best_of::MAX_CANDIDATES);
+                return self.bridge.send(SystemEvent::Error(usage)).await;
+            }
+        };
+        let prompt = rest.trim().to_string();
+        self.bridge.send(SystemEvent::Info(format!("Generating {} candidates...", n))).await?;
+
+        let started = Instant::now();
+        let deadline = self.turn_deadline.map(|d| started + d);
+        let generations = best_of::temperatures(n).into_iter().map(|temperature| {
+            let context = TurnContext {
+                prompt: prompt.clone(),
+                system_instruction: self.system_instruction(),
+                response_schema: None,
+                previous_interaction_id: self.previous_interaction_id.clone(),
+                tool_results: Vec::new(),
+                thinking_level: None,
+                temperature: Some(temperature),
+                model: None,
+                allowed_tools: self.allowed_tools.clone(),
+                search_grounding: self.search_grounding,
+                attachments: Vec::new(),
+            };
+            best_of::generate(&*self.brain, context)
+        });
+        let Some(results) = with_deadline(deadline, futures_util::future::join_all(generations)).await else {
+            return self.bridge.send(SystemEvent::Error(format!("Best-of cancelled after {:.1}s", started.elapsed().as_secs_f64()))).await;
+        };
+
+        let mut candidates = Vec::new();
+        for result in results {
+            match result {
+                Ok(c) if !c.text.trim().is_empty() => candidates.push(c),
+                Ok(_) => {}
+                Err(e) => self.bridge.send(SystemEvent::Warning(format!("A candidate failed: {}", e))).await?,
+            }
+        }
+        if candidates.is_empty() {
+            return self.bridge.send(SystemEvent::Error("No candidate produced an answer".to_string())).await;
+        }
+
+        let mut listing = String::from("Alternatives (expand with /best-of show <i>):");
+        for (i, c) in candidates.iter().enumerate() {
+            listing.push_str(&format!("\n  {}", best_of::summary(i, c)));
+        }
+        self.bridge.send(SystemEvent::Info(listing)).await?;
+
+        let judge = TurnContext {
+            prompt: best_of::judge_prompt(&prompt, &candidates),
+            system_instruction: self.system_instruction(),
+            response_schema: None,
+            previous_interaction_id: self.previous_interaction_id.clone(),
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: self.allowed_tools.clone(),
+            search_grounding: self.search_grounding,
+            attachments: Vec::new(),
+        };
+        self.alternatives = candidates;
+        self.last_response.clear();
+        match self.stream_turn(judge, deadline).await? {
+            TurnOutcome::TimedOut => {
+                self.bridge.send(SystemEvent::Error("Judge turn exceeded the deadline".to_string())).await?;
+            }
+            TurnOutcome::ContextTooLong(err) => {
+                self.bridge.send(SystemEvent::Error(format!("The candidates are too long to judge together: {}", err))).await?;
+            }
+            TurnOutcome::RateLimited(_) => {
+                self.bridge.send(SystemEvent::Error("Rate limited while judging; try again shortly".to_string())).await?;
+            }
+            TurnOutcome::Done(_) | TurnOutcome::Aborted => {}
+        }
+        self.bridge.send(SystemEvent::Text("\n".to_string())).await
+    }
+
+    /// `/files [list | rm <name>... | gc [--hours N]]`: the `chitti files` subcommand, run
+    /// with the session's client.
+    async fn files_command(&mut self, arg: &str) -> Result<()> {
+        let Some((client, gc_age)) = &self.files else {
+            return self.bridge.send(SystemEvent::Error("File management is not available".to_string())).await;
+        };
+        let args: Vec<String> = arg.split_whitespace().map(str::to_string).collect();
+        let event = match crate::cli::files::command(client, &args, *gc_age).await {
+            Ok(out) => SystemEvent::Info(out),
+            Err(e) => SystemEvent::Error(format!("{:#}", e).replace("chitti files", "/files")),
+        };
+        self.bridge.send(event).await
+    }
+
+    /// `/compare <model-a> <model-b> <prompt>` runs the prompt against both
+    /// models concurrently and shows the answers side by side. Both continue
+    /// from the current conversation, which itself is left unchanged.
+    async fn compare(&mut self, arg: &str) -> Result<()> {
+        let mut parts = arg.splitn(3, ' ');
+        let (Some(a), Some(b), Some(prompt)) = (parts.next(), parts.next(), parts.next().map(str::trim)) else {
+            return self.bridge.send(SystemEvent::Error("Usage: /compare <model-a> <model-b> <prompt>".to_string())).await;
+        };
+        if prompt.is_empty() {
+            return self.bridge.send(SystemEvent::Error("Usage: /compare <model-a> <model-b> <prompt>".to_string())).await;
+        }
+        self.bridge.send(SystemEvent::Info(format!("Comparing {} and {}...", a, b))).await?;
+
+        let started = Instant::now();
+        let deadline = self.turn_deadline.map(|d| started + d);
+        let runs = [a, b].map(|model| {
+            let context = TurnContext {
+                prompt: prompt.to_string(),
+                system_instruction: self.system_instruction(),
+                response_schema: None,
+                previous_interaction_id: self.previous_interaction_id.clone(),
+                tool_results: Vec::new(),
+                thinking_level: None,
+                temperature: None,
+                model: Some(model.to_string()),
+                allowed_tools: self.allowed_tools.clone(),
+                search_grounding: self.search_grounding,
+                attachments: Vec::new(),
+            };
+            compare::run(&*self.brain, context)
+        });
+        let Some(columns) = with_deadline(deadline, futures_util::future::join_all(runs)).await else {
+            return self.bridge.send(SystemEvent::Error(format!("Comparison cancelled after {:.1}s", started.elapsed().as_secs_f64()))).await;
+        };
+        self.bridge.send(SystemEvent::Comparison { columns }).await
+    }
+
+    async fn show_alternative(&mut self, index: &str) -> Result<()> {
+        if self.alternatives.is_empty() {
+            return self.bridge.send(SystemEvent::Error("No alternatives yet; run /best-of <n> <prompt> first".to_string())).await;
+        }
+        match index.parse::<usize>().ok().and_then(|i| i.checked_sub(1)).and_then(|i| self.alternatives.get(i)) {
+            Some(c) => self.bridge.send(SystemEvent::Text(format!("{}\n", c.text.trim_end()))).await,
+            None => {
+                let mut listing = String::from("Alternatives:");
+                for (i, c) in self.alternatives.iter().enumerate() {
+                    listing.push_str(&format!("\n  {}", best_of::summary(i, c)));
+                }
+                self.bridge.send(SystemEvent::Info(listing)).await
+            }
+        }
+    }
+
+    /// Waits for Approve or Reject. Other input is triaged as during a turn;
+    /// a cancel counts as a rejection and marks the turn cancelled.
+    async fn wait_for_approval(&mut self) -> Result<bool> {
+        while let Some(user_evt) = self.events_rx.recv().await {
+            match user_evt {
+                UserEvent::Approve => return Ok(true),
+                UserEvent::Reject => return Ok(false),
+                UserEvent::Steer(msg) => {
+                    self.pending_steering.push_back(msg);
+                    // We keep waiting for approval/rejection of the tool,
+                    // but we've noted the steering for the next turn.
+                    self.bridge.send(SystemEvent::Text(i18n::tr(self.lang(), Msg::SteeringNoted).to_string())).await?;
+                }
+                evt => {
+                    if self.triage(evt).await? {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Handles input that arrives while a turn is in flight. `/cancel` and
+    /// `/exit` jump the queue and cancel the turn (returning true); steering
+    /// joins the next request; messages and other commands wait in the queue.
+    /// The first `/exit` during a turn only asks for confirmation, so a long
+    /// answer isn't lost to a stray keystroke; end of input can't be asked
+    /// twice, so it cancels and exits straight away.
+    async fn triage(&mut self, evt: UserEvent) -> Result<bool> {
+        match evt {
+            UserEvent::Command(cmd) if cmd == "/panic" => {
+                self.panic().await?;
+                Ok(true)
+            }
+            UserEvent::Command(cmd) if cmd == "/exit" && self.in_turn && !self.exit_requested => {
+                self.exit_requested = true;
+                self.bridge.send(SystemEvent::Warning(i18n::tr(self.lang(), Msg::ExitWhileBusy).to_string())).await?;
+                Ok(false)
+            }
+            UserEvent::EndOfInput => {
+                self.exit_requested = true;
+                self.queue.push_front(UserEvent::EndOfInput);
+                self.turn_cancelled = true;
+                self.pending_steering.clear();
+                Ok(true)
+            }
+            UserEvent::Command(cmd) if cmd == "/cancel" || cmd == "/exit" => {
+                if cmd == "/exit" {
+                    self.queue.push_front(UserEvent::Command(cmd));
+                }
+                self.turn_cancelled = true;
+                self.pending_steering.clear();
+                self.bridge.send(SystemEvent::Info(i18n::tr(self.lang(), Msg::TurnCancelled).to_string())).await?;
+                Ok(true)
+            }
+            UserEvent::Steer(msg) => {
+                self.pending_steering.push_back(msg);
+                Ok(false)
+            }
+            // Nothing is awaiting approval.
+            UserEvent::Approve | UserEvent::Reject => Ok(false),
+            UserEvent::Replay { after } => {
+                self.buffer.drain().await;
+                self.sequencer.replay(after).await?;
+                Ok(false)
+            }
+            evt => {
+                self.queue.push_back(evt);
+                let msg = format!("{}: {}", i18n::tr(self.lang(), Msg::InputQueued), self.queue.len());
+                self.bridge.send(SystemEvent::Info(msg)).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Fuzzy-searches commands, quick actions and recent files: `/palette [query]`.
+    /// The next message, if it is a number from the list, runs that entry.
+    async fn show_palette(&mut self, query: &str) -> Result<()> {
+        let entries = palette::commands(i18n::tr(self.lang(), Msg::Help)).into_iter()
+            .chain(self.quick_actions.iter().map(|(name, action)| palette::Entry::QuickAction {
+                name: name.clone(),
+                description: action.description.clone(),
+            }))
+            .chain(self.recent_files.iter().cloned().map(palette::Entry::File))
+            .collect();
+        self.palette = palette::search(entries, query, 15);
+        self.bridge.send(SystemEvent::Info(palette::listing(&self.palette))).await
+    }
+
+    /// Remembers a file the user worked with, most recent first, for the palette.
+    fn remember_file(&mut self, path: &str) {
+        self.recent_files.retain(|p| p != path);
+        self.recent_files.push_front(path.to_string());
+        self.recent_files.truncate(20);
+    }
+
+    /// `/qa` lists quick actions; `/qa <name|number> [input]` runs one.
+    async fn quick_action(&mut self, arg: &str) -> Result<()> {
+        if arg.is_empty() {
+            return self.bridge.send(SystemEvent::Info(quick_actions::listing(&self.quick_actions))).await;
+        }
+        let (key, input) = arg.split_once(' ').unwrap_or((arg, ""));
+        let Some(mut action) = quick_actions::find(&self.quick_actions, key).cloned() else {
+            return self.bridge.send(SystemEvent::Error(format!("Unknown quick action '{}'; /qa lists them", key))).await;
+        };
+        action.prompt = self.vars.substitute(&action.prompt);
+        let input = self.vars.substitute(input.trim());
+        match quick_actions::expand(&action, &input).await {
+            Ok(prompt) => {
+                if action.prompt.contains("{file}") {
+                    self.remember_file(&input);
+                }
+                self.sequencer.begin_turn();
+                self.handle_conversation(prompt).await
+            }
+            Err(e) => self.bridge.send(SystemEvent::Error(format!("{:#}", e))).await,
+        }
+    }
+
+    /// `/translate <lang> [file]` translates the file, or else the last answer,
+    /// outside the conversation. Code, URLs and placeholders are masked so the
+    /// model can't alter them, and the glossary is passed as instructions.
+    async fn translate(&mut self, arg: &str) -> Result<()> {
+        let (lang, path) = arg.split_once(' ').map(|(l, p)| (l, p.trim())).unwrap_or((arg, ""));
+        if lang.is_empty() {
+            return self.bridge.send(SystemEvent::Error("Usage: /translate <lang> [file]".to_string())).await;
+        }
+        let source = if path.is_empty() {
+            if self.last_response.trim().is_empty() {
+                return self.bridge.send(SystemEvent::Error("Nothing to translate yet; give a file: /translate <lang> <file>".to_string())).await;
+            }
+            self.last_response.clone()
+        } else {
+            match tokio::fs::read_to_string(path).await {
+                Ok(text) => {
+                    self.remember_file(path);
+                    text
+                }
+                Err(e) => return self.bridge.send(SystemEvent::Error(format!("Failed to read {}: {}", path, e))).await,
+            }
+        };
+        let terms = match &self.glossary {
+            Some(file) => match translate::Glossary::load(file) {
+                Ok(glossary) => glossary.terms(lang),
+                Err(e) => return self.bridge.send(SystemEvent::Error(format!("{:#}", e))).await,
+            },
+            None => Default::default(),
+        };
+
+        let Some(source) = self.guard_secrets(source).await? else {
+            return self.bridge.send(SystemEvent::Text("Not sent.\n".to_string())).await;
+        };
+        let (masked, spans) = translate::protect(&source);
+        let context = TurnContext {
+            prompt: masked,
+            system_instruction: Some(translate::instruction(i18n::language_name(lang), &terms)),
+            response_schema: None,
+            previous_interaction_id: None,
+            tool_results: Vec::new(),
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: Some(Vec::new()),
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let started = Instant::now();
+        let deadline = self.turn_deadline.map(|d| started + d);
+        let translated = match with_deadline(deadline, best_of::generate(&*self.brain, context)).await {
+            Some(Ok(candidate)) => candidate.text,
+            Some(Err(e)) => return self.bridge.send(SystemEvent::Error(format!("Translation failed: {}", e))).await,
+            None => return self.bridge.send(SystemEvent::Error("Translation exceeded the deadline".to_string())).await,
+        };
+        let (translated, missing) = translate::restore(&translated, &spans);
+        if !missing.is_empty() {
+            let dropped: Vec<&str> = missing.iter().map(|&i| spans[i].as_str()).collect();
+            self.bridge.send(SystemEvent::Warning(format!("The translation dropped protected text: {}", dropped.join(", ")))).await?;
+        }
+        self.bridge.send(SystemEvent::Text(format!("{}\n", translated.trim_end()))).await?;
+        self.last_response = translated;
+        Ok(())
+    }
+
+    /// Writes the nth (1-based, default 1) fenced code block of the last model
+    /// message to a file after approval: `/save-code [n] <path>`.
+    async fn save_code(&mut self, arg: &str) -> Result<()> {
+        let parts: Vec<&str> = arg.split_whitespace().collect();
+        let (n, path) = match parts.as_slice() {
+            [n, path] if n.parse::<usize>().is_ok() => (n.parse::<usize>().unwrap(), *path),
+            [path] => (1, *path),
+            _ => {
+                return self.bridge.send(SystemEvent::Error("Usage: /save-code [n] <path>".to_string())).await;
+            }
+        };
+        let blocks = code_blocks::extract(&self.last_response);
+        let Some(block) = n.checked_sub(1).and_then(|i| blocks.get(i)) else {
+            let msg = format!("The last response has {} code block(s); there is no block {}", blocks.len(), n);
+            return self.bridge.send(SystemEvent::Error(msg)).await;
+        };
+
+        let mut description = format!(
+            "Write code block {} ({}{} lines) to {}",
+            n,
+            block.language.as_deref().map(|l| format!("{}, ", l)).unwrap_or_default(),
+            block.code.lines().count(),
+            path
+        );
+        if tokio::fs::try_exists(path).await.unwrap_or(false) {
+            description.push_str(" (overwrites the existing file)");
+        }
+        let code = block.code.clone();
+        self.bridge.send(SystemEvent::RequestApproval { description }).await?;
+        if !self.wait_for_approval().await? {
+            return self.bridge.send(SystemEvent::Text("Not saved.\n".to_string())).await;
+        }
+        match tokio::fs::write(path, code).await {
+            Ok(()) => {
+                self.remember_file(path);
+                self.bridge.send(SystemEvent::Text(format!("Saved code block {} to {}\n", n, path))).await
+            }
+            Err(e) => self.bridge.send(SystemEvent::Error(format!("Could not write {}: {}", path, e))).await,
+        }
+    }
+
+    /// Sends one model request and streams its events to the bridge, collecting
+    /// tool calls. Gives up when `deadline` passes, dropping the stream.
+    async fn stream_turn(&mut self, context: TurnContext, deadline: Option<Instant>) -> Result<TurnOutcome> {
+        let rendered = if self.dev_mode && self.preview_requests {
+            self.brain.render_request(&context)?
+        } else {
+            None
+        };
+        let request_future = match rendered {
+            Some(request) => match self.review_request(request).await? {
+                Some(request) => self.brain.process_request(request),
+                None => {
+                    self.bridge.send(SystemEvent::Text("Request aborted.\n".to_string())).await?;
+                    return Ok(TurnOutcome::Aborted);
+                }
+            },
+            None => self.brain.process_turn(context),
+        };
+        let sent = Instant::now();
+        let Some(brain_stream) = with_deadline(deadline, request_future).await else {
+            return Ok(TurnOutcome::TimedOut);
+        };
+        let mut brain_stream = match brain_stream {
+            Err(e) if overflow::is_context_length_error(&format!("{:#}", e)) => {
+                return Ok(TurnOutcome::ContextTooLong(format!("{:#}", e)));
+            }
+            Err(e) => match e.downcast::<RateLimited>() {
+                Ok(limited) => {
+                    warn!("{}", limited);
+                    return Ok(TurnOutcome::RateLimited(limited.retry_after));
+                }
+                Err(e) => return Err(e),
+            },
+            Ok(stream) => stream,
+        };
+        let mut tool_calls = Vec::new();
+        let mut first_event = None;
+
+        loop {
+            let flush_at = self.coalescer.deadline();
+            let next = tokio::select! {
+                next = with_deadline(deadline, brain_stream.next()) => next,
+                Some(evt) = self.events_rx.recv() => {
+                    if self.triage(evt).await? {
+                        self.flush_text().await?;
+                        return Ok(TurnOutcome::Aborted);
+                    }
+                    continue;
+                }
+                _ = tokio::time::sleep_until(flush_at.unwrap_or_else(Instant::now)), if flush_at.is_some() => {
+                    self.flush_text().await?;
+                    continue;
+                }
+            };
+            let Some(brain_res) = next else {
+                self.flush_text().await?;
+                return Ok(TurnOutcome::TimedOut);
+            };
+            let Some(brain_res) = brain_res else {
+                break;
+            };
+            let event = brain_res?;
+            // The request id arrives with the response headers, before any output.
+            if !matches!(event, BrainEvent::RequestId(_)) {
+                first_event.get_or_insert_with(Instant::now);
+            }
+            match event {
+                BrainEvent::TextDelta(text) => {
+                    self.tee_text(&text).await?;
+                    self.last_response.push_str(&text);
+                    self.send_text(&text).await?;
+                }
+                BrainEvent::ThoughtDelta(thought) => {
+                    self.flush_text().await?;
+                    self.bridge.send(SystemEvent::Thought(thought)).await?;
+                }
+                BrainEvent::ToolCall { name, id, args } => {
+                    tool_calls.push((name, id, args));
+                }
+                BrainEvent::StructuredChunk { value, complete, errors } => {
+                    self.flush_text().await?;
+                    self.bridge.send(SystemEvent::StructuredChunk { value, complete, errors }).await?;
+                }
+                BrainEvent::Image { mime_type, data, uri } => {
+                    self.flush_text().await?;
+                    self.save_artifact(&mime_type, data.as_deref(), uri.as_deref()).await?;
+                    self.bridge.send(SystemEvent::Image { mime_type, data, uri }).await?;
+                }
+                BrainEvent::File { mime_type, data, uri } => {
+                    self.flush_text().await?;
+                    self.save_artifact(&mime_type, data.as_deref(), uri.as_deref()).await?;
+                }
+                BrainEvent::Usage(usage) => self.turn_usage += usage,
+                BrainEvent::RequestId(id) => {
+                    self.request_ids.push(id);
+                    self.send_debug("Streaming model response".to_string()).await?;
+                }
+                BrainEvent::Complete { interaction_id } => {
+                    if let Some(id) = interaction_id {
+                        self.interaction_ids.push(id.clone());
+                        self.previous_interaction_id = Some(id);
+                    }
+                }
+                BrainEvent::Error(err) => {
+                    self.flush_text().await?;
+                    self.bridge.send(SystemEvent::Error(err)).await?;
+                }
+            }
+        }
+        self.flush_text().await?;
+        self.timings.record_request(
+            first_event.map(|at| at - sent),
+            first_event.map(|at| at.elapsed()).unwrap_or_default(),
+        );
+        Ok(TurnOutcome::Done(tool_calls))
+    }
+
+    /// Makes a request that overflowed the context window smaller, one step
+    /// per attempt: drop the oldest tool outputs, then the server-side
+    /// history, then trim what is left by half. Returns what was dropped,
+    /// or `None` once there is nothing more to try. Cached tool results are
+    /// forgotten with it, so a repeated call isn't answered "unchanged" with
+    /// content the model no longer has.
+    fn shrink_context(&mut self, context: &mut TurnContext, attempt: u32) -> Option<String> {
+        if attempt > overflow::MAX_ATTEMPTS {
+            return None;
+        }
+        self.tools.clear_cache();
+        let dropped = overflow::drop_oldest_tool_outputs(context, &self.output_store);
+        if !dropped.is_empty() {
+            return Some(format!(
+                "dropped the output of {} older tool call(s) ({}); the model can page it back in with read_tool_output",
+                dropped.len(),
+                dropped.join(", ")
+            ));
+        }
+        if context.previous_interaction_id.is_some() {
+            overflow::restart_without_history(context, &self.last_prompt);
+            self.previous_interaction_id = None;
+            self.unanswered.clear();
+            return Some("dropped the earlier conversation history".to_string());
+        }
+        let bytes: usize = budget::sources(context).iter().map(|(_, text)| text.len()).sum();
+        let limit = budget::estimate(bytes) / 2;
+        if limit == 0 {
+            return None;
+        }
+        budget::trim(context, limit, &self.output_store);
+        Some(format!("trimmed the prompt and tool results to about {} tokens", limit))
+    }
+
+    /// Stores a generated file in the session's artifacts directory, downloading
+    /// File API references, and tells the bridge where it went.
+    async fn save_artifact(&mut self, mime_type: &str, data: Option<&str>, uri: Option<&str>) -> Result<()> {
+        let client = self.files.as_ref().map(|(client, _)| client);
+        match self.artifacts.save(client, mime_type, data, uri).await {
+            Ok(Some(path)) => self.bridge.send(SystemEvent::Artifact { path, mime_type: mime_type.to_string() }).await,
+            Ok(None) => Ok(()),
+            Err(e) => self.bridge.send(SystemEvent::Warning(format!("Failed to save generated {}: {}", mime_type, e))).await,
+        }
+    }
+
+    /// Lists the files tools read or changed this turn, with current line counts.
+    async fn send_file_refs(&mut self) -> Result<()> {
+        if self.turn_files.is_empty() {
+            return Ok(());
+        }
+        let files: Vec<events::FileRef> = std::mem::take(&mut self.turn_files).into_iter()
+            .map(|(path, access)| {
+                let lines = std::fs::read(&path).ok().map(|bytes| line_count(&bytes));
+                events::FileRef { path, changed: access == FileAccess::Write, lines }
+            })
+            .collect();
+        for file in &files {
+            self.remember_file(&file.path);
+        }
+        self.bridge.send(SystemEvent::FileRefs { files }).await?;
+        let changed = self.snapshots.changes().len();
+        if changed > 1 {
+            let hint = format!("{} files changed this turn; /review to keep or revert them hunk by hunk", changed);
+            self.bridge.send(SystemEvent::Info(hint)).await?;
+        }
+        Ok(())
+    }
+
+    /// `/review`: walks the last turn's file edits one hunk at a time. Each
+    /// hunk is kept (y), reverted (n) or replaced with typed lines (e); q
+    /// keeps whatever hasn't been reviewed yet.
+    async fn review(&mut self) -> Result<()> {
+        let changes = self.snapshots.changes();
+        if changes.is_empty() {
+            return self.bridge.send(SystemEvent::Info("No file changes from the last turn to review".to_string())).await;
+        }
+        let mut summary = Vec::new();
+        'files: for (n, change) in changes.iter().enumerate() {
+            let (old, new) = (change.original.as_deref().unwrap_or(""), change.current.as_deref().unwrap_or(""));
+            let hunks = change.hunks();
+            let mut decisions = Vec::new();
+            for (i, hunk) in hunks.iter().enumerate() {
+                let header = format!("[{}/{}] {} hunk {}/{}", n + 1, changes.len(), change.path, i + 1, hunks.len());
+                self.bridge.send(SystemEvent::Info(format!("{}\n{}", header, review::render(old, new, hunk)))).await?;
+                self.bridge.send(SystemEvent::Text("Keep this change? y = keep, n = revert, e = edit, q = stop reviewing\n".to_string())).await?;
+                let decision = loop {
+                    let Some(reply) = self.next_reply().await? else {
+                        return self.bridge.send(SystemEvent::Info("Review cancelled; files left as they are".to_string())).await;
+                    };
+                    match reply.trim().to_lowercase().as_str() {
+                        "y" | "yes" | "a" | "accept" | "keep" => break review::Decision::Accept,
+                        "n" | "no" | "r" | "revert" => break review::Decision::Revert,
+                        "e" | "edit" => break review::Decision::Replace(self.read_replacement().await?),
+                        "q" | "quit" | "stop" => {
+                            self.write_review(change, &decisions, &mut summary).await?;
+                            break 'files;
+                        }
+                        _ => self.bridge.send(SystemEvent::Text("Answer y, n, e or q\n".to_string())).await?,
+                    }
+                };
+                decisions.push(decision);
+            }
+            self.write_review(change, &decisions, &mut summary).await?;
+        }
+        self.snapshots.clear();
+        self.bridge.send(SystemEvent::Info(format!("Review done:\n  {}", summary.join("\n  ")))).await
+    }
+
+    /// Applies one file's decisions (unreviewed hunks stay as they are).
+    async fn write_review(&mut self, change: &review::Change, decisions: &[review::Decision], summary: &mut Vec<String>) -> Result<()> {
+        let reverted = decisions.iter().filter(|d| **d == review::Decision::Revert).count();
+        let edited = decisions.iter().filter(|d| matches!(d, review::Decision::Replace(_))).count();
+        if reverted + edited == 0 {
+            summary.push(format!("{}: kept", change.path));
+            return Ok(());
+        }
+        let result = match change.resolve(decisions) {
+            Some(text) => tokio::fs::write(&change.path, text).await,
+            None => tokio::fs::remove_file(&change.path).await,
+        };
+        match result {
+            Ok(()) => summary.push(format!("{}: {} reverted, {} edited, {} kept", change.path, reverted, edited, decisions.len() - reverted - edited)),
+            Err(e) => self.bridge.send(SystemEvent::Error(format!("Could not write {}: {}", change.path, e))).await?,
+        }
+        Ok(())
+    }
+
+    /// Collects replacement lines for a hunk until a line with a single `.`.
+    async fn read_replacement(&mut self) -> Result<Vec<String>> {
+        self.bridge.send(SystemEvent::Text("Type the replacement lines, then a line with a single '.'\n".to_string())).await?;
+        let mut lines = Vec::new();
+        while let Some(line) = self.next_reply().await? {
+            if line.trim() == "." {
+                break;
+            }
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+
+    /// The next line the user typed (y/n count as lines), or `None` if the
+    /// interaction was cancelled.
+    async fn next_reply(&mut self) -> Result<Option<String>> {
+        while let Some(evt) = self.events_rx.recv().await {
+            match evt {
+                UserEvent::Approve => return Ok(Some("y".to_string())),
+                UserEvent::Reject => return Ok(Some("n".to_string())),
+                UserEvent::Message(text) => return Ok(Some(text)),
+                evt => {
+                    if self.triage(evt).await? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks content about to be attached or sent again for secrets. Matches
+    /// are shown and the user decides whether to send them, redact them or
+    /// abort; with auto-redaction on they are masked without asking. Returns
+    /// `None` when the user aborts.
+    async fn guard_secrets(&mut self, text: String) -> Result<Option<String>> {
+        let findings = self.secrets.scan(&text);
+        if findings.is_empty() {
+            return Ok(Some(text));
+        }
+        if self.redact_secrets {
+            let msg = format!("Redacted {} likely secret(s) before sending", findings.len());
+            self.bridge.send(SystemEvent::Warning(msg)).await?;
+            return Ok(Some(sensitive::redact(&text, &findings)));
+        }
+        self.bridge.send(SystemEvent::Warning(sensitive::describe(&text, &findings))).await?;
+        self.bridge.send(SystemEvent::Text("Send it? y = send as is, r = redact and send, n = abort\n".to_string())).await?;
+        loop {
+            let Some(reply) = self.next_reply().await? else {
+                return Ok(None);
+            };
+            match reply.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Ok(Some(text)),
+                "r" | "redact" => return Ok(Some(sensitive::redact(&text, &findings))),
+                "n" | "no" => return Ok(None),
+                _ => self.bridge.send(SystemEvent::Text("Please answer y, r or n\n".to_string())).await?,
+            }
+        }
+    }
+
+    /// Guards against accidentally sending huge inputs. Sizes are estimated
+    /// first; only a request that may be over the limit is counted exactly.
+    /// Returns false when the user aborts.
+    async fn confirm_request_size(&mut self, context: &mut TurnContext) -> Result<bool> {
+        let Some(limit) = self.confirm_tokens else {
+            return Ok(true);
+        };
+        let parts = budget::sources(context);
+        let estimated: u64 = parts.iter().map(|(_, text)| budget::estimate(text.len())).sum();
+        if estimated < limit / 2 {
+            return Ok(true);
+        }
+        let mut sources = Vec::new();
+        for (label, text) in parts {
+            let counted = match self.brain.count_tokens(&text).await {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    warn!("countTokens failed, estimating instead: {}", e);
+                    None
+                }
+            };
+            sources.push(budget::Source {
+                label,
+                bytes: text.len(),
+                tokens: counted.unwrap_or_else(|| budget::estimate(text.len())),
+                counted: counted.is_some(),
+            });
+        }
+        if sources.iter().map(|s| s.tokens).sum::<u64>() <= limit {
+            return Ok(true);
+        }
+
+        self.bridge.send(SystemEvent::Info(budget::summary(&sources, limit))).await?;
+        self.bridge.send(SystemEvent::Text("Send it? y = send, t = trim to fit, n = abort\n".to_string())).await?;
+        loop {
+            let Some(reply) = self.next_reply().await? else {
+                return Ok(false);
+            };
+            match reply.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                "t" | "trim" => {
+                    budget::trim(context, limit, &self.output_store);
+                    let tokens: u64 = budget::sources(context).iter().map(|(_, text)| budget::estimate(text.len())).sum();
+                    self.bridge.send(SystemEvent::Info(format!("Trimmed to about {} tokens", tokens))).await?;
+                    return Ok(true);
+                }
+                _ => self.bridge.send(SystemEvent::Text("Please answer y, t or n\n".to_string())).await?,
+            }
+        }
+    }
+
+    /// Sends streamed text through the bridge's coalescing policy.
+    async fn send_text(&mut self, text: &str) -> Result<()> {
+        match self.coalescer.push(text) {
+            Some(batch) => self.bridge.send(SystemEvent::Text(batch)).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn flush_text(&mut self) -> Result<()> {
+        match self.coalescer.flush() {
+            Some(batch) => self.bridge.send(SystemEvent::Text(batch)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Runs a single user prompt to completion, including any tool loop, and
+    /// waits for the bridge to receive everything the turn produced.
+    pub async fn handle_conversation(&mut self, initial_prompt: String) -> Result<()> {
+        let started = Instant::now();
+        self.timings = timings::TurnTimings::default();
+        self.in_turn = true;
+        let result = self.converse(initial_prompt).await;
+        self.in_turn = false;
+        self.timings.total = started.elapsed();
+        self.timing_stats.record(&self.timings);
+        self.send_debug(format!("Timings: {}", self.timings.summary())).await?;
+        self.buffer.drain().await;
+        result
+    }
+
+    /// Keeps a cancelled turn's tool results and answers the calls that never
+    /// ran as cancelled, so the next message doesn't continue a conversation
+    /// still waiting on them.
+    fn answer_cancelled(&mut self, mut results: Vec<ToolResult>, cancelled: impl Iterator<Item = (String, String)>) {
+        results.extend(cancelled.map(|(name, call_id)| ToolResult {
+            call_id,
+            name,
+            result: serde_json::json!({ "error": "Cancelled by the user." }),
+            is_error: true,
+        }));
+        self.unanswered = results;
+    }
+
+    /// Pauses the turn for `wait`, counting down in the status line. User
+    /// events are triaged meanwhile, so /cancel, /panic and /exit still work.
+    /// Returns false if the turn was cancelled.
+    async fn wait_out_rate_limit(&mut self, wait: Duration) -> Result<bool> {
+        let resume_at = Instant::now() + wait;
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            let left = resume_at.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            let status = format!("Rate limited: retrying in {}s (/cancel to stop)", left.as_secs_f64().ceil() as u64);
+            self.bridge.send(SystemEvent::Status(Some(status))).await?;
+            tokio::select! {
+                _ = tick.tick() => {}
+                _ = tokio::time::sleep_until(resume_at) => {}
+                Some(evt) = self.events_rx.recv() => {
+                    if self.triage(evt).await? {
+                        self.bridge.send(SystemEvent::Status(None)).await?;
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        self.bridge.send(SystemEvent::Status(None)).await?;
+        Ok(true)
+    }
+
+    async fn converse(&mut self, initial_prompt: String) -> Result<()> {
+        let mut current_prompt = initial_prompt;
+        let mut current_tool_results = std::mem::take(&mut self.unanswered);
+        self.last_prompt = current_prompt.clone();
+        self.last_response.clear();
+        self.turn_usage = events::Usage::default();
+        self.request_ids.clear();
+        self.turn_files.clear();
+        self.snapshots.clear();
+        self.turn_cancelled = false;
+        self.turn_completed = false;
+        self.exit_requested = false;
+        let mut overflow_attempts = 0;
+        let mut rate_limit_waits = 0;
+        // The first request's build time includes picking the thinking level.
+        let mut build_started = Some(Instant::now());
+        let thinking_level = if self.fast_draft {
+            Some(ThinkingLevel::Minimal)
+        } else {
+            self.thinking_level_for(&current_prompt).await?
+        };
+
+        loop {
+            let building = build_started.take().unwrap_or_else(Instant::now);
+            // Steering sent since the last request goes with this one, next
+            // to any tool results, framed so it isn't read as their output.
+            while let Some(steer) = self.pending_steering.pop_front() {
+                if !current_prompt.is_empty() {
+                    current_prompt.push_str("\n\n");
+                }
+                current_prompt.push_str(&interjection(&steer));
+            }
+
+            let mut context = TurnContext {
+                prompt: current_prompt.clone(),
+                system_instruction: self.system_instruction(),
+                response_schema: self.response_schema.clone(),
+                previous_interaction_id: self.previous_interaction_id.clone(),
+                tool_results: current_tool_results,
+                thinking_level,
+                temperature: None,
+                model: self.model.clone(),
+                allowed_tools: self.allowed_tools.clone(),
+                search_grounding: self.search_grounding,
+                attachments: std::mem::take(&mut self.attachments),
+            };
+
+            current_prompt = String::new();
+            current_tool_results = Vec::new();
+            // Owed again if this request never gets through.
+            self.unanswered = context.tool_results.clone();
+
+            self.send_debug(format!("Turn context: {:?}", context)).await?;
+            if !self.confirm_request_size(&mut context).await? {
+                self.bridge.send(SystemEvent::Text("Request aborted.\n".to_string())).await?;
+                return Ok(());
+            }
+            self.timings.request_build += building.elapsed();
+
+            let tool_calls = loop {
+                let started = Instant::now();
+                let deadline = self.turn_deadline.map(|d| started + d);
+                match self.stream_turn(context.clone(), deadline).await? {
+                    TurnOutcome::Done(tool_calls) => break tool_calls,
+                    TurnOutcome::Aborted => return Ok(()),
+                    TurnOutcome::TimedOut => {
+                        let msg = format!(
+                            "Turn cancelled after {:.1}s (deadline {}s)",
+                            started.elapsed().as_secs_f64(),
+                            self.turn_deadline.unwrap_or_default().as_secs()
+                        );
+                        warn!("{}", msg);
+                        self.bridge.send(SystemEvent::Error(msg)).await?;
+                        let current = context.thinking_level.unwrap_or(ThinkingLevel::High);
+                        let Some(lower) = current.lower() else {
+                            return Ok(());
+                        };
+                        let description = format!("Retry with thinking level '{}'?", lower.as_str());
+                        self.bridge.send(SystemEvent::RequestApproval { description }).await?;
+                        if !self.wait_for_approval().await? {
+                            return Ok(());
+                        }
+                        context.thinking_level = Some(lower);
+                    }
+                    TurnOutcome::ContextTooLong(err) => {
+                        overflow_attempts += 1;
+                        warn!(attempt = overflow_attempts, "Request exceeded the context window: {}", err);
+                        match self.shrink_context(&mut context, overflow_attempts) {
+                            Some(dropped) => {
+                                let msg = format!("The request was too long for the model's context window; retrying after I {}", dropped);
+                                self.bridge.send(SystemEvent::Warning(msg)).await?;
+                            }
+                            None => {
+                                let msg = format!("The request is still too long for the model's context window; try /clear. ({})", err);
+                                self.bridge.send(SystemEvent::Error(msg)).await?;
+                                return Ok(());
+                            }
+                        }
+                    }
+                    TurnOutcome::RateLimited(retry_after) => {
+                        rate_limit_waits += 1;
+                        if rate_limit_waits > MAX_RATE_LIMIT_WAITS {
+                            let msg = format!("Still rate limited after {} waits; try again later", MAX_RATE_LIMIT_WAITS);
+                            self.bridge.send(SystemEvent::Error(msg)).await?;
+                            return Ok(());
+                        }
+                        if !self.wait_out_rate_limit(retry_after.unwrap_or(DEFAULT_RATE_LIMIT_WAIT)).await? {
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            self.unanswered.clear();
+
+            // Steering that arrived while the answer streamed gets a reply of
+            // its own rather than waiting for the next message.
+            if tool_calls.is_empty() && !self.pending_steering.is_empty() {
+                self.bridge.send(SystemEvent::Text("\n\n".to_string())).await?;
+                continue;
+            }
+            if tool_calls.is_empty() {
+                self.tee_text("\n").await?;
+                self.bridge.send(SystemEvent::Text("\n".to_string())).await?;
+                self.send_file_refs().await?;
+                self.turn_completed = true;
+                break;
+            }
+
+            // GATING: Ask for approval for all tool calls in this turn
+            let mut calls = tool_calls.into_iter();
+            while let Some((name, id, args)) = calls.next() {
+                if let Err(errors) = self.tools.validate_args(&name, &args) {
+                    warn!(tool = %name, "Rejecting tool call with invalid arguments: {:?}", errors);
+                    let details: Vec<serde_json::Value> = errors.iter()
+                        .map(|e| serde_json::json!({ "path": e.path, "message": e.message }))
+                        .collect();
+                    current_tool_results.push(ToolResult {
+                        call_id: id,
+                        result: serde_json::json!({
+                            "error": format!("Invalid arguments for tool '{}'", name),
+                            "validation_errors": details,
+                            "hint": "Fix the arguments so they match the tool's parameter schema and call it again.",
+                        }),
+                        name,
+                        is_error: true,
+                    });
+                    continue;
+                }
+
+                let args_map: std::collections::HashMap<String, serde_json::Value> = match &args {
+                    serde_json::Value::Object(map) => map.clone().into_iter().collect(),
+                    _ => Default::default(),
+                };
+                // Refused calls (e.g. in read-only mode) never reach the approval prompt.
+                let allowed = match &self.allowed_tools {
+                    Some(tools) if !tools.contains(&name) => {
+                        Err(anyhow::anyhow!("Tool '{}' is not available on this bridge", name))
+                    }
+                    _ => self.tools.check_allowed(&name, &args_map),
+                };
+                if let Err(e) = allowed {
+                    warn!(tool = %name, "Refusing tool call: {}", e);
+                    current_tool_results.push(ToolResult {
+                        call_id: id,
+                        result: serde_json::json!({ "error": e.to_string() }),
+                        name,
+                        is_error: true,
+                    });
+                    continue;
+                }
+
+                let bash_command = (name == "execute_bash")
+                    .then(|| args_map.get("command").and_then(|v| v.as_str()).map(str::to_string))
+                    .flatten();
+                let auto_approved = (self.auto_approve.iter().any(|t| t == "*" || *t == name)
+                    || bash_command.as_deref().is_some_and(|c| self.allow_list.allows(c)))
+                    && !self.tools.always_confirm(&name, &args_map);
+                let approved = if auto_approved {
+                    true
+                } else {
+                    let description = format!("Execute tool '{}' with args: {}", name, args);
+                    self.bridge.send(SystemEvent::RequestApproval { description }).await?;
+                    self.wait_for_approval().await?
+                };
+                if self.turn_cancelled {
+                    self.answer_cancelled(current_tool_results, std::iter::once((name, id)).chain(calls.map(|(name, id, _)| (name, id))));
+                    return Ok(());
+                }
+                if approved && !auto_approved {
+                    if let Some(prefix) = bash_command.as_deref().and_then(|c| self.allow_list.record_approval(c)) {
+                        self.offer_allow(&prefix).await?;
+                        if self.turn_cancelled {
+                            self.answer_cancelled(current_tool_results, std::iter::once((name, id)).chain(calls.map(|(name, id, _)| (name, id))));
+                            return Ok(());
+                        }
+                    }
+                }
+
+                if approved {
+                    for (path, access) in self.tools.files_touched(&name, &args_map) {
+                        if access == FileAccess::Write {
+                            self.snapshots.capture(&path);
+                        }
+                        let entry = self.turn_files.entry(path).or_insert(access);
+                        *entry = (*entry).max(access);
+                    }
+                    let started = Instant::now();
+                    let tools = self.tools.clone();
+                    let execution = {
+                        let execution = with_deadline(self.turn_deadline.map(|d| started + d), tools.execute(&name, args_map));
+                        tokio::pin!(execution);
+                        loop {
+                            tokio::select! {
+                                res = &mut execution => break res,
+                                Some(evt) = self.events_rx.recv() => {
+                                    // Dropping the execution future kills the tool process.
+                                    if self.triage(evt).await? {
+                                        let cancelled = std::iter::once((name.clone(), id.clone()))
+                                            .chain(calls.map(|(name, id, _)| (name, id)));
+                                        self.answer_cancelled(current_tool_results, cancelled);
+                                        return Ok(());
+                                    }
+                                }
+                            }
+                        }
+                    };
+                    let execution = execution.unwrap_or_else(|| {
+                        Err(anyhow::anyhow!("Tool timed out after {:.1}s and was cancelled", started.elapsed().as_secs_f64()))
+                    });
+                    self.timings.tools.push((name.clone(), started.elapsed()));
+                    for warning in self.tools.stats().take_warnings() {
+                        self.bridge.send(SystemEvent::Warning(warning)).await?;
+                    }
+                    match execution {
+                        Ok(res) => {
+                            // Secrets injected into tools must not reach the model or the tee.
+                            let output = redact::redact_value(res.output);
+                            self.tee_tool_result(&name, &output).await?;
+                            let output = truncate::truncate_output(output, self.max_tool_result_bytes, &self.output_store);
+                            let mut result = self.screen_tool_output(&name, output).await?;
+                            if res.is_error && self.error_hints {
+                                let found = hints::for_error(&args, &result.to_string());
+                                result = hints::annotate(result, found);
+                            }
+                            current_tool_results.push(ToolResult {
+                                call_id: id,
+                                name,
+                                result,
+                                is_error: res.is_error,
+                            });
+                        }
+                        Err(e) => {
+                            let mut result = serde_json::json!({ "error": e.to_string() });
+                            if self.error_hints {
+                                result = hints::annotate(result, hints::for_error(&args, &format!("{:#}", e)));
+                            }
+                            current_tool_results.push(ToolResult {
+                                call_id: id,
+                                name,
+                                result,
+                                is_error: true,
+                            });
+                        }
+                    }
+                } else {
+                    self.tools.stats().record_rejection(&name);
+                    current_tool_results.push(ToolResult {
+                        call_id: id,
+                        name,
+                        result: serde_json::json!({ "error": "User rejected tool execution." }),
+                        is_error: true,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Marks steering as the user's words sent mid-turn.
+fn interjection(steer: &str) -> String {
+    format!("[User interjection] {}", steer.trim())
+}
+
+fn line_count(bytes: &[u8]) -> usize {
+    let newlines = bytes.iter().filter(|&&b| b == b'\n').count();
+    if bytes.last().is_some_and(|&b| b != b'\n') { newlines + 1 } else { newlines }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::{BrainEvent, UserEvent, SystemEvent, TurnContext};
+    use async_trait::async_trait;
+    use futures_util::stream;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    struct MockBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for MockBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            self.calls.lock().unwrap().push(context);
+            let id = format!("id_{}", self.calls.lock().unwrap().len());
+            Ok(Box::pin(stream::iter(vec![
+                Ok(BrainEvent::TextDelta("hello".to_string())),
+                Ok(BrainEvent::Complete { interaction_id: Some(id) }),
+            ])))
+        }
+    }
+
+    struct TestBridge {
+        sent: Arc<Mutex<Vec<SystemEvent>>>,
+    }
+
+    #[async_trait]
+    impl CommBridge for TestBridge {
+        async fn send(&self, event: SystemEvent) -> Result<()> {
+            self.sent.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conductor_state_persistence() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let brain = Box::new(MockBrain { calls: calls.clone() });
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let bridge = Arc::new(TestBridge { sent: sent.clone() });
+        
+        let (_tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(brain, bridge, rx, Arc::new(ToolRegistry::new()));
+        conductor.handle_conversation("ping".to_string()).await?;
+        assert_eq!(conductor.previous_interaction_id, Some("id_1".to_string()));
+        conductor.handle_conversation("pong".to_string()).await?;
+        let history = calls.lock().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].prompt, "ping");
+        assert_eq!(history[0].previous_interaction_id, None);
+        assert_eq!(history[1].previous_interaction_id, Some("id_1".to_string()));
+        Ok(())
+    }
+        struct ToolMockBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for ToolMockBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            self.calls.lock().unwrap().push(context);
+            if self.calls.lock().unwrap().len() == 1 {
+                Ok(Box::pin(stream::iter(vec![
+                    Ok(BrainEvent::ToolCall { name: "test_tool".to_string(), id: "call_1".to_string(), args: serde_json::json!({}) }),
+                    Ok(BrainEvent::Complete { interaction_id: Some("id_1".to_string()) }),
+                ])))
+            } else {
+                Ok(Box::pin(stream::iter(vec![
+                    Ok(BrainEvent::TextDelta("ok".to_string())),
+                    Ok(BrainEvent::Complete { interaction_id: Some("id_2".to_string()) }),
+                ])))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conductor_answers_tool_call_cancelled_mid_run_with_next_message() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut tools = ToolRegistry::new();
+        tools.register_fn("test_tool", "Takes a while", serde_json::json!({ "type": "object" }), |_| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(serde_json::json!("done"))
+        });
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(ToolMockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(tools)
+        ).with_auto_approve(vec!["test_tool".to_string()]);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send(UserEvent::Command("/cancel".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.handle_conversation("list the disks".to_string())).await??;
+        assert_eq!(conductor.previous_interaction_id, Some("id_1".to_string()));
+
+        conductor.handle_conversation("never mind".to_string()).await?;
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].previous_interaction_id, Some("id_1".to_string()));
+        assert_eq!(calls[1].prompt, "never mind");
+        assert_eq!(calls[1].tool_results.len(), 1);
+        assert_eq!(calls[1].tool_results[0].call_id, "call_1");
+        assert_eq!(calls[1].tool_results[0].result, serde_json::json!({ "error": "Cancelled by the user." }));
+        assert!(conductor.unanswered.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_steering_injection() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(ToolMockBrain { calls: calls.clone() }), 
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
+            rx, 
+            Arc::new(ToolRegistry::new())
+        );
+        // Start turn
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            // Wait for tool approval request
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            // Send steering
+            tx_clone.send(UserEvent::Steer("actually do X".to_string())).await.unwrap();
+            // Send approval
+            tx_clone.send(UserEvent::Approve).await.unwrap();
+        });
+
+        conductor.handle_conversation("start".to_string()).await?;
+
+        let history = calls.lock().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].prompt, "start");
+        assert_eq!(history[1].prompt, "[User interjection] actually do X");
+        assert_eq!(history[1].tool_results.len(), 1);
+        assert_eq!(history[1].tool_results[0].name, "test_tool");
+        Ok(())
+    }
+
+    /// Answers slowly the first time, so steering can arrive mid-answer.
+    struct SlowAnswerBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for SlowAnswerBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            let n = {
+                let mut calls = self.calls.lock().unwrap();
+                calls.push(context);
+                calls.len()
+            };
+            let delay = if n == 1 { Duration::from_millis(100) } else { Duration::ZERO };
+            let complete = stream::once(async move {
+                tokio::time::sleep(delay).await;
+                Ok(BrainEvent::Complete { interaction_id: Some(format!("id_{}", n)) })
+            });
+            Ok(Box::pin(stream::iter(vec![Ok(BrainEvent::TextDelta("answer".to_string()))]).chain(complete)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conductor_steering_during_answer_gets_follow_up() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(SlowAnswerBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            tx.send(UserEvent::Steer("shorter please".to_string())).await.unwrap();
+        });
+
+        conductor.handle_conversation("explain raft".to_string()).await?;
+
+        let history = calls.lock().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].prompt, "[User interjection] shorter please");
+        assert_eq!(history[1].previous_interaction_id, Some("id_1".to_string()));
+        assert!(history[1].tool_results.is_empty());
+        assert!(conductor.turn_completed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_clear_command() -> Result<()> {
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: Arc::new(Mutex::new(Vec::new())) }), 
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
+            rx, 
+            Arc::new(ToolRegistry::new())
+        );
+
+        conductor.previous_interaction_id = Some("existing".to_string());
+        
+        // Simulate /clear command
+        tx.send(UserEvent::Command("/clear".to_string())).await?;
+        
+        // Run the main loop for a bit
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx_clone.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+
+        conductor.run().await?;
+
+        assert_eq!(conductor.previous_interaction_id, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_language_command() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+
+        tx.send(UserEvent::Command("/lang ta".to_string())).await?;
+        tx.send(UserEvent::Message("vanakkam".to_string())).await?;
+        tokio::spawn(async move {
+            // Sent while the turn runs, /exit would only ask for confirmation.
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        assert_eq!(conductor.language, Some("ta".to_string()));
+        let history = calls.lock().unwrap();
+        assert!(history[0].system_instruction.as_ref().unwrap().contains("Tamil"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_dev_mode_redacts_debug_events() -> Result<()> {
+        crate::redact::register_secret("sk-test-secret-123");
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (_tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: Arc::new(Mutex::new(Vec::new())) }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_dev_mode(true);
+
+        conductor.handle_conversation("my key is sk-test-secret-123".to_string()).await?;
+
+        let sent = sent.lock().unwrap();
+        let debug = sent.iter().find_map(|e| match e {
+            SystemEvent::Debug { message, .. } => Some(message.clone()),
+            _ => None,
+        }).expect("dev mode should emit a debug event");
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("sk-test-secret-123"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_tool_rejection() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(ToolMockBrain { calls: calls.clone() }), 
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
+            rx, 
+            Arc::new(ToolRegistry::new())
+        );
+
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            // REJECT the tool
+            tx_clone.send(UserEvent::Reject).await.unwrap();
+        });
+
+        conductor.handle_conversation("start".to_string()).await?;
+
+        let history = calls.lock().unwrap();
+        assert_eq!(history.len(), 2);
+        // Turn 2 should contain an error result for the tool
+        assert_eq!(history[1].tool_results.len(), 1);
+        assert!(history[1].tool_results[0].is_error);
+        assert_eq!(history[1].tool_results[0].result["error"], "User rejected tool execution.");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_bridge_tool_allow_list() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (_tx, rx) = mpsc::channel(10);
+        let bridge_tools = HashMap::from([("unknown".to_string(), vec!["read_file".to_string()])]);
+        let mut conductor = Conductor::new(
+            Box::new(ToolMockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_bridge_tools(&bridge_tools);
+
+        // No approval is sent: the call is refused before the prompt.
+        conductor.handle_conversation("start".to_string()).await?;
+
+        let history = calls.lock().unwrap();
+        assert_eq!(history[0].allowed_tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(history[1].tool_results[0].result["error"], "Tool 'test_tool' is not available on this bridge");
+        Ok(())
+    }
+
+    struct RenderingBrain {
+        sent_requests: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for RenderingBrain {
+        async fn process_turn(&self, _context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            panic!("reviewed turns must go through process_request");
+        }
+
+        fn render_request(&self, context: &TurnContext) -> Result<Option<serde_json::Value>> {
+            Ok(Some(serde_json::json!({ "input": context.prompt })))
+        }
+
+        async fn process_request(&self, request: serde_json::Value) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            self.sent_requests.lock().unwrap().push(request);
+            Ok(Box::pin(stream::iter(vec![Ok(BrainEvent::Complete { interaction_id: None })])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conductor_sends_hand_edited_request() -> Result<()> {
+        let sent_requests = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(RenderingBrain { sent_requests: sent_requests.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_dev_mode(true).with_request_preview(true);
+
+        let events = sent.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let path = events.lock().unwrap().iter().find_map(|e| match e {
+                SystemEvent::RequestApproval { description } => description
+                    .split("Edit ").nth(1)
+                    .and_then(|rest| rest.split(" first").next())
+                    .map(str::to_string),
+                _ => None,
+            }).unwrap();
+            std::fs::write(path, r#"{"input": "edited"}"#).unwrap();
+            tx.send(UserEvent::Approve).await.unwrap();
+        });
+
+        conductor.handle_conversation("original".to_string()).await?;
+
+        assert_eq!(*sent_requests.lock().unwrap(), vec![serde_json::json!({ "input": "edited" })]);
+        Ok(())
+    }
+
+    struct SlowFirstBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for SlowFirstBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            let first = {
+                let mut calls = self.calls.lock().unwrap();
+                calls.push(context);
+                calls.len() == 1
+            };
+            let delay = if first { Duration::from_secs(5) } else { Duration::ZERO };
+            Ok(Box::pin(futures_util::stream::once(async move {
+                tokio::time::sleep(delay).await;
+                Ok(BrainEvent::TextDelta("done".to_string()))
+            })))
+        }
+    }
+
+    /// Rejects any request that continues a stored conversation as too long.
+    struct OverflowBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for OverflowBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            let continues = context.previous_interaction_id.is_some();
+            self.calls.lock().unwrap().push(context);
+            if continues {
+                anyhow::bail!("API Error: The input token count (1100000) exceeds the maximum number of tokens allowed (1048576). (code: 400 Bad Request)");
+            }
+            Ok(Box::pin(futures_util::stream::iter(vec![
+                Ok(BrainEvent::TextDelta("fresh".to_string())),
+                Ok(BrainEvent::Complete { interaction_id: Some("id_new".to_string()) }),
+            ])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conductor_retries_without_history_when_context_is_too_long() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (_tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(OverflowBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        conductor.previous_interaction_id = Some("id_old".to_string());
+        conductor.handle_conversation("and now?".to_string()).await?;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].previous_interaction_id, None);
+        assert!(calls[1].prompt.ends_with("dropped. Ask the user if you need something from it.]\n\nand now?"));
+        assert_eq!(conductor.previous_interaction_id, Some("id_new".to_string()));
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Warning(msg) if msg.ends_with("dropped the earlier conversation history"))));
+        Ok(())
+    }
+
+    /// A cacheable tool returning the contents of one file.
+    struct ReadNoteTool {
+        path: std::path::PathBuf,
+    }
 
-            if let Some(steer) = self.pending_steering.pop_front() {
-                current_prompt = steer;
+    #[async_trait]
+    impl crate::tools::ToolExecutor for ReadNoteTool {
+        fn name(&self) -> String {
+            "read_note".to_string()
+        }
+
+        fn definition(&self) -> crate::brains::gemini::types::FunctionDeclaration {
+            crate::brains::gemini::types::FunctionDeclaration {
+                name: self.name(),
+                description: "Read the note".to_string(),
+                parameters: None,
             }
         }
 
-        Ok(())
+        fn cacheable(&self) -> bool {
+            true
+        }
+
+        fn cache_dependencies(&self, _args: &std::collections::HashMap<String, serde_json::Value>) -> Vec<std::path::PathBuf> {
+            vec![self.path.clone()]
+        }
+
+        async fn execute(&self, _args: std::collections::HashMap<String, serde_json::Value>) -> Result<crate::tools::ToolResult> {
+            Ok(crate::tools::ToolResult { output: serde_json::json!(std::fs::read_to_string(&self.path)?), is_error: false })
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::conductor::events::{BrainEvent, UserEvent, SystemEvent, TurnContext};
-    use async_trait::async_trait;
-    use futures_util::stream;
-    use std::sync::Mutex;
-    use std::time::Duration;
-    struct MockBrain {
+    /// Reads the note at the start of every turn; continuing the second
+    /// turn's stored conversation overflows.
+    struct RereadBrain {
         calls: Arc<Mutex<Vec<TurnContext>>>,
     }
 
     #[async_trait]
-    impl BrainEngine for MockBrain {
+    impl BrainEngine for RereadBrain {
         async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            let overflows = context.previous_interaction_id.as_deref() == Some("id_2");
+            let reads = context.tool_results.is_empty();
             self.calls.lock().unwrap().push(context);
-            let id = format!("id_{}", self.calls.lock().unwrap().len());
+            if overflows {
+                anyhow::bail!("API Error: The input token count (1100000) exceeds the maximum number of tokens allowed (1048576). (code: 400 Bad Request)");
+            }
+            let n = self.calls.lock().unwrap().len();
+            let event = if reads {
+                BrainEvent::ToolCall { name: "read_note".to_string(), id: format!("call_{}", n), args: serde_json::json!({}) }
+            } else {
+                BrainEvent::TextDelta("noted".to_string())
+            };
             Ok(Box::pin(stream::iter(vec![
-                Ok(BrainEvent::TextDelta("hello".to_string())),
-                Ok(BrainEvent::Complete { interaction_id: Some(id) }),
+                Ok(event),
+                Ok(BrainEvent::Complete { interaction_id: Some(format!("id_{}", n)) }),
             ])))
         }
     }
 
-    struct TestBridge {
-        sent: Arc<Mutex<Vec<SystemEvent>>>,
+    #[tokio::test]
+    async fn test_conductor_rereads_file_in_full_after_dropping_history() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("chitti-note-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "the backup runs at 2am")?;
+        let mut tools = ToolRegistry::new();
+        tools.enable_cache();
+        tools.register(Box::new(ReadNoteTool { path: path.clone() }));
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (_tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(RereadBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(tools)
+        ).with_auto_approve(vec!["read_note".to_string()]);
+
+        conductor.handle_conversation("when does the backup run?".to_string()).await?;
+        conductor.handle_conversation("remind me again".to_string()).await?;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 5);
+        assert_eq!(calls[3].previous_interaction_id, None);
+        assert_eq!(calls[4].tool_results[0].result, serde_json::json!("the backup runs at 2am"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    struct RateLimitedFirstBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
     }
 
     #[async_trait]
-    impl CommBridge for TestBridge {
-        async fn send(&self, event: SystemEvent) -> Result<()> {
-            self.sent.lock().unwrap().push(event);
-            Ok(())
+    impl BrainEngine for RateLimitedFirstBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            self.calls.lock().unwrap().push(context);
+            if self.calls.lock().unwrap().len() == 1 {
+                return Err(RateLimited {
+                    message: "Resource has been exhausted".to_string(),
+                    retry_after: Some(Duration::from_millis(1500)),
+                }.into());
+            }
+            Ok(Box::pin(futures_util::stream::iter(vec![
+                Ok(BrainEvent::TextDelta("done".to_string())),
+                Ok(BrainEvent::Complete { interaction_id: Some("id_1".to_string()) }),
+            ])))
         }
     }
 
     #[tokio::test]
-    async fn test_conductor_state_persistence() -> Result<()> {
+    async fn test_conductor_waits_out_rate_limit_and_resumes() -> Result<()> {
         let calls = Arc::new(Mutex::new(Vec::new()));
-        let brain = Box::new(MockBrain { calls: calls.clone() });
         let sent = Arc::new(Mutex::new(Vec::new()));
-        let bridge = Arc::new(TestBridge { sent: sent.clone() });
-        
         let (_tx, rx) = mpsc::channel(10);
-        let mut conductor = Conductor::new(brain, bridge, rx, Arc::new(ToolRegistry::new()));
-        conductor.handle_conversation("ping".to_string()).await?;
+        let mut conductor = Conductor::new(
+            Box::new(RateLimitedFirstBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        conductor.handle_conversation("hi".to_string()).await?;
+
+        assert_eq!(calls.lock().unwrap().len(), 2);
         assert_eq!(conductor.previous_interaction_id, Some("id_1".to_string()));
-        conductor.handle_conversation("pong".to_string()).await?;
-        let history = calls.lock().unwrap();
-        assert_eq!(history.len(), 2);
-        assert_eq!(history[0].prompt, "ping");
-        assert_eq!(history[0].previous_interaction_id, None);
-        assert_eq!(history[1].previous_interaction_id, Some("id_1".to_string()));
+        let sent = sent.lock().unwrap();
+        let statuses: Vec<&Option<String>> = sent.iter()
+            .filter_map(|e| match e { SystemEvent::Status(s) => Some(s), _ => None })
+            .collect();
+        assert_eq!(statuses.first(), Some(&&Some("Rate limited: retrying in 2s (/cancel to stop)".to_string())));
+        assert_eq!(statuses.last(), Some(&&None));
         Ok(())
     }
-        struct ToolMockBrain {
-        calls: Arc<Mutex<Vec<TurnContext>>>,
+
+    #[tokio::test]
+    async fn test_conductor_cancels_while_waiting_out_rate_limit() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(RateLimitedFirstBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        tx.send(UserEvent::Command("/cancel".to_string())).await?;
+        conductor.handle_conversation("hi".to_string()).await?;
+
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        assert!(conductor.turn_cancelled);
+        Ok(())
     }
 
-    #[async_trait]
-    impl BrainEngine for ToolMockBrain {
-        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
-            self.calls.lock().unwrap().push(context);
-            if self.calls.lock().unwrap().len() == 1 {
-                Ok(Box::pin(stream::iter(vec![
-                    Ok(BrainEvent::ToolCall { name: "test_tool".to_string(), id: "call_1".to_string(), args: serde_json::json!({}) }),
-                    Ok(BrainEvent::Complete { interaction_id: Some("id_1".to_string()) }),
-                ])))
-            } else {
-                Ok(Box::pin(stream::iter(vec![
-                    Ok(BrainEvent::TextDelta("ok".to_string())),
-                    Ok(BrainEvent::Complete { interaction_id: Some("id_2".to_string()) }),
-                ])))
-            }
-        }
+    #[tokio::test]
+    async fn test_conductor_deadline_cancels_and_retries_lower() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(SlowFirstBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_turn_deadline(Some(Duration::from_millis(50)));
+
+        tokio::spawn(async move {
+            // Approvals sent before the retry prompt would be dropped as stale.
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            tx.send(UserEvent::Approve).await.unwrap();
+        });
+        conductor.handle_conversation("think hard".to_string()).await?;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].thinking_level, None);
+        assert_eq!(calls[1].thinking_level, Some(ThinkingLevel::Medium));
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Error(msg) if msg.starts_with("Turn cancelled after"))));
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_conductor_steering_injection() -> Result<()> {
+    async fn test_conductor_queues_input_and_cancels_turn() -> Result<()> {
         let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
         let (tx, rx) = mpsc::channel(10);
         let mut conductor = Conductor::new(
-            Box::new(ToolMockBrain { calls: calls.clone() }), 
-            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
-            rx, 
+            Box::new(SlowFirstBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
             Arc::new(ToolRegistry::new())
         );
-        // Start turn
-        let tx_clone = tx.clone();
+
+        tx.send(UserEvent::Message("first".to_string())).await?;
         tokio::spawn(async move {
-            // Wait for tool approval request
             tokio::time::sleep(Duration::from_millis(50)).await;
-            // Send steering
-            tx_clone.send(UserEvent::Steer("actually do X".to_string())).await.unwrap();
-            // Send approval
-            tx_clone.send(UserEvent::Approve).await.unwrap();
+            tx.send(UserEvent::Message("second".to_string())).await.unwrap();
+            tx.send(UserEvent::Command("/cancel".to_string())).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
         });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
 
-        conductor.handle_conversation("start".to_string()).await?;
+        let prompts: Vec<String> = calls.lock().unwrap().iter().map(|c| c.prompt.clone()).collect();
+        assert_eq!(prompts, vec!["first", "second"]);
+        let sent = sent.lock().unwrap();
+        assert!(sent.iter().any(|e| matches!(e, SystemEvent::Info(msg) if msg.ends_with("pending inputs: 1"))));
+        assert!(sent.iter().any(|e| matches!(e, SystemEvent::Info(msg) if msg == "Turn cancelled.")));
+        Ok(())
+    }
 
-        let history = calls.lock().unwrap();
-        assert_eq!(history.len(), 2);
-        assert_eq!(history[0].prompt, "start");
-        assert_eq!(history[1].prompt, "actually do X");
-        assert_eq!(history[1].tool_results.len(), 1);
-        assert_eq!(history[1].tool_results[0].name, "test_tool");
+    #[tokio::test]
+    async fn test_conductor_panic_cancels_and_enters_read_only() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let tools = Arc::new(ToolRegistry::new());
+        let (tx, rx) = mpsc::channel(10);
+        // Killing real descendants would take out processes of tests running alongside.
+        let kills = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let recorded = kills.clone();
+        let mut conductor = Conductor::new(
+            Box::new(SlowFirstBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            tools.clone()
+        ).with_process_killer(Arc::new(move || {
+            recorded.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            2
+        }));
+
+        tx.send(UserEvent::Message("first".to_string())).await?;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send(UserEvent::Message("second".to_string())).await.unwrap();
+            tx.send(UserEvent::Command("/panic".to_string())).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        // Unlike /cancel, queued input is dropped too.
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        assert!(tools.is_read_only());
+        assert_eq!(kills.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Warning(msg) if msg.starts_with("Stopped: turn cancelled, 2 child process(es) killed"))));
         Ok(())
     }
 
+    /// Streams half an answer, then stalls.
+    struct StallingBrain;
+
+    #[async_trait]
+    impl BrainEngine for StallingBrain {
+        async fn process_turn(&self, _context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            let stall = futures_util::stream::once(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(BrainEvent::TextDelta(" and the rest".to_string()))
+            });
+            Ok(Box::pin(futures_util::stream::iter(vec![Ok(BrainEvent::TextDelta("The first half".to_string()))]).chain(stall)))
+        }
+    }
+
     #[tokio::test]
-    async fn test_conductor_clear_command() -> Result<()> {
+    async fn test_conductor_confirms_exit_during_turn() -> Result<()> {
+        let sent = Arc::new(Mutex::new(Vec::new()));
         let (tx, rx) = mpsc::channel(10);
+        let root = std::env::temp_dir().join(format!("chitti-artifacts-{}", uuid::Uuid::new_v4()));
+        let vault = Arc::new(crate::vault::Vault::from_key(&[3; 32]));
         let mut conductor = Conductor::new(
-            Box::new(MockBrain { calls: Arc::new(Mutex::new(Vec::new())) }), 
-            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
-            rx, 
+            Box::new(StallingBrain),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
             Arc::new(ToolRegistry::new())
-        );
+        ).with_artifacts_dir(root.clone(), Some(vault.clone()));
+        assert!(!root.exists());
 
-        conductor.previous_interaction_id = Some("existing".to_string());
-        
-        // Simulate /clear command
-        tx.send(UserEvent::Command("/clear".to_string())).await?;
-        
-        // Run the main loop for a bit
-        let tx_clone = tx.clone();
+        tx.send(UserEvent::Message("explain".to_string())).await?;
+        let exits = sent.clone();
         tokio::spawn(async move {
             tokio::time::sleep(Duration::from_millis(50)).await;
-            tx_clone.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            // Still running after the first /exit.
+            assert!(exits.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Warning(msg) if msg.starts_with("A reply is still in progress"))));
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
         });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
 
-        conductor.run().await?;
+        // The partial answer is saved like any other exchange: encrypted under the vault.
+        let saved = std::fs::read(conductor.artifacts.dir().join("session.json"))?;
+        assert!(crate::vault::Vault::is_encrypted(&saved));
+        let log: serde_json::Value = serde_json::from_slice(&vault.decrypt(&saved)?)?;
+        assert_eq!(log["exchanges"][0]["response"], "The first half\n\n[Interrupted by /exit]");
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
 
-        assert_eq!(conductor.previous_interaction_id, None);
+    /// Answers with the request id a provider reports in `X-Request-ID`.
+    struct RequestIdBrain;
+
+    #[async_trait]
+    impl BrainEngine for RequestIdBrain {
+        async fn process_turn(&self, _context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            Ok(Box::pin(stream::iter(vec![
+                Ok(BrainEvent::RequestId("req-42".to_string())),
+                Ok(BrainEvent::TextDelta("hi".to_string())),
+                Ok(BrainEvent::Complete { interaction_id: Some("id_1".to_string()) }),
+            ])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id_reaches_debug_events_and_turn_log() -> Result<()> {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let log_path = std::env::temp_dir().join(format!("chitti-turns-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut conductor = Conductor::new(
+            Box::new(RequestIdBrain),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_dev_mode(true).with_turn_log(Some(crate::turn_log::TurnLog::Jsonl(log_path.clone())));
+
+        tx.send(UserEvent::Message("hello".to_string())).await?;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let _ = tx.send(UserEvent::Command("/exit".to_string())).await;
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e,
+            SystemEvent::Debug { message, request_id: Some(id) } if message == "Streaming model response" && id == "req-42")));
+        let record: serde_json::Value = serde_json::from_str(std::fs::read_to_string(&log_path)?.trim())?;
+        assert_eq!(record["request_ids"], serde_json::json!(["req-42"]));
+        assert_eq!(record["response"], "hi");
+        std::fs::remove_file(&log_path)?;
         Ok(())
     }
 
+    /// Records each turn and fails it, like a classifier call that errors out.
+    struct FailingBrain {
+        calls: Arc<Mutex<Vec<TurnContext>>>,
+    }
+
+    #[async_trait]
+    impl BrainEngine for FailingBrain {
+        async fn process_turn(&self, context: TurnContext) -> Result<futures_util::stream::BoxStream<'static, Result<BrainEvent>>> {
+            self.calls.lock().unwrap().push(context);
+            Ok(Box::pin(stream::iter(vec![Err(anyhow::anyhow!("quota exceeded"))])))
+        }
+    }
+
     #[tokio::test]
-    async fn test_conductor_tool_rejection() -> Result<()> {
+    async fn test_injection_classifier_gets_no_tools_and_its_failure_is_not_fatal() -> Result<()> {
         let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(crate::tools::bash::BashTool::new(None)));
+        let (_tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(FailingBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(tools)
+        ).with_injection_classifier(true);
+        conductor.allowed_tools = Some(vec!["execute_bash".to_string()]);
+
+        let screened = conductor.screen_tool_output("execute_bash", serde_json::json!("total 0")).await?;
+        assert_eq!(screened, sanitize::wrap_untrusted("execute_bash", serde_json::json!("total 0")));
+        assert_eq!(calls.lock().unwrap()[0].allowed_tools, Some(Vec::new()));
+        assert!(sent.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_keeps_running_when_the_session_cant_be_saved() -> Result<()> {
+        let sent = Arc::new(Mutex::new(Vec::new()));
         let (tx, rx) = mpsc::channel(10);
+        // A file where the artifacts directory should be makes every save fail.
+        let root = std::env::temp_dir().join(format!("chitti-artifacts-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&root, "not a directory")?;
         let mut conductor = Conductor::new(
-            Box::new(ToolMockBrain { calls: calls.clone() }), 
-            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }), 
-            rx, 
+            Box::new(MockBrain { calls: Arc::new(Mutex::new(Vec::new())) }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_artifacts_dir(root.clone(), None);
+
+        for cmd in ["/tag work", "/untag work", "/tag home", "/exit"] {
+            tx.send(UserEvent::Command(cmd.to_string())).await?;
+        }
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        let sent = sent.lock().unwrap();
+        let errors: Vec<&String> = sent.iter().filter_map(|e| match e { SystemEvent::Error(msg) => Some(msg), _ => None }).collect();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[1].starts_with("Removed tag 'work', but saving the session failed"));
+        assert_eq!(conductor.session.tags(), ["home"]);
+        std::fs::remove_file(&root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_exits_on_end_of_input_during_turn() -> Result<()> {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(StallingBrain),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
             Arc::new(ToolRegistry::new())
         );
 
-        let tx_clone = tx.clone();
+        tx.send(UserEvent::Message("explain".to_string())).await?;
         tokio::spawn(async move {
             tokio::time::sleep(Duration::from_millis(50)).await;
-            // REJECT the tool
-            tx_clone.send(UserEvent::Reject).await.unwrap();
+            // Ctrl-D sends this once; the sender then stays open but silent.
+            tx.send(UserEvent::EndOfInput).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            drop(tx);
         });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
 
-        conductor.handle_conversation("start".to_string()).await?;
+        assert!(!sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Warning(msg) if msg.starts_with("A reply is still in progress"))));
+        Ok(())
+    }
 
-        let history = calls.lock().unwrap();
-        assert_eq!(history.len(), 2);
-        // Turn 2 should contain an error result for the tool
-        assert_eq!(history[1].tool_results.len(), 1);
-        assert!(history[1].tool_results[0].is_error);
-        assert_eq!(history[1].tool_results[0].result["error"], "User rejected tool execution.");
+    #[tokio::test]
+    async fn test_conductor_fast_draft_refines_in_background() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        ).with_fast_draft(true);
+
+        tx.send(UserEvent::Message("why?".to_string())).await?;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].thinking_level, Some(ThinkingLevel::Minimal));
+        assert_eq!(calls[1].thinking_level, Some(ThinkingLevel::High));
+        assert_eq!(calls[1].previous_interaction_id, Some("id_1".to_string()));
+        assert_eq!(conductor.previous_interaction_id, Some("id_2".to_string()));
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Info(msg) if msg.starts_with("Refined answer"))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_pipeline_feeds_output_forward() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: Arc::new(Mutex::new(Vec::new())) }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        let out = std::env::temp_dir().join(format!("chitti-pipeline-{}.md", uuid::Uuid::new_v4()));
+        tx.send(UserEvent::Command(format!("/prompt greet | /prompt shorten | /tee {}", out.display()))).await?;
+        tokio::spawn(async move {
+            // Sent while the pipeline runs, /exit would cancel it.
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+
+        let prompts: Vec<String> = calls.lock().unwrap().iter().map(|c| c.prompt.clone()).collect();
+        assert_eq!(prompts, vec!["greet".to_string(), "shorten\n\n<input>\nhello\n</input>".to_string()]);
+        assert_eq!(std::fs::read_to_string(&out)?, "hello");
+        std::fs::remove_file(&out)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conductor_asks_before_attaching_secrets() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel(10);
+        let mut conductor = Conductor::new(
+            Box::new(MockBrain { calls: calls.clone() }),
+            Arc::new(TestBridge { sent: sent.clone() }),
+            rx,
+            Arc::new(ToolRegistry::new())
+        );
+        let env = std::env::temp_dir().join(format!("chitti-secrets-{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&env, "REGION=eu-west-1\nAWS_SECRET_ACCESS_KEY=wJalrXUtnFEMI/K7MDENG\n")?;
+        tx.send(UserEvent::Command(format!("/prompt why does this fail? @{}", env.display()))).await?;
+        tx.send(UserEvent::Message("r".to_string())).await?;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(UserEvent::Command("/exit".to_string())).await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(2), conductor.run()).await??;
+        std::fs::remove_file(&env)?;
 
+        assert!(sent.lock().unwrap().iter().any(|e| matches!(e, SystemEvent::Warning(msg)
+            if msg.contains("line 5 (AWS credentials): AWS_SECRET_ACCESS_KEY=»wJalrXUtnFEMI/K7MDENG«"))));
+        let prompt = calls.lock().unwrap()[0].prompt.clone();
+        assert!(prompt.contains("REGION=eu-west-1\nAWS_SECRET_ACCESS_KEY=[REDACTED]\n</file>"));
         Ok(())
     }
-}
\ No newline at end of file
+}
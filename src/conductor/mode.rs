@@ -0,0 +1,144 @@
+use crate::brains::gemini::types::ThinkingLevel;
+use crate::conductor::thinking::ThinkingMode;
+
+/// A `/mode` preset: a bundle of settings switched together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Plain conversation: no tools, quick answers.
+    Chat,
+    /// Hands-on work with every configured tool.
+    Agent,
+    /// Grounded in Google Search, deep thinking, tools limited to reading.
+    Research,
+}
+
+/// Whether tool calls are approved as configured, or every call is approved
+/// because read-only mode refuses the ones that could change anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Approvals {
+    AsConfigured,
+    ReadsOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    pub tools: bool,
+    pub thinking: ThinkingMode,
+    pub search_grounding: bool,
+    pub approvals: Approvals,
+    /// Appended to the system instruction.
+    pub instruction: &'static str,
+}
+
+/// The settings in place before the first `/mode`, which each mode starts from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    pub allowed_tools: Option<Vec<String>>,
+    pub auto_approve: Vec<String>,
+    pub read_only: bool,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 3] = [Mode::Chat, Mode::Agent, Mode::Research];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == s.trim().to_lowercase())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Chat => "chat",
+            Mode::Agent => "agent",
+            Mode::Research => "research",
+        }
+    }
+
+    pub fn preset(&self) -> Preset {
+        match self {
+            Mode::Chat => Preset {
+                tools: false,
+                thinking: ThinkingMode::Fixed(ThinkingLevel::Low),
+                search_grounding: false,
+                approvals: Approvals::AsConfigured,
+                instruction: "Answer conversationally and keep replies short unless asked for detail.",
+            },
+            Mode::Agent => Preset {
+                tools: true,
+                thinking: ThinkingMode::Auto,
+                search_grounding: false,
+                approvals: Approvals::AsConfigured,
+                instruction: "Carry out the task with your tools. Check each step's result before moving on, \
+                              and finish with a short summary of what changed.",
+            },
+            Mode::Research => Preset {
+                tools: true,
+                thinking: ThinkingMode::Fixed(ThinkingLevel::High),
+                search_grounding: true,
+                approvals: Approvals::ReadsOnly,
+                instruction: "Research the question thoroughly before answering. Compare sources, cite them, \
+                              and say where they disagree or the evidence is thin.",
+            },
+        }
+    }
+
+    /// One line describing what the mode switched, for the confirmation message.
+    pub fn summary(&self) -> String {
+        let preset = self.preset();
+        let tools = match (preset.tools, preset.approvals) {
+            (false, _) => "tools off",
+            (true, Approvals::AsConfigured) => "tools on",
+            (true, Approvals::ReadsOnly) => "read-only tools, auto-approved",
+        };
+        format!(
+            "Mode: {} ({}, thinking {}, search grounding {})",
+            self.as_str(),
+            tools,
+            preset.thinking.as_str(),
+            if preset.search_grounding { "on" } else { "off" }
+        )
+    }
+}
+
+impl Baseline {
+    /// The tools offered in `mode`, never more than the baseline allows.
+    pub fn allowed_tools(&self, mode: Mode) -> Option<Vec<String>> {
+        if mode.preset().tools {
+            self.allowed_tools.clone()
+        } else {
+            Some(Vec::new())
+        }
+    }
+
+    pub fn auto_approve(&self, mode: Mode) -> Vec<String> {
+        match mode.preset().approvals {
+            Approvals::AsConfigured => self.auto_approve.clone(),
+            Approvals::ReadsOnly => vec!["*".to_string()],
+        }
+    }
+
+    pub fn read_only(&self, mode: Mode) -> bool {
+        self.read_only || mode.preset().approvals == Approvals::ReadsOnly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_never_widen_the_baseline() {
+        assert_eq!(Mode::parse(" Research "), Some(Mode::Research));
+        assert_eq!(Mode::parse("expert"), None);
+
+        let untrusted = Baseline { allowed_tools: Some(Vec::new()), auto_approve: Vec::new(), read_only: false };
+        assert_eq!(untrusted.allowed_tools(Mode::Agent), Some(Vec::new()));
+
+        let baseline = Baseline { allowed_tools: None, auto_approve: vec!["read_file".to_string()], read_only: true };
+        assert_eq!(baseline.allowed_tools(Mode::Chat), Some(Vec::new()));
+        assert_eq!(baseline.allowed_tools(Mode::Agent), None);
+        assert_eq!(baseline.auto_approve(Mode::Agent), vec!["read_file".to_string()]);
+        assert!(baseline.read_only(Mode::Agent));
+        assert_eq!(baseline.auto_approve(Mode::Research), vec!["*".to_string()]);
+        assert!(Mode::Research.summary().contains("search grounding on"));
+    }
+}
@@ -0,0 +1,115 @@
+use regex::Regex;
+use serde_json::json;
+use std::sync::LazyLock;
+use crate::conductor::events::TurnContext;
+use crate::tools::truncate::OutputStore;
+
+/// Shrinking steps tried before giving up on a request that doesn't fit.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+static CONTEXT_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(input token count .*exceeds|exceeds the maximum number of tokens|context (length|window)|too many tokens|prompt is too long|request payload size exceeds|maximum context)",
+    )
+    .unwrap()
+});
+
+/// Whether an API error means the request didn't fit in the model's context
+/// window, as opposed to being malformed or failing for another reason.
+pub fn is_context_length_error(message: &str) -> bool {
+    CONTEXT_LENGTH.is_match(message)
+}
+
+/// Replaces the oldest half of the tool outputs still in the request with a
+/// note pointing at `read_tool_output`, which can page them back in.
+/// Returns the names of the tools whose output was dropped.
+pub fn drop_oldest_tool_outputs(context: &mut TurnContext, store: &OutputStore) -> Vec<String> {
+    let remaining: Vec<usize> = context.tool_results.iter()
+        .enumerate()
+        .filter(|(_, r)| r.result.get("dropped_output_id").is_none())
+        .map(|(i, _)| i)
+        .collect();
+    let mut dropped = Vec::new();
+    for &i in remaining.iter().take(remaining.len().div_ceil(2)) {
+        let res = &mut context.tool_results[i];
+        let id = store.put(serde_json::to_string_pretty(&res.result).unwrap_or_default());
+        res.result = json!({
+            "dropped_output_id": id,
+            "note": format!(
+                "This output was dropped because the request no longer fit in the context window. \
+                 Call read_tool_output with output_id={} to read parts of it.",
+                id
+            ),
+        });
+        dropped.push(res.name.clone());
+    }
+    dropped
+}
+
+/// Detaches the request from the server-side conversation, which is what
+/// overflowed. Tool results only make sense next to the call that asked
+/// for them, so they are inlined into the prompt along with the question
+/// being worked on.
+pub fn restart_without_history(context: &mut TurnContext, question: &str) {
+    context.previous_interaction_id = None;
+    let mut prompt = String::from(
+        "[The earlier conversation no longer fit in the context window and was dropped. \
+         Ask the user if you need something from it.]\n\n",
+    );
+    if context.tool_results.is_empty() {
+        prompt.push_str(&context.prompt);
+    } else {
+        prompt.push_str(&format!("The user asked:\n{}\n\nYou called tools; their results:", question));
+        for res in std::mem::take(&mut context.tool_results) {
+            prompt.push_str(&format!("\n\n<{}>\n{}\n</{}>", res.name, res.result, res.name));
+        }
+        if !context.prompt.is_empty() {
+            prompt.push_str(&format!("\n\n{}", context.prompt));
+        }
+    }
+    context.prompt = prompt;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::events::ToolResult;
+
+    #[test]
+    fn test_overflow_detection_and_shrinking() {
+        assert!(is_context_length_error("API Error: The input token count (1200000) exceeds the maximum number of tokens allowed (1048576). (code: 400 Bad Request)"));
+        assert!(!is_context_length_error("API Error: API key not valid (code: 400 Bad Request)"));
+
+        let result = |name: &str| ToolResult {
+            call_id: name.to_string(),
+            name: name.to_string(),
+            result: json!({ "stdout": format!("{} output", name) }),
+            is_error: false,
+        };
+        let mut context = TurnContext {
+            prompt: String::new(),
+            system_instruction: None,
+            response_schema: None,
+            previous_interaction_id: Some("id_1".to_string()),
+            tool_results: vec![result("a"), result("b"), result("c")],
+            thinking_level: None,
+            temperature: None,
+            model: None,
+            allowed_tools: None,
+            search_grounding: false,
+            attachments: Vec::new(),
+        };
+        let store = OutputStore::new();
+        assert_eq!(drop_oldest_tool_outputs(&mut context, &store), vec!["a", "b"]);
+        assert_eq!(drop_oldest_tool_outputs(&mut context, &store), vec!["c"]);
+        assert!(drop_oldest_tool_outputs(&mut context, &store).is_empty());
+        let id = context.tool_results[0].result["dropped_output_id"].as_str().unwrap().to_string();
+        assert!(store.read_lines(&id, 1, 10).unwrap().0.contains("a output"));
+
+        restart_without_history(&mut context, "check the disks");
+        assert_eq!(context.previous_interaction_id, None);
+        assert!(context.tool_results.is_empty());
+        assert!(context.prompt.contains("The user asked:\ncheck the disks"));
+        assert!(context.prompt.contains("<c>\n{\"dropped_output_id\""));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::conductor::events::UserEvent;
+
+/// Something the palette can run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// A slash command as listed in the help text, e.g. `/lang <code>`.
+    Command { usage: String, description: String },
+    QuickAction { name: String, description: String },
+    File(String),
+    /// A suggested follow-up prompt, sent as-is.
+    FollowUp(String),
+}
+
+impl Entry {
+    fn label(&self) -> String {
+        match self {
+            Entry::Command { usage, description } => format!("{:<26} {}", usage, description),
+            Entry::QuickAction { name, description } => format!("{:<26} {}", format!("/qa {}", name), description),
+            Entry::File(path) => format!("{:<26} Recent file", path),
+            Entry::FollowUp(prompt) => prompt.clone(),
+        }
+    }
+
+    fn search_text(&self) -> String {
+        match self {
+            Entry::Command { usage, description } => format!("{} {}", usage, description),
+            Entry::QuickAction { name, description } => format!("{} {}", name, description),
+            Entry::File(path) => path.clone(),
+            Entry::FollowUp(prompt) => prompt.clone(),
+        }
+    }
+
+    /// The input selecting this entry amounts to, or a usage hint for
+    /// commands that need an argument first.
+    pub fn action(&self) -> Result<UserEvent, String> {
+        match self {
+            Entry::Command { usage, description } if usage.contains('<') => {
+                Err(format!("Usage: {}  ({})", usage, description))
+            }
+            Entry::Command { usage, .. } => {
+                let name = usage.split([' ', ',']).next().unwrap_or_default();
+                Ok(UserEvent::Command(name.to_string()))
+            }
+            Entry::QuickAction { name, .. } => Ok(UserEvent::Command(format!("/qa {}", name))),
+            Entry::File(path) => Ok(UserEvent::Command(format!("/qa explain-file {}", path))),
+            Entry::FollowUp(prompt) => Ok(UserEvent::Message(prompt.clone())),
+        }
+    }
+}
+
+/// Slash commands parsed from the help text, so the palette lists exactly
+/// what `/help` documents.
+pub fn commands(help: &str) -> Vec<Entry> {
+    help.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .filter_map(|line| {
+            let split = line.find("  ")?;
+            Some(Entry::Command {
+                usage: line[..split].trim().to_string(),
+                description: line[split..].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Scores `candidate` against `query` as a case-insensitive subsequence;
+/// consecutive matches and matches at word starts score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + candidate[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous == Some(found.wrapping_sub(1)) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+    // Prefer shorter candidates among equal matches.
+    Some(score * 1000 - candidate.len() as i64)
+}
+
+/// The best `limit` entries for `query`; every entry, in order, for an empty query.
+pub fn search(entries: Vec<Entry>, query: &str, limit: usize) -> Vec<Entry> {
+    if query.trim().is_empty() {
+        return entries.into_iter().take(limit).collect();
+    }
+    let mut scored: Vec<(i64, Entry)> = entries.into_iter()
+        .filter_map(|e| fuzzy_score(query, &e.search_text()).map(|s| (s, e)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(limit).map(|(_, e)| e).collect()
+}
+
+pub fn listing(entries: &[Entry]) -> String {
+    if entries.is_empty() {
+        return "No matches.".to_string();
+    }
+    let mut out = String::from("Enter a number to run it:");
+    for (i, entry) in entries.iter().enumerate() {
+        out.push_str(&format!("\n  {:>2}. {}", i + 1, entry.label()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_parses_help_and_ranks_fuzzy_matches() {
+        let help = "Commands:\n  /help          Show this help\n  /clear         Clear the conversation context\n  /lang <code>   Respond in the given language\n  /exit, /quit   Exit Chitti\n";
+        let mut entries = commands(help);
+        assert_eq!(entries.len(), 4);
+        entries.push(Entry::QuickAction { name: "standup".into(), description: "Standup update from git log".into() });
+        entries.push(Entry::File("src/main.rs".into()));
+
+        let found = search(entries.clone(), "clr", 3);
+        assert!(matches!(&found[0], Entry::Command { usage, .. } if usage == "/clear"));
+        assert!(matches!(found[0].action(), Ok(UserEvent::Command(c)) if c == "/clear"));
+
+        let found = search(entries.clone(), "mainrs", 3);
+        assert_eq!(found, vec![Entry::File("src/main.rs".into())]);
+
+        let lang = search(entries.clone(), "lang", 1);
+        assert!(lang[0].action().unwrap_err().starts_with("Usage: /lang <code>"));
+        let exit = search(entries.clone(), "quit", 1);
+        assert!(matches!(exit[0].action(), Ok(UserEvent::Command(c)) if c == "/exit"));
+        assert!(search(entries, "zzz", 5).is_empty());
+    }
+}
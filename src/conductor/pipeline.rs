@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+static MENTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|\s)@([^\s@]+)").unwrap());
+
+/// Splits `/prompt ... | /tee out.md` into its stages. Only a `|` followed
+/// by another command separates stages, so pipes inside a prompt are kept.
+/// Returns `None` for a single command.
+pub fn parse(line: &str) -> Option<Vec<String>> {
+    let mut stages = Vec::new();
+    let mut start = 0;
+    for (i, _) in line.match_indices('|') {
+        if line[i + 1..].trim_start().starts_with('/') {
+            stages.push(line[start..i].trim().to_string());
+            start = i + 1;
+        }
+    }
+    if stages.is_empty() {
+        return None;
+    }
+    stages.push(line[start..].trim().to_string());
+    Some(stages)
+}
+
+/// Appends the contents of every `@path` that names a readable text file, and
+/// returns those paths. Binary files (images, PDFs, audio) can't be inlined,
+/// so they are returned separately to go with the message as attachments.
+/// Mentions of anything else (handles, missing files) are left as written.
+pub fn expand_mentions(text: &str) -> Result<(String, Vec<String>, Vec<PathBuf>)> {
+    let mut out = text.to_string();
+    let mut files = Vec::new();
+    let mut attachments = Vec::new();
+    for caps in MENTION.captures_iter(text) {
+        let mention = caps[1].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        let path = crate::tools::env::expand_home(Path::new(mention));
+        if !path.is_file() || files.iter().any(|f| f == mention) {
+            continue;
+        }
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("Failed to read @{}", mention))?;
+        match String::from_utf8(bytes) {
+            Ok(contents) => out.push_str(&format!("\n\n<file path=\"{}\">\n{}\n</file>", mention, contents.trim_end())),
+            Err(_) => attachments.push(path),
+        }
+        files.push(mention.to_string());
+    }
+    Ok((out, files, attachments))
+}
+
+/// The prompt for a `/prompt` stage that receives the previous stage's output.
+pub fn with_input(prompt: &str, input: &str) -> String {
+    format!("{}\n\n<input>\n{}\n</input>", prompt, input.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_parse_and_mentions() -> Result<()> {
+        assert_eq!(
+            parse("/prompt summarize @notes/today.md | /tee summary.md"),
+            Some(vec!["/prompt summarize @notes/today.md".to_string(), "/tee summary.md".to_string()])
+        );
+        assert_eq!(parse("/prompt explain `a | b` in bash"), None);
+        assert_eq!(parse("/qa review x|/translate ta | /tee t.md").map(|s| s.len()), Some(3));
+
+        let path = std::env::temp_dir().join(format!("chitti-mention-{}.md", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "- shipped the release\n")?;
+        let text = format!("summarize @{}. cc @someone", path.display());
+        let (expanded, files, attachments) = expand_mentions(&text)?;
+        assert_eq!(files, vec![path.display().to_string()]);
+        assert!(expanded.starts_with(&text));
+        assert!(expanded.ends_with("- shipped the release\n</file>"));
+        assert!(attachments.is_empty());
+        std::fs::remove_file(&path)?;
+
+        let image = std::env::temp_dir().join(format!("chitti-mention-{}.png", uuid::Uuid::new_v4()));
+        std::fs::write(&image, b"\x89PNG\r\n\x1a\n\0\0")?;
+        let text = format!("what is in @{}?", image.display());
+        let (expanded, files, attachments) = expand_mentions(&text)?;
+        assert_eq!(expanded, text);
+        assert_eq!(files.len(), 1);
+        assert_eq!(attachments, vec![image.clone()]);
+        std::fs::remove_file(&image)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::process::Command;
+
+/// A named prompt template run with `/qa <name> [input]`. The template may
+/// use these placeholders, filled in only when present:
+///
+/// - `{input}`: whatever follows the action name
+/// - `{file}`: contents of the file named by the input
+/// - `{clipboard}`: the system clipboard
+/// - `{git_log}`: the last day of commits in the current repository
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuickAction {
+    pub description: String,
+    pub prompt: String,
+}
+
+/// Built-in actions, then those from the user's file (which may replace them).
+pub fn load(path: Option<&Path>) -> Result<BTreeMap<String, QuickAction>> {
+    let mut actions = builtin();
+    if let Some(path) = path {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read quick actions file {}", path.display()))?;
+        let custom: BTreeMap<String, QuickAction> = serde_yaml::from_str(&text)
+            .with_context(|| format!("Invalid quick actions file {}", path.display()))?;
+        actions.extend(custom);
+    }
+    Ok(actions)
+}
+
+pub fn builtin() -> BTreeMap<String, QuickAction> {
+    [
+        ("summarize-clipboard", "Summarize clipboard", "Summarize the following text concisely:\n\n{clipboard}"),
+        ("standup", "Standup update from git log", "Write a short standup update (done, next, blockers) from these commits:\n\n{git_log}"),
+        ("explain-file", "Explain a file: /qa explain-file <path>", "Explain what this file ({input}) does and how it fits together:\n\n```\n{file}\n```"),
+    ]
+    .into_iter()
+    .map(|(name, description, prompt)| {
+        (name.to_string(), QuickAction { description: description.to_string(), prompt: prompt.to_string() })
+    })
+    .collect()
+}
+
+/// Numbered listing shown by `/qa` without arguments.
+pub fn listing(actions: &BTreeMap<String, QuickAction>) -> String {
+    let mut out = String::from("Quick actions (/qa <name|number> [input]):");
+    for (i, (name, action)) in actions.iter().enumerate() {
+        out.push_str(&format!("\n  {}. {:<20} {}", i + 1, name, action.description));
+    }
+    out
+}
+
+/// Looks an action up by name or 1-based position in the listing.
+pub fn find<'a>(actions: &'a BTreeMap<String, QuickAction>, key: &str) -> Option<&'a QuickAction> {
+    match key.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).and_then(|i| actions.values().nth(i)),
+        Err(_) => actions.get(key),
+    }
+}
+
+/// Fills in the template's placeholders.
+pub async fn expand(action: &QuickAction, input: &str) -> Result<String> {
+    let mut prompt = action.prompt.replace("{input}", input);
+    if prompt.contains("{file}") {
+        anyhow::ensure!(!input.is_empty(), "This action needs a file path");
+        let contents = tokio::fs::read_to_string(input).await
+            .with_context(|| format!("Failed to read {}", input))?;
+        prompt = prompt.replace("{file}", &contents);
+    }
+    if prompt.contains("{clipboard}") {
+        prompt = prompt.replace("{clipboard}", &clipboard().await?);
+    }
+    if prompt.contains("{git_log}") {
+        let log = run("git", &["log", "--since=1.day", "--no-merges", "--pretty=format:%h %s (%an)"]).await?;
+        prompt = prompt.replace("{git_log}", if log.trim().is_empty() { "(no commits in the last day)" } else { &log });
+    }
+    Ok(prompt)
+}
+
+async fn clipboard() -> Result<String> {
+    let candidates: &[(&str, &[&str])] = &[
+        ("pbpaste", &[]),
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("powershell", &["-NoProfile", "-Command", "Get-Clipboard"]),
+    ];
+    for (program, args) in candidates {
+        if let Ok(text) = run(program, args).await {
+            return Ok(text);
+        }
+    }
+    anyhow::bail!("Could not read the clipboard (tried pbpaste, wl-paste, xclip)")
+}
+
+async fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output().await?;
+    anyhow::ensure!(output.status.success(), "{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim());
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_custom_actions_expand_input_and_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("chitti-qa-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("actions.yaml");
+        std::fs::write(&file, "translate:\n  description: Translate\n  prompt: \"Translate to French: {input}\"\n")?;
+        let actions = load(Some(&file))?;
+        assert!(actions.contains_key("standup"));
+        assert!(listing(&actions).contains("translate"));
+
+        let translate = find(&actions, "translate").unwrap();
+        assert_eq!(expand(translate, "good morning").await?, "Translate to French: good morning");
+
+        let explain = find(&actions, "explain-file").unwrap();
+        let prompt = expand(explain, file.to_str().unwrap()).await?;
+        assert!(prompt.contains("prompt: \"Translate to French"));
+        assert!(expand(explain, "").await.is_err());
+        assert!(find(&actions, "99").is_none());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Lines of unchanged context shown around each hunk.
+const CONTEXT: usize = 2;
+
+/// Contents of files as they were before the turn's tools first wrote them;
+/// `None` records a file that didn't exist yet.
+#[derive(Debug, Default)]
+pub struct Snapshots(BTreeMap<String, Option<String>>);
+
+impl Snapshots {
+    /// Records `path` unless it was already captured this turn.
+    pub fn capture(&mut self, path: &str) {
+        self.0.entry(path.to_string()).or_insert_with(|| std::fs::read_to_string(path).ok());
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Captured files whose contents have changed since.
+    pub fn changes(&self) -> Vec<Change> {
+        self.0.iter()
+            .map(|(path, original)| Change {
+                path: path.clone(),
+                original: original.clone(),
+                current: std::fs::read_to_string(path).ok(),
+            })
+            .filter(|c| c.original != c.current)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub path: String,
+    pub original: Option<String>,
+    pub current: Option<String>,
+}
+
+impl Change {
+    /// Line-level hunks between the original and current contents.
+    pub fn hunks(&self) -> Vec<Hunk> {
+        hunks(self.original.as_deref().unwrap_or(""), self.current.as_deref().unwrap_or(""))
+    }
+
+    /// The file contents after applying `decisions`, or `None` when the file
+    /// didn't exist before and every hunk was reverted.
+    pub fn resolve(&self, decisions: &[Decision]) -> Option<String> {
+        let (old, new) = (self.original.as_deref().unwrap_or(""), self.current.as_deref().unwrap_or(""));
+        if self.original.is_none() && decisions.iter().all(|d| *d == Decision::Revert) {
+            return None;
+        }
+        Some(apply(old, new, &self.hunks(), decisions))
+    }
+}
+
+/// A run of changed lines: `old` lines in the original became `new` lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    Accept,
+    Revert,
+    /// Use these lines instead of the hunk's new side.
+    Replace(Vec<String>),
+}
+
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+pub fn hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let (old, new) = (lines(old), lines(new));
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        // Adjacent delete/insert ops form one hunk.
+        match hunks.last_mut() {
+            Some(last) if last.old.end == old_range.start && last.new.end == new_range.start => {
+                last.old.end = old_range.end;
+                last.new.end = new_range.end;
+            }
+            _ => hunks.push(Hunk { old: old_range, new: new_range }),
+        }
+    }
+    hunks
+}
+
+/// Rebuilds the file from the original, taking each hunk's new side only
+/// where it was accepted. Missing decisions count as accepted.
+pub fn apply(old: &str, new: &str, hunks: &[Hunk], decisions: &[Decision]) -> String {
+    let (old, new) = (lines(old), lines(new));
+    let mut out = String::new();
+    let mut pos = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        out.extend(old[pos..hunk.old.start].iter().copied());
+        match decisions.get(i).unwrap_or(&Decision::Accept) {
+            Decision::Accept => out.extend(new[hunk.new.clone()].iter().copied()),
+            Decision::Revert => out.extend(old[hunk.old.clone()].iter().copied()),
+            Decision::Replace(replacement) => {
+                for line in replacement {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+        pos = hunk.old.end;
+    }
+    out.extend(old[pos..].iter().copied());
+    out
+}
+
+/// A unified-diff style rendering of one hunk with a little context.
+pub fn render(old: &str, new: &str, hunk: &Hunk) -> String {
+    let (old, new) = (lines(old), lines(new));
+    let before = hunk.old.start.saturating_sub(CONTEXT);
+    let after = (hunk.old.end + CONTEXT).min(old.len());
+    let mut out = format!(
+        "@@ -{},{} +{},{} @@\n",
+        hunk.old.start + 1, hunk.old.len(), hunk.new.start + 1, hunk.new.len()
+    );
+    let mut push = |prefix: char, line: &str| {
+        out.push(prefix);
+        out.push_str(line.strip_suffix('\n').unwrap_or(line));
+        out.push('\n');
+    };
+    old[before..hunk.old.start].iter().for_each(|l| push(' ', l));
+    old[hunk.old.clone()].iter().for_each(|l| push('-', l));
+    new[hunk.new.clone()].iter().for_each(|l| push('+', l));
+    old[hunk.old.end..after].iter().for_each(|l| push(' ', l));
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_applies_only_accepted_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\n";
+        let hunks = hunks(old, new);
+        assert_eq!(hunks, vec![Hunk { old: 1..2, new: 1..2 }, Hunk { old: 7..7, new: 7..8 }]);
+        assert_eq!(render(old, new, &hunks[0]), "@@ -2,1 +2,1 @@\n a\n-b\n+B\n c\n d");
+
+        assert_eq!(apply(old, new, &hunks, &[Decision::Accept, Decision::Accept]), new);
+        assert_eq!(apply(old, new, &hunks, &[Decision::Revert, Decision::Revert]), old);
+        assert_eq!(apply(old, new, &hunks, &[Decision::Revert, Decision::Accept]), "a\nb\nc\nd\ne\nf\ng\nh\n");
+        assert_eq!(
+            apply(old, new, &hunks, &[Decision::Replace(vec!["beta".into()]), Decision::Revert]),
+            "a\nbeta\nc\nd\ne\nf\ng\n"
+        );
+
+        let created = Change { path: "new.txt".into(), original: None, current: Some("x\n".into()) };
+        assert_eq!(created.resolve(&[Decision::Revert]), None);
+        assert_eq!(created.resolve(&[Decision::Accept]), Some("x\n".to_string()));
+    }
+}
@@ -1,3 +1,236 @@
-// Session placeholder
-#[allow(dead_code)]
-pub struct Session {}
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::vault::{self, Vault};
+
+/// Characters of a prompt or match shown in `/sessions` and `/search`.
+const EXCERPT: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub prompt: String,
+    pub response: String,
+}
+
+/// What a session keeps in its `session.json`: its tags and completed
+/// exchanges, so later sessions can list and search it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionLog {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub exchanges: Vec<Exchange>,
+}
+
+/// The current session's log, saved in its artifacts directory (encrypted
+/// when the vault is on). Without a path it is only kept in memory.
+#[derive(Default)]
+pub struct Session {
+    path: Option<PathBuf>,
+    vault: Option<Arc<Vault>>,
+    log: SessionLog,
+}
+
+/// Reads a `session.json`, decrypting it with the vault if needed.
+fn read_log(path: &Path, vault: Option<&Vault>) -> Option<SessionLog> {
+    serde_json::from_slice(&vault::read(vault, path).ok()?).ok()
+}
+
+impl Session {
+    pub fn load(path: PathBuf, vault: Option<Arc<Vault>>) -> Self {
+        let log = read_log(&path, vault.as_deref()).unwrap_or_default();
+        Self { path: Some(path), vault, log }
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.log.tags
+    }
+
+    /// Adds `tag`; returns false if the session already had it.
+    pub fn tag(&mut self, tag: &str) -> Result<bool> {
+        if self.log.tags.iter().any(|t| t == tag) {
+            return Ok(false);
+        }
+        self.log.tags.push(tag.to_string());
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Removes `tag`; returns false if the session didn't have it.
+    pub fn untag(&mut self, tag: &str) -> Result<bool> {
+        let before = self.log.tags.len();
+        self.log.tags.retain(|t| t != tag);
+        if self.log.tags.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn record(&mut self, prompt: &str, response: &str) -> Result<()> {
+        self.log.exchanges.push(Exchange { prompt: prompt.to_string(), response: response.to_string() });
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        vault::write(self.vault.as_deref(), path, serde_json::to_string_pretty(&self.log)?.as_bytes())
+    }
+}
+
+/// Normalizes a tag as typed (`#Work` and `work` are the same tag).
+/// Returns `None` for an empty tag or one with spaces.
+pub fn parse_tag(arg: &str) -> Option<String> {
+    let tag = arg.trim().trim_start_matches('#').to_lowercase();
+    (!tag.is_empty() && !tag.contains(char::is_whitespace)).then_some(tag)
+}
+
+/// Every saved session under `root` with `tag` (all of them for `None`),
+/// newest first. Directory names start with the session's start time.
+pub fn list(root: &Path, tag: Option<&str>, vault: Option<&Vault>) -> Vec<(String, SessionLog)> {
+    let mut sessions: Vec<(String, SessionLog)> = std::fs::read_dir(root)
+        .map(|dir| dir.filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let log = read_log(&e.path().join("session.json"), vault)?;
+                Some((e.file_name().to_string_lossy().to_string(), log))
+            })
+            .filter(|(_, log)| tag.is_none_or(|tag| log.tags.iter().any(|t| t == tag)))
+            .collect())
+        .unwrap_or_default();
+    sessions.sort_by(|a, b| b.0.cmp(&a.0));
+    sessions
+}
+
+/// What `/sessions [tag]` shows: each session's tags, size and first prompt.
+pub fn listing(root: &Path, tag: Option<&str>, vault: Option<&Vault>) -> String {
+    let sessions = list(root, tag, vault);
+    if sessions.is_empty() {
+        return match tag {
+            Some(tag) => format!("No sessions tagged '{}'", tag),
+            None => "No saved sessions yet".to_string(),
+        };
+    }
+    let mut out = format!("Sessions{}:", tag.map(|t| format!(" tagged '{}'", t)).unwrap_or_default());
+    for (name, log) in sessions {
+        let tags: String = log.tags.iter().map(|t| format!(" #{}", t)).collect();
+        let first = log.exchanges.first().map(|e| excerpt(&e.prompt)).unwrap_or_default();
+        out.push_str(&format!("\n  {}{} ({} exchanges) {}", name, tags, log.exchanges.len(), first));
+    }
+    out
+}
+
+/// What `/search [#tag] <query>` shows: exchanges in saved sessions whose
+/// prompt or answer contains `query`, ignoring case.
+pub fn search(root: &Path, query: &str, tag: Option<&str>, vault: Option<&Vault>) -> String {
+    let needle = query.to_lowercase();
+    let mut hits = Vec::new();
+    for (name, log) in list(root, tag, vault) {
+        for (i, exchange) in log.exchanges.iter().enumerate() {
+            let hit = [&exchange.prompt, &exchange.response].into_iter()
+                .find_map(|text| {
+                    let at = text.to_lowercase().find(&needle)?;
+                    // Lowercasing can shift byte offsets; fall back to the start.
+                    let start = text.floor_char_boundary(at.min(text.len()).saturating_sub(EXCERPT / 4));
+                    Some(excerpt(&text[start..]))
+                });
+            if let Some(hit) = hit {
+                hits.push(format!("  {} #{}: {}", name, i + 1, hit));
+            }
+        }
+    }
+    if hits.is_empty() {
+        return format!("No matches for '{}'", query);
+    }
+    format!("{} match{} for '{}':\n{}", hits.len(), if hits.len() == 1 { "" } else { "es" }, query, hits.join("\n"))
+}
+
+fn excerpt(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > EXCERPT {
+        format!("{}…", line.chars().take(EXCERPT).collect::<String>())
+    } else {
+        line
+    }
+}
+
+/// Defaults applied when a session is tagged, from `[tags.<name>]` in
+/// `~/.chitti/config.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagDefaults {
+    pub model: Option<String>,
+    pub read_only: Option<bool>,
+    pub auto_approve: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    tags: BTreeMap<String, TagDefaults>,
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitti").join("config.toml"))
+}
+
+/// Reads the per-tag defaults; a missing file means none.
+pub fn load_tag_defaults(path: Option<&Path>) -> Result<BTreeMap<String, TagDefaults>> {
+    let Some(text) = path.and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return Ok(BTreeMap::new());
+    };
+    let file: ConfigFile = toml::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.unwrap_or(Path::new("")).display()))?;
+    Ok(file.tags.into_iter().filter_map(|(tag, defaults)| Some((parse_tag(&tag)?, defaults))).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_are_tagged_listed_and_searched() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("chitti-sessions-{}", uuid::Uuid::new_v4()));
+        let mut older = Session::load(root.join("1700000000-aaaa").join("session.json"), None);
+        older.tag("homelab")?;
+        older.record("Why is the NAS slow?", "The RAID is rebuilding.")?;
+        let mut newer = Session::load(root.join("1800000000-bbbb").join("session.json"), None);
+        assert!(newer.tag("work")?);
+        assert!(!newer.tag("work")?);
+        newer.record("Draft the release notes", "Here are the notes for the NAS sync fix.")?;
+
+        assert_eq!(parse_tag(" #Work "), Some("work".to_string()));
+        assert_eq!(parse_tag("two words"), None);
+        let all = listing(&root, None, None);
+        assert!(all.find("1800000000-bbbb #work").unwrap() < all.find("1700000000-aaaa #homelab").unwrap());
+        assert_eq!(list(&root, Some("homelab"), None).len(), 1);
+        assert_eq!(listing(&root, Some("garden"), None), "No sessions tagged 'garden'");
+
+        assert!(search(&root, "nas", None, None).starts_with("2 matches for 'nas':"));
+        let homelab = search(&root, "nas", Some("homelab"), None);
+        assert_eq!(homelab, "1 match for 'nas':\n  1700000000-aaaa #1: Why is the NAS slow?");
+
+        assert!(Session::load(root.join("1700000000-aaaa").join("session.json"), None).untag("homelab")?);
+        assert!(list(&root, Some("homelab"), None).is_empty());
+
+        let vault = Arc::new(Vault::from_key(&[7; 32]));
+        let path = root.join("1900000000-cccc").join("session.json");
+        Session::load(path.clone(), Some(vault.clone())).record("What is the wifi password?", "hunter2")?;
+        assert!(Vault::is_encrypted(&std::fs::read(&path)?));
+        assert_eq!(list(&root, None, None).len(), 2);
+        assert_eq!(list(&root, None, Some(&vault)).len(), 3);
+        assert_eq!(search(&root, "wifi", None, Some(&vault)), "1 match for 'wifi':\n  1900000000-cccc #1: What is the wifi password?");
+
+        let config = root.join("config.toml");
+        std::fs::write(&config, "[tags.Work]\nmodel = \"gemini-2.5-pro\"\nread_only = true\n\n[tags.homelab]\nauto_approve = [\"execute_bash\"]\n")?;
+        let defaults = load_tag_defaults(Some(&config))?;
+        assert_eq!(defaults["work"].model.as_deref(), Some("gemini-2.5-pro"));
+        assert_eq!(defaults["homelab"].auto_approve, Some(vec!["execute_bash".to_string()]));
+        assert!(load_tag_defaults(Some(&root.join("missing.toml")))?.is_empty());
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
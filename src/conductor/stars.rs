@@ -0,0 +1,142 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::vault::{self, Vault};
+
+/// Characters of a starred answer repeated in the system instruction when
+/// the star has no note of its own.
+const INSTRUCTION_EXCERPT: usize = 600;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Star {
+    pub prompt: String,
+    pub response: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Exchanges marked with `/star`, kept in the session's `starred.json` so
+/// they survive `/clear` and can be repeated to the model as prior decisions.
+#[derive(Default)]
+pub struct Stars {
+    path: Option<PathBuf>,
+    vault: Option<Arc<Vault>>,
+    items: Vec<Star>,
+    pub in_context: bool,
+}
+
+impl Stars {
+    pub fn load(path: Option<PathBuf>, vault: Option<Arc<Vault>>) -> Self {
+        let items = path.as_ref()
+            .and_then(|p| vault::read(vault.as_deref(), p).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        Self { path, vault, items, in_context: false }
+    }
+
+    /// Stars an exchange. Returns its 1-based number.
+    pub fn add(&mut self, star: Star) -> Result<usize> {
+        self.items.push(star);
+        self.save()?;
+        Ok(self.items.len())
+    }
+
+    /// Removes star `n` (1-based). Returns it if it existed.
+    pub fn remove(&mut self, n: usize) -> Result<Option<Star>> {
+        if n == 0 || n > self.items.len() {
+            return Ok(None);
+        }
+        let star = self.items.remove(n - 1);
+        self.save()?;
+        Ok(Some(star))
+    }
+
+    pub fn listing(&self) -> String {
+        if self.items.is_empty() {
+            return "Nothing starred yet; /star [note] marks the last answer".to_string();
+        }
+        let mut out = format!(
+            "Starred ({}):",
+            if self.in_context { "repeated to the model" } else { "not sent to the model, /starred context on to send" }
+        );
+        for (i, star) in self.items.iter().enumerate() {
+            out.push_str(&format!("\n  {}. {}", i + 1, first_line(&star.prompt, 60)));
+            if let Some(note) = &star.note {
+                out.push_str(&format!(" [{}]", note));
+            }
+            out.push_str(&format!("\n     {}", first_line(&star.response, 100)));
+        }
+        out
+    }
+
+    /// The starred decisions as a system instruction section, when enabled.
+    pub fn instruction(&self) -> Option<String> {
+        if !self.in_context || self.items.is_empty() {
+            return None;
+        }
+        let mut out = String::from(
+            "Important prior decisions the user starred earlier in this session. \
+             Keep to them unless the user explicitly changes them:",
+        );
+        for star in &self.items {
+            let decision = match &star.note {
+                Some(note) => note.clone(),
+                None => excerpt(&star.response, INSTRUCTION_EXCERPT),
+            };
+            out.push_str(&format!("\n- Asked: {}\n  Decided: {}", excerpt(&star.prompt, 200), decision));
+        }
+        Some(out)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        vault::write(self.vault.as_deref(), path, serde_json::to_string_pretty(&self.items)?.as_bytes())
+    }
+}
+
+fn first_line(text: &str, max: usize) -> String {
+    excerpt(text.trim().lines().next().unwrap_or(""), max)
+}
+
+fn excerpt(text: &str, max: usize) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stars_persist_and_feed_the_instruction() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("chitti-stars-{}", uuid::Uuid::new_v4())).join("starred.json");
+        let mut stars = Stars::load(Some(path.clone()), None);
+        let star = |prompt: &str, response: &str, note: Option<&str>| Star {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+            note: note.map(str::to_string),
+        };
+        assert_eq!(stars.add(star("Which database?", "Use Postgres 16.", None))?, 1);
+        assert_eq!(stars.add(star("Tabs or spaces?", "Spaces, four of them.", Some("4-space indent")))?, 2);
+        assert!(stars.instruction().is_none());
+
+        let mut stars = Stars::load(Some(path.clone()), None);
+        stars.in_context = true;
+        let instruction = stars.instruction().unwrap();
+        assert!(instruction.contains("Asked: Which database?\n  Decided: Use Postgres 16."));
+        assert!(instruction.contains("Decided: 4-space indent"));
+        assert!(stars.listing().contains("2. Tabs or spaces? [4-space indent]"));
+
+        assert_eq!(stars.remove(1)?.map(|s| s.prompt).as_deref(), Some("Which database?"));
+        assert!(stars.remove(5)?.is_none());
+        assert_eq!(Stars::load(Some(path.clone()), None).items.len(), 1);
+        std::fs::remove_dir_all(path.parent().unwrap())?;
+        Ok(())
+    }
+}
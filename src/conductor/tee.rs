@@ -0,0 +1,65 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// Mirrors streamed model output (and optionally tool results) to a file.
+pub struct Tee {
+    path: PathBuf,
+    file: File,
+    include_tools: bool,
+}
+
+impl Tee {
+    /// Opens `path` for appending, creating it if needed.
+    pub async fn open(path: impl AsRef<Path>, include_tools: bool) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path).await?;
+        Ok(Self { path, file, include_tools })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn write_text(&mut self, text: &str) -> Result<()> {
+        self.file.write_all(text.as_bytes()).await?;
+        self.file.flush().await?;
+        Ok(())
+    }
+
+    /// Writes a tool result as a fenced JSON block when tool mirroring is on.
+    pub async fn write_tool_result(&mut self, name: &str, output: &Value) -> Result<()> {
+        if !self.include_tools {
+            return Ok(());
+        }
+        let pretty = serde_json::to_string_pretty(output)?;
+        self.write_text(&format!("\n```json tool:{}\n{}\n```\n", name, pretty)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tee_appends_text_and_tool_results() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("chitti-tee-{}.md", uuid::Uuid::new_v4()));
+        let mut tee = Tee::open(&path, true).await?;
+        tee.write_text("Hello ").await?;
+        tee.write_text("world").await?;
+        tee.write_tool_result("execute_bash", &json!({ "stdout": "ok" })).await?;
+
+        let mut quiet = Tee::open(&path, false).await?;
+        quiet.write_tool_result("execute_bash", &json!({ "stdout": "skipped" })).await?;
+
+        let written = tokio::fs::read_to_string(&path).await?;
+        tokio::fs::remove_file(&path).await?;
+        assert!(written.starts_with("Hello world\n```json tool:execute_bash\n"));
+        assert!(written.contains("\"stdout\": \"ok\""));
+        assert!(!written.contains("skipped"));
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use regex::Regex;
+use std::sync::LazyLock;
+use crate::brains::gemini::types::ThinkingLevel;
+
+/// Asks for careful reasoning, whatever the prompt's length.
+static REASONING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(why|prove|derive|step by step|analy[sz]e|debug|root cause|optimi[sz]e|design|architect\w*|trade-?offs?|compare|refactor|implement|algorithm|complexity|edge cases?)\b").unwrap()
+});
+
+/// Source code, stack traces or compiler output in the prompt.
+static CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)```|^\s*(fn|def|class|impl|func|function|import|#include|SELECT)\b|;\s*$|\{\s*$|Traceback \(most recent|error\[E\d+\]|at [\w.$]+\(").unwrap()
+});
+
+static QUESTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\s*(what|who|when|where|which|is|are|does|do|can|how much|how many)\b").unwrap()
+});
+
+/// How each turn's thinking level is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThinkingMode {
+    /// Leave it to the model.
+    #[default]
+    Default,
+    Fixed(ThinkingLevel),
+    /// Classify every prompt and pick Minimal, Low or High.
+    Auto,
+}
+
+impl ThinkingMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s.trim().to_lowercase().as_str() {
+            "" | "default" | "off" => ThinkingMode::Default,
+            "auto" => ThinkingMode::Auto,
+            "minimal" => ThinkingMode::Fixed(ThinkingLevel::Minimal),
+            "low" => ThinkingMode::Fixed(ThinkingLevel::Low),
+            "medium" => ThinkingMode::Fixed(ThinkingLevel::Medium),
+            "high" => ThinkingMode::Fixed(ThinkingLevel::High),
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThinkingMode::Default => "default",
+            ThinkingMode::Auto => "auto",
+            ThinkingMode::Fixed(level) => level.as_str(),
+        }
+    }
+}
+
+/// Picks a level from the prompt alone: short lookups and chit-chat get
+/// Minimal, code and explicit reasoning requests get High.
+pub fn classify(prompt: &str) -> ThinkingLevel {
+    let words = prompt.split_whitespace().count();
+    let code = CODE.is_match(prompt);
+    let reasoning = REASONING.is_match(prompt);
+    if !code && !reasoning && words <= 12 {
+        return ThinkingLevel::Minimal;
+    }
+    let mut score = 0;
+    if code {
+        score += 2;
+    }
+    if reasoning {
+        score += 2;
+    }
+    if words > 150 {
+        score += 2;
+    } else if words > 40 {
+        score += 1;
+    }
+    // Factual questions rarely need long deliberation; tasks do.
+    if QUESTION.is_match(prompt) && !reasoning {
+        score -= 1;
+    }
+    match score {
+        s if s >= 3 => ThinkingLevel::High,
+        s if s >= 1 => ThinkingLevel::Low,
+        _ => ThinkingLevel::Minimal,
+    }
+}
+
+/// Whether the prompt contains source code, a stack trace or compiler output.
+pub fn looks_like_code(prompt: &str) -> bool {
+    CODE.is_match(prompt)
+}
+
+/// The prompt for the optional cheap classifier model.
+pub fn classifier_prompt(prompt: &str) -> String {
+    format!(
+        "Decide how much reasoning a capable assistant needs to answer the message below well. \
+         Reply with exactly one word: minimal (chit-chat, simple facts), low (routine tasks, short \
+         explanations) or high (code, debugging, multi-step reasoning, design).\n\n<message>\n{}\n</message>",
+        prompt
+    )
+}
+
+/// Reads the classifier's one-word answer.
+pub fn parse_answer(answer: &str) -> Option<ThinkingLevel> {
+    match answer.trim().trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase().as_str() {
+        "minimal" => Some(ThinkingLevel::Minimal),
+        "low" => Some(ThinkingLevel::Low),
+        "medium" => Some(ThinkingLevel::Medium),
+        "high" => Some(ThinkingLevel::High),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_prompts() {
+        assert_eq!(classify("hi there!"), ThinkingLevel::Minimal);
+        assert_eq!(classify("What is the capital of France?"), ThinkingLevel::Minimal);
+        assert_eq!(classify("Why does this panic?\n```rust\nlet v: Vec<u8> = vec![];\nv[0];\n```"), ThinkingLevel::High);
+        assert_eq!(classify("Please write a short, friendly note to my team saying the meeting tomorrow moves to 3pm and the agenda stays the same."), ThinkingLevel::Minimal);
+        assert_eq!(classify("Design a rate limiter for our public API that is fair between tenants"), ThinkingLevel::Low);
+        assert_eq!(ThinkingMode::parse("AUTO"), Some(ThinkingMode::Auto));
+        assert_eq!(ThinkingMode::parse("high"), Some(ThinkingMode::Fixed(ThinkingLevel::High)));
+        assert_eq!(ThinkingMode::parse("max"), None);
+        assert_eq!(parse_answer(" High.\n"), Some(ThinkingLevel::High));
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Where the wall clock of one turn went. A turn with tool calls makes
+/// several model requests; their build and streaming times are summed, and
+/// time to first token is taken from the first.
+#[derive(Debug, Clone, Default)]
+pub struct TurnTimings {
+    /// Picking the thinking level, assembling the context and checking its size.
+    pub request_build: Duration,
+    pub first_token: Option<Duration>,
+    pub streaming: Duration,
+    pub tools: Vec<(String, Duration)>,
+    pub total: Duration,
+    pub requests: u32,
+}
+
+impl TurnTimings {
+    /// Records one model request: how long until its first event, and how
+    /// long it streamed after that.
+    pub fn record_request(&mut self, first_event: Option<Duration>, streaming: Duration) {
+        self.requests += 1;
+        if self.first_token.is_none() {
+            self.first_token = first_event;
+        }
+        self.streaming += streaming;
+    }
+
+    pub fn tool_time(&self) -> Duration {
+        self.tools.iter().map(|(_, d)| *d).sum()
+    }
+
+    /// One line for the dev-mode event.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "build {} · first token {} · streaming {} · tools {}",
+            fmt(self.request_build),
+            self.first_token.map(fmt).unwrap_or_else(|| "-".to_string()),
+            fmt(self.streaming),
+            fmt(self.tool_time()),
+        );
+        if !self.tools.is_empty() {
+            let each: Vec<String> = self.tools.iter().map(|(name, d)| format!("{} {}", name, fmt(*d))).collect();
+            out.push_str(&format!(" ({})", each.join(", ")));
+        }
+        out.push_str(&format!(
+            " · total {} · {} request{}",
+            fmt(self.total),
+            self.requests,
+            if self.requests == 1 { "" } else { "s" }
+        ));
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Total {
+    count: u32,
+    sum: Duration,
+}
+
+impl Total {
+    fn add(&mut self, d: Duration) {
+        self.count += 1;
+        self.sum += d;
+    }
+
+    fn mean(&self) -> Duration {
+        if self.count == 0 { Duration::ZERO } else { self.sum / self.count }
+    }
+}
+
+/// Turn timings aggregated over the session, for `/stats timings`.
+#[derive(Debug, Default)]
+pub struct TimingStats {
+    turns: u32,
+    request_build: Total,
+    first_token: Total,
+    streaming: Total,
+    tool_time: Total,
+    total: Total,
+    tools: BTreeMap<String, Total>,
+}
+
+impl TimingStats {
+    pub fn record(&mut self, turn: &TurnTimings) {
+        self.turns += 1;
+        self.request_build.add(turn.request_build);
+        if let Some(first) = turn.first_token {
+            self.first_token.add(first);
+        }
+        self.streaming.add(turn.streaming);
+        self.tool_time.add(turn.tool_time());
+        self.total.add(turn.total);
+        for (name, d) in &turn.tools {
+            self.tools.entry(name.clone()).or_default().add(*d);
+        }
+    }
+
+    pub fn table(&self) -> String {
+        if self.turns == 0 {
+            return "No turns yet.".to_string();
+        }
+        let mut out = format!("Mean per turn over {} turn{}:", self.turns, if self.turns == 1 { "" } else { "s" });
+        for (label, total) in [
+            ("request build", self.request_build),
+            ("first token", self.first_token),
+            ("streaming", self.streaming),
+            ("tools", self.tool_time),
+            ("total", self.total),
+        ] {
+            out.push_str(&format!("\n  {:<14} {:>9}", label, fmt(total.mean())));
+        }
+        if !self.tools.is_empty() {
+            let width = self.tools.keys().map(|n| n.len()).max().unwrap_or(0).max(4);
+            out.push_str(&format!("\nTool execution:\n  {:width$}  {:>5}  {:>9}  {:>9}", "tool", "calls", "mean", "total", width = width));
+            for (name, total) in &self.tools {
+                out.push_str(&format!(
+                    "\n  {:width$}  {:>5}  {:>9}  {:>9}",
+                    name, total.count, fmt(total.mean()), fmt(total.sum),
+                    width = width
+                ));
+            }
+        }
+        out
+    }
+}
+
+fn fmt(d: Duration) -> String {
+    if d < Duration::from_secs(1) {
+        format!("{} ms", d.as_millis())
+    } else {
+        format!("{:.1} s", d.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_timings_summary_and_aggregate() {
+        let ms = Duration::from_millis;
+        let mut turn = TurnTimings { request_build: ms(12), ..Default::default() };
+        turn.record_request(Some(ms(840)), ms(300));
+        turn.tools.push(("execute_bash".to_string(), ms(3100)));
+        turn.record_request(Some(ms(500)), ms(1800));
+        turn.total = ms(6400);
+        assert_eq!(
+            turn.summary(),
+            "build 12 ms · first token 840 ms · streaming 2.1 s · tools 3.1 s (execute_bash 3.1 s) · total 6.4 s · 2 requests"
+        );
+
+        let mut stats = TimingStats::default();
+        assert_eq!(stats.table(), "No turns yet.");
+        stats.record(&turn);
+        stats.record(&TurnTimings { total: ms(400), requests: 1, ..Default::default() });
+        let table = stats.table();
+        assert!(table.starts_with("Mean per turn over 2 turns:"));
+        assert!(table.contains("first token       840 ms"));
+        assert!(table.contains("total              3.4 s"));
+        assert!(table.contains("execute_bash      1      3.1 s      3.1 s"));
+    }
+}
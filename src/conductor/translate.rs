@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Spans the model must not touch: fenced code blocks, inline code, URLs and
+/// template placeholders (`{name}`, `{{name}}`, `${VAR}`, `$VAR`, `%s`, `%1$d`).
+static PROTECTED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)```.*?```|`[^`\n]+`|https?://[^\s)>\]]*[^\s)>\].,;:!?'"]|\{\{[^{}\n]+\}\}|\{[A-Za-z0-9_.]+\}|\$\{[A-Za-z_][A-Za-z0-9_]*\}|\$[A-Z_][A-Z0-9_]*|%(?:\d+\$)?[sdfi@]"#).unwrap()
+});
+
+/// User-maintained terminology, by target language code. Terms under `"*"`
+/// apply to every language; mapping a term to itself keeps it untranslated.
+///
+/// ```yaml
+/// "*":
+///   Chitti: Chitti
+/// ta:
+///   pull request: இழு கோரிக்கை
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Glossary(BTreeMap<String, BTreeMap<String, String>>);
+
+impl Glossary {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read glossary {}", path.display()))?;
+        let terms = serde_yaml::from_str(&text)
+            .with_context(|| format!("Invalid glossary {}", path.display()))?;
+        Ok(Self(terms))
+    }
+
+    /// Terms for `lang`; language-specific entries override `"*"`.
+    pub fn terms(&self, lang: &str) -> BTreeMap<String, String> {
+        let mut terms = self.0.get("*").cloned().unwrap_or_default();
+        if let Some(specific) = self.0.get(&lang.to_lowercase()) {
+            terms.extend(specific.clone());
+        }
+        terms
+    }
+}
+
+/// Replaces protected spans with numbered markers the model copies through.
+pub fn protect(text: &str) -> (String, Vec<String>) {
+    let mut spans = Vec::new();
+    let masked = PROTECTED.replace_all(text, |caps: &regex::Captures| {
+        spans.push(caps[0].to_string());
+        marker(spans.len() - 1)
+    });
+    (masked.into_owned(), spans)
+}
+
+fn marker(i: usize) -> String {
+    format!("⟦{}⟧", i)
+}
+
+/// Puts the protected spans back. Returns the indexes of markers the model
+/// dropped, whose spans are then missing from the translation.
+pub fn restore(translated: &str, spans: &[String]) -> (String, Vec<usize>) {
+    let mut out = translated.to_string();
+    let mut missing = Vec::new();
+    // Highest first, so ⟦1⟧ never matches inside ⟦10⟧.
+    for (i, span) in spans.iter().enumerate().rev() {
+        let m = marker(i);
+        if out.contains(&m) {
+            out = out.replace(&m, span);
+        } else {
+            missing.push(i);
+        }
+    }
+    missing.reverse();
+    (out, missing)
+}
+
+/// The system instruction for a translation into `language`.
+pub fn instruction(language: &str, terms: &BTreeMap<String, String>) -> String {
+    let mut out = format!(
+        "You are a translator. Translate the user's text into {}. Reply with the translation only, \
+         keeping the original formatting (markdown, line breaks, lists). Markers like ⟦0⟧ stand for \
+         code or placeholders: copy every marker exactly once, unchanged, in the right place.",
+        language
+    );
+    if !terms.is_empty() {
+        out.push_str("\n\nAlways use this glossary (source term => required translation):");
+        for (term, translation) in terms {
+            out.push_str(&format!("\n- {} => {}", term, translation));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_protects_code_and_placeholders() -> Result<()> {
+        let text = "Run `cargo test` for {name} at https://x.test/docs, then:\n```sh\necho $HOME\n```\nHello %s, ${USER} and {{count}}.";
+        let (masked, spans) = protect(text);
+        assert_eq!(masked, "Run ⟦0⟧ for ⟦1⟧ at ⟦2⟧, then:\n⟦3⟧\nHello ⟦4⟧, ⟦5⟧ and ⟦6⟧.");
+
+        let translated = "Führe ⟦0⟧ für ⟦1⟧ auf ⟦2⟧ aus, dann:\n⟦3⟧\nHallo ⟦4⟧, ⟦5⟧ und ⟦6⟧.";
+        let (restored, missing) = restore(translated, &spans);
+        assert!(missing.is_empty());
+        assert!(restored.starts_with("Führe `cargo test` für {name} auf https://x.test/docs aus"));
+        assert!(restored.contains("```sh\necho $HOME\n```"));
+        assert_eq!(restore("Hallo ⟦0⟧", &spans).1, vec![1, 2, 3, 4, 5, 6]);
+
+        let glossary: Glossary = Glossary(serde_yaml::from_str("'*': { Chitti: Chitti, build: build }\nde: { build: Build }")?);
+        let terms = glossary.terms("DE");
+        assert_eq!(terms.get("build").map(String::as_str), Some("Build"));
+        assert!(instruction("German", &terms).contains("- Chitti => Chitti"));
+        Ok(())
+    }
+}
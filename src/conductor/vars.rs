@@ -0,0 +1,100 @@
+use anyhow::Result;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, RwLock};
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z_][\w-]*)\s*\}\}").unwrap());
+
+/// `/set key=value` variables for the session. They are substituted into
+/// prompts and quick-action templates as `{{key}}`, listed in the system
+/// instruction, and exported to execute_bash as `$CHITTI_VAR_<KEY>`.
+#[derive(Clone, Default)]
+pub struct SessionVars(Arc<RwLock<BTreeMap<String, String>>>);
+
+impl SessionVars {
+    /// Parses and stores `key=value`; an empty value removes the key.
+    pub fn assign(&self, assignment: &str) -> Result<()> {
+        let (key, value) = assignment.split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Usage: /set key=value"))?;
+        let key = key.trim();
+        if !PLACEHOLDER.is_match(&format!("{{{{{}}}}}", key)) {
+            anyhow::bail!("Invalid variable name '{}': use letters, digits, '_' and '-'", key);
+        }
+        let value = value.trim();
+        let mut vars = self.0.write().unwrap();
+        if value.is_empty() {
+            vars.remove(key);
+        } else {
+            vars.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    /// Replaces `{{key}}` with its value, leaving unknown keys as they are.
+    pub fn substitute(&self, text: &str) -> String {
+        let vars = self.0.read().unwrap();
+        if vars.is_empty() {
+            return text.to_string();
+        }
+        PLACEHOLDER.replace_all(text, |caps: &regex::Captures| {
+            vars.get(&caps[1]).cloned().unwrap_or_else(|| caps[0].to_string())
+        }).into_owned()
+    }
+
+    pub fn listing(&self) -> String {
+        let vars = self.0.read().unwrap();
+        if vars.is_empty() {
+            return "No session variables; set one with /set key=value".to_string();
+        }
+        let mut out = String::from("Session variables:");
+        for (key, value) in vars.iter() {
+            out.push_str(&format!("\n  {} = {}", key, value));
+        }
+        out
+    }
+
+    /// Tells the model which values the user is working with.
+    pub fn instruction(&self) -> Option<String> {
+        let vars = self.0.read().unwrap();
+        if vars.is_empty() {
+            return None;
+        }
+        let mut out = String::from(
+            "The user set these session variables; use them where relevant, including in tool calls \
+             (execute_bash also has them as $CHITTI_VAR_<NAME>):",
+        );
+        for (key, value) in vars.iter() {
+            out.push_str(&format!("\n- {} = {}", key, value));
+        }
+        Some(out)
+    }
+
+    pub fn env(&self) -> HashMap<String, String> {
+        self.0.read().unwrap().iter()
+            .map(|(key, value)| (format!("CHITTI_VAR_{}", key.to_uppercase().replace('-', "_")), value.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_vars_substitute_and_export() -> Result<()> {
+        let vars = SessionVars::default();
+        vars.assign("ticket = JIRA-42")?;
+        vars.assign("server-name=db01.internal")?;
+        assert_eq!(
+            vars.substitute("Update {{ticket}} on {{server-name}}, not {{other}}"),
+            "Update JIRA-42 on db01.internal, not {{other}}"
+        );
+        assert_eq!(vars.env().get("CHITTI_VAR_SERVER_NAME").map(String::as_str), Some("db01.internal"));
+        assert!(vars.instruction().unwrap().contains("- ticket = JIRA-42"));
+        assert!(vars.assign("no value").is_err());
+        assert!(vars.assign("bad key=1").is_err());
+        vars.assign("ticket=")?;
+        assert_eq!(vars.substitute("{{ticket}}"), "{{ticket}}");
+        Ok(())
+    }
+}
@@ -1,23 +1,507 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use crate::turn_log::TurnLog;
+
+/// The OpenAI-compatible backend, chosen with `CHITTI_BRAIN=openai`.
+#[derive(Clone, Debug)]
+pub struct OpenAiConfig {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+}
+
+/// A local Ollama server, chosen with `CHITTI_BRAIN=ollama`.
+#[derive(Clone, Debug)]
+pub struct OllamaConfig {
+    pub base_url: String,
+    pub model: String,
+    /// Send tools natively instead of describing them in the system prompt;
+    /// only models with tool support in their template accept them.
+    pub native_tools: bool,
+}
+
+/// Gemini through Vertex AI, chosen with `GOOGLE_GENAI_USE_VERTEXAI=true`.
+/// Authenticates with Application Default Credentials instead of an API key.
+#[derive(Clone, Debug)]
+pub struct VertexConfig {
+    pub project: String,
+    pub location: String,
+}
+
+/// Which engine answers chat turns. Gemini-only features (files, batch)
+/// are disabled with the others.
+#[derive(Clone, Debug)]
+pub enum Backend {
+    Gemini,
+    OpenAi(OpenAiConfig),
+    Ollama(OllamaConfig),
+}
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub gemini_api_key: String,
+    /// Every configured key, `gemini_api_key` first; the client moves to the
+    /// next on quota errors.
+    pub gemini_api_keys: Vec<String>,
     pub gemini_model: String,
+    pub backend: Backend,
+    /// Answers turns the primary backend can't, on quota and server errors.
+    pub fallback: Option<Backend>,
+    pub vertex: Option<VertexConfig>,
+    pub language: Option<String>,
+    pub dev_mode: bool,
+    pub redact_patterns: Vec<String>,
+    pub secret_patterns: Vec<String>,
+    pub secret_redact: bool,
+    pub privacy_filter: bool,
+    pub privacy_names: Vec<String>,
+    pub privacy_patterns: Vec<String>,
+    pub injection_classifier: bool,
+    pub max_tool_result_bytes: usize,
+    pub tool_cache: bool,
+    pub notify_after_secs: Option<u64>,
+    pub notify_bell: bool,
+    pub persona_file: Option<PathBuf>,
+    pub preview_requests: bool,
+    pub response_cache: bool,
+    pub response_cache_ttl_secs: u64,
+    pub turn_deadline_secs: Option<u64>,
+    pub fast_draft: bool,
+    pub starred_context: bool,
+    pub knowledge_graph: bool,
+    pub follow_ups: bool,
+    pub trust_prompt: bool,
+    pub error_hints: bool,
+    pub confirm_request_tokens: Option<u64>,
+    pub thinking: crate::conductor::thinking::ThinkingMode,
+    pub thinking_classifier_model: Option<String>,
+    pub auto_approve_tools: Vec<String>,
+    pub bridge_tools: HashMap<String, Vec<String>>,
+    pub quick_actions_file: Option<PathBuf>,
+    pub glossary_file: Option<PathBuf>,
+    pub remote: Option<crate::tools::remote::Remote>,
+    pub tool_env_file: Option<PathBuf>,
+    pub plugin_dir: Option<PathBuf>,
+    pub openapi_file: Option<PathBuf>,
+    pub vault: bool,
+    pub secrets_lookup: bool,
+    pub retention_days: Option<u64>,
+    pub purge_on_clear: bool,
+    pub files_gc_hours: u64,
+    pub turn_log: Option<TurnLog>,
+    pub kube_contexts: Vec<String>,
+    pub kube_namespaces: Vec<String>,
+    pub archive_roots: Vec<PathBuf>,
+    pub feeds: Vec<String>,
+    pub browser: Option<PathBuf>,
+    pub browser_profile: Option<PathBuf>,
+    pub digest_at: String,
+    pub digest_to: String,
+    /// App-level token (`xapp-`) that opens the Socket Mode connection for `chitti slack`.
+    pub slack_app_token: Option<String>,
+    /// Bot token (`xoxb-`) the Slack bridge posts replies with.
+    pub slack_bot_token: Option<String>,
+    /// Slack user ids allowed to talk to the bot; empty allows no one.
+    pub slack_users: Vec<String>,
+    /// YAML allow-list with per-user policy for networked bridges (see
+    /// `bridges::identity::Identities`); replaces `slack_users` when set.
+    pub users_file: Option<PathBuf>,
+}
+
+/// Reads the backend named by `var` (`CHITTI_BRAIN`, `CHITTI_FALLBACK_BRAIN`)
+/// with its settings; `None` when the variable is unset or empty.
+fn backend_from_env(var: &str) -> Result<Option<Backend>> {
+    let name = env::var(var).unwrap_or_default().trim().to_lowercase();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    backend_named(&name, var).map(Some)
+}
+
+/// The backend called `name` with its settings from the environment; `var`
+/// is the setting that named it, for error messages.
+pub fn backend_named(name: &str, var: &str) -> Result<Backend> {
+    let backend = match name {
+        "gemini" => Backend::Gemini,
+        "openai" => Backend::OpenAi(OpenAiConfig {
+            api_key: env::var("OPENAI_API_KEY")
+                .with_context(|| format!("OPENAI_API_KEY must be set when {}=openai", var))?,
+            base_url: env::var("OPENAI_BASE_URL")
+                .ok()
+                .filter(|u| !u.trim().is_empty())
+                .unwrap_or_else(|| crate::brains::openai::DEFAULT_BASE_URL.to_string()),
+            model: env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+        }),
+        "ollama" => Backend::Ollama(OllamaConfig {
+            base_url: env::var("OLLAMA_BASE_URL")
+                .ok()
+                .filter(|u| !u.trim().is_empty())
+                .unwrap_or_else(|| crate::brains::ollama::DEFAULT_BASE_URL.to_string()),
+            model: env::var("OLLAMA_MODEL").unwrap_or_else(|_| "llama3.1".to_string()),
+            native_tools: env::var("OLLAMA_NATIVE_TOOLS")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+        }),
+        other => anyhow::bail!("Unknown {} '{}': use gemini, openai or ollama", var, other),
+    };
+    Ok(backend)
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Gemini => "gemini",
+            Backend::OpenAi(_) => "openai",
+            Backend::Ollama(_) => "ollama",
+        }
+    }
+}
+
+fn vertex_enabled() -> bool {
+    env::var("GOOGLE_GENAI_USE_VERTEXAI")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Keys from `GEMINI_API_KEY` and `GEMINI_API_KEYS`; without either, the
+/// key the setup wizard saved in the OS keychain.
+fn api_keys_from_env() -> Vec<String> {
+    let mut api_keys: Vec<String> = env::var("GEMINI_API_KEY").into_iter()
+        .chain(env::var("GEMINI_API_KEYS").ok())
+        .flat_map(|v| v.split(',').map(|k| k.trim().to_string()).collect::<Vec<_>>())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if api_keys.is_empty() {
+        return crate::vault::stored_api_key().into_iter().collect();
+    }
+    let mut seen = std::collections::HashSet::new();
+    api_keys.retain(|k| seen.insert(k.clone()));
+    api_keys
+}
+
+/// Whether the main or fallback brain calls the Gemini API with a key;
+/// Vertex AI authenticates with OAuth instead.
+fn needs_key(backend: &Backend, fallback: Option<&Backend>, vertex: bool) -> bool {
+    !vertex && [Some(backend), fallback].into_iter().any(|b| matches!(b, Some(Backend::Gemini)))
+}
+
+/// Whether `Config::from_env` would fail for want of a Gemini API key, which
+/// is when the first-run setup wizard is worth offering.
+pub fn missing_api_key() -> bool {
+    let (Ok(backend), Ok(fallback)) = (backend_from_env("CHITTI_BRAIN"), backend_from_env("CHITTI_FALLBACK_BRAIN")) else {
+        // A bad backend name is reported by `from_env`, not fixed by the wizard.
+        return false;
+    };
+    needs_key(&backend.unwrap_or(Backend::Gemini), fallback.as_ref(), vertex_enabled()) && api_keys_from_env().is_empty()
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
-        let api_key = env::var("GEMINI_API_KEY")
-            .context("GEMINI_API_KEY must be set in .env or environment")?;
+        let backend = backend_from_env("CHITTI_BRAIN")?.unwrap_or(Backend::Gemini);
+        let fallback = backend_from_env("CHITTI_FALLBACK_BRAIN")?;
+
+        // Gemini-only features (files, batch) still read the key when it is set.
+        let vertex = if vertex_enabled() {
+            Some(VertexConfig {
+                project: env::var("GOOGLE_CLOUD_PROJECT")
+                    .context("GOOGLE_CLOUD_PROJECT must be set when GOOGLE_GENAI_USE_VERTEXAI is on")?,
+                location: env::var("GOOGLE_CLOUD_LOCATION")
+                    .ok()
+                    .filter(|l| !l.trim().is_empty())
+                    .unwrap_or_else(|| "global".to_string()),
+            })
+        } else {
+            None
+        };
+
+        let api_keys = api_keys_from_env();
+        let needs_key = needs_key(&backend, fallback.as_ref(), vertex.is_some());
+        let api_key = match api_keys.first() {
+            Some(key) => key.clone(),
+            None if needs_key => anyhow::bail!("GEMINI_API_KEY must be set in .env or environment"),
+            None => String::new(),
+        };
         
         let model = env::var("GEMINI_MODEL")
             .unwrap_or_else(|_| "gemini-1.5-flash".to_string());
 
+        let language = env::var("CHITTI_LANGUAGE")
+            .ok()
+            .filter(|l| !l.trim().is_empty());
+
+        let dev_mode = env::var("CHITTI_DEV_MODE")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        // Regexes contain commas, so these are separated by whitespace.
+        let redact_patterns = env::var("CHITTI_REDACT_PATTERNS")
+            .map(|v| v.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        let secret_patterns = env::var("CHITTI_SECRET_PATTERNS")
+            .map(|v| v.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let secret_redact = env::var("CHITTI_SECRET_REDACT")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let privacy_filter = env::var("CHITTI_PRIVACY_FILTER")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let privacy_names = env::var("CHITTI_PRIVACY_NAMES")
+            .map(|v| v.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect())
+            .unwrap_or_default();
+
+        let privacy_patterns = env::var("CHITTI_PRIVACY_PATTERNS")
+            .map(|v| v.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let injection_classifier = env::var("CHITTI_INJECTION_CLASSIFIER")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let max_tool_result_bytes = env::var("CHITTI_MAX_TOOL_RESULT_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(crate::tools::truncate::DEFAULT_MAX_TOOL_RESULT_BYTES);
+
+        let tool_cache = env::var("CHITTI_TOOL_CACHE")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let notify_after_secs = env::var("CHITTI_NOTIFY_AFTER_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0);
+
+        let notify_bell = env::var("CHITTI_NOTIFY_BELL")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let persona_file = env::var("CHITTI_PERSONA_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let preview_requests = env::var("CHITTI_PREVIEW_REQUESTS")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let response_cache = env::var("CHITTI_RESPONSE_CACHE")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let response_cache_ttl_secs = env::var("CHITTI_RESPONSE_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(crate::brains::cache::DEFAULT_TTL.as_secs());
+
+        let turn_deadline_secs = env::var("CHITTI_TURN_DEADLINE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0);
+
+        let fast_draft = env::var("CHITTI_FAST_DRAFT")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let follow_ups = env::var("CHITTI_FOLLOW_UPS")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let starred_context = env::var("CHITTI_STARRED_CONTEXT")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let knowledge_graph = env::var("CHITTI_KNOWLEDGE_GRAPH")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let confirm_request_tokens = env::var("CHITTI_CONFIRM_REQUEST_TOKENS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|&t: &u64| t > 0);
+
+        let trust_prompt = env::var("CHITTI_TRUST_PROMPT")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+            .unwrap_or(true);
+
+        let error_hints = env::var("CHITTI_ERROR_HINTS")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let thinking = env::var("CHITTI_THINKING_LEVEL")
+            .ok()
+            .and_then(|v| crate::conductor::thinking::ThinkingMode::parse(&v))
+            .unwrap_or_default();
+
+        let thinking_classifier_model = env::var("CHITTI_THINKING_CLASSIFIER_MODEL")
+            .ok()
+            .filter(|m| !m.trim().is_empty());
+
+        let auto_approve_tools = env::var("CHITTI_AUTO_APPROVE_TOOLS")
+            .map(|v| v.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default();
+
+        // e.g. "telegram=read_file,web_fetch;headless=read_file"
+        let bridge_tools = env::var("CHITTI_BRIDGE_TOOLS")
+            .map(|v| {
+                v.split(';')
+                    .filter_map(|entry| entry.split_once('='))
+                    .map(|(bridge, tools)| {
+                        let tools = tools.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+                        (bridge.trim().to_string(), tools)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let quick_actions_file = env::var("CHITTI_QUICK_ACTIONS_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let glossary_file = env::var("CHITTI_GLOSSARY_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let remote = env::var("CHITTI_REMOTE_HOST")
+            .ok()
+            .filter(|h| !h.trim().is_empty())
+            .map(|host| crate::tools::remote::Remote {
+                host: host.trim().to_string(),
+                user: env::var("CHITTI_REMOTE_USER").ok().filter(|u| !u.trim().is_empty()),
+                key: env::var("CHITTI_REMOTE_KEY").ok().filter(|k| !k.trim().is_empty()).map(PathBuf::from),
+                port: env::var("CHITTI_REMOTE_PORT").ok().and_then(|p| p.parse().ok()),
+            });
+
+        let tool_env_file = env::var("CHITTI_TOOL_ENV_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let plugin_dir = match env::var("CHITTI_PLUGIN_DIR") {
+            Ok(dir) if dir.trim().is_empty() => None,
+            Ok(dir) => Some(PathBuf::from(dir)),
+            Err(_) => crate::tools::plugin::default_dir(),
+        };
+
+        let openapi_file = env::var("CHITTI_OPENAPI_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let vault = env::var("CHITTI_VAULT")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let secrets_lookup = env::var("CHITTI_SECRETS_LOOKUP")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let retention_days = env::var("CHITTI_RETENTION_DAYS")
+            .ok()
+            .and_then(|d| d.trim().parse().ok())
+            .filter(|&d: &u64| d > 0);
+
+        let purge_on_clear = env::var("CHITTI_PURGE_ON_CLEAR")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let files_gc_hours = env::var("CHITTI_FILES_GC_HOURS")
+            .ok()
+            .and_then(|h| h.trim().parse().ok())
+            .unwrap_or(24);
+
+        let list = |name: &str| -> Vec<String> {
+            env::var(name)
+                .map(|v| v.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+                .unwrap_or_default()
+        };
+        let kube_contexts = list("CHITTI_KUBE_CONTEXTS");
+        let kube_namespaces = list("CHITTI_KUBE_NAMESPACES");
+        let archive_roots = list("CHITTI_ARCHIVE_DIRS").into_iter().map(PathBuf::from).collect();
+        let feeds = list("CHITTI_FEEDS");
+        let browser = env::var("CHITTI_BROWSER").ok().filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+        let browser_profile = env::var("CHITTI_BROWSER_PROFILE").ok().filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+        let digest_at = env::var("CHITTI_DIGEST_AT").ok().filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "07:30".to_string());
+        let digest_to = env::var("CHITTI_DIGEST_TO").unwrap_or_default();
+        let slack_app_token = env::var("SLACK_APP_TOKEN").ok().filter(|t| !t.trim().is_empty());
+        let slack_bot_token = env::var("SLACK_BOT_TOKEN").ok().filter(|t| !t.trim().is_empty());
+        let slack_users = env::var("CHITTI_SLACK_USERS")
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let users_file = env::var("CHITTI_USERS_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let turn_log = env::var("CHITTI_TURN_LOG").ok().and_then(|t| TurnLog::parse(&t));
+
         Ok(Self {
             gemini_api_key: api_key,
+            gemini_api_keys: api_keys,
             gemini_model: model,
+            backend,
+            fallback,
+            vertex,
+            language,
+            dev_mode,
+            redact_patterns,
+            secret_patterns,
+            secret_redact,
+            privacy_filter,
+            privacy_names,
+            privacy_patterns,
+            injection_classifier,
+            max_tool_result_bytes,
+            tool_cache,
+            notify_after_secs,
+            notify_bell,
+            persona_file,
+            preview_requests,
+            response_cache,
+            response_cache_ttl_secs,
+            turn_deadline_secs,
+            fast_draft,
+            starred_context,
+            knowledge_graph,
+            follow_ups,
+            trust_prompt,
+            error_hints,
+            confirm_request_tokens,
+            thinking,
+            thinking_classifier_model,
+            auto_approve_tools,
+            bridge_tools,
+            quick_actions_file,
+            glossary_file,
+            remote,
+            tool_env_file,
+            plugin_dir,
+            openapi_file,
+            vault,
+            secrets_lookup,
+            retention_days,
+            purge_on_clear,
+            files_gc_hours,
+            turn_log,
+            kube_contexts,
+            kube_namespaces,
+            archive_roots,
+            feeds,
+            browser,
+            browser_profile,
+            digest_at,
+            digest_to,
+            slack_app_token,
+            slack_bot_token,
+            slack_users,
+            users_file,
         })
     }
 }
@@ -41,7 +525,12 @@ mod tests {
         assert_eq!(config.gemini_api_key, "test-key");
         assert_eq!(config.gemini_model, "test-model");
         
+        env::set_var("GEMINI_API_KEYS", "second-key, test-key,third-key");
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.gemini_api_keys, vec!["test-key", "second-key", "third-key"]);
+
         env::remove_var("GEMINI_API_KEY");
+        env::remove_var("GEMINI_API_KEYS");
         env::remove_var("GEMINI_MODEL");
     }
 
@@ -54,5 +543,14 @@ mod tests {
         let result = Config::from_env();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("GEMINI_API_KEY must be set"));
+        assert!(missing_api_key());
+
+        env::set_var("CHITTI_BRAIN", "ollama");
+        assert!(!missing_api_key());
+        assert!(Config::from_env().is_ok());
+        env::set_var("CHITTI_FALLBACK_BRAIN", "gemini");
+        assert!(missing_api_key());
+        env::remove_var("CHITTI_FALLBACK_BRAIN");
+        env::remove_var("CHITTI_BRAIN");
     }
 }
\ No newline at end of file
@@ -0,0 +1,219 @@
+//! Turn-level evaluation harness: run a YAML suite of prompts against a brain
+//! and check each response against simple assertions.
+
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::brains::BrainEngine;
+use crate::conductor::events::{BrainEvent, TurnContext};
+
+#[derive(Debug, Deserialize)]
+pub struct EvalSuite {
+    /// Model to evaluate; defaults to the configured model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Tools offered to the model. Calls are recorded, never executed.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub system_instruction: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvalCase {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default, rename = "assert")]
+    pub assertions: Vec<Assertion>,
+}
+
+/// One check, written in YAML as a single-key map (e.g. `- regex: "..."`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Assertion {
+    /// Response text matches the regex.
+    Regex { regex: String },
+    /// Response text does not match the regex.
+    NotRegex { not_regex: String },
+    /// Response text parses as JSON and validates against the schema.
+    JsonSchema { json_schema: Value },
+    /// The model called this tool at least once.
+    CallsTool { calls_tool: String },
+    /// The model made no tool calls (`- no_tool_calls: true`).
+    NoToolCalls { no_tool_calls: bool },
+}
+
+/// What the brain produced for one case.
+#[derive(Debug, Default)]
+pub struct TurnOutput {
+    pub text: String,
+    pub tool_calls: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct CaseResult {
+    pub name: String,
+    pub failures: Vec<String>,
+    pub elapsed_ms: u128,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+pub fn load_suite(text: &str) -> Result<EvalSuite> {
+    serde_yaml::from_str(text).context("Failed to parse eval suite")
+}
+
+/// Runs every case as a fresh, context-free turn.
+pub async fn run_suite(brain: &dyn BrainEngine, suite: &EvalSuite) -> Result<Vec<CaseResult>> {
+    let mut results = Vec::new();
+    for case in &suite.cases {
+        let started = std::time::Instant::now();
+        let output = run_turn(brain, &case.prompt, suite.system_instruction.clone()).await;
+        let failures = match output {
+            Ok(output) => check(&output, &case.assertions),
+            Err(e) => vec![format!("turn failed: {:#}", e)],
+        };
+        results.push(CaseResult {
+            name: case.name.clone(),
+            failures,
+            elapsed_ms: started.elapsed().as_millis(),
+        });
+    }
+    Ok(results)
+}
+
+async fn run_turn(brain: &dyn BrainEngine, prompt: &str, system_instruction: Option<String>) -> Result<TurnOutput> {
+    let context = TurnContext {
+        prompt: prompt.to_string(),
+        system_instruction,
+        response_schema: None,
+        previous_interaction_id: None,
+        tool_results: Vec::new(),
+        thinking_level: None,
+        temperature: None,
+        model: None,
+        allowed_tools: None,
+        search_grounding: false,
+        attachments: Vec::new(),
+    };
+    let mut stream = brain.process_turn(context).await?;
+    let mut output = TurnOutput::default();
+    while let Some(evt) = stream.next().await {
+        match evt? {
+            BrainEvent::TextDelta(text) => output.text.push_str(&text),
+            BrainEvent::ToolCall { name, .. } => output.tool_calls.push(name),
+            BrainEvent::Error(err) => output.errors.push(err),
+            _ => {}
+        }
+    }
+    Ok(output)
+}
+
+/// Returns a description of every assertion the output fails.
+pub fn check(output: &TurnOutput, assertions: &[Assertion]) -> Vec<String> {
+    let mut failures: Vec<String> = output.errors.iter().map(|e| format!("brain error: {}", e)).collect();
+    for assertion in assertions {
+        let failure = match assertion {
+            Assertion::Regex { regex: pattern } => match regex::Regex::new(pattern) {
+                Ok(re) if re.is_match(&output.text) => None,
+                Ok(_) => Some(format!("response does not match /{}/", pattern)),
+                Err(e) => Some(format!("invalid regex /{}/: {}", pattern, e)),
+            },
+            Assertion::NotRegex { not_regex: pattern } => match regex::Regex::new(pattern) {
+                Ok(re) if !re.is_match(&output.text) => None,
+                Ok(_) => Some(format!("response unexpectedly matches /{}/", pattern)),
+                Err(e) => Some(format!("invalid regex /{}/: {}", pattern, e)),
+            },
+            Assertion::JsonSchema { json_schema: schema } => check_json(&output.text, schema),
+            Assertion::CallsTool { calls_tool: name } => {
+                if output.tool_calls.iter().any(|c| c == name) {
+                    None
+                } else {
+                    Some(format!("expected a call to '{}', got {:?}", name, output.tool_calls))
+                }
+            }
+            Assertion::NoToolCalls { no_tool_calls } => {
+                if !no_tool_calls || output.tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(format!("expected no tool calls, got {:?}", output.tool_calls))
+                }
+            }
+        };
+        failures.extend(failure);
+    }
+    failures
+}
+
+fn check_json(text: &str, schema: &Value) -> Option<String> {
+    // Models often wrap JSON in a fenced block; accept that.
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    let value: Value = match serde_json::from_str(body.trim()) {
+        Ok(v) => v,
+        Err(e) => return Some(format!("response is not JSON: {}", e)),
+    };
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(v) => v,
+        Err(e) => return Some(format!("invalid schema: {}", e)),
+    };
+    let errors: Vec<String> = validator.iter_errors(&value).map(|e| e.to_string()).collect();
+    if errors.is_empty() {
+        None
+    } else {
+        Some(format!("schema violations: {}", errors.join("; ")))
+    }
+}
+
+/// Formats a plain-text summary report.
+pub fn report(results: &[CaseResult]) -> String {
+    let mut out = String::new();
+    for r in results {
+        let mark = if r.passed() { "PASS" } else { "FAIL" };
+        out.push_str(&format!("{} {} ({} ms)\n", mark, r.name, r.elapsed_ms));
+        for f in &r.failures {
+            out.push_str(&format!("     - {}\n", f));
+        }
+    }
+    let passed = results.iter().filter(|r| r.passed()).count();
+    out.push_str(&format!("\n{}/{} cases passed\n", passed, results.len()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_assertions() {
+        let suite = load_suite(r#"
+cases:
+  - name: json
+    prompt: "give json"
+    assert:
+      - regex: "(?i)count"
+      - json_schema: { "type": "object", "required": ["count"] }
+      - calls_tool: execute_bash
+      - not_regex: "sorry"
+"#).unwrap();
+        let output = TurnOutput {
+            text: "```json\n{\"count\": 3}\n```".to_string(),
+            tool_calls: vec![],
+            errors: vec![],
+        };
+        let failures = check(&output, &suite.cases[0].assertions);
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].contains("execute_bash"));
+    }
+}
@@ -0,0 +1,113 @@
+/// Built-in user-facing strings that bridges and the Conductor emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Help,
+    ApprovalRequired,
+    ConfirmPrompt,
+    CallingTool,
+    Error,
+    ContextCleared,
+    SteeringNoted,
+    InputQueued,
+    TurnCancelled,
+    ExitWhileBusy,
+    ExitWhileRefining,
+    LanguageSet,
+    LanguageReset,
+    InjectionWarning,
+    Warning,
+}
+
+/// Returns the human readable name for a language code, falling back to the code itself.
+pub fn language_name(code: &str) -> &str {
+    match code.to_lowercase().as_str() {
+        "en" => "English",
+        "ta" => "Tamil",
+        "hi" => "Hindi",
+        "te" => "Telugu",
+        "kn" => "Kannada",
+        "ml" => "Malayalam",
+        "fr" => "French",
+        "de" => "German",
+        "es" => "Spanish",
+        "ja" => "Japanese",
+        "zh" => "Chinese",
+        _ => code,
+    }
+}
+
+/// Builds the instruction sent to the model so it answers in the chosen language.
+pub fn response_instruction(code: &str) -> String {
+    format!(
+        "Always respond in {} ({}), regardless of the language the user writes in. Keep code, commands and file paths unchanged.",
+        language_name(code),
+        code
+    )
+}
+
+/// Looks up a built-in string for the given language code.
+/// Unknown languages and missing entries fall back to English.
+pub fn tr(lang: &str, msg: Msg) -> &'static str {
+    match lang.to_lowercase().as_str() {
+        "ta" => tamil(msg),
+        _ => english(msg),
+    }
+}
+
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Help => "Commands:\n  /help          Show this help\n  /clear         Clear the conversation context\n  /lang <code>   Respond in the given language (e.g. /lang ta), /lang off to reset\n  /schema <file> Ask for JSON output matching a schema, /schema off to disable\n  /tee <path>    Mirror model output to a file (--tools adds tool results), /tee off to stop\n  /save-code [n] <path>  Save the nth code block of the last answer to a file\n  /reload        Re-read .env and the persona file\n  /best-of <n> <prompt>  Generate n answers and keep the best, /best-of show [i] to see them\n  /compare <a> <b> <prompt>  Run a prompt against two models side by side\n  /qa [name] [input]  List quick actions or run one\n  /prompt <text>  Send a message, inlining @path files; chain commands with |, e.g. /prompt summarize @notes.md | /tee summary.md\n  /set [key=value]  Set a session variable used as {{key}} in prompts and quick actions (key= removes it)\n  /translate <lang> [file]  Translate the last answer or a file, keeping code and placeholders\n  /think [level|auto]  Set the thinking level (minimal, low, medium, high), auto to pick per prompt\n  /draft [on|off]  Answer fast with minimal thinking, then refine in the background\n  /palette [query]  Fuzzy-find commands, quick actions and recent files (/p)\n  /thoughts      Show or hide the pane with the model's live thought summary\n  /steer <text>  Add guidance to the turn in flight\n  /cancel        Cancel the turn in flight (input sent meanwhile is queued)\n  /panic         Stop everything (also Ctrl-\\): cancel the turn, kill tool processes, switch to read-only\n  /readonly [on|off]  Refuse tool calls that may modify the system\n  /mode chat|agent|research  Switch tools, thinking, search grounding, approvals and prompt style together\n  /trust list|remove <prefix>  Show or remove bash commands learned to run without approval\n  /stats tools   Show tool call counts, failure and rejection rates\n  /stats bridge  Show delivered, coalesced and dropped bridge events\n  /stats timings  Show mean request build, first-token, streaming, tool and total time per turn\n  /files [list|rm <name>|gc [--hours N]]  Manage uploaded files\n  /keys          Show the configured API keys, which is active and their usage\n  /star [note]   Star the last answer, /unstar <n> to remove it\n  /starred [context on|off]  List starred answers, or repeat them to the model as prior decisions\n  /tag [name]    Tag this session and apply the tag's defaults from ~/.chitti/config.toml, /untag <name> to remove\n  /sessions [tag]  List saved sessions, optionally only those with a tag\n  /search [#tag] <text>  Search earlier sessions' prompts and answers\n  /artifacts     List files generated this session\n  /review        Keep or revert the last turn's file edits hunk by hunk\n  /exit, /quit   Exit Chitti (send twice to stop a reply in progress)\nAnswer y/n when a tool asks for approval.",
+        Msg::ApprovalRequired => "Approval required",
+        Msg::ConfirmPrompt => "Confirm? (y/n): ",
+        Msg::CallingTool => "Chitti calling tool",
+        Msg::Error => "Error",
+        Msg::ContextCleared => "Context cleared.",
+        Msg::SteeringNoted => "[Steering noted. Waiting for tool approval/rejection...]",
+        Msg::InputQueued => "Queued until the current turn finishes; pending inputs",
+        Msg::TurnCancelled => "Turn cancelled.",
+        Msg::ExitWhileBusy => "A reply is still in progress. Send /exit again to stop it and quit (the partial answer is saved), or keep waiting.",
+        Msg::ExitWhileRefining => "The draft is still being refined in the background. Send /exit again to quit without the refined answer.",
+        Msg::LanguageSet => "Response language set to",
+        Msg::LanguageReset => "Response language reset to the model default.",
+        Msg::InjectionWarning => "Output of this tool may contain instructions aimed at the assistant",
+        Msg::Warning => "Warning",
+    }
+}
+
+fn tamil(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Help => "கட்டளைகள்:\n  /help          இந்த உதவியைக் காட்டு\n  /clear         உரையாடல் சூழலை அழி\n  /lang <code>   குறிப்பிட்ட மொழியில் பதிலளி (எ.கா. /lang ta), /lang off மீட்டமைக்க\n  /schema <file> JSON schema-க்கு ஏற்ற பதில், /schema off முடக்க\n  /tee <path>    மாதிரி வெளியீட்டை கோப்பிலும் எழுது (--tools கருவி முடிவுகளையும் சேர்க்கும்), /tee off நிறுத்த\n  /save-code [n] <path>  கடைசி பதிலின் n-ஆவது குறியீட்டுத் தொகுதியை கோப்பில் சேமி\n  /reload        .env மற்றும் persona கோப்பை மீண்டும் படி\n  /best-of <n> <prompt>  n பதில்களை உருவாக்கி சிறந்ததைத் தேர்ந்தெடு, /best-of show [i] அவற்றைப் பார்க்க\n  /compare <a> <b> <prompt>  ஒரு கேள்வியை இரண்டு மாதிரிகளில் அருகருகே ஓட்டு\n  /qa [name] [input]  விரைவுச் செயல்களைப் பட்டியலிடு அல்லது ஒன்றை இயக்கு\n  /prompt <text>  @path கோப்புகளைச் சேர்த்து செய்தி அனுப்பு; | மூலம் கட்டளைகளை இணை, எ.கா. /prompt summarize @notes.md | /tee summary.md\n  /set [key=value]  கேள்விகளிலும் விரைவுச் செயல்களிலும் {{key}} ஆகப் பயன்படும் அமர்வு மாறியை அமை (key= நீக்க)\n  /translate <lang> [file]  கடைசி பதிலை அல்லது கோப்பை மொழிபெயர் (குறியீடு, placeholder-கள் மாறாது)\n  /think [level|auto]  சிந்தனை அளவை அமை (minimal, low, medium, high), auto ஒவ்வொரு கேள்விக்கும் தானே தேர்ந்தெடுக்க\n  /draft [on|off]  குறைந்த சிந்தனையுடன் விரைவாகப் பதிலளித்து, பின்னணியில் செம்மைப்படுத்து\n  /palette [query]  கட்டளைகள், விரைவுச் செயல்கள், சமீபத்திய கோப்புகளைத் தேடு (/p)\n  /thoughts      மாதிரியின் நேரடி சிந்தனைச் சுருக்கப் பலகத்தைக் காட்டு அல்லது மறை\n  /steer <text>  நடப்பு சுற்றுக்கு வழிகாட்டலைச் சேர்\n  /cancel        நடப்பு சுற்றை ரத்து செய் (இடையில் அனுப்பியவை வரிசையில் காத்திருக்கும்)\n  /panic         அனைத்தையும் நிறுத்து (Ctrl-\\ உம்): சுற்றை ரத்து செய், கருவி செயல்முறைகளை அழி, படிக்க-மட்டும் நிலைக்கு மாறு\n  /readonly [on|off]  அமைப்பை மாற்றக்கூடிய கருவி அழைப்புகளை மறு\n  /mode chat|agent|research  கருவிகள், சிந்தனை அளவு, தேடல் அடிப்படை, ஒப்புதல்கள், பதில் பாணியை ஒருசேர மாற்று\n  /trust list|remove <prefix>  ஒப்புதலின்றி இயங்கக் கற்ற bash கட்டளைகளைக் காட்டு அல்லது நீக்கு\n  /stats tools   கருவி அழைப்பு எண்ணிக்கை, தோல்வி, நிராகரிப்பு விகிதங்களைக் காட்டு\n  /stats bridge  அனுப்பப்பட்ட, இணைக்கப்பட்ட, கைவிடப்பட்ட நிகழ்வுகளைக் காட்டு\n  /stats timings  ஒவ்வொரு சுற்றின் கோரிக்கை உருவாக்கம், முதல் டோக்கன், ஓட்டம், கருவி, மொத்த சராசரி நேரங்களைக் காட்டு\n  /files [list|rm <name>|gc [--hours N]]  பதிவேற்றிய கோப்புகளை நிர்வகி\n  /keys          அமைத்த API விசைகள், எது செயலில் உள்ளது, அவற்றின் பயன்பாட்டைக் காட்டு\n  /star [note]   கடைசி பதிலை நட்சத்திரமிடு, /unstar <n> நீக்க\n  /starred [context on|off]  நட்சத்திரமிட்ட பதில்களைப் பட்டியலிடு, அல்லது அவற்றை முந்தைய முடிவுகளாக மாதிரிக்கு நினைவூட்டு\n  /tag [name]    இந்த அமர்வுக்குக் குறிச்சொல் இட்டு, ~/.chitti/config.toml இல் உள்ள அதன் இயல்புநிலைகளைப் பயன்படுத்து, /untag <name> நீக்க\n  /sessions [tag]  சேமித்த அமர்வுகளைப் பட்டியலிடு, விரும்பினால் ஒரு குறிச்சொல் உள்ளவை மட்டும்\n  /search [#tag] <text>  முந்தைய அமர்வுகளின் கேள்விகளிலும் பதில்களிலும் தேடு\n  /artifacts     இந்த அமர்வில் உருவான கோப்புகளைப் பட்டியலிடு\n  /review        கடைசி சுற்றின் கோப்பு மாற்றங்களை ஒவ்வொன்றாக வைத்திரு அல்லது திரும்பப் பெறு\n  /exit, /quit   சிட்டியிலிருந்து வெளியேறு (பதில் வந்துகொண்டிருக்கும்போது இருமுறை அனுப்பவும்)\nகருவி ஒப்புதல் கேட்கும்போது y/n என பதிலளிக்கவும்.",
+        Msg::ApprovalRequired => "ஒப்புதல் தேவை",
+        Msg::ConfirmPrompt => "உறுதிப்படுத்தவா? (y/n): ",
+        Msg::CallingTool => "சிட்டி கருவியை அழைக்கிறது",
+        Msg::Error => "பிழை",
+        Msg::ContextCleared => "சூழல் அழிக்கப்பட்டது.",
+        Msg::SteeringNoted => "[வழிகாட்டல் குறிக்கப்பட்டது. கருவி ஒப்புதல்/நிராகரிப்புக்காக காத்திருக்கிறது...]",
+        Msg::InputQueued => "தற்போதைய சுற்று முடியும் வரை வரிசையில் வைக்கப்பட்டது; நிலுவையில் உள்ளவை",
+        Msg::TurnCancelled => "சுற்று ரத்து செய்யப்பட்டது.",
+        Msg::ExitWhileBusy => "பதில் இன்னும் வந்துகொண்டிருக்கிறது. அதை நிறுத்தி வெளியேற மீண்டும் /exit அனுப்பவும் (பகுதி பதில் சேமிக்கப்படும்), அல்லது காத்திருக்கவும்.",
+        Msg::ExitWhileRefining => "வரைவு இன்னும் பின்னணியில் மேம்படுத்தப்படுகிறது. மேம்படுத்திய பதில் இல்லாமல் வெளியேற மீண்டும் /exit அனுப்பவும்.",
+        Msg::LanguageSet => "பதில் மொழி அமைக்கப்பட்டது",
+        Msg::LanguageReset => "பதில் மொழி இயல்புநிலைக்கு மீட்டமைக்கப்பட்டது.",
+        Msg::InjectionWarning => "இந்த கருவியின் வெளியீட்டில் உதவியாளருக்கான அறிவுறுத்தல்கள் இருக்கலாம்",
+        Msg::Warning => "எச்சரிக்கை",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tr_falls_back_to_english() {
+        assert_eq!(tr("xx", Msg::Error), "Error");
+        assert_eq!(tr("TA", Msg::Error), "பிழை");
+    }
+
+    #[test]
+    fn test_response_instruction_names_language() {
+        let instruction = response_instruction("ta");
+        assert!(instruction.contains("Tamil (ta)"));
+        assert_eq!(language_name("xx"), "xx");
+    }
+}
@@ -1,8 +1,22 @@
+// Lets `#[derive(ChittiTool)]` refer to this crate as `::chitti` from inside it too.
+extern crate self as chitti;
+
 pub mod config;
+pub mod i18n;
+pub mod notifier;
+pub mod turn_log;
+pub mod trust;
+pub mod redact;
+pub mod reload;
+pub mod retention;
 pub mod brains;
 pub mod bridges;
+pub mod cli;
 pub mod conductor;
+pub mod eval;
 pub mod tools;
+pub mod vault;
+pub mod workspace;
 
 // Re-export gemini for backward compatibility during refactor if needed, 
 // or simply expose the new path.
@@ -1,21 +1,37 @@
 use anyhow::{Context, Result};
+use futures_util::FutureExt;
 use dotenvy::dotenv;
 use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 use std::env;
 use std::sync::Arc;
 
+// Lets `#[derive(ChittiTool)]` refer to this crate as `::chitti` from inside it too.
+extern crate self as chitti;
+
 mod config;
+mod i18n;
+mod notifier;
+mod turn_log;
+mod trust;
+mod redact;
+mod reload;
+mod retention;
 mod brains;
 mod bridges;
+mod cli;
 mod conductor;
+mod eval;
 mod tools;
+mod vault;
+mod workspace;
 
 use brains::gemini::adapter::GeminiEngine;
 use bridges::tui::TuiBridge;
 use conductor::Conductor;
 use tools::ToolRegistry;
 use tools::bash::BashTool;
+use tools::truncate::{OutputStore, ReadToolOutputTool};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -24,28 +40,352 @@ async fn main() -> Result<()> {
     info!("Starting Chitti personal assistant (Omni-Channel Refactor)...");
 
     // 2. Load Configuration
-    if let Err(e) = dotenv() {
-        warn!("No .env file found or error reading it: {}. Using environment variables.", e);
-    }
+    let mut env_file = match dotenv() {
+        Ok(path) => Some(path),
+        Err(e) => {
+            warn!("No .env file found or error reading it: {}. Using environment variables.", e);
+            None
+        }
+    };
     
+    if env::args().nth(1).as_deref() == Some("vault") {
+        return vault::run_command(env::args().nth(2).as_deref());
+    }
+
+    if env::args().nth(1).as_deref() == Some("trust") {
+        return trust::run_command(env::args().nth(2).as_deref());
+    }
+
+    if env::args().nth(1).as_deref() == Some("purge") {
+        return retention::run_command(&env::args().skip(2).collect::<Vec<_>>());
+    }
+
+    // `chitti doctor` reports problems instead of failing on them, so it runs before config loading.
+    if env::args().nth(1).as_deref() == Some("doctor") {
+        let checks = cli::doctor::run(env_file.as_deref()).await;
+        print!("{}", cli::doctor::report(&checks));
+        if checks.iter().any(|c| c.status == cli::doctor::Status::Fail) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // First run: walk the user through setup instead of failing on the missing key.
+    if config::missing_api_key() && std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        let path = env_file.clone().unwrap_or_else(|| std::path::PathBuf::from(".env"));
+        cli::setup::run(&path).await?;
+        dotenvy::from_path_override(&path)
+            .with_context(|| format!("Failed to load {}", path.display()))?;
+        env_file = Some(path);
+    }
+
     let config = config::Config::from_env().context("Failed to load configuration")?;
+    for key in &config.gemini_api_keys {
+        redact::register_secret(key);
+    }
+    for token in config.slack_app_token.iter().chain(&config.slack_bot_token) {
+        redact::register_secret(token);
+    }
+    for pattern in &config.redact_patterns {
+        redact::register_pattern(pattern).context("Invalid CHITTI_REDACT_PATTERNS")?;
+    }
     info!("Chitti initialized with model: {}", config.gemini_model);
+    if let Some(days) = config.retention_days {
+        match retention::purge_all(retention::days(days)) {
+            Ok((removed, sessions)) if removed + sessions > 0 => {
+                info!("Retention: deleted {} file(s) and {} artifact session(s) older than {} day(s)", removed, sessions, days)
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Retention purge failed: {}", e),
+        }
+    }
+    if let Some(remote) = &config.remote {
+        info!("Shell tools run on {} over SSH", remote.destination());
+    }
 
     // 3. Initialize Tool Registry
     let mut registry = ToolRegistry::new();
-    registry.register(Box::new(BashTool));
+    let output_store = Arc::new(OutputStore::new());
+    let mut tool_env = match &config.tool_env_file {
+        Some(path) => tools::env::load(path)?,
+        None => Default::default(),
+    };
+    let secrets = tools::secrets::InjectedSecrets::default();
+    let session_vars = conductor::vars::SessionVars::default();
+    registry.register(Box::new(
+        BashTool::new(config.remote.clone())
+            .with_env(tool_env.remove("execute_bash").unwrap_or_default())
+            .with_secrets(secrets.clone())
+            .with_session_vars(session_vars.clone()),
+    ));
+    if let Some(dir) = &config.plugin_dir {
+        for tool in tools::plugin::discover(dir).await {
+            info!("Registered plugin tool {}", tools::ToolExecutor::name(&tool));
+            registry.register(Box::new(tool));
+        }
+        #[cfg(feature = "wasm-plugins")]
+        for tool in tools::wasm::discover(dir).await {
+            info!("Registered WASM plugin tool {}", tools::ToolExecutor::name(&tool));
+            registry.register(Box::new(tool));
+        }
+    }
+    if let Some(path) = &config.openapi_file {
+        for tool in tools::openapi::load_services(path).await? {
+            registry.register(Box::new(tool));
+        }
+    }
+    for tool in tool_env.keys() {
+        warn!(tool = %tool, "Tool env file configures a tool that doesn't take environment variables");
+    }
+    registry.register(Box::new(ReadToolOutputTool::new(output_store.clone())));
+    registry.register(Box::new(tools::tail::TailLogTool));
+    registry.register(Box::new(tools::sysinfo::SysInfoTool));
+    registry.register(Box::new(tools::convert::ConvertDocumentTool));
+    registry.register(Box::new(tools::archive::ArchiveTool::new(config.archive_roots.clone())));
+    if let Some(browser) = tools::browser::BrowserTool::detect(config.browser.clone(), config.browser_profile.clone()) {
+        registry.register(Box::new(browser.with_secrets(secrets.clone())));
+    }
+    if !config.feeds.is_empty() {
+        registry.register(Box::new(tools::feeds::FeedsTool::new(config.feeds.clone())));
+    }
+    if let Some(kubectl) = tools::kubectl::KubectlTool::detect(config.kube_contexts.clone(), config.kube_namespaces.clone()) {
+        registry.register(Box::new(kubectl));
+    }
+    if let Some(ocr) = tools::ocr::OcrTool::detect() {
+        registry.register(Box::new(ocr));
+    }
+    if config.secrets_lookup {
+        registry.register(Box::new(tools::secrets::SecretsLookupTool::new(secrets.clone())));
+    }
+    let vault = if config.vault {
+        Some(Arc::new(vault::Vault::open().context("Failed to open the vault")?))
+    } else {
+        None
+    };
+    let graph = config.knowledge_graph.then(|| Arc::new(tools::graph::GraphStore::load(tools::graph::GraphStore::default_path(), vault.clone())));
+    if let Some(graph) = &graph {
+        registry.register(Box::new(tools::graph::GraphLookupTool::who_is(graph.clone())));
+        registry.register(Box::new(tools::graph::GraphLookupTool::what_is(graph.clone())));
+    }
+    if config.tool_cache {
+        registry.enable_cache();
+    }
+    if env::args().any(|a| a == "--read-only") {
+        registry.set_read_only(true);
+    }
     let tools = Arc::new(registry);
 
     // 4. Initialize Components
-    let client = brains::gemini::Client::new(config.gemini_api_key, config.gemini_model);
-    let brain = Box::new(GeminiEngine::new(client, tools.clone()));
+    let mut client = brains::gemini::Client::new(config.gemini_api_key.clone(), config.gemini_model.clone())
+        .with_keys(config.gemini_api_keys.clone());
+    if let Some(vertex) = &config.vertex {
+        let credentials = brains::gemini::vertex::Credentials::discover()?;
+        info!(project = %vertex.project, location = %vertex.location, "Using Vertex AI");
+        client = client.with_vertex(brains::gemini::vertex::Vertex::new(vertex.project.clone(), vertex.location.clone(), credentials));
+    }
+
+    // Non-interactive subcommands
+    let args: Vec<String> = env::args().collect();
+    let use_response_cache = config.response_cache && !args.iter().any(|a| a == "--no-cache");
+    let one_shot_brain = |brain: GeminiEngine| -> Box<dyn brains::BrainEngine> {
+        if use_response_cache {
+            Box::new(brains::cache::CachedBrain::new(
+                Box::new(brain),
+                brains::cache::CachedBrain::default_dir(),
+                std::time::Duration::from_secs(config.response_cache_ttl_secs),
+            ).with_vault(vault.clone()))
+        } else {
+            Box::new(brain)
+        }
+    };
+    match args.get(1).map(|s| s.as_str()) {
+        Some("ask") => {
+            let mut prompt = cli::ask::prompt_from_args(&args);
+            if prompt.is_empty() {
+                std::io::Read::read_to_string(&mut std::io::stdin(), &mut prompt)?;
+            }
+            if prompt.trim().is_empty() {
+                anyhow::bail!("Usage: chitti ask [--no-cache] <prompt> (or pipe the prompt on stdin)");
+            }
+            let brain = one_shot_brain(GeminiEngine::new(client, Arc::new(ToolRegistry::new())));
+            return cli::ask::ask(&*brain, prompt).await;
+        }
+        Some("run") => {
+            let path = args.get(2).context("Usage: chitti run <tasks.yaml>")?;
+            return cli::run::run_tasks(path, client, tools).await;
+        }
+        Some("batch") if args.get(2).map(|s| s.as_str()) == Some("ask") => {
+            let usage = "Usage: chitti batch ask --input prompts.jsonl --out results.jsonl [--poll-secs N]";
+            let input = cli::flag(&args, "--input").context(usage)?;
+            let out = cli::flag(&args, "--out").context(usage)?;
+            let poll_secs = cli::flag(&args, "--poll-secs").and_then(|s| s.parse().ok()).unwrap_or(30);
+            return cli::batch::ask(&client, input, out, poll_secs).await;
+        }
+        Some("slack") => {
+            return cli::slack::run(client, tools, &config, vault.clone()).await;
+        }
+        Some("watch") => {
+            return cli::watch::run(&args[2..], client, tools).await;
+        }
+        Some("digest") => {
+            let brain = Box::new(GeminiEngine::new(client, Arc::new(ToolRegistry::new())));
+            let delivery = cli::digest::Delivery::parse(&config.digest_to);
+            return cli::digest::run(&args[2..], brain, &config.feeds, &config.digest_at, delivery).await;
+        }
+        Some("files") => {
+            let age = std::time::Duration::from_secs(config.files_gc_hours * 3600);
+            println!("{}", cli::files::command(&client, &args[2..], age).await?);
+            return Ok(());
+        }
+        Some("eval") => {
+            let path = args.get(2).context("Usage: chitti eval <suite.yaml> [--model M] [--no-cache]")?;
+            let text = tokio::fs::read_to_string(path).await
+                .with_context(|| format!("Failed to read {}", path))?;
+            let suite = eval::load_suite(&text)?;
+            let model = cli::flag(&args, "--model").map(str::to_string).or(suite.model.clone());
+            let client = match model {
+                Some(model) => client.with_model(model),
+                None => client,
+            };
+            let brain = one_shot_brain(GeminiEngine::new(client, Arc::new(tools.subset(&suite.tools))));
+            let results = eval::run_suite(&*brain, &suite).await?;
+            print!("{}", eval::report(&results));
+            if results.iter().any(|r| !r.passed()) {
+                anyhow::bail!("Eval suite had failures");
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let files_client = client.clone();
+    let make_brain = |backend: &config::Backend| -> Box<dyn brains::BrainEngine> {
+        match backend {
+            config::Backend::Gemini => Box::new(GeminiEngine::new(client.clone(), tools.clone())),
+            config::Backend::OpenAi(openai) => Box::new(brains::openai::OpenAiEngine::new(
+                openai.api_key.clone(),
+                openai.base_url.clone(),
+                openai.model.clone(),
+                tools.clone(),
+            )),
+            config::Backend::Ollama(ollama) => Box::new(brains::ollama::OllamaEngine::new(
+                ollama.base_url.clone(),
+                ollama.model.clone(),
+                ollama.native_tools,
+                tools.clone(),
+            )),
+        }
+    };
+    let brain = match &config.fallback {
+        Some(fallback) => Box::new(brains::fallback::FallbackBrain::new(
+            make_brain(&config.backend),
+            make_brain(fallback),
+            fallback.name(),
+        )),
+        None => make_brain(&config.backend),
+    };
+    let routes = brains::router::load_routes(conductor::session::default_config_path().as_deref())?;
+    let brain = if routes.is_empty() {
+        brain
+    } else {
+        let mut engines = std::collections::BTreeMap::from([(brains::router::DEFAULT_ENGINE.to_string(), brain)]);
+        for name in routes.iter().filter_map(|r| r.brain.as_deref()) {
+            if engines.contains_key(name) {
+                continue;
+            }
+            let backend = config::backend_named(name, "routes.brain")?;
+            if matches!(backend, config::Backend::Gemini) && config.vertex.is_none() && config.gemini_api_keys.is_empty() {
+                anyhow::bail!("GEMINI_API_KEY must be set when routes.brain=gemini");
+            }
+            engines.insert(name.to_string(), make_brain(&backend));
+        }
+        Box::new(brains::router::RouterBrain::new(engines, routes)?)
+    };
+    let brain: Box<dyn brains::BrainEngine> = if config.privacy_filter {
+        let filter = brains::privacy::PrivacyFilter::new(&config.privacy_names, &config.privacy_patterns)?;
+        Box::new(brains::privacy::PrivacyBrain::new(brain, filter))
+    } else {
+        brain
+    };
+    let trust = if config.trust_prompt { trust::for_current_dir()? } else { trust::Trust::Full };
+    if trust == trust::Trust::ReadOnly {
+        tools.set_read_only(true);
+    }
     
+    bridges::tui::install_panic_hook();
     let (tui, rx) = TuiBridge::new();
-    let bridge = Arc::new(tui);
+    let reload_tx = tui.sender();
+    let keymap = bridges::keymap::Keymap::load(conductor::session::default_config_path().as_deref())?;
+    let bridge = Arc::new(tui.with_language(config.language.clone()).with_keymap(keymap));
+    let settings = reload::Settings::from_config(&config)?;
+
+    // Keep the watcher alive for the whole session.
+    let watched: Vec<_> = env_file.iter().chain(config.persona_file.iter()).cloned().collect();
+    let _watcher = match reload::watch(watched, reload_tx) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            warn!("Hot reload disabled: {:#}", e);
+            None
+        }
+    };
 
     // 5. Start the Conductor
-    let mut conductor = Conductor::new(brain, bridge.clone(), rx, tools.clone());
+    let mut conductor = Conductor::new(brain, bridge.clone(), rx, tools.clone())
+        .with_artifacts_dir(conductor::artifacts::root(), vault.clone())
+        .with_language(config.language.clone())
+        .with_dev_mode(config.dev_mode)
+        .with_request_preview(config.preview_requests)
+        .with_auto_approve(config.auto_approve_tools.clone())
+        .with_bridge_tools(&config.bridge_tools)
+        .with_trust(trust)
+        .with_allow_list(conductor::allow_list::AllowList::load(conductor::allow_list::AllowList::default_path()))
+        .with_quick_actions(conductor::quick_actions::load(config.quick_actions_file.as_deref())?)
+        .with_glossary(config.glossary_file.clone())
+        .with_fast_draft(config.fast_draft)
+        .with_starred_context(config.starred_context)
+        .with_tag_defaults(conductor::session::load_tag_defaults(conductor::session::default_config_path().as_deref())?)
+        .with_knowledge_graph(graph)
+        .with_follow_ups(config.follow_ups)
+        .with_session_vars(session_vars)
+        .with_error_hints(config.error_hints)
+        .with_request_confirmation(config.confirm_request_tokens)
+        .with_secret_guard(conductor::sensitive::Scanner::new(&config.secret_patterns)?, config.secret_redact)
+        .with_thinking(config.thinking, config.thinking_classifier_model.clone())
+        .with_purge_on_clear(config.purge_on_clear)
+        .with_turn_deadline(config.turn_deadline_secs.map(std::time::Duration::from_secs))
+        .with_injection_classifier(config.injection_classifier)
+        .with_tool_output_limit(config.max_tool_result_bytes, output_store)
+        .with_notifier(config.notify_after_secs.map(|secs| {
+            notifier::Notifier::new(std::time::Duration::from_secs(secs), config.notify_bell)
+        }))
+        .with_turn_log(config.turn_log.clone())
+        .with_persona(settings.persona.clone())
+        .with_hot_reload(env_file, settings);
+    // Vertex AI has no File API, and its keys are OAuth tokens.
+    if matches!(config.backend, config::Backend::Gemini) && config.vertex.is_none() {
+        conductor = conductor
+            .with_api_keys(files_client.keys.clone())
+            .with_files(files_client, std::time::Duration::from_secs(config.files_gc_hours * 3600));
+    }
     
+    // Ctrl-\ (SIGQUIT) is the panic hotkey: it works even while a line is half typed.
+    #[cfg(unix)]
+    {
+        let panic_tx = bridge.sender();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let Ok(mut quit) = signal(SignalKind::quit()) else {
+                warn!("Could not install the SIGQUIT panic hotkey");
+                return;
+            };
+            while quit.recv().await.is_some() {
+                if panic_tx.send(conductor::events::UserEvent::Command("/panic".to_string())).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     // Spawn TUI input loop
     let tui_handle = bridge.clone();
     tokio::spawn(async move {
@@ -54,9 +394,56 @@ async fn main() -> Result<()> {
         }
     });
 
-    conductor.run().await?;
+    // A panic inside the Conductor is caught here so the terminal is restored
+    // and the user gets an error instead of a torn-down session.
+    let run = std::panic::AssertUnwindSafe(conductor.run()).catch_unwind();
+    tokio::select! {
+        result = run => match result {
+            Ok(result) => result?,
+            Err(panic) => {
+                let msg = panic.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                bridges::tui::restore_terminal();
+                anyhow::bail!("Conductor panicked: {}", msg);
+            }
+        },
+        signal = shutdown_signal() => {
+            // Dropping the run future aborts the in-flight request and kills child tool processes.
+            conductor.shutdown(signal).await?;
+            std::process::exit(if signal == "SIGTERM" { 143 } else { 130 });
+        }
+    }
 
-    Ok(())
+    // The input loop is still blocked reading stdin, which would keep the
+    // runtime from shutting down.
+    drop(conductor);
+    std::process::exit(0);
+}
+
+/// Resolves with the name of the first exit signal received.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = match signal(SignalKind::terminate()) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Could not install SIGTERM handler: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                return "SIGINT";
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = term.recv() => "SIGTERM",
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
 }
 
 fn setup_logging() -> Result<()> {
@@ -71,6 +458,7 @@ fn setup_logging() -> Result<()> {
 
     let subscriber = FmtSubscriber::builder()
         .with_max_level(log_level)
+        .with_writer(redact::RedactingWriter)
         .finish();
 
     tracing::subscriber::set_global_default(subscriber)
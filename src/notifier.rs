@@ -0,0 +1,88 @@
+use std::io::Write;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::debug;
+
+/// Alerts the user when a long turn finishes so they can switch away while Chitti works.
+/// The line-based TUI can't observe terminal focus, so any turn longer than the
+/// threshold triggers the alert.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    threshold: Duration,
+    bell: bool,
+}
+
+impl Notifier {
+    pub fn new(threshold: Duration, bell: bool) -> Self {
+        Self { threshold, bell }
+    }
+
+    pub fn should_notify(&self, elapsed: Duration) -> bool {
+        elapsed >= self.threshold
+    }
+
+    /// Rings the bell and sends a desktop notification if the turn ran long enough.
+    /// Failures are logged and otherwise ignored; alerts are best effort.
+    pub async fn turn_finished(&self, elapsed: Duration, summary: &str) {
+        if !self.should_notify(elapsed) {
+            return;
+        }
+        if self.bell {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07");
+            let _ = stdout.flush();
+        }
+        let body = format!("Finished after {}s: {}", elapsed.as_secs(), preview(summary));
+        if let Err(e) = desktop_notification("Chitti", &body).await {
+            debug!("Desktop notification failed: {}", e);
+        }
+    }
+}
+
+/// Sends a desktop notification showing the first line of `body`.
+pub async fn notify(title: &str, body: &str) -> std::io::Result<()> {
+    desktop_notification(title, &preview(body)).await
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    if line.chars().count() > 80 {
+        format!("{}…", line.chars().take(80).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn desktop_notification(title: &str, body: &str) -> std::io::Result<()> {
+    let script = format!(
+        "display notification {} with title {}",
+        applescript_string(body),
+        applescript_string(title)
+    );
+    Command::new("osascript").arg("-e").arg(script).status().await.map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(not(target_os = "macos"))]
+async fn desktop_notification(title: &str, body: &str) -> std::io::Result<()> {
+    Command::new("notify-send").arg(title).arg(body).status().await.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_and_preview() {
+        let notifier = Notifier::new(Duration::from_secs(30), false);
+        assert!(!notifier.should_notify(Duration::from_secs(5)));
+        assert!(notifier.should_notify(Duration::from_secs(30)));
+        assert_eq!(preview("first line\nsecond"), "first line");
+        assert_eq!(preview(&"x".repeat(100)).chars().count(), 81);
+    }
+}
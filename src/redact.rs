@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::io::{self, Write};
+use std::sync::RwLock;
+use tracing_subscriber::fmt::MakeWriter;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Header names whose values are always masked.
+const SENSITIVE_HEADERS: &[&str] = &["x-goog-api-key", "authorization", "proxy-authorization"];
+
+/// Literal secrets registered at runtime (API keys, bot tokens).
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Regexes registered at runtime from `CHITTI_REDACT_PATTERNS`.
+static PATTERNS: RwLock<Vec<Regex>> = RwLock::new(Vec::new());
+
+/// Registers a literal secret that must never appear in logs or debug events.
+/// Very short values are ignored so they don't mangle unrelated text.
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < 4 {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap();
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+        // Longest first so a secret containing another is masked whole.
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+}
+
+/// Registers a regex whose matches must never appear in logs or debug events.
+/// Patterns that match the empty string are refused; they would mask everywhere.
+pub fn register_pattern(pattern: &str) -> Result<()> {
+    let regex = Regex::new(pattern).with_context(|| format!("Invalid redact pattern '{}'", pattern))?;
+    if regex.is_match("") {
+        anyhow::bail!("Redact pattern '{}' matches empty text", pattern);
+    }
+    PATTERNS.write().unwrap().push(regex);
+    Ok(())
+}
+
+/// Masks registered secrets and patterns, Google API keys, bearer tokens and
+/// sensitive header values.
+pub fn redact(input: &str) -> String {
+    let mut out = input.to_string();
+    for secret in SECRETS.read().unwrap().iter() {
+        if out.contains(secret.as_str()) {
+            out = out.replace(secret.as_str(), REDACTED);
+        }
+    }
+    for pattern in PATTERNS.read().unwrap().iter() {
+        out = pattern.replace_all(&out, REDACTED).into_owned();
+    }
+    out = mask_google_keys(&out);
+    out = mask_after(&out, "bearer ");
+    for header in SENSITIVE_HEADERS {
+        out = mask_after(&out, &format!("{}: ", header));
+        out = mask_after(&out, &format!("\"{}\": \"", header));
+    }
+    out
+}
+
+/// Applies `redact` to every string in a JSON value.
+pub fn redact_value(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Value::String(redact(&s)),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact_value).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, redact_value(v))).collect()),
+        other => other,
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '+' | '=')
+}
+
+/// Masks Google API keys ("AIza" followed by 35 key characters).
+fn mask_google_keys(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find("AIza") {
+        out.push_str(&rest[..pos]);
+        let candidate = &rest[pos..];
+        let len = candidate
+            .char_indices()
+            .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+            .map(|(i, _)| i)
+            .unwrap_or(candidate.len());
+        if len >= 39 {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&candidate[..len]);
+        }
+        rest = &candidate[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Masks the token that follows `marker` (matched case-insensitively).
+fn mask_after(input: &str, marker: &str) -> String {
+    let lower = input.to_ascii_lowercase();
+    let mut out = String::with_capacity(input.len());
+    let mut cursor = 0;
+    while let Some(found) = lower[cursor..].find(marker) {
+        let value_start = cursor + found + marker.len();
+        out.push_str(&input[cursor..value_start]);
+        let value_len = input[value_start..]
+            .char_indices()
+            .find(|(_, c)| !is_token_char(*c))
+            .map(|(i, _)| i)
+            .unwrap_or(input.len() - value_start);
+        if value_len > 0 {
+            out.push_str(REDACTED);
+        }
+        cursor = value_start + value_len;
+    }
+    out.push_str(&input[cursor..]);
+    out
+}
+
+/// A `MakeWriter` for tracing that redacts every formatted log line before it hits stdout.
+#[derive(Clone, Copy, Default)]
+pub struct RedactingWriter;
+
+impl<'a> MakeWriter<'a> for RedactingWriter {
+    type Writer = RedactingLine;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingLine { buf: Vec::new() }
+    }
+}
+
+/// Buffers one log event and writes its redacted form on flush/drop.
+pub struct RedactingLine {
+    buf: Vec<u8>,
+}
+
+impl Write for RedactingLine {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let line = redact(&String::from_utf8_lossy(&self.buf));
+        self.buf.clear();
+        let mut stdout = io::stdout();
+        stdout.write_all(line.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for RedactingLine {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_registered_secret_and_headers() {
+        register_secret("super-secret-value");
+        let line = "token=super-secret-value authorization: Bearer abc.def x-goog-api-key: xyz123";
+        let redacted = redact(line);
+        assert!(!redacted.contains("super-secret-value"));
+        assert!(!redacted.contains("abc.def"));
+        assert!(!redacted.contains("xyz123"));
+        assert!(redacted.starts_with("token=[REDACTED]"));
+    }
+
+    #[test]
+    fn test_redacts_registered_patterns() -> Result<()> {
+        register_pattern(r"acct-\d{4,}")?;
+        assert_eq!(redact("billing acct-123456, not acct-12"), "billing [REDACTED], not acct-12");
+        assert!(register_pattern("(").is_err());
+        assert!(register_pattern("x*").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_redacts_google_api_key_shape() {
+        let key = format!("AIza{}", "A".repeat(35));
+        let redacted = redact(&format!("key is {} ok", key));
+        assert_eq!(redacted, "key is [REDACTED] ok");
+        assert_eq!(redact("AIza is just a word"), "AIza is just a word");
+    }
+}
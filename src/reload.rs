@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use crate::config::Config;
+use crate::conductor::events::UserEvent;
+
+/// The subset of configuration the Conductor can apply without a restart.
+/// Model, API key and tool registration still need one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub language: Option<String>,
+    pub dev_mode: bool,
+    pub injection_classifier: bool,
+    pub max_tool_result_bytes: usize,
+    pub notify_after_secs: Option<u64>,
+    pub notify_bell: bool,
+    pub persona: Option<String>,
+}
+
+impl Settings {
+    /// Builds settings from a loaded config, reading the persona file if one is set.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let persona = match &config.persona_file {
+            Some(path) => Some(std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read persona file {}", path.display()))?),
+            None => None,
+        };
+        Ok(Self {
+            language: config.language.clone(),
+            dev_mode: config.dev_mode,
+            injection_classifier: config.injection_classifier,
+            max_tool_result_bytes: config.max_tool_result_bytes,
+            notify_after_secs: config.notify_after_secs,
+            notify_bell: config.notify_bell,
+            persona: persona.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()),
+        })
+    }
+
+    /// Names of the settings that differ between `self` and `other`.
+    pub fn diff(&self, other: &Settings) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.language != other.language {
+            changed.push("language");
+        }
+        if self.dev_mode != other.dev_mode {
+            changed.push("dev mode");
+        }
+        if self.injection_classifier != other.injection_classifier {
+            changed.push("injection classifier");
+        }
+        if self.max_tool_result_bytes != other.max_tool_result_bytes {
+            changed.push("tool output limit");
+        }
+        if self.notify_after_secs != other.notify_after_secs || self.notify_bell != other.notify_bell {
+            changed.push("notifications");
+        }
+        if self.persona != other.persona {
+            changed.push("persona");
+        }
+        changed
+    }
+}
+
+/// Re-reads the env file (overriding the process environment) and the persona file.
+pub fn load(env_file: Option<&Path>) -> Result<Settings> {
+    if let Some(path) = env_file {
+        dotenvy::from_path_override(path)
+            .with_context(|| format!("Failed to reload {}", path.display()))?;
+    }
+    let config = Config::from_env()?;
+    Settings::from_config(&config)
+}
+
+/// Watches `files` and asks the Conductor to `/reload` when any of them changes.
+/// Parent directories are watched so editors that save by renaming are noticed.
+/// The returned watcher must be kept alive.
+pub fn watch(files: Vec<PathBuf>, tx: mpsc::Sender<UserEvent>) -> Result<RecommendedWatcher> {
+    let files: HashSet<PathBuf> = files.into_iter()
+        .map(|f| f.canonicalize().unwrap_or(f))
+        .collect();
+    let watched = files.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        let relevant = event.paths.iter().any(|p| {
+            watched.contains(p) || p.canonicalize().map(|p| watched.contains(&p)).unwrap_or(false)
+        });
+        if relevant {
+            // A burst of events for one save only costs redundant, no-op reloads.
+            let _ = tx.try_send(UserEvent::Command("/reload".to_string()));
+        }
+    })?;
+
+    let dirs: HashSet<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    }
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lists_changed_settings() {
+        let base = Settings {
+            language: None,
+            dev_mode: false,
+            injection_classifier: false,
+            max_tool_result_bytes: 1024,
+            notify_after_secs: None,
+            notify_bell: false,
+            persona: None,
+        };
+        assert!(base.diff(&base.clone()).is_empty());
+
+        let changed = Settings { language: Some("ta".to_string()), persona: Some("Be terse.".to_string()), ..base.clone() };
+        assert_eq!(base.diff(&changed), vec!["language", "persona"]);
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Deletes files in `dirs` last modified more than `max_age` ago; returns how
+/// many were removed. Paths that aren't directories are skipped.
+pub fn purge_older_than(dirs: &[impl AsRef<Path>], max_age: Duration) -> Result<usize> {
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut removed = 0;
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir.as_ref()) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_file() && meta.modified().is_ok_and(|m| m < cutoff) {
+                std::fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Removes session directories under `root` (e.g. artifacts) that haven't
+/// changed in `max_age`; returns how many were removed.
+pub fn purge_sessions_older_than(root: &Path, max_age: Duration) -> Result<usize> {
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() && meta.modified().is_ok_and(|m| m < cutoff) {
+            std::fs::remove_dir_all(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Applies the retention period to cached data and artifact sessions;
+/// returns the number of files and sessions removed.
+pub fn purge_all(max_age: Duration) -> Result<(usize, usize)> {
+    let files = purge_older_than(&crate::vault::data_dirs(), max_age)?;
+    let sessions = purge_sessions_older_than(&crate::conductor::artifacts::root(), max_age)?;
+    Ok((files, sessions))
+}
+
+pub fn days(n: u64) -> Duration {
+    Duration::from_secs(n * 24 * 60 * 60)
+}
+
+/// `chitti purge [--days N]`; without `--days`, uses `CHITTI_RETENTION_DAYS`.
+pub fn run_command(args: &[String]) -> Result<()> {
+    let days_arg = match args {
+        [] => std::env::var("CHITTI_RETENTION_DAYS").ok().filter(|d| !d.trim().is_empty()),
+        [flag, n] if flag == "--days" => Some(n.clone()),
+        _ => anyhow::bail!("Usage: chitti purge [--days N]"),
+    };
+    let Some(days_arg) = days_arg else {
+        anyhow::bail!("No retention period: pass --days N or set CHITTI_RETENTION_DAYS");
+    };
+    let n: u64 = days_arg.trim().parse()
+        .map_err(|_| anyhow::anyhow!("Invalid number of days: {}", days_arg))?;
+    let (removed, sessions) = purge_all(days(n))?;
+    println!("Deleted {} file(s) and {} artifact session(s) older than {} day(s)", removed, sessions, n);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_removes_only_old_files() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("chitti-retention-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let old = dir.join("old.json");
+        std::fs::write(&old, "{}")?;
+        std::fs::File::options().write(true).open(&old)?
+            .set_modified(SystemTime::now() - days(10))?;
+        std::fs::write(dir.join("new.json"), "{}")?;
+
+        assert_eq!(purge_older_than(&[&dir], days(7))?, 1);
+        assert!(!old.exists());
+        assert!(dir.join("new.json").exists());
+        assert_eq!(purge_older_than(&[dir.join("missing")], days(7))?, 0);
+
+        let session = dir.join("session");
+        std::fs::create_dir(&session)?;
+        std::fs::write(session.join("artifact-1.png"), "png")?;
+        assert_eq!(purge_sessions_older_than(&dir, days(7))?, 0);
+        std::fs::File::open(&session)?.set_modified(SystemTime::now() - days(10))?;
+        assert_eq!(purge_sessions_older_than(&dir, days(7))?, 1);
+        assert!(!session.exists());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
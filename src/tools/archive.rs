@@ -0,0 +1,413 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
+
+/// Entries returned by `list` before the rest are only counted.
+const MAX_LISTED: usize = 500;
+/// Refuse to unpack more than this, whatever the archive claims (zip bombs).
+const MAX_EXTRACTED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Format {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Format::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Format::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Format::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Lists, extracts and creates zip and tar(.gz) archives without a shell.
+/// Every path must lie under one of the allowed roots, and extraction never
+/// writes outside the destination: entries with absolute paths, `..` or
+/// links are skipped.
+pub struct ArchiveTool {
+    roots: Vec<PathBuf>,
+}
+
+impl ArchiveTool {
+    /// `roots` are the directories archives may be read from and written to;
+    /// empty means the working directory.
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+
+    /// The absolute form of `path`, if it lies under an allowed root.
+    pub fn check(&self, path: &str) -> Result<PathBuf> {
+        let cwd = std::env::current_dir()?;
+        let absolute = normalize(&cwd.join(crate::tools::env::expand_home(Path::new(path))));
+        let roots = if self.roots.is_empty() { vec![cwd] } else { self.roots.clone() };
+        // Resolve symlinks in the part that exists so a link can't point outside.
+        let resolved = resolve_existing(&absolute);
+        let allowed = roots.iter()
+            .map(|root| resolve_existing(&normalize(root)))
+            .any(|root| resolved.starts_with(&root));
+        if !allowed {
+            anyhow::bail!("{} is outside the allowed directories ({})", absolute.display(),
+                roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", "));
+        }
+        Ok(absolute)
+    }
+}
+
+/// Removes `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Canonicalizes the longest existing ancestor and re-appends the rest.
+fn resolve_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest.iter().rev().fold(canonical, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_owned());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// An archive entry's path, if it stays inside the destination.
+fn safe_relative(name: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+fn tar_reader(path: &Path, format: Format) -> Result<tar::Archive<Box<dyn Read>>> {
+    let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    let reader: Box<dyn Read> = match format {
+        Format::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+pub fn list(path: &Path, format: Format) -> Result<Value> {
+    let mut entries = Vec::new();
+    let mut total = 0usize;
+    let mut push = |name: String, size: u64, is_dir: bool| {
+        total += 1;
+        if entries.len() < MAX_LISTED {
+            entries.push(json!({ "name": name, "size": size, "is_dir": is_dir }));
+        }
+    };
+    match format {
+        Format::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+            for i in 0..zip.len() {
+                let entry = zip.by_index_raw(i)?;
+                push(entry.name().to_string(), entry.size(), entry.is_dir());
+            }
+        }
+        Format::Tar | Format::TarGz => {
+            for entry in tar_reader(path, format)?.entries()? {
+                let entry = entry?;
+                let name = entry.path()?.display().to_string();
+                push(name, entry.size(), entry.header().entry_type().is_dir());
+            }
+        }
+    }
+    Ok(json!({ "entries": entries, "total_entries": total, "truncated": total > MAX_LISTED }))
+}
+
+pub fn extract(path: &Path, format: Format, destination: &Path, overwrite: bool) -> Result<Value> {
+    let mut written = 0usize;
+    let mut bytes = 0u64;
+    let mut skipped = Vec::new();
+    // Writes one regular file, enforcing the overwrite flag and the size cap.
+    // False when the file already exists and is kept.
+    let mut write = |relative: &Path, reader: &mut dyn Read| -> Result<bool> {
+        let target = destination.join(relative);
+        if target.exists() && !overwrite {
+            return Ok(false);
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = File::create(&target)?;
+        let copied = io::copy(&mut reader.take(MAX_EXTRACTED_BYTES - bytes + 1), &mut out)?;
+        bytes += copied;
+        if bytes > MAX_EXTRACTED_BYTES {
+            drop(out);
+            let _ = std::fs::remove_file(&target);
+            anyhow::bail!("Archive expands to more than {} bytes; stopped", MAX_EXTRACTED_BYTES);
+        }
+        written += 1;
+        Ok(true)
+    };
+
+    std::fs::create_dir_all(destination)?;
+    match format {
+        Format::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i)?;
+                let name = entry.name().to_string();
+                match safe_relative(Path::new(&name)) {
+                    Some(relative) if entry.is_dir() => std::fs::create_dir_all(destination.join(relative))?,
+                    Some(_) if entry.is_symlink() => skipped.push(format!("{} (link)", name)),
+                    Some(relative) => {
+                        if !write(&relative, &mut entry)? {
+                            skipped.push(format!("{} (exists)", name));
+                        }
+                    }
+                    None => skipped.push(format!("{} (unsafe path)", name)),
+                }
+            }
+        }
+        Format::Tar | Format::TarGz => {
+            for entry in tar_reader(path, format)?.entries()? {
+                let mut entry = entry?;
+                let name = entry.path()?.display().to_string();
+                let kind = entry.header().entry_type();
+                match safe_relative(Path::new(&name)) {
+                    Some(relative) if kind.is_dir() => std::fs::create_dir_all(destination.join(relative))?,
+                    Some(relative) if kind.is_file() => {
+                        if !write(&relative, &mut entry)? {
+                            skipped.push(format!("{} (exists)", name));
+                        }
+                    }
+                    Some(_) => skipped.push(format!("{} (link or special file)", name)),
+                    None => skipped.push(format!("{} (unsafe path)", name)),
+                }
+            }
+        }
+    }
+    Ok(json!({ "destination": destination.display().to_string(), "files_written": written, "bytes": bytes, "skipped": skipped }))
+}
+
+/// Every regular file under `source`, paired with its name in the archive
+/// (relative to the source's parent, so `dist/` stays `dist/...`).
+fn collect(source: &Path, out: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    let base = source.parent().unwrap_or(Path::new(""));
+    let mut stack = vec![source.to_path_buf()];
+    while let Some(path) = stack.pop() {
+        let meta = std::fs::symlink_metadata(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+        if meta.is_dir() {
+            let mut children: Vec<PathBuf> = std::fs::read_dir(&path)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+            children.sort_by(|a, b| b.cmp(a));
+            stack.extend(children);
+        } else if meta.is_file() {
+            let name = path.strip_prefix(base).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            out.push((path, name));
+        }
+    }
+    Ok(())
+}
+
+pub fn create(path: &Path, format: Format, sources: &[PathBuf]) -> Result<Value> {
+    let mut files = Vec::new();
+    for source in sources {
+        collect(source, &mut files)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let out = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+    match format {
+        Format::Zip => {
+            let mut zip = zip::ZipWriter::new(out);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .large_file(true);
+            for (file, name) in &files {
+                zip.start_file(name.as_str(), options)?;
+                io::copy(&mut File::open(file)?, &mut zip)?;
+            }
+            zip.finish()?;
+        }
+        Format::Tar => {
+            let mut tar = tar::Builder::new(out);
+            for (file, name) in &files {
+                tar.append_path_with_name(file, name)?;
+            }
+            tar.finish()?;
+        }
+        Format::TarGz => {
+            let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(out, flate2::Compression::default()));
+            for (file, name) in &files {
+                tar.append_path_with_name(file, name)?;
+            }
+            tar.into_inner()?.finish()?;
+        }
+    }
+    Ok(json!({ "archive": path.display().to_string(), "files": files.len() }))
+}
+
+#[async_trait]
+impl ToolExecutor for ArchiveTool {
+    fn name(&self) -> String {
+        "archive".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "List, extract or create .zip, .tar and .tar.gz/.tgz archives. Paths must be inside the working directory (or the configured archive directories); entries that would land outside the destination are skipped.".to_string(),
+            parameters: Some(Params::object()
+                .one_of("operation", "What to do.", &["list", "extract", "create"])
+                .string("archive", "Path of the archive; its extension picks the format.")
+                .string("destination", "For extract: directory to unpack into (default: next to the archive, named after it).")
+                .array("sources", "For create: files and directories to add.", json!({ "type": "string" }))
+                .boolean("overwrite", "For extract: replace existing files (default false).")
+                .required(&["operation", "archive"])
+                .build()),
+        }
+    }
+
+    fn read_only(&self, args: &HashMap<String, Value>) -> bool {
+        args.get("operation").and_then(|v| v.as_str()) == Some("list")
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        let access = match args.get("operation").and_then(|v| v.as_str()) {
+            Some("create") => FileAccess::Write,
+            _ => FileAccess::Read,
+        };
+        args.get("archive").and_then(|v| v.as_str())
+            .map(|p| vec![(p.to_string(), access)])
+            .unwrap_or_default()
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let get = |key: &str| args.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+        let operation = get("operation").ok_or_else(|| anyhow::anyhow!("Missing 'operation' argument"))?.to_string();
+        let archive = get("archive").ok_or_else(|| anyhow::anyhow!("Missing 'archive' argument"))?;
+        let refuse = |message: String| Ok(ToolResult { output: json!({ "error": message }), is_error: true });
+
+        let Some(format) = Format::for_path(Path::new(archive)) else {
+            return refuse(format!("{} is not a .zip, .tar, .tar.gz or .tgz file", archive));
+        };
+        let archive = match self.check(archive) {
+            Ok(path) => path,
+            Err(e) => return refuse(e.to_string()),
+        };
+        let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+        let destination = match (operation.as_str(), get("destination")) {
+            ("extract", Some(dir)) => Some(self.check(dir)),
+            ("extract", None) => {
+                let name = archive.file_name().unwrap_or_default().to_string_lossy();
+                let stem = [".tar.gz", ".tgz", ".tar", ".zip"].iter()
+                    .find_map(|ext| name.to_ascii_lowercase().strip_suffix(ext).map(|s| name[..s.len()].to_string()))
+                    .unwrap_or_else(|| name.to_string());
+                Some(Ok(archive.with_file_name(stem)))
+            }
+            _ => None,
+        }.transpose();
+        let destination = match destination {
+            Ok(destination) => destination,
+            Err(e) => return refuse(e.to_string()),
+        };
+        let mut sources = Vec::new();
+        if operation == "create" {
+            let listed = args.get("sources").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            for source in listed.iter().filter_map(|v| v.as_str()) {
+                match self.check(source) {
+                    Ok(path) => sources.push(path),
+                    Err(e) => return refuse(e.to_string()),
+                }
+            }
+            if sources.is_empty() {
+                return refuse("'create' needs at least one path in 'sources'".to_string());
+            }
+        }
+
+        let result = tokio::task::spawn_blocking(move || match operation.as_str() {
+            "list" => list(&archive, format),
+            "extract" => extract(&archive, format, destination.as_deref().unwrap_or(Path::new(".")), overwrite),
+            "create" => create(&archive, format, &sources),
+            other => anyhow::bail!("Unknown operation '{}'", other),
+        }).await?;
+        match result {
+            Ok(output) => Ok(ToolResult { output, is_error: false }),
+            Err(e) => refuse(format!("{:#}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip_stays_in_destination() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("chitti-archive-{}", uuid::Uuid::new_v4()));
+        let release = dir.join("release");
+        std::fs::create_dir_all(release.join("docs"))?;
+        std::fs::write(release.join("CHANGELOG.md"), "## 1.2.0\n")?;
+        std::fs::write(release.join("docs/guide.txt"), "guide")?;
+
+        for name in ["release.zip", "release.tar.gz"] {
+            let archive = dir.join(name);
+            let format = Format::for_path(&archive).unwrap();
+            assert_eq!(create(&archive, format, std::slice::from_ref(&release))?["files"], 2);
+            let listing = list(&archive, format)?;
+            let names: Vec<&str> = listing["entries"].as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap()).collect();
+            assert!(names.contains(&"release/CHANGELOG.md"), "{:?}", names);
+
+            let out = dir.join(format!("out-{}", name));
+            let result = extract(&archive, format, &out, false)?;
+            assert_eq!(result["files_written"], 2);
+            assert_eq!(std::fs::read_to_string(out.join("release/CHANGELOG.md"))?, "## 1.2.0\n");
+            assert_eq!(extract(&archive, format, &out, false)?["files_written"], 0);
+        }
+
+        // A zip entry climbing out of the destination is skipped, not written.
+        let evil = dir.join("evil.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&evil)?);
+        zip.start_file("../escaped.txt", zip::write::SimpleFileOptions::default())?;
+        io::Write::write_all(&mut zip, b"x")?;
+        zip.finish()?;
+        let result = extract(&evil, Format::Zip, &dir.join("evil"), false)?;
+        assert_eq!(result["files_written"], 0);
+        assert!(!dir.join("escaped.txt").exists());
+
+        let tool = ArchiveTool::new(vec![dir.clone()]);
+        assert!(tool.check(dir.join("release.zip").to_str().unwrap()).is_ok());
+        assert!(tool.check(dir.join("../elsewhere.zip").to_str().unwrap()).is_err());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
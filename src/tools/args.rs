@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use crate::brains::gemini::types::FunctionDeclaration;
+
+#[allow(unused_imports)]
+pub use chitti_macros::ChittiTool;
+// Lets code generated by `#[derive(ChittiTool)]` build schemas without the
+// caller depending on serde_json directly.
+#[doc(hidden)]
+pub use serde_json;
+
+/// A typed argument struct for a tool. Derive it with `#[derive(ChittiTool)]`
+/// (alongside `serde::Deserialize`) so the declared schema and the parsing
+/// can't drift apart; see `ToolRegistry::register_typed`.
+pub trait ToolArgs: DeserializeOwned + Send + 'static {
+    fn declaration() -> FunctionDeclaration;
+
+    fn parse(args: HashMap<String, serde_json::Value>) -> Result<Self> {
+        let name = Self::declaration().name;
+        serde_json::from_value(serde_json::Value::Object(args.into_iter().collect()))
+            .with_context(|| format!("Invalid arguments for tool '{}'", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolRegistry;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    /// Add two numbers.
+    #[derive(Deserialize, ChittiTool)]
+    #[allow(dead_code)]
+    struct AddNumbersArgs {
+        /// The first number.
+        a: i64,
+        b: i64,
+        /// Optional labels to echo back.
+        labels: Option<Vec<String>>,
+    }
+
+    #[tokio::test]
+    async fn test_derived_declaration_matches_parsing() -> Result<()> {
+        let declaration = AddNumbersArgs::declaration();
+        assert_eq!(declaration.name, "add_numbers");
+        assert_eq!(declaration.description, "Add two numbers.");
+        assert_eq!(declaration.parameters, Some(json!({
+            "type": "object",
+            "properties": {
+                "a": { "type": "integer", "description": "The first number." },
+                "b": { "type": "integer" },
+                "labels": { "type": "array", "items": { "type": "string" }, "description": "Optional labels to echo back." },
+            },
+            "required": ["a", "b"],
+        })));
+
+        let mut registry = ToolRegistry::new();
+        registry.register_typed(|args: AddNumbersArgs| async move { Ok(json!(args.a + args.b)) });
+        assert!(registry.validate_args("add_numbers", &json!({ "a": 1 })).is_err());
+        let result = registry.execute("add_numbers", [("a".to_string(), json!(2)), ("b".to_string(), json!(3))].into()).await?;
+        assert_eq!(result.output, json!(5));
+        Ok(())
+    }
+}
@@ -3,10 +3,46 @@ use serde_json::{Value, json};
 use anyhow::Result;
 use std::collections::HashMap;
 use tokio::process::Command;
-use crate::tools::{ToolExecutor, ToolResult};
+use crate::tools::remote::Remote;
+use crate::tools::secrets::InjectedSecrets;
+use crate::conductor::vars::SessionVars;
+use crate::tools::params::Params;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
 use crate::brains::gemini::types::FunctionDeclaration;
 
-pub struct BashTool;
+/// Runs bash commands locally, or on a remote host over SSH.
+#[derive(Default)]
+pub struct BashTool {
+    remote: Option<Remote>,
+    env: HashMap<String, String>,
+    secrets: Option<InjectedSecrets>,
+    vars: Option<SessionVars>,
+}
+
+impl BashTool {
+    pub fn new(remote: Option<Remote>) -> Self {
+        Self { remote, env: HashMap::new(), secrets: None, vars: None }
+    }
+
+    /// Variables set for every command. They are not part of the tool
+    /// definition, so the model never sees them.
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Also exports credentials fetched by `secrets_lookup`.
+    pub fn with_secrets(mut self, secrets: InjectedSecrets) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Also exports the user's `/set` variables as `CHITTI_VAR_<KEY>`.
+    pub fn with_session_vars(mut self, vars: SessionVars) -> Self {
+        self.vars = Some(vars);
+        self
+    }
+}
 
 #[async_trait]
 impl ToolExecutor for BashTool {
@@ -17,30 +53,50 @@ impl ToolExecutor for BashTool {
     fn definition(&self) -> FunctionDeclaration {
         FunctionDeclaration {
             name: self.name(),
-            description: "Execute a bash command on the local macOS system to read files, search code, or manage system state.".to_string(),
-            parameters: Some(json!({
-                "type": "object",
-                "properties": {
-                    "command": {
-                        "type": "string",
-                        "description": "The full bash command to execute (e.g., 'ls -la' or 'rg search_term')."
-                    }
-                },
-                "required": ["command"]
-            })),
+            description: match &self.remote {
+                Some(remote) => format!(
+                    "Execute a bash command on the remote host {} (over SSH) to read files, search code, or manage system state.",
+                    remote.destination()
+                ),
+                None => "Execute a bash command on the local macOS system to read files, search code, or manage system state.".to_string(),
+            },
+            parameters: Some(Params::object()
+                .string("command", "The full bash command to execute (e.g., 'ls -la' or 'rg search_term').")
+                .required(&["command"])
+                .build()),
         }
     }
 
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        args.get("command").and_then(|v| v.as_str()).map(files_in_command).unwrap_or_default()
+    }
+
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
         let command_str = args.get("command")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'command' argument"))?;
 
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(command_str)
-            .output()
-            .await?;
+        let mut env = self.env.clone();
+        if let Some(vars) = &self.vars {
+            env.extend(vars.env());
+        }
+        if let Some(secrets) = &self.secrets {
+            env.extend(secrets.snapshot());
+        }
+        let output = match &self.remote {
+            Some(remote) => remote.output(command_str, &env).await?,
+            None => Command::new("bash")
+                .arg("-c")
+                .arg(command_str)
+                .envs(&env)
+                .kill_on_drop(true)
+                .output()
+                .await?,
+        };
 
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -57,3 +113,84 @@ impl ToolExecutor for BashTool {
         })
     }
 }
+
+/// Best-effort guess at the files a shell command reads and writes: redirections,
+/// plus the path arguments of common file commands. Anything it can't recognise
+/// is left out rather than guessed.
+pub fn files_in_command(command: &str) -> Vec<(String, FileAccess)> {
+    let mut files = Vec::new();
+    for segment in command.split(['|', ';', '&', '\n']) {
+        let tokens: Vec<String> = segment.split_whitespace()
+            .map(|t| t.trim_matches(|c| c == '\'' || c == '"').to_string())
+            .collect();
+        let mut words = Vec::new();
+        let mut iter = tokens.iter().peekable();
+        while let Some(token) = iter.next() {
+            let redirect = [(">>", FileAccess::Write), (">", FileAccess::Write), ("<", FileAccess::Read)]
+                .into_iter()
+                .find(|(op, _)| token.trim_start_matches(['1', '2']).starts_with(op));
+            match redirect {
+                Some((op, access)) => {
+                    let target = token.trim_start_matches(['1', '2'])[op.len()..].to_string();
+                    let target = if target.is_empty() { iter.next().cloned().unwrap_or_default() } else { target };
+                    if looks_like_path(&target) {
+                        files.push((target, access));
+                    }
+                }
+                None => words.push(token.as_str()),
+            }
+        }
+        // Skip environment assignments and sudo before the program name.
+        let start = words.iter().position(|w| !w.contains('=') && *w != "sudo").unwrap_or(words.len());
+        let Some((program, rest)) = words[start..].split_first() else {
+            continue;
+        };
+        let in_place = rest.iter().any(|a| *a == "-i" || a.starts_with("-i.") || *a == "--in-place");
+        let args: Vec<&str> = rest.iter().copied().filter(|a| !a.starts_with('-')).collect();
+        let (skip, access) = match *program {
+            "cat" | "head" | "tail" | "less" | "more" | "wc" | "bat" | "nl" | "diff" | "stat" | "file" => (0, FileAccess::Read),
+            "grep" | "rg" | "awk" | "jq" => (1, FileAccess::Read),
+            "sed" if in_place => (1, FileAccess::Write),
+            "sed" => (1, FileAccess::Read),
+            "tee" | "touch" | "rm" | "truncate" | "mv" => (0, FileAccess::Write),
+            "cp" => {
+                if let Some((dest, sources)) = args.split_last() {
+                    files.extend(sources.iter().filter(|a| looks_like_path(a)).map(|a| (a.to_string(), FileAccess::Read)));
+                    if looks_like_path(dest) {
+                        files.push((dest.to_string(), FileAccess::Write));
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        files.extend(args.iter().skip(skip).filter(|a| looks_like_path(a)).map(|a| (a.to_string(), access)));
+    }
+    files
+}
+
+fn looks_like_path(token: &str) -> bool {
+    !token.is_empty()
+        && !token.starts_with("/dev/")
+        && !token.contains(['*', '?', '$', '`', '(', ')', '{', '}'])
+        && !token.starts_with('&')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_in_command_finds_reads_and_writes() {
+        use FileAccess::*;
+        let found = |cmd: &str| files_in_command(cmd);
+        assert_eq!(found("cat src/main.rs | grep -n fn"), vec![("src/main.rs".to_string(), Read)]);
+        assert_eq!(found("rg -n 'TODO' src/lib.rs README.md"), vec![("src/lib.rs".to_string(), Read), ("README.md".to_string(), Read)]);
+        assert_eq!(found("echo hi > notes.txt 2>/dev/null"), vec![("notes.txt".to_string(), Write)]);
+        assert_eq!(found("sed -i 's/a/b/' Cargo.toml && head -5 Cargo.toml"), vec![("Cargo.toml".to_string(), Write), ("Cargo.toml".to_string(), Read)]);
+        assert_eq!(found("cp a.txt b.txt"), vec![("a.txt".to_string(), Read), ("b.txt".to_string(), Write)]);
+        assert_eq!(found("FOO=1 sudo tee -a log.txt < input.txt"), vec![("input.txt".to_string(), Read), ("log.txt".to_string(), Write)]);
+        assert!(found("ls -la && git status").is_empty());
+        assert!(found("cat *.rs").is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use base64::Engine;
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::secrets::InjectedSecrets;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
+
+const ACTIONS: &[&str] = &["navigate", "extract_text", "screenshot", "click", "fill", "close"];
+const MAX_TEXT_CHARS: usize = 40_000;
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+const LOAD_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Binaries tried, in order, when no browser is configured.
+const CANDIDATES: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+];
+
+type Sink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+type Pending = Arc<StdMutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// A headless browser and the DevTools connection to its one page.
+struct Session {
+    _child: Child,
+    /// Temporary profile directory, removed with the session.
+    temp_profile: Option<PathBuf>,
+    sink: Mutex<Sink>,
+    pending: Pending,
+    next_id: AtomicU64,
+    page: String,
+    reader: JoinHandle<()>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.reader.abort();
+        if let Some(dir) = &self.temp_profile {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+impl Session {
+    async fn launch(binary: &Path, profile: Option<&Path>) -> Result<Self> {
+        let temp_profile = match profile {
+            Some(_) => None,
+            None => Some(std::env::temp_dir().join(format!("chitti-browser-{}", uuid::Uuid::new_v4()))),
+        };
+        let profile_dir = profile.map(Path::to_path_buf).or_else(|| temp_profile.clone()).unwrap_or_default();
+        let mut child = Command::new(binary)
+            .arg("--headless=new")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile_dir.display()))
+            .args(["--no-first-run", "--no-default-browser-check", "--disable-gpu", "about:blank"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", binary.display()))?;
+
+        // Chromium announces the DevTools endpoint on stderr.
+        let mut lines = BufReader::new(child.stderr.take().context("No stderr from browser")?).lines();
+        let ws_url = tokio::time::timeout(Duration::from_secs(15), async {
+            while let Some(line) = lines.next_line().await? {
+                if let Some(url) = devtools_url(&line) {
+                    return Ok(url);
+                }
+            }
+            anyhow::bail!("Browser exited before opening DevTools")
+        }).await.context("Timed out waiting for the browser to start")??;
+        // Keep draining stderr so the browser never blocks on a full pipe.
+        tokio::spawn(async move { while let Ok(Some(_)) = lines.next_line().await {} });
+
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url.as_str()).await
+            .context("Failed to connect to the browser's DevTools endpoint")?;
+        let (sink, mut stream) = socket.split();
+        let pending: Pending = Arc::default();
+        let reader_pending = pending.clone();
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                let Ok(text) = message.into_text() else { continue };
+                let Ok(reply) = serde_json::from_str::<Value>(text.as_str()) else { continue };
+                // Events have no id; only command replies are routed.
+                let Some(id) = reply.get("id").and_then(|v| v.as_u64()) else { continue };
+                if let Some(tx) = reader_pending.lock().unwrap().remove(&id) {
+                    let result = match reply.get("error") {
+                        Some(error) => Err(error.get("message").and_then(|m| m.as_str()).unwrap_or("DevTools error").to_string()),
+                        None => Ok(reply.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = tx.send(result);
+                }
+            }
+        });
+
+        let mut session = Self {
+            _child: child,
+            temp_profile,
+            sink: Mutex::new(sink),
+            pending,
+            next_id: AtomicU64::new(1),
+            page: String::new(),
+            reader,
+        };
+        let target = session.call("Target.createTarget", json!({ "url": "about:blank" }), false).await?;
+        let target_id = target["targetId"].as_str().context("No target id from the browser")?;
+        let attached = session.call("Target.attachToTarget", json!({ "targetId": target_id, "flatten": true }), false).await?;
+        session.page = attached["sessionId"].as_str().context("No session id from the browser")?.to_string();
+        Ok(session)
+    }
+
+    /// Sends a DevTools command to the page, or to the browser when `page` is false.
+    async fn call(&self, method: &str, params: Value, page: bool) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        let mut request = json!({ "id": id, "method": method, "params": params });
+        if page {
+            request["sessionId"] = json!(self.page);
+        }
+        self.sink.lock().await.send(Message::text(request.to_string())).await
+            .context("Lost the connection to the browser")?;
+        let reply = tokio::time::timeout(COMMAND_TIMEOUT, rx).await;
+        self.pending.lock().unwrap().remove(&id);
+        match reply {
+            Ok(Ok(result)) => result.map_err(|e| anyhow::anyhow!("{} failed: {}", method, e)),
+            Ok(Err(_)) => anyhow::bail!("Lost the connection to the browser"),
+            Err(_) => anyhow::bail!("{} timed out", method),
+        }
+    }
+
+    /// The value of a JavaScript expression in the page.
+    async fn evaluate(&self, expression: &str) -> Result<Value> {
+        let result = self.call("Runtime.evaluate", json!({
+            "expression": expression,
+            "returnByValue": true,
+            "awaitPromise": true,
+        }), true).await?;
+        if let Some(exception) = result.get("exceptionDetails") {
+            let text = exception.pointer("/exception/description").or_else(|| exception.get("text"));
+            anyhow::bail!("Script error: {}", text.and_then(|t| t.as_str()).unwrap_or("unknown"));
+        }
+        Ok(result.pointer("/result/value").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Waits until the page has finished loading, then reports where it is.
+    async fn settle(&self) -> Result<Value> {
+        let deadline = tokio::time::Instant::now() + LOAD_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            if self.evaluate("document.readyState").await.ok().as_ref().and_then(|v| v.as_str()) == Some("complete") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        self.evaluate("({ url: location.href, title: document.title })").await
+    }
+}
+
+/// The `ws://` endpoint in Chromium's "DevTools listening on ..." line.
+pub fn devtools_url(line: &str) -> Option<String> {
+    line.trim().strip_prefix("DevTools listening on ")
+        .filter(|url| url.starts_with("ws://"))
+        .map(str::to_string)
+}
+
+/// The script for a DOM action; selector and value are embedded as JSON
+/// string literals, so they can't break out of the script.
+pub fn script(action: &str, selector: Option<&str>, value: &str) -> String {
+    let selector = serde_json::to_string(&selector).unwrap_or_default();
+    let value = serde_json::to_string(value).unwrap_or_default();
+    match action {
+        "extract_text" => format!(
+            "(() => {{ const s = {selector}; const el = s ? document.querySelector(s) : document.body; return el ? el.innerText : null; }})()"
+        ),
+        "click" => format!(
+            "(() => {{ const el = document.querySelector({selector}); if (!el) return false; el.scrollIntoView({{ block: 'center' }}); el.click(); return true; }})()"
+        ),
+        // The native setter keeps frameworks like React in sync with the new value.
+        "fill" => format!(
+            "(() => {{ const el = document.querySelector({selector}); if (!el) return false; el.focus(); \
+             const proto = el instanceof HTMLTextAreaElement ? HTMLTextAreaElement.prototype : el instanceof HTMLSelectElement ? HTMLSelectElement.prototype : HTMLInputElement.prototype; \
+             Object.getOwnPropertyDescriptor(proto, 'value').set.call(el, {value}); \
+             el.dispatchEvent(new Event('input', {{ bubbles: true }})); el.dispatchEvent(new Event('change', {{ bubbles: true }})); return true; }})()"
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Drives a headless Chromium over the DevTools protocol for pages that
+/// need JavaScript or a login. One browser is started on first use and kept
+/// for the session. Clicking and filling always ask for approval.
+pub struct BrowserTool {
+    binary: PathBuf,
+    profile: Option<PathBuf>,
+    secrets: Option<InjectedSecrets>,
+    session: Mutex<Option<Session>>,
+}
+
+impl BrowserTool {
+    /// `profile` keeps cookies and logins between sessions; without it each
+    /// session starts from a fresh, temporary profile.
+    pub fn new(binary: PathBuf, profile: Option<PathBuf>) -> Self {
+        Self { binary, profile, secrets: None, session: Mutex::new(None) }
+    }
+
+    /// The tool with `binary`, or the first Chrome/Chromium found.
+    pub fn detect(binary: Option<PathBuf>, profile: Option<PathBuf>) -> Option<Self> {
+        if let Some(binary) = binary {
+            return Some(Self::new(binary, profile));
+        }
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        CANDIDATES.iter()
+            .find_map(|name| {
+                let candidate = Path::new(name);
+                if candidate.is_absolute() {
+                    candidate.is_file().then(|| candidate.to_path_buf())
+                } else {
+                    dirs.iter().map(|d| d.join(name)).find(|p| p.is_file())
+                }
+            })
+            .map(|binary| Self::new(binary, profile))
+    }
+
+    /// Lets `fill` take `$VAR` values looked up with `secrets_lookup`, so
+    /// passwords reach the page without passing through the model.
+    pub fn with_secrets(mut self, secrets: InjectedSecrets) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    fn fill_value(&self, value: &str) -> String {
+        let secret = value.strip_prefix('$')
+            .and_then(|var| self.secrets.as_ref()?.snapshot().remove(var));
+        secret.unwrap_or_else(|| value.to_string())
+    }
+
+    async fn run(&self, action: &str, args: &HashMap<String, Value>) -> Result<Value> {
+        let get = |key: &str| args.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+        let mut guard = self.session.lock().await;
+        if action == "close" {
+            let was_open = guard.take().is_some();
+            return Ok(json!({ "closed": was_open }));
+        }
+        if guard.is_none() {
+            *guard = Some(Session::launch(&self.binary, self.profile.as_deref()).await?);
+        }
+        let session = guard.as_ref().unwrap();
+        let selector = get("selector");
+
+        match action {
+            "navigate" => {
+                let url = get("url").context("'navigate' needs a 'url'")?;
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    anyhow::bail!("Only http(s) URLs can be opened");
+                }
+                session.call("Page.enable", json!({}), true).await?;
+                let result = session.call("Page.navigate", json!({ "url": url }), true).await?;
+                if let Some(error) = result.get("errorText").and_then(|e| e.as_str()) {
+                    anyhow::bail!("Navigation failed: {}", error);
+                }
+                session.settle().await
+            }
+            "extract_text" => {
+                let text = session.evaluate(&script("extract_text", selector, "")).await?;
+                let Some(text) = text.as_str() else {
+                    anyhow::bail!("No element matches '{}'", selector.unwrap_or("body"));
+                };
+                let total = text.chars().count();
+                Ok(json!({
+                    "text": text.chars().take(MAX_TEXT_CHARS).collect::<String>(),
+                    "truncated": total > MAX_TEXT_CHARS,
+                }))
+            }
+            "screenshot" => {
+                let full_page = args.get("full_page").and_then(|v| v.as_bool()).unwrap_or(false);
+                let shot = session.call("Page.captureScreenshot", json!({ "format": "png", "captureBeyondViewport": full_page }), true).await?;
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(shot["data"].as_str().context("No screenshot data")?)?;
+                let path = get("path").map(PathBuf::from).unwrap_or_else(|| {
+                    std::env::temp_dir().join(format!("chitti-screenshot-{}.png", uuid::Uuid::new_v4()))
+                });
+                tokio::fs::write(&path, &data).await
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(json!({ "path": path.display().to_string(), "bytes": data.len() }))
+            }
+            "click" | "fill" => {
+                let selector = selector.with_context(|| format!("'{}' needs a 'selector'", action))?;
+                let value = self.fill_value(get("value").unwrap_or(""));
+                if session.evaluate(&script(action, Some(selector), &value)).await? != json!(true) {
+                    anyhow::bail!("No element matches '{}'", selector);
+                }
+                session.settle().await
+            }
+            other => anyhow::bail!("Unknown action '{}'", other),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for BrowserTool {
+    fn name(&self) -> String {
+        "browser".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Control a headless Chromium for pages that need JavaScript or a login: navigate to a URL, extract the rendered text, take a screenshot, click elements and fill form fields (the user approves every click and fill). The page stays open between calls until 'close'.".to_string(),
+            parameters: Some(Params::object()
+                .one_of("action", "What to do.", ACTIONS)
+                .string("url", "For navigate: the http(s) URL to open.")
+                .string("selector", "CSS selector of the element to read, click or fill (extract_text defaults to the whole page).")
+                .string("value", "For fill: the text to enter. \"$VAR\" uses a credential from secrets_lookup without revealing it.")
+                .boolean("full_page", "For screenshot: capture the whole page, not just the viewport.")
+                .string("path", "For screenshot: where to save the PNG (default: a temp file).")
+                .required(&["action"])
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, args: &HashMap<String, Value>) -> bool {
+        matches!(args.get("action").and_then(|v| v.as_str()), Some("navigate" | "extract_text" | "close"))
+            || (args.get("action").and_then(|v| v.as_str()) == Some("screenshot") && !args.contains_key("path"))
+    }
+
+    fn always_confirm(&self, args: &HashMap<String, Value>) -> bool {
+        matches!(args.get("action").and_then(|v| v.as_str()), Some("click" | "fill"))
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        match (args.get("action").and_then(|v| v.as_str()), args.get("path").and_then(|v| v.as_str())) {
+            (Some("screenshot"), Some(path)) => vec![(path.to_string(), FileAccess::Write)],
+            _ => Vec::new(),
+        }
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let action = args.get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' argument"))?;
+        match self.run(action, &args).await {
+            Ok(output) => Ok(ToolResult { output, is_error: false }),
+            Err(e) => Ok(ToolResult { output: json!({ "error": format!("{:#}", e) }), is_error: true }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_scripts_and_approval() {
+        assert_eq!(
+            devtools_url("DevTools listening on ws://127.0.0.1:40123/devtools/browser/abc\n").as_deref(),
+            Some("ws://127.0.0.1:40123/devtools/browser/abc")
+        );
+        assert_eq!(devtools_url("[1234:5678:ERROR] something else"), None);
+
+        // Quotes in selectors and values stay inside string literals.
+        let fill = script("fill", Some("input[name=\"q\"]"), "it's \"quoted\"");
+        assert!(fill.contains(r#"document.querySelector("input[name=\"q\"]")"#));
+        assert!(fill.contains(r#"set.call(el, "it's \"quoted\"")"#));
+        assert!(script("extract_text", None, "").contains("const s = null;"));
+
+        let secrets = InjectedSecrets::default();
+        secrets.insert("CHITTI_SECRET_PW", "s3cret-value".to_string());
+        let tool = BrowserTool::new(PathBuf::from("chromium"), None).with_secrets(secrets);
+        assert_eq!(tool.fill_value("$CHITTI_SECRET_PW"), "s3cret-value");
+        assert_eq!(tool.fill_value("$NOT_SET"), "$NOT_SET");
+
+        let call = |action: &str| -> HashMap<String, Value> { [("action".to_string(), json!(action))].into() };
+        assert!(tool.read_only(&call("extract_text")) && !tool.always_confirm(&call("navigate")));
+        assert!(!tool.read_only(&call("click")) && tool.always_confirm(&call("fill")));
+    }
+}
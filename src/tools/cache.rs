@@ -0,0 +1,95 @@
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Session-scoped cache of tool results keyed by tool, canonical args and the
+/// state of the files the call depends on.
+#[derive(Default)]
+pub struct ToolCache {
+    entries: Mutex<HashMap<u64, Value>>,
+}
+
+impl ToolCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: u64) -> Option<Value> {
+        self.entries.lock().unwrap().get(&key).cloned()
+    }
+
+    pub fn insert(&self, key: u64, output: Value) {
+        self.entries.lock().unwrap().insert(key, output);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Builds the content-addressed key for a call. Object keys are sorted so
+/// argument order doesn't matter; dependency paths contribute their size and mtime.
+pub fn cache_key(tool: &str, args: &HashMap<String, Value>, dependencies: &[PathBuf]) -> u64 {
+    let canonical: BTreeMap<&String, String> = args.iter().map(|(k, v)| (k, v.to_string())).collect();
+    let mut hasher = DefaultHasher::new();
+    tool.hash(&mut hasher);
+    canonical.hash(&mut hasher);
+    workspace_state(dependencies).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn workspace_state(dependencies: &[PathBuf]) -> Vec<(PathBuf, u64, u128)> {
+    dependencies
+        .iter()
+        .map(|path| {
+            let (len, modified) = std::fs::metadata(path)
+                .map(|m| {
+                    let modified = m.modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_nanos())
+                        .unwrap_or(0);
+                    (m.len(), modified)
+                })
+                .unwrap_or((0, 0));
+            (path.clone(), len, modified)
+        })
+        .collect()
+}
+
+/// The payload sent instead of repeating an identical result.
+pub fn unchanged_marker() -> Value {
+    json!({
+        "unchanged": true,
+        "note": "Identical to the result of an earlier call with the same arguments in this conversation; the underlying content has not changed since.",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_ignores_arg_order_and_tracks_files() {
+        let mut a = HashMap::new();
+        a.insert("x".to_string(), json!(1));
+        a.insert("y".to_string(), json!("two"));
+        let mut b = HashMap::new();
+        b.insert("y".to_string(), json!("two"));
+        b.insert("x".to_string(), json!(1));
+        assert_eq!(cache_key("t", &a, &[]), cache_key("t", &b, &[]));
+        assert_ne!(cache_key("t", &a, &[]), cache_key("u", &a, &[]));
+
+        let path = std::env::temp_dir().join(format!("chitti-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "one").unwrap();
+        let before = cache_key("t", &a, std::slice::from_ref(&path));
+        std::fs::write(&path, "changed").unwrap();
+        let after = cache_key("t", &a, std::slice::from_ref(&path));
+        std::fs::remove_file(&path).unwrap();
+        assert_ne!(before, after);
+    }
+}
@@ -0,0 +1,384 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tokio::process::Command;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::workspace::PathResolver;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
+
+/// Converts documents between formats with `pandoc` (markdown to docx, PDF,
+/// HTML, ...), so written deliverables can be produced as part of a task.
+/// Without pandoc only markdown and HTML convert, using the built-in
+/// converter below.
+pub struct ConvertDocumentTool;
+
+/// The pandoc format name for a file extension.
+pub fn format_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "md" | "markdown" => "markdown",
+        "html" | "htm" => "html",
+        "docx" => "docx",
+        "odt" => "odt",
+        "pdf" => "pdf",
+        "rtf" => "rtf",
+        "epub" => "epub",
+        "tex" => "latex",
+        "rst" => "rst",
+        "txt" => "plain",
+        "pptx" => "pptx",
+        _ => return None,
+    })
+}
+
+fn pandoc_available() -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("pandoc").is_file()))
+}
+
+#[async_trait]
+impl ToolExecutor for ConvertDocumentTool {
+    fn name(&self) -> String {
+        "convert_document".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Convert a document to another format, e.g. a markdown report to docx or PDF. Formats follow the file extensions (md, html, docx, odt, pdf, rtf, epub, tex, rst, txt, pptx). Uses pandoc when installed; otherwise only markdown <-> HTML is supported.".to_string(),
+            parameters: Some(Params::object()
+                .string("input", "Path of the document to convert.")
+                .string("output", "Path to write; its extension picks the target format.")
+                .string("title", "Document title for formats that have one.")
+                .required(&["input", "output"])
+                .build()),
+        }
+    }
+
+    /// Converting an unchanged input again would write the same output.
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    fn cache_dependencies(&self, args: &HashMap<String, Value>) -> Vec<PathBuf> {
+        args.get("input").and_then(|v| v.as_str())
+            .map(|p| vec![PathBuf::from(p)])
+            .unwrap_or_default()
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        false
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        let mut files = Vec::new();
+        if let Some(input) = args.get("input").and_then(|v| v.as_str()) {
+            files.push((input.to_string(), FileAccess::Read));
+        }
+        if let Some(output) = args.get("output").and_then(|v| v.as_str()) {
+            files.push((output.to_string(), FileAccess::Write));
+        }
+        files
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let get = |key: &str| args.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+        let input = get("input").ok_or_else(|| anyhow::anyhow!("Missing 'input' argument"))?;
+        let output = get("output").ok_or_else(|| anyhow::anyhow!("Missing 'output' argument"))?;
+        let title = get("title");
+        let refuse = |message: String| Ok(ToolResult { output: json!({ "error": message }), is_error: true });
+
+        let resolved = match PathResolver::current().resolve(input) {
+            Ok(resolved) => resolved,
+            Err(missing) => return Ok(ToolResult { output: missing.to_json(), is_error: true }),
+        };
+        let resolved_path = resolved.corrected.then(|| resolved.path.display().to_string());
+        let input = resolved.path.as_path();
+
+        let (from, to) = match (format_for(input), format_for(Path::new(output))) {
+            (Some(from), Some(to)) => (from, to),
+            (None, _) => return refuse(format!("Unknown input format for {}", input.display())),
+            (_, None) => return refuse(format!("Unknown output format for {}", output)),
+        };
+        if from == "pdf" {
+            return refuse("PDF can only be an output format".to_string());
+        }
+
+        if pandoc_available() {
+            let mut command = Command::new("pandoc");
+            command.arg("--standalone").arg("--from").arg(from).arg("--output").arg(output);
+            if to != "pdf" {
+                command.arg("--to").arg(to);
+            }
+            if let Some(title) = title {
+                command.arg("--metadata").arg(format!("title={}", title));
+            }
+            let result = command.arg("--").arg(input).kill_on_drop(true).output().await
+                .context("Failed to run pandoc")?;
+            if !result.status.success() {
+                let stderr = String::from_utf8_lossy(&result.stderr);
+                return refuse(format!("pandoc failed: {}", stderr.trim()));
+            }
+            return Ok(ToolResult { output: json!({ "output": output, "converter": "pandoc", "resolved_input": resolved_path }), is_error: false });
+        }
+
+        let source = tokio::fs::read_to_string(input).await
+            .with_context(|| format!("Failed to read {}", input.display()))?;
+        let converted = match (from, to) {
+            ("markdown", "html") => markdown_to_html(&source, title),
+            ("html", "markdown") => html_to_markdown(&source),
+            _ => return refuse(format!("Converting {} to {} needs pandoc, which isn't installed", from, to)),
+        };
+        tokio::fs::write(output, converted).await
+            .with_context(|| format!("Failed to write {}", output))?;
+        Ok(ToolResult { output: json!({ "output": output, "converter": "built-in", "resolved_input": resolved_path }), is_error: false })
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+static IMAGE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"!\[([^\]]*)\]\(([^)\s]+)\)").unwrap());
+static LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+static BOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*([^*]+)\*\*|__([^_]+)__").unwrap());
+static ITALIC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*([^*]+)\*|\b_([^_]+)_\b").unwrap());
+static ORDERED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+[.)]\s+").unwrap());
+
+/// Emphasis, links, images and code spans within one block.
+fn inline(text: &str) -> String {
+    // Odd segments are code spans, which get no further formatting.
+    text.split('`').enumerate().map(|(i, part)| {
+        if i % 2 == 1 {
+            return format!("<code>{}</code>", escape(part));
+        }
+        let html = escape(part);
+        let html = IMAGE.replace_all(&html, r#"<img alt="$1" src="$2">"#);
+        let html = LINK.replace_all(&html, r#"<a href="$2">$1</a>"#);
+        let html = BOLD.replace_all(&html, "<strong>$1$2</strong>");
+        ITALIC.replace_all(&html, "<em>$1$2</em>").into_owned()
+    }).collect()
+}
+
+/// A small CommonMark subset: headings, paragraphs, lists, quotes, rules and
+/// fenced code, rendered as a standalone HTML page.
+pub fn markdown_to_html(markdown: &str, title: Option<&str>) -> String {
+    let mut body = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut fence: Option<String> = None;
+    let mut first_heading = None;
+
+    fn flush(body: &mut String, paragraph: &mut Vec<&str>, list: &mut Option<&str>) {
+        if !paragraph.is_empty() {
+            body.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+        if let Some(tag) = list.take() {
+            body.push_str(&format!("</{}>\n", tag));
+        }
+    }
+
+    for line in markdown.lines() {
+        if let Some(code) = &mut fence {
+            if line.trim_start().starts_with("```") {
+                body.push_str(&format!("{}</code></pre>\n", escape(code)));
+                fence = None;
+            } else {
+                code.push_str(line);
+                code.push('\n');
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            flush(&mut body, &mut paragraph, &mut list);
+            match lang.trim() {
+                "" => body.push_str("<pre><code>"),
+                lang => body.push_str(&format!("<pre><code class=\"language-{}\">", escape(lang))),
+            }
+            fence = Some(String::new());
+        } else if trimmed.is_empty() {
+            flush(&mut body, &mut paragraph, &mut list);
+        } else if trimmed.starts_with('#') && trimmed.trim_start_matches('#').starts_with(' ') && trimmed.len() - trimmed.trim_start_matches('#').len() <= 6 {
+            flush(&mut body, &mut paragraph, &mut list);
+            let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+            let text = trimmed[level..].trim();
+            first_heading.get_or_insert_with(|| text.to_string());
+            body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline(text)));
+        } else if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-' || c == '*' || c == '_') {
+            flush(&mut body, &mut paragraph, &mut list);
+            body.push_str("<hr>\n");
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            flush(&mut body, &mut paragraph, &mut list);
+            body.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", inline(quote.trim())));
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| trimmed.strip_prefix(m)).map(|i| ("ul", i))
+            .or_else(|| ORDERED.find(trimmed).map(|m| ("ol", &trimmed[m.end()..])))
+        {
+            let (tag, text) = item;
+            if list != Some(tag) {
+                flush(&mut body, &mut paragraph, &mut list);
+                body.push_str(&format!("<{}>\n", tag));
+                list = Some(tag);
+            }
+            body.push_str(&format!("<li>{}</li>\n", inline(text.trim())));
+        } else {
+            if list.is_some() {
+                flush(&mut body, &mut paragraph, &mut list);
+            }
+            paragraph.push(trimmed);
+        }
+    }
+    if let Some(code) = fence {
+        body.push_str(&format!("{}</code></pre>\n", escape(&code)));
+    }
+    flush(&mut body, &mut paragraph, &mut list);
+
+    let title = title.map(str::to_string).or(first_heading).unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(&title), body
+    )
+}
+
+static DROPPED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<(script|style|head)\b.*?</(script|style|head)>|<!--.*?-->|<![^>]*>").unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<(/?)([a-zA-Z][a-zA-Z0-9]*)([^>]*)>").unwrap());
+static HREF: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?i)\b(href|src|alt)\s*=\s*"([^"]*)""#).unwrap());
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+static SPACES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+fn unescape(text: &str) -> String {
+    text.replace("&nbsp;", " ").replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&quot;", "\"").replace("&#39;", "'").replace("&amp;", "&")
+}
+
+/// Converts the common structural tags to markdown and drops the rest.
+pub fn html_to_markdown(html: &str) -> String {
+    let html = DROPPED.replace_all(html, "");
+    let mut out = String::new();
+    let mut href: Option<String> = None;
+    let mut ordered: Vec<Option<usize>> = Vec::new();
+    let mut pre = false;
+    let mut last = 0;
+
+    for caps in TAG.captures_iter(&html) {
+        let whole = caps.get(0).unwrap();
+        let text = &html[last..whole.start()];
+        last = whole.end();
+        if pre {
+            out.push_str(&unescape(text));
+        } else {
+            out.push_str(&unescape(&SPACES.replace_all(text, " ")));
+        }
+
+        let closing = &caps[1] == "/";
+        let tag = caps[2].to_ascii_lowercase();
+        let attr = |name: &str| HREF.captures_iter(&caps[3])
+            .find(|c| c[1].eq_ignore_ascii_case(name))
+            .map(|c| unescape(&c[2]));
+        match (tag.as_str(), closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                let level = tag[1..].parse().unwrap_or(1);
+                out.push_str(&format!("\n\n{} ", "#".repeat(level)));
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "blockquote" | "table", true) => out.push_str("\n\n"),
+            ("p" | "div" | "table", false) => out.push_str("\n\n"),
+            ("blockquote", false) => out.push_str("\n\n> "),
+            ("br", _) => out.push('\n'),
+            ("tr", true) => out.push('\n'),
+            ("td" | "th", true) => out.push_str(" | "),
+            ("hr", _) => out.push_str("\n\n---\n\n"),
+            ("strong" | "b", _) => out.push_str("**"),
+            ("em" | "i", _) => out.push('*'),
+            ("code", _) if !pre => out.push('`'),
+            ("pre", false) => {
+                out.push_str("\n\n```\n");
+                pre = true;
+            }
+            ("pre", true) => {
+                if !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str("```\n\n");
+                pre = false;
+            }
+            ("ul", false) => ordered.push(None),
+            ("ol", false) => ordered.push(Some(0)),
+            ("ul" | "ol", true) => {
+                ordered.pop();
+                out.push('\n');
+            }
+            ("li", false) => {
+                let indent = "  ".repeat(ordered.len().saturating_sub(1));
+                match ordered.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        out.push_str(&format!("\n{}{}. ", indent, n));
+                    }
+                    _ => out.push_str(&format!("\n{}- ", indent)),
+                }
+            }
+            ("a", false) => {
+                href = attr("href");
+                if href.is_some() {
+                    out.push('[');
+                }
+            }
+            ("a", true) => {
+                if let Some(url) = href.take() {
+                    out.push_str(&format!("]({})", url));
+                }
+            }
+            ("img", _) => {
+                if let Some(src) = attr("src") {
+                    out.push_str(&format!("![{}]({})", attr("alt").unwrap_or_default(), src));
+                }
+            }
+            _ => {}
+        }
+    }
+    out.push_str(&unescape(&SPACES.replace_all(&html[last..], " ")));
+
+    let lines: Vec<&str> = out.lines().map(str::trim_end).collect();
+    let text = BLANK_LINES.replace_all(&lines.join("\n"), "\n\n").trim().to_string();
+    // Lines that only had whitespace between block tags start with a space.
+    let text: Vec<&str> = text.lines().map(|l| if l.trim().is_empty() { "" } else { l.strip_prefix(' ').unwrap_or(l) }).collect();
+    format!("{}\n", BLANK_LINES.replace_all(&text.join("\n"), "\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_markdown_html_conversion() {
+        let markdown = "# Report\n\nSales **grew** by *10%*, see [the data](https://x.test/d?a=1&b=2).\n\n- one\n- `two < three`\n\n1. first\n2. second\n\n```rust\nfn main() {}\n```\n";
+        let html = markdown_to_html(markdown, None);
+        assert!(html.contains("<title>Report</title>"));
+        assert!(html.contains("<h1>Report</h1>"));
+        assert!(html.contains("<p>Sales <strong>grew</strong> by <em>10%</em>, see <a href=\"https://x.test/d?a=1&amp;b=2\">the data</a>.</p>"));
+        assert!(html.contains("<ul>\n<li>one</li>\n<li><code>two &lt; three</code></li>\n</ul>"));
+        assert!(html.contains("<ol>\n<li>first</li>\n<li>second</li>\n</ol>"));
+        assert!(html.contains("<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"));
+
+        assert_eq!(html_to_markdown(&html), "# Report\n\nSales **grew** by *10%*, see [the data](https://x.test/d?a=1&b=2).\n\n- one\n- `two < three`\n\n1. first\n2. second\n\n```\nfn main() {}\n```\n");
+
+        assert_eq!(format_for(Path::new("out/Report.DOCX")), Some("docx"));
+        assert_eq!(format_for(Path::new("notes")), None);
+    }
+
+    #[test]
+    fn test_conversions_are_cached_against_the_input_file() {
+        let args = HashMap::from([
+            ("input".to_string(), json!("report.md")),
+            ("output".to_string(), json!("report.docx")),
+        ]);
+        assert!(ConvertDocumentTool.cacheable());
+        assert_eq!(ConvertDocumentTool.cache_dependencies(&args), [PathBuf::from("report.md")]);
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::redact;
+
+/// Where a secret's value comes from, so the config file itself holds no secrets.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretRef {
+    /// A variable in Chitti's own environment (or `.env`).
+    Env(String),
+    /// A file whose trimmed contents are the value.
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Literal(String),
+    Secret(SecretRef),
+}
+
+/// Per-tool environment variables, keyed by tool name:
+///
+/// ```yaml
+/// execute_bash:
+///   AWS_PROFILE: homelab
+///   GITHUB_TOKEN: { env: GH_TOKEN }
+///   VAULT_TOKEN: { file: ~/.vault-token }
+/// ```
+pub type ToolEnvConfig = HashMap<String, HashMap<String, EnvValue>>;
+
+/// Resolved variables per tool.
+pub type ToolEnv = HashMap<String, HashMap<String, String>>;
+
+pub fn load(path: &Path) -> Result<ToolEnv> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read tool env file {}", path.display()))?;
+    let config: ToolEnvConfig = serde_yaml::from_str(&text)
+        .with_context(|| format!("Failed to parse tool env file {}", path.display()))?;
+    resolve(config)
+}
+
+/// Resolves secret references. Secret values are registered for redaction so
+/// they are masked in logs, debug events and tool output sent to the model.
+pub fn resolve(config: ToolEnvConfig) -> Result<ToolEnv> {
+    let mut resolved = ToolEnv::new();
+    for (tool, vars) in config {
+        let mut env = HashMap::new();
+        for (name, value) in vars {
+            let value = value.resolve()
+                .with_context(|| format!("{} for tool '{}'", name, tool))?;
+            env.insert(name, value);
+        }
+        resolved.insert(tool, env);
+    }
+    Ok(resolved)
+}
+
+impl EnvValue {
+    /// The value, reading secrets from their source and registering them for redaction.
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            EnvValue::Literal(value) => Ok(value.clone()),
+            EnvValue::Secret(secret) => {
+                let value = match secret {
+                    SecretRef::Env(var) => std::env::var(var)
+                        .with_context(|| format!("refers to unset variable {}", var))?,
+                    SecretRef::File(path) => std::fs::read_to_string(expand_home(path))
+                        .with_context(|| format!("refers to unreadable file {}", path.display()))?
+                        .trim()
+                        .to_string(),
+                };
+                redact::register_secret(&value);
+                Ok(value)
+            }
+        }
+    }
+}
+
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_literals_and_secret_references() -> Result<()> {
+        let secret_file = std::env::temp_dir().join(format!("chitti-tool-env-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&secret_file, "file-secret-value\n")?;
+        std::env::set_var("CHITTI_TEST_TOOL_TOKEN", "env-secret-value");
+
+        let yaml = format!(
+            "execute_bash:\n  PROFILE: homelab\n  TOKEN: {{ env: CHITTI_TEST_TOOL_TOKEN }}\n  VAULT: {{ file: {} }}\n",
+            secret_file.display()
+        );
+        let env = resolve(serde_yaml::from_str(&yaml)?)?;
+        let bash = &env["execute_bash"];
+        assert_eq!(bash["PROFILE"], "homelab");
+        assert_eq!(bash["TOKEN"], "env-secret-value");
+        assert_eq!(bash["VAULT"], "file-secret-value");
+        assert_eq!(redact::redact("token=env-secret-value"), "token=[REDACTED]");
+        assert_eq!(redact::redact("profile=homelab"), "profile=homelab");
+
+        let missing: ToolEnvConfig = serde_yaml::from_str("execute_bash:\n  X: { env: CHITTI_TEST_UNSET_VAR }\n")?;
+        assert!(resolve(missing).is_err());
+
+        std::env::remove_var("CHITTI_TEST_TOOL_TOKEN");
+        std::fs::remove_file(&secret_file)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+
+const SUMMARY_CHARS: usize = 400;
+/// Seen item ids are forgotten after this long, keeping the state file small.
+const SEEN_RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedItem {
+    pub feed: String,
+    pub title: String,
+    pub link: Option<String>,
+    /// The guid/id, or the link when the feed has none.
+    pub id: String,
+    /// Seconds since the epoch.
+    pub published: Option<u64>,
+    pub summary: String,
+}
+
+impl FeedItem {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "feed": self.feed,
+            "title": self.title,
+            "link": self.link,
+            "published": self.published.and_then(|s| chrono::DateTime::from_timestamp(s as i64, 0)).map(|d| d.to_rfc3339()),
+            "summary": self.summary,
+        })
+    }
+}
+
+/// Items of an RSS 2.0 or Atom document, newest first.
+pub fn parse_feed(xml: &str) -> Result<(String, Vec<FeedItem>)> {
+    let doc = roxmltree::Document::parse(xml).context("Feed is not valid XML")?;
+    let root = doc.root_element();
+    let child_text = |node: roxmltree::Node, name: &str| -> Option<String> {
+        node.children()
+            .find(|c| c.is_element() && c.tag_name().name() == name)
+            .map(|c| c.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect::<String>().trim().to_string())
+            .filter(|t| !t.is_empty())
+    };
+
+    let (container, item_tag) = match root.tag_name().name() {
+        "feed" => (root, "entry"),
+        "rss" | "RDF" => (
+            root.children().find(|c| c.tag_name().name() == "channel").unwrap_or(root),
+            "item",
+        ),
+        other => anyhow::bail!("Not an RSS or Atom feed (root element <{}>)", other),
+    };
+    let feed_title = child_text(container, "title").unwrap_or_default();
+    // RSS 1.0 puts items next to the channel rather than inside it.
+    let items = if root.tag_name().name() == "RDF" { root } else { container };
+
+    let mut out: Vec<FeedItem> = items.children()
+        .filter(|c| c.is_element() && c.tag_name().name() == item_tag)
+        .map(|item| {
+            let link = item.children()
+                .find(|c| c.is_element() && c.tag_name().name() == "link" && c.attribute("rel").is_none_or(|r| r == "alternate"))
+                .and_then(|l| l.attribute("href").map(str::to_string).or_else(|| l.text().map(|t| t.trim().to_string())))
+                .filter(|l| !l.is_empty());
+            let title = child_text(item, "title").unwrap_or_else(|| "(untitled)".to_string());
+            let id = child_text(item, "guid").or_else(|| child_text(item, "id"))
+                .or_else(|| link.clone())
+                .unwrap_or_else(|| title.clone());
+            let published = ["pubDate", "published", "updated", "date"].iter()
+                .find_map(|tag| child_text(item, tag))
+                .and_then(|d| parse_date(&d));
+            let summary = ["description", "summary", "content"].iter()
+                .find_map(|tag| child_text(item, tag))
+                .map(|s| plain_summary(&s))
+                .unwrap_or_default();
+            FeedItem { feed: feed_title.clone(), title, link, id, published, summary }
+        })
+        .collect();
+    out.sort_by_key(|i| std::cmp::Reverse(i.published));
+    Ok((feed_title, out))
+}
+
+/// RFC 2822 (RSS) or RFC 3339 (Atom) dates.
+fn parse_date(date: &str) -> Option<u64> {
+    chrono::DateTime::parse_from_rfc2822(date)
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(date))
+        .ok()
+        .and_then(|d| u64::try_from(d.timestamp()).ok())
+}
+
+/// Descriptions are often HTML; keep a short plain-text preview.
+fn plain_summary(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&quot;", "\"").replace("&#39;", "'").replace("&nbsp;", " ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > SUMMARY_CHARS {
+        format!("{}…", text.chars().take(SUMMARY_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Fetches every feed; one failing feed doesn't sink the rest.
+pub async fn fetch_all(http: &reqwest::Client, urls: &[String]) -> (Vec<FeedItem>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for url in urls {
+        let result = async {
+            let body = http.get(url).timeout(Duration::from_secs(20)).send().await?
+                .error_for_status()?
+                .text().await?;
+            parse_feed(&body)
+        }.await;
+        match result {
+            Ok((_, feed_items)) => items.extend(feed_items),
+            Err(e) => {
+                warn!(feed = %url, "Failed to fetch feed: {:#}", e);
+                errors.push(format!("{}: {:#}", url, e));
+            }
+        }
+    }
+    (items, errors)
+}
+
+/// Item ids already delivered, so only new items reach the next digest.
+pub struct SeenItems {
+    path: Option<PathBuf>,
+    seen: HashMap<String, u64>,
+}
+
+impl SeenItems {
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitti").join("feeds-seen.json"))
+    }
+
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let seen = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self { path, seen }
+    }
+
+    pub fn is_new(&self, item: &FeedItem) -> bool {
+        !self.seen.contains_key(&item.id)
+    }
+
+    pub fn mark(&mut self, items: &[FeedItem]) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        for item in items {
+            self.seen.insert(item.id.clone(), now);
+        }
+        self.seen.retain(|_, at| now.saturating_sub(*at) < SEEN_RETENTION.as_secs());
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string(&self.seen)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the user's configured RSS/Atom feeds.
+pub struct FeedsTool {
+    urls: Vec<String>,
+    http: reqwest::Client,
+}
+
+impl FeedsTool {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self { urls, http: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for FeedsTool {
+    fn name(&self) -> String {
+        "feeds".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: format!(
+                "Fetch the latest items from the user's subscribed RSS/Atom feeds ({}). Returns titles, links, dates and short summaries, newest first.",
+                self.urls.join(", ")
+            ),
+            parameters: Some(Params::object()
+                .string("feed", "Only feeds whose URL contains this text.")
+                .boolean("only_new", "Skip items already included in a morning digest.")
+                .integer("limit", "Maximum number of items (default 30).")
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let filter = args.get("feed").and_then(|v| v.as_str()).unwrap_or("");
+        let only_new = args.get("only_new").and_then(|v| v.as_bool()).unwrap_or(false);
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(30) as usize;
+        let urls: Vec<String> = self.urls.iter().filter(|u| u.contains(filter)).cloned().collect();
+        if urls.is_empty() {
+            return Ok(ToolResult { output: json!({ "error": format!("No configured feed matches '{}'", filter) }), is_error: true });
+        }
+
+        let (mut items, errors) = fetch_all(&self.http, &urls).await;
+        if only_new {
+            let seen = SeenItems::load(SeenItems::default_path());
+            items.retain(|i| seen.is_new(i));
+        }
+        items.sort_by_key(|i| std::cmp::Reverse(i.published));
+        let total = items.len();
+        items.truncate(limit);
+        Ok(ToolResult {
+            output: json!({
+                "items": items.iter().map(FeedItem::to_json).collect::<Vec<_>>(),
+                "total_items": total,
+                "errors": errors,
+            }),
+            is_error: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_and_atom_feeds() -> Result<()> {
+        let rss = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Rust Blog</title>
+  <item><title>Old post</title><link>https://blog.test/old</link><pubDate>Mon, 01 Sep 2025 10:00:00 +0000</pubDate></item>
+  <item><title>Rust 1.90</title><link>https://blog.test/1.90</link><guid>post-190</guid>
+    <pubDate>Thu, 18 Sep 2025 00:00:00 GMT</pubDate><description>&lt;p&gt;The Rust team is &lt;b&gt;happy&lt;/b&gt; to announce&lt;/p&gt;</description></item>
+</channel></rss>"#;
+        let (title, items) = parse_feed(rss)?;
+        assert_eq!(title, "Rust Blog");
+        assert_eq!(items[0].title, "Rust 1.90");
+        assert_eq!(items[0].id, "post-190");
+        assert_eq!(items[0].summary, "The Rust team is happy to announce");
+        assert_eq!(items[1].id, "https://blog.test/old");
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Releases</title>
+  <entry><title>v2.0</title><id>tag:x,2025:2</id><link rel="alternate" href="https://x.test/v2"/><updated>2025-10-01T08:30:00Z</updated><summary>Big one</summary></entry>
+</feed>"#;
+        let (_, items) = parse_feed(atom)?;
+        assert_eq!(items[0].link.as_deref(), Some("https://x.test/v2"));
+        assert_eq!(items[0].published, Some(1759307400));
+
+        let mut seen = SeenItems::load(None);
+        assert!(seen.is_new(&items[0]));
+        seen.mark(&items)?;
+        assert!(!seen.is_new(&items[0]));
+        assert!(parse_feed("<html></html>").is_err());
+        Ok(())
+    }
+}
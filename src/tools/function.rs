@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+use std::collections::HashMap;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::{ToolExecutor, ToolResult};
+
+type Handler = Box<dyn Fn(HashMap<String, Value>) -> BoxFuture<'static, Result<Value>> + Send + Sync>;
+
+/// A tool backed by an async closure; see `ToolRegistry::register_fn`.
+pub struct FnTool {
+    declaration: FunctionDeclaration,
+    handler: Handler,
+    read_only: bool,
+}
+
+impl FnTool {
+    pub fn new<F, Fut>(name: &str, description: &str, parameters: Value, f: F) -> Self
+    where
+        F: Fn(HashMap<String, Value>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<Value>> + Send + 'static,
+    {
+        Self {
+            declaration: FunctionDeclaration {
+                name: name.to_string(),
+                description: description.to_string(),
+                parameters: Some(parameters),
+            },
+            handler: Box::new(move |args| Box::pin(f(args))),
+            read_only: false,
+        }
+    }
+
+    /// Marks the function as side-effect free so it stays available in read-only mode.
+    #[allow(dead_code)]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for FnTool {
+    fn name(&self) -> String {
+        self.declaration.name.clone()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        self.declaration.clone()
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        self.read_only
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let output = (self.handler)(args).await?;
+        Ok(ToolResult { output, is_error: false })
+    }
+}
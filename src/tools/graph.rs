@@ -0,0 +1,293 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+use crate::vault::{self, Vault};
+
+/// Facts kept per entity; the oldest are dropped first.
+const MAX_FACTS: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub name: String,
+    /// person, project, server, ...
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub facts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Relation {
+    pub from: String,
+    pub relation: String,
+    pub to: String,
+}
+
+/// What the extractor pulls out of one exchange.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Extraction {
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+    #[serde(default)]
+    pub relations: Vec<Relation>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Graph {
+    entities: BTreeMap<String, Entity>,
+    relations: Vec<Relation>,
+}
+
+/// People, projects, servers and how they relate, extracted from past
+/// conversations and kept in `~/.chitti/graph.json` (encrypted when the
+/// vault is on).
+#[derive(Default)]
+pub struct GraphStore {
+    path: Option<PathBuf>,
+    vault: Option<Arc<Vault>>,
+    graph: Mutex<Graph>,
+}
+
+impl GraphStore {
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitti").join("graph.json"))
+    }
+
+    pub fn load(path: Option<PathBuf>, vault: Option<Arc<Vault>>) -> Self {
+        let graph = path.as_ref()
+            .and_then(|p| vault::read(vault.as_deref(), p).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        Self { path, vault, graph: Mutex::new(graph) }
+    }
+
+    /// Adds new entities, facts and relations, skipping ones already known.
+    /// Returns how many were new.
+    pub fn merge(&self, extraction: Extraction) -> Result<usize> {
+        let mut added = 0;
+        {
+            let mut graph = self.graph.lock().unwrap();
+            for entity in extraction.entities {
+                let name = entity.name.trim();
+                if name.is_empty() {
+                    continue;
+                }
+                let known = graph.entities.entry(key(name)).or_insert_with(|| {
+                    added += 1;
+                    Entity { name: name.to_string(), ..Default::default() }
+                });
+                if known.kind.is_empty() {
+                    known.kind = entity.kind.trim().to_lowercase();
+                }
+                for fact in entity.facts {
+                    let fact = fact.trim();
+                    if !fact.is_empty() && !known.facts.iter().any(|f| f.eq_ignore_ascii_case(fact)) {
+                        known.facts.push(fact.to_string());
+                        added += 1;
+                    }
+                }
+                let excess = known.facts.len().saturating_sub(MAX_FACTS);
+                known.facts.drain(..excess);
+            }
+            for relation in extraction.relations {
+                let known = graph.relations.iter().any(|r| {
+                    key(&r.from) == key(&relation.from) && key(&r.to) == key(&relation.to)
+                        && r.relation.eq_ignore_ascii_case(&relation.relation)
+                });
+                if !known && !relation.from.trim().is_empty() && !relation.to.trim().is_empty() {
+                    graph.relations.push(relation);
+                    added += 1;
+                }
+            }
+        }
+        if added > 0 {
+            self.save()?;
+        }
+        Ok(added)
+    }
+
+    /// The entity called `name` (or, failing that, the first whose name
+    /// contains it) with every relation it takes part in.
+    pub fn lookup(&self, name: &str) -> Option<Value> {
+        let graph = self.graph.lock().unwrap();
+        let wanted = key(name);
+        let entity = graph.entities.get(&wanted)
+            .or_else(|| graph.entities.iter().find(|(k, _)| k.contains(&wanted)).map(|(_, e)| e))?;
+        let id = key(&entity.name);
+        let relations: Vec<String> = graph.relations.iter()
+            .filter(|r| key(&r.from) == id || key(&r.to) == id)
+            .map(|r| format!("{} {} {}", r.from, r.relation, r.to))
+            .collect();
+        Some(json!({
+            "name": entity.name,
+            "kind": entity.kind,
+            "facts": entity.facts,
+            "relations": relations,
+        }))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.graph.lock().unwrap().entities.values().map(|e| e.name.clone()).collect()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let text = serde_json::to_string_pretty(&*self.graph.lock().unwrap())?;
+        vault::write(self.vault.as_deref(), path, text.as_bytes())
+            .with_context(|| format!("Failed to save {}", path.display()))
+    }
+}
+
+fn key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Asks for the durable entities and relations in one exchange.
+pub fn extraction_prompt(prompt: &str, response: &str) -> String {
+    format!(
+        "Extract the people, projects, servers, services and other named things from this exchange \
+         that are worth remembering in later conversations, with short durable facts about each and \
+         the relations between them (e.g. \"alice\" \"owns\" \"billing-api\"). Skip generic concepts, \
+         one-off values and anything only true for this moment. Reply with empty lists if there is \
+         nothing worth keeping.\n\n<user>\n{}\n</user>\n\n<assistant>\n{}\n</assistant>",
+        prompt, response
+    )
+}
+
+/// JSON schema the extractor's answer must follow; parses into [`Extraction`].
+pub fn extraction_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "entities": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "kind": { "type": "string" },
+                        "facts": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["name", "kind", "facts"]
+                }
+            },
+            "relations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "string" },
+                        "relation": { "type": "string" },
+                        "to": { "type": "string" }
+                    },
+                    "required": ["from", "relation", "to"]
+                }
+            }
+        },
+        "required": ["entities", "relations"]
+    })
+}
+
+/// Looks an entity up in the graph. Registered twice, as `who_is` for
+/// people and `what_is` for everything else; both search the whole graph.
+pub struct GraphLookupTool {
+    name: &'static str,
+    store: std::sync::Arc<GraphStore>,
+}
+
+impl GraphLookupTool {
+    pub fn who_is(store: std::sync::Arc<GraphStore>) -> Self {
+        Self { name: "who_is", store }
+    }
+
+    pub fn what_is(store: std::sync::Arc<GraphStore>) -> Self {
+        Self { name: "what_is", store }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for GraphLookupTool {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        let subject = if self.name == "who_is" { "a person" } else { "a project, server, service or other named thing" };
+        FunctionDeclaration {
+            name: self.name(),
+            description: format!(
+                "Look up what earlier conversations established about {}: known facts and relations to other entities.",
+                subject
+            ),
+            parameters: Some(Params::object()
+                .string("name", "Name of the entity to look up.")
+                .required(&["name"])
+                .build()),
+        }
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let name = args.get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' argument"))?;
+        Ok(match self.store.lookup(name) {
+            Some(entity) => ToolResult { output: entity, is_error: false },
+            None => ToolResult {
+                output: json!({ "error": format!("Nothing known about '{}'", name), "known": self.store.names() }),
+                is_error: true,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_graph_merges_extractions_and_answers_lookups() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("chitti-graph-{}.json", uuid::Uuid::new_v4()));
+        let vault = Arc::new(Vault::from_key(&[5; 32]));
+        let store = Arc::new(GraphStore::load(Some(path.clone()), Some(vault.clone())));
+        let extraction: Extraction = serde_json::from_value(json!({
+            "entities": [
+                { "name": "Alice", "kind": "Person", "facts": ["On-call for billing this week"] },
+                { "name": "db01.internal", "kind": "server", "facts": ["Primary Postgres"] }
+            ],
+            "relations": [{ "from": "Alice", "relation": "administers", "to": "db01.internal" }]
+        }))?;
+        assert_eq!(store.merge(extraction)?, 5);
+        let again: Extraction = serde_json::from_value(json!({
+            "entities": [{ "name": "alice", "kind": "person", "facts": ["on-call for billing this week"] }],
+            "relations": [{ "from": "ALICE", "relation": "administers", "to": "db01.internal" }]
+        }))?;
+        assert_eq!(store.merge(again)?, 0);
+        assert!(Vault::is_encrypted(&std::fs::read(&path)?));
+
+        let reloaded = Arc::new(GraphStore::load(Some(path.clone()), Some(vault)));
+        let args: HashMap<String, Value> = [("name".to_string(), json!("alice"))].into();
+        let found = GraphLookupTool::who_is(reloaded.clone()).execute(args).await?;
+        assert_eq!(found.output["kind"], "person");
+        assert_eq!(found.output["relations"], json!(["Alice administers db01.internal"]));
+        let args: HashMap<String, Value> = [("name".to_string(), json!("db01"))].into();
+        let found = GraphLookupTool::what_is(reloaded.clone()).execute(args).await?;
+        assert_eq!(found.output["facts"], json!(["Primary Postgres"]));
+        let args: HashMap<String, Value> = [("name".to_string(), json!("bob"))].into();
+        assert!(GraphLookupTool::who_is(reloaded).execute(args).await?.is_error);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use regex::Regex;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::workspace::{distance, PathResolver};
+
+const MAX_SUGGESTIONS: usize = 5;
+
+static COMMAND_NOT_FOUND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)(?:^|: )([\w.+-]+): (?:command )?not found").unwrap()
+});
+
+/// Hints for a failed tool call, computed locally so the model can correct
+/// the call instead of guessing: similar paths for a missing file, similar
+/// commands for an unknown one.
+pub fn for_error(args: &Value, error: &str) -> Vec<String> {
+    let lower = error.to_lowercase();
+    let mut hints = Vec::new();
+    if ["no such file", "not found", "does not exist", "cannot find", "os error 2"].iter().any(|p| lower.contains(p)) {
+        let resolver = PathResolver::current();
+        for arg in path_args(args) {
+            let path = crate::tools::env::expand_home(Path::new(&arg));
+            if path.exists() {
+                continue;
+            }
+            let similar = resolver.suggest(&arg);
+            if similar.is_empty() {
+                if let Some(dir) = path.ancestors().skip(1).find(|a| a.is_dir() && !a.as_os_str().is_empty()) {
+                    hints.push(format!("{} does not exist; the nearest existing directory is {}", path.display(), dir.display()));
+                }
+            } else {
+                let list: Vec<String> = similar.iter().map(|p| p.display().to_string()).collect();
+                hints.push(format!("{} does not exist; similar paths: {}", path.display(), list.join(", ")));
+            }
+        }
+    }
+    for caps in COMMAND_NOT_FOUND.captures_iter(error) {
+        let similar = similar_commands(&caps[1]);
+        if !similar.is_empty() {
+            hints.push(format!("'{}' is not installed; similar commands on PATH: {}", &caps[1], similar.join(", ")));
+        }
+    }
+    if lower.contains("permission denied") {
+        hints.push("Permission denied: check the path is the one intended; retrying the same call will fail again".to_string());
+    }
+    if lower.contains("timed out") {
+        hints.push("The call timed out: narrow it (a smaller range, fewer files, a filter) before retrying".to_string());
+    }
+    hints.dedup();
+    hints
+}
+
+/// Adds `hints` to a tool's error output.
+pub fn annotate(output: Value, hints: Vec<String>) -> Value {
+    if hints.is_empty() {
+        return output;
+    }
+    match output {
+        Value::Object(mut map) => {
+            map.insert("hints".to_string(), Value::from(hints));
+            Value::Object(map)
+        }
+        other => serde_json::json!({ "error": other, "hints": hints }),
+    }
+}
+
+/// String arguments that name files or directories.
+fn path_args(args: &Value) -> Vec<String> {
+    let Value::Object(map) = args else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for (key, value) in map {
+        let key = key.to_lowercase();
+        if !["path", "file", "dir", "source", "destination"].iter().any(|k| key.contains(k)) {
+            continue;
+        }
+        match value {
+            Value::String(s) if !s.is_empty() => out.push(s.clone()),
+            Value::Array(items) => out.extend(items.iter().filter_map(|v| v.as_str()).map(str::to_string)),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn similar_commands(command: &str) -> Vec<String> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut ranked: Vec<(usize, String)> = std::env::split_paths(&path)
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter_map(|name| {
+            let distance = distance(command, &name);
+            (distance > 0 && distance <= command.len().max(3) / 3).then_some((distance, name))
+        })
+        .collect();
+    ranked.sort();
+    ranked.dedup_by(|a, b| a.1 == b.1);
+    ranked.into_iter().take(MAX_SUGGESTIONS).map(|(_, n)| n).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hints_for_missing_path() {
+        let dir = std::env::temp_dir().join(format!("chitti-hints-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("config.yaml"), "").unwrap();
+        let missing = dir.join("confg.yaml");
+        let args = json!({ "path": missing.to_string_lossy() });
+        let hints = for_error(&args, "No such file or directory (os error 2)");
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("similar paths:"));
+        assert!(hints[0].contains("config.yaml"));
+
+        let out = annotate(json!({ "error": "boom" }), hints);
+        assert_eq!(out["hints"].as_array().map(Vec::len), Some(1));
+        assert!(for_error(&args, "exit status 1").is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
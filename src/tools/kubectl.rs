@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+
+/// The only verbs the tool runs. None of them change cluster state.
+const VERBS: &[&str] = &["get", "describe", "logs", "events", "top"];
+
+/// Read-only `kubectl` access for diagnosing cluster issues. Commands are
+/// built from structured arguments (never a shell string), limited to
+/// read verbs, and confined to the configured contexts and namespaces.
+pub struct KubectlTool {
+    contexts: Vec<String>,
+    namespaces: Vec<String>,
+}
+
+impl KubectlTool {
+    /// `contexts`/`namespaces` restrict what the model may target; empty
+    /// means the current context and any namespace.
+    pub fn new(contexts: Vec<String>, namespaces: Vec<String>) -> Self {
+        Self { contexts, namespaces }
+    }
+
+    /// The tool, if `kubectl` is on the PATH.
+    pub fn detect(contexts: Vec<String>, namespaces: Vec<String>) -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .any(|dir| Path::new(&dir).join("kubectl").is_file())
+            .then(|| Self::new(contexts, namespaces))
+    }
+
+    /// The kubectl argument list for a call, or why it was refused.
+    pub fn build_args(&self, args: &HashMap<String, Value>) -> Result<Vec<String>> {
+        let get = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+        let verb = get("verb").ok_or_else(|| anyhow::anyhow!("Missing 'verb' argument"))?;
+        if !VERBS.contains(&verb) {
+            anyhow::bail!("Verb '{}' is not allowed; use one of: {}", verb, VERBS.join(", "));
+        }
+        // Everything the model supplies is a positional value; a leading dash
+        // could smuggle in flags such as --kubeconfig or --server.
+        for key in ["resource", "name", "namespace", "context", "selector", "container"] {
+            if get(key).is_some_and(|v| v.starts_with('-')) {
+                anyhow::bail!("Invalid value for '{}'", key);
+            }
+        }
+
+        let mut out = vec![verb.to_string()];
+        if verb == "top" {
+            out.push(get("resource").unwrap_or("pods").to_string());
+        } else if verb != "logs" && verb != "events" {
+            out.push(get("resource").ok_or_else(|| anyhow::anyhow!("'{}' needs a 'resource' (e.g. pods, deployments)", verb))?.to_string());
+        }
+        if let Some(name) = get("name") {
+            out.push(name.to_string());
+        } else if verb == "logs" {
+            anyhow::bail!("'logs' needs the pod 'name' (or type/name, e.g. deployment/api)");
+        }
+
+        match get("context") {
+            Some(context) if !self.contexts.is_empty() && !self.contexts.iter().any(|c| c == context) => {
+                anyhow::bail!("Context '{}' is not allowed; configured contexts: {}", context, self.contexts.join(", "))
+            }
+            Some(context) => out.push(format!("--context={}", context)),
+            None => {
+                if let Some(first) = self.contexts.first() {
+                    out.push(format!("--context={}", first));
+                }
+            }
+        }
+        let all_namespaces = args.get("all_namespaces").and_then(|v| v.as_bool()).unwrap_or(false);
+        match get("namespace") {
+            Some(ns) if !self.namespaces.is_empty() && !self.namespaces.iter().any(|n| n == ns) => {
+                anyhow::bail!("Namespace '{}' is not allowed; configured namespaces: {}", ns, self.namespaces.join(", "))
+            }
+            Some(ns) => out.push(format!("--namespace={}", ns)),
+            None if all_namespaces && self.namespaces.is_empty() => out.push("--all-namespaces".to_string()),
+            None if all_namespaces => anyhow::bail!("all_namespaces is not allowed when namespaces are restricted"),
+            None => {
+                if let Some(first) = self.namespaces.first() {
+                    out.push(format!("--namespace={}", first));
+                }
+            }
+        }
+        if let Some(selector) = get("selector") {
+            out.push(format!("--selector={}", selector));
+        }
+        if verb == "logs" {
+            let tail = args.get("tail").and_then(|v| v.as_u64()).unwrap_or(200);
+            out.push(format!("--tail={}", tail));
+            if let Some(container) = get("container") {
+                out.push(format!("--container={}", container));
+            }
+            if args.get("previous").and_then(|v| v.as_bool()).unwrap_or(false) {
+                out.push("--previous".to_string());
+            }
+        }
+        if verb == "get" || verb == "events" {
+            if let Some(output) = get("output") {
+                if !["wide", "yaml", "json"].contains(&output) {
+                    anyhow::bail!("Output must be wide, yaml or json");
+                }
+                out.push(format!("--output={}", output));
+            }
+        }
+        out.push("--request-timeout=20s".to_string());
+        Ok(out)
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for KubectlTool {
+    fn name(&self) -> String {
+        "kubectl".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        let mut description = "Inspect a Kubernetes cluster with read-only kubectl commands (get, describe, logs, events, top). Nothing can be created, changed or deleted.".to_string();
+        if !self.contexts.is_empty() {
+            description.push_str(&format!(" Contexts: {}.", self.contexts.join(", ")));
+        }
+        if !self.namespaces.is_empty() {
+            description.push_str(&format!(" Namespaces: {}.", self.namespaces.join(", ")));
+        }
+        FunctionDeclaration {
+            name: self.name(),
+            description,
+            parameters: Some(Params::object()
+                .one_of("verb", "The kubectl command to run.", VERBS)
+                .string("resource", "Resource type, e.g. pods, deployments, nodes (not needed for logs or events).")
+                .string("name", "Resource name; for logs, the pod or type/name such as deployment/api.")
+                .string("namespace", "Namespace to query.")
+                .boolean("all_namespaces", "Query every namespace.")
+                .string("context", "kubeconfig context to use.")
+                .string("selector", "Label selector, e.g. app=api.")
+                .string("container", "Container name for logs.")
+                .integer("tail", "Number of log lines (default 200).")
+                .boolean("previous", "Logs of the previous, crashed container instance.")
+                .one_of("output", "Output format for get and events.", &["wide", "yaml", "json"])
+                .required(&["verb"])
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let argv = match self.build_args(&args) {
+            Ok(argv) => argv,
+            Err(e) => return Ok(ToolResult { output: json!({ "error": e.to_string() }), is_error: true }),
+        };
+        let output = Command::new("kubectl")
+            .args(&argv)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        Ok(ToolResult {
+            output: json!({
+                "command": format!("kubectl {}", argv.join(" ")),
+                "stdout": String::from_utf8_lossy(&output.stdout),
+                "stderr": String::from_utf8_lossy(&output.stderr),
+                "exit_code": output.status.code().unwrap_or(-1),
+            }),
+            is_error: !output.status.success(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn test_kubectl_builds_only_read_commands() {
+        let tool = KubectlTool::new(vec!["staging".into(), "prod".into()], vec!["api".into()]);
+        assert_eq!(
+            tool.build_args(&call(&[("verb", json!("get")), ("resource", json!("pods")), ("output", json!("wide"))])).unwrap(),
+            vec!["get", "pods", "--context=staging", "--namespace=api", "--output=wide", "--request-timeout=20s"]
+        );
+        assert_eq!(
+            tool.build_args(&call(&[("verb", json!("logs")), ("name", json!("deployment/web")), ("context", json!("prod")), ("previous", json!(true))])).unwrap(),
+            vec!["logs", "deployment/web", "--context=prod", "--namespace=api", "--tail=200", "--previous", "--request-timeout=20s"]
+        );
+
+        let refused = |pairs: &[(&str, Value)]| tool.build_args(&call(pairs)).unwrap_err().to_string();
+        assert!(refused(&[("verb", json!("delete")), ("resource", json!("pods"))]).contains("not allowed"));
+        assert!(refused(&[("verb", json!("get")), ("resource", json!("pods")), ("context", json!("dev"))]).contains("Context 'dev'"));
+        assert!(refused(&[("verb", json!("get")), ("resource", json!("pods")), ("namespace", json!("kube-system"))]).contains("Namespace"));
+        assert!(refused(&[("verb", json!("get")), ("resource", json!("--kubeconfig=/tmp/x"))]).contains("Invalid value"));
+        assert!(refused(&[("verb", json!("logs"))]).contains("needs the pod"));
+    }
+}
@@ -2,9 +2,36 @@ use async_trait::async_trait;
 use serde_json::Value;
 use anyhow::Result;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use crate::brains::gemini::types::FunctionDeclaration;
 
+pub mod archive;
+pub mod args;
 pub mod bash;
+pub mod browser;
+pub mod cache;
+pub mod convert;
+pub mod env;
+pub mod feeds;
+pub mod function;
+pub mod graph;
+pub mod hints;
+pub mod kubectl;
+pub mod ocr;
+pub mod openapi;
+pub mod params;
+pub mod plugin;
+pub mod remote;
+pub mod sanitize;
+pub mod secrets;
+pub mod stats;
+pub mod sysinfo;
+pub mod tail;
+pub mod truncate;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
 
 #[derive(Debug, Clone)]
 pub struct ToolResult {
@@ -12,38 +39,348 @@ pub struct ToolResult {
     pub is_error: bool,
 }
 
+/// How a tool call used a file. `Write` wins when a turn did both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileAccess {
+    Read,
+    Write,
+}
+
 #[async_trait]
 pub trait ToolExecutor: Send + Sync {
     fn name(&self) -> String;
     fn definition(&self) -> FunctionDeclaration;
+    /// Whether this tool returns content from outside sources (files, web pages, emails)
+    /// that must be sanitized before it reaches the model.
+    fn untrusted_output(&self) -> bool {
+        false
+    }
+    /// Whether identical calls may be answered from the session cache.
+    /// Only deterministic tools should opt in, and only those whose side
+    /// effects a repeat would merely redo.
+    fn cacheable(&self) -> bool {
+        false
+    }
+    /// Whether this call only reads. Anything else (writes, shell commands,
+    /// network requests with side effects) is refused in read-only mode.
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        false
+    }
+    /// Files whose size and modification time are part of the cache key.
+    fn cache_dependencies(&self, _args: &HashMap<String, Value>) -> Vec<PathBuf> {
+        Vec::new()
+    }
+    /// Files this call reads or writes, as far as the tool can tell from its
+    /// arguments; shown to the user after the turn.
+    fn files_touched(&self, _args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        Vec::new()
+    }
+    /// Whether this call must be approved by the user even when the tool is
+    /// auto-approved (e.g. acting on a logged-in web page).
+    fn always_confirm(&self, _args: &HashMap<String, Value>) -> bool {
+        false
+    }
     async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult>;
 }
 
+/// A single argument that failed schema validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgError {
+    pub path: String,
+    pub message: String,
+}
+
 pub struct ToolRegistry {
-    tools: HashMap<String, Box<dyn ToolExecutor>>,
+    tools: HashMap<String, Arc<dyn ToolExecutor>>,
+    validators: HashMap<String, jsonschema::Validator>,
+    cache: Option<cache::ToolCache>,
+    read_only: Arc<AtomicBool>,
+    /// The read-only switches of the registries this is a subset of, so
+    /// `/readonly` and `/panic` on a parent reach it too.
+    inherited_read_only: Vec<Arc<AtomicBool>>,
+    stats: Arc<stats::ToolStats>,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            validators: HashMap::new(),
+            cache: None,
+            read_only: Arc::new(AtomicBool::new(false)),
+            inherited_read_only: Vec::new(),
+            stats: Arc::new(stats::ToolStats::default()),
+        }
+    }
+
+    /// Session counters for calls made through this registry and its subsets.
+    pub fn stats(&self) -> &stats::ToolStats {
+        &self.stats
+    }
+
+    /// Refuses every call a tool doesn't declare read-only, whatever the approvals.
+    pub fn set_read_only(&self, enabled: bool) {
+        self.read_only.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        std::iter::once(&self.read_only).chain(&self.inherited_read_only).any(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    /// Errors if the call may not run in the current mode. Unknown tools are
+    /// left for `execute` to report.
+    pub fn check_allowed(&self, name: &str, args: &HashMap<String, Value>) -> Result<()> {
+        let Some(tool) = self.tools.get(name) else {
+            return Ok(());
+        };
+        if self.is_read_only() && !tool.read_only(args) {
+            anyhow::bail!("Tool '{}' may modify the system and is disabled in read-only mode", name);
+        }
+        Ok(())
+    }
+
+    /// Files the call reads or writes; empty for unknown tools.
+    pub fn files_touched(&self, name: &str, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        self.tools.get(name).map(|tool| tool.files_touched(args)).unwrap_or_default()
+    }
+
+    /// Whether the call needs the user's approval regardless of auto-approval.
+    pub fn always_confirm(&self, name: &str, args: &HashMap<String, Value>) -> bool {
+        self.tools.get(name).is_some_and(|tool| tool.always_confirm(args))
+    }
+
+    /// Turns on result caching for tools that opt in via `cacheable`.
+    pub fn enable_cache(&mut self) {
+        self.cache = Some(cache::ToolCache::new());
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
         }
     }
 
     pub fn register(&mut self, tool: Box<dyn ToolExecutor>) {
-        self.tools.insert(tool.name(), tool);
+        self.register_shared(Arc::from(tool));
+    }
+
+    /// Registers an async function as a tool without implementing `ToolExecutor`:
+    ///
+    /// ```ignore
+    /// registry.register_fn("get_weather", "Current weather for a city",
+    ///     Params::object().string("city", "City name").required(&["city"]).build(),
+    ///     |args| async move { Ok(json!({ "city": args["city"], "temp_c": 21 })) });
+    /// ```
+    ///
+    /// Arguments are validated against `parameters` before the function runs.
+    #[allow(dead_code)]
+    pub fn register_fn<F, Fut>(&mut self, name: &str, description: &str, parameters: Value, f: F)
+    where
+        F: Fn(HashMap<String, Value>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<Value>> + Send + 'static,
+    {
+        self.register(Box::new(function::FnTool::new(name, description, parameters, f)));
+    }
+
+    /// Registers a tool whose arguments are a `#[derive(ChittiTool)]` struct;
+    /// the schema comes from the struct and `f` receives it parsed.
+    #[allow(dead_code)]
+    pub fn register_typed<A, F, Fut>(&mut self, f: F)
+    where
+        A: args::ToolArgs,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<Value>> + Send + 'static,
+    {
+        let declaration = A::declaration();
+        let f = Arc::new(f);
+        self.register_fn(
+            &declaration.name,
+            &declaration.description,
+            declaration.parameters.clone().unwrap_or_else(|| serde_json::json!({ "type": "object" })),
+            move |raw| {
+                let f = f.clone();
+                async move { f(A::parse(raw)?).await }
+            },
+        );
     }
 
-    pub fn get_definitions(&self) -> Vec<crate::brains::gemini::types::Tool> {
-        self.tools.values().map(|t| {
-            crate::brains::gemini::types::Tool::Function {
-                declaration: t.definition()
+    fn register_shared(&mut self, tool: Arc<dyn ToolExecutor>) {
+        let name = tool.name();
+        if let Some(schema) = tool.definition().parameters {
+            match jsonschema::validator_for(&schema) {
+                Ok(validator) => {
+                    self.validators.insert(name.clone(), validator);
+                }
+                Err(e) => tracing::warn!(tool = %name, "Tool declares an invalid parameter schema: {}", e),
             }
-        }).collect()
+        }
+        self.tools.insert(name, tool);
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.tools.keys().cloned().collect()
     }
 
+    /// Returns a registry exposing only the named tools, sharing their executors.
+    pub fn subset(&self, names: &[String]) -> ToolRegistry {
+        let mut restricted = ToolRegistry::new();
+        for name in names {
+            match self.tools.get(name) {
+                Some(tool) => restricted.register_shared(tool.clone()),
+                None => tracing::warn!(tool = %name, "Unknown tool requested in subset"),
+            }
+        }
+        if self.cache.is_some() {
+            restricted.enable_cache();
+        }
+        restricted.inherited_read_only = self.inherited_read_only.iter().cloned()
+            .chain(std::iter::once(self.read_only.clone()))
+            .collect();
+        restricted.stats = self.stats.clone();
+        restricted
+    }
+
+    /// Checks model-provided arguments against the tool's declared parameter schema.
+    pub fn validate_args(&self, name: &str, args: &Value) -> std::result::Result<(), Vec<ArgError>> {
+        if !args.is_object() {
+            return Err(vec![ArgError {
+                path: "/".to_string(),
+                message: format!("arguments must be a JSON object, got {}", args),
+            }]);
+        }
+        let Some(validator) = self.validators.get(name) else {
+            return Ok(());
+        };
+        let errors: Vec<ArgError> = validator
+            .iter_errors(args)
+            .map(|e| ArgError {
+                path: if e.instance_path().as_str().is_empty() { "/".to_string() } else { e.instance_path().to_string() },
+                message: e.to_string(),
+            })
+            .collect();
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Definitions of the tools named in `allowed`, or of every tool when `None`.
+    pub fn get_definitions(&self, allowed: Option<&[String]>) -> Vec<crate::brains::gemini::types::Tool> {
+        self.tools.iter()
+            .filter(|(name, _)| allowed.is_none_or(|allowed| allowed.contains(name)))
+            .map(|(_, t)| {
+                crate::brains::gemini::types::Tool::Function {
+                    declaration: t.definition()
+                }
+            }).collect()
+    }
+
+    pub fn is_untrusted(&self, name: &str) -> bool {
+        self.tools.get(name).map(|t| t.untrusted_output()).unwrap_or(true)
+    }
+
+    /// Runs a tool and records it in the session stats.
     pub async fn execute(&self, name: &str, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let started = std::time::Instant::now();
+        let result = self.execute_uncounted(name, args).await;
+        if self.tools.contains_key(name) {
+            let failed = result.as_ref().map(|r| r.is_error).unwrap_or(true);
+            if self.stats.record_call(name, started.elapsed(), failed) {
+                tracing::warn!(tool = name, "Tool fails more than half of the time");
+            }
+        }
+        result
+    }
+
+    async fn execute_uncounted(&self, name: &str, args: HashMap<String, Value>) -> Result<ToolResult> {
+        self.check_allowed(name, &args)?;
         let tool = self.tools.get(name).ok_or_else(|| anyhow::anyhow!("Tool not found: {}", name))?;
-        tool.execute(args).await
+        let cache = self.cache.as_ref().filter(|_| tool.cacheable());
+        let Some(cache) = cache else {
+            return tool.execute(args).await;
+        };
+
+        let key = cache::cache_key(name, &args, &tool.cache_dependencies(&args));
+        if cache.get(key).is_some() {
+            tracing::debug!(tool = name, "Tool result served from cache");
+            return Ok(ToolResult { output: cache::unchanged_marker(), is_error: false });
+        }
+        let result = tool.execute(args).await?;
+        if !result.is_error {
+            cache.insert(key, result.output.clone());
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_args_against_schema() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(bash::BashTool::default()));
+
+        assert!(registry.validate_args("execute_bash", &json!({ "command": "ls" })).is_ok());
+
+        let errors = registry.validate_args("execute_bash", &json!({ "command": 42 })).unwrap_err();
+        assert_eq!(errors[0].path, "/command");
+
+        let errors = registry.validate_args("execute_bash", &json!({})).unwrap_err();
+        assert!(errors[0].message.contains("command"));
+
+        assert!(registry.validate_args("execute_bash", &json!("ls")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_fn_exposes_closure_as_tool() -> Result<()> {
+        let mut registry = ToolRegistry::new();
+        let suffix = "!".to_string();
+        registry.register_fn(
+            "shout",
+            "Upper-cases text",
+            params::Params::object().string("text", "Text to shout").required(&["text"]).build(),
+            move |args| {
+                let suffix = suffix.clone();
+                async move { Ok(json!(format!("{}{}", args["text"].as_str().unwrap_or_default().to_uppercase(), suffix))) }
+            },
+        );
+
+        assert_eq!(registry.get_definitions(None).len(), 1);
+        assert!(registry.validate_args("shout", &json!({})).is_err());
+        let result = registry.execute("shout", [("text".to_string(), json!("hi"))].into()).await?;
+        assert_eq!(result.output, json!("HI!"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode_refuses_mutating_tools() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(bash::BashTool::default()));
+        registry.register(Box::new(truncate::ReadToolOutputTool::new(Arc::new(truncate::OutputStore::new()))));
+        registry.set_read_only(true);
+
+        let args: HashMap<String, Value> = [("command".to_string(), json!("touch /tmp/x"))].into();
+        let err = registry.execute("execute_bash", args).await.unwrap_err();
+        assert!(err.to_string().contains("read-only mode"));
+
+        let args: HashMap<String, Value> = [
+            ("output_id".to_string(), json!("missing")),
+            ("start_line".to_string(), json!(1)),
+            ("end_line".to_string(), json!(2)),
+        ].into();
+        assert!(registry.check_allowed("read_tool_output", &args).is_ok());
+
+        registry.set_read_only(false);
+        assert!(registry.check_allowed("execute_bash", &HashMap::new()).is_ok());
+
+        // Subsets follow the parent's switch after they are made.
+        let subset = registry.subset(&["execute_bash".to_string()]);
+        registry.set_read_only(true);
+        assert!(subset.check_allowed("execute_bash", &HashMap::new()).is_err());
+        // A subset made read-only on its own leaves the parent alone.
+        registry.set_read_only(false);
+        subset.set_read_only(true);
+        assert!(registry.check_allowed("execute_bash", &HashMap::new()).is_ok());
+        assert!(subset.check_allowed("execute_bash", &HashMap::new()).is_err());
     }
 }
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
+
+const DEFAULT_MAX_PAGES: u64 = 10;
+/// Rendering resolution for scanned PDF pages; tesseract is tuned for ~300 DPI.
+const PDF_DPI: &str = "300";
+
+/// Extracts text from images and scanned PDFs on this machine with
+/// `tesseract`, so documents never leave it. PDFs are rasterized with
+/// `pdftoppm` (poppler) first.
+pub struct OcrTool;
+
+impl OcrTool {
+    /// The tool, if `tesseract` is on the PATH.
+    pub fn detect() -> Option<Self> {
+        on_path("tesseract").then_some(Self)
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Tesseract language codes joined with `+`, e.g. `eng+deu`.
+pub fn validate_language(language: &str) -> Result<&str> {
+    let valid = !language.is_empty()
+        && language.split('+').all(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    if !valid {
+        anyhow::bail!("Invalid language '{}'; use tesseract codes such as eng or eng+deu", language);
+    }
+    Ok(language)
+}
+
+async fn recognize(image: &Path, language: &str) -> Result<String> {
+    let output = Command::new("tesseract")
+        .arg(image).arg("stdout").arg("-l").arg(language)
+        .kill_on_drop(true)
+        .output().await
+        .context("Failed to run tesseract")?;
+    if !output.status.success() {
+        anyhow::bail!("tesseract failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Renders the first `max_pages` pages of a PDF to PNGs in `dir`, in page order.
+async fn rasterize(pdf: &Path, dir: &Path, max_pages: u64) -> Result<Vec<PathBuf>> {
+    if !on_path("pdftoppm") {
+        anyhow::bail!("OCR of PDFs needs pdftoppm (poppler-utils) on the PATH");
+    }
+    let output = Command::new("pdftoppm")
+        .args(["-r", PDF_DPI, "-png", "-f", "1", "-l", &max_pages.to_string()])
+        .arg(pdf).arg(dir.join("page"))
+        .kill_on_drop(true)
+        .output().await
+        .context("Failed to run pdftoppm")?;
+    if !output.status.success() {
+        anyhow::bail!("pdftoppm failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let mut pages: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "png"))
+        .collect();
+    // pdftoppm zero-pads page numbers, so name order is page order.
+    pages.sort();
+    Ok(pages)
+}
+
+#[async_trait]
+impl ToolExecutor for OcrTool {
+    fn name(&self) -> String {
+        "ocr".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Extract text from a local image or scanned PDF with on-device OCR. Nothing is uploaded, so prefer this over attaching the file when only the text is needed.".to_string(),
+            parameters: Some(Params::object()
+                .string("path", "Path of the image (png, jpg, tiff, ...) or PDF.")
+                .string("language", "Tesseract language codes, e.g. eng (default) or eng+deu.")
+                .integer("max_pages", "For PDFs, how many pages to read from the start (default 10).")
+                .required(&["path"])
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    fn cache_dependencies(&self, args: &HashMap<String, Value>) -> Vec<PathBuf> {
+        args.get("path").and_then(|v| v.as_str())
+            .map(|p| vec![PathBuf::from(p)])
+            .unwrap_or_default()
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        args.get("path").and_then(|v| v.as_str())
+            .map(|p| vec![(p.to_string(), FileAccess::Read)])
+            .unwrap_or_default()
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = args.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' argument"))?;
+        let language = match validate_language(args.get("language").and_then(|v| v.as_str()).unwrap_or("eng")) {
+            Ok(language) => language,
+            Err(e) => return Ok(ToolResult { output: json!({ "error": e.to_string() }), is_error: true }),
+        };
+        let max_pages = args.get("max_pages").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_MAX_PAGES).max(1);
+        let path = Path::new(path);
+        if !path.is_file() {
+            return Ok(ToolResult { output: json!({ "error": format!("{} is not a file", path.display()) }), is_error: true });
+        }
+
+        let is_pdf = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+        if !is_pdf {
+            let text = recognize(path, language).await?;
+            return Ok(ToolResult { output: json!({ "text": text }), is_error: false });
+        }
+
+        let dir = std::env::temp_dir().join(format!("chitti-ocr-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let result = async {
+            let mut pages = Vec::new();
+            for (i, image) in rasterize(path, &dir, max_pages).await?.iter().enumerate() {
+                pages.push(json!({ "page": i + 1, "text": recognize(image, language).await? }));
+            }
+            Ok::<_, anyhow::Error>(pages)
+        }.await;
+        let _ = std::fs::remove_dir_all(&dir);
+        let pages = result?;
+        Ok(ToolResult { output: json!({ "pages": pages }), is_error: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ocr_language_codes_are_validated() {
+        assert_eq!(validate_language("eng").unwrap(), "eng");
+        assert_eq!(validate_language("eng+chi_sim").unwrap(), "eng+chi_sim");
+        assert!(validate_language("eng+").is_err());
+        assert!(validate_language("-l eng").is_err());
+        assert!(validate_language("../../tmp/x").is_err());
+    }
+
+    #[test]
+    fn test_ocr_results_are_cached_against_the_input_file() {
+        let args = HashMap::from([("path".to_string(), json!("scan.png"))]);
+        assert!(OcrTool.cacheable());
+        assert_eq!(OcrTool.cache_dependencies(&args), [PathBuf::from("scan.png")]);
+    }
+}
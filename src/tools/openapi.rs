@@ -0,0 +1,401 @@
+//! Turns the operations of an OpenAPI 3 spec into tools.
+//!
+//! Each operation becomes one function declaration whose parameters are the
+//! operation's path, query and header parameters plus a `body` property for a
+//! JSON request body. Services are listed in a YAML file:
+//!
+//! ```yaml
+//! - spec: https://api.example.com/openapi.json   # or a local path, JSON or YAML
+//!   prefix: example                              # optional tool name prefix
+//!   base_url: https://api.example.com/v2         # optional, defaults to servers[0]
+//!   auth:
+//!     bearer: { env: EXAMPLE_TOKEN }
+//!     # or header: { name: X-API-Key, value: { file: ~/.example-key } }
+//! ```
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::env::EnvValue;
+use crate::tools::{ToolExecutor, ToolResult};
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "patch", "head", "options"];
+/// How deep `$ref`s are inlined; recursive schemas are cut off below this.
+const MAX_REF_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Service {
+    pub spec: String,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub auth: Option<Auth>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Auth {
+    Bearer(EnvValue),
+    Header { name: String, value: EnvValue },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Location {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    name: String,
+    location: Location,
+}
+
+/// Resolved connection details shared by all tools of one service.
+struct Connection {
+    http: reqwest::Client,
+    base_url: String,
+    auth_header: Option<(String, String)>,
+}
+
+/// One API operation exposed as a tool.
+pub struct OpenApiTool {
+    declaration: FunctionDeclaration,
+    method: Method,
+    path: String,
+    params: Vec<Param>,
+    has_body: bool,
+    connection: Arc<Connection>,
+}
+
+/// Reads the services file and builds the tools of every service in it.
+pub async fn load_services(path: &Path) -> Result<Vec<OpenApiTool>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read OpenAPI services file {}", path.display()))?;
+    let services: Vec<Service> = serde_yaml::from_str(&text)
+        .with_context(|| format!("Failed to parse OpenAPI services file {}", path.display()))?;
+    let mut tools = Vec::new();
+    for service in services {
+        let spec = fetch_spec(&service.spec).await?;
+        tools.extend(tools_from_spec(&spec, &service)
+            .with_context(|| format!("Invalid OpenAPI spec {}", service.spec))?);
+    }
+    Ok(tools)
+}
+
+async fn fetch_spec(location: &str) -> Result<Value> {
+    let text = if location.starts_with("http://") || location.starts_with("https://") {
+        reqwest::get(location).await?.error_for_status()?.text().await?
+    } else {
+        std::fs::read_to_string(location).with_context(|| format!("Failed to read {}", location))?
+    };
+    // YAML is a superset of JSON, so one parser covers both spec formats.
+    serde_yaml::from_str(&text).with_context(|| format!("Failed to parse {}", location))
+}
+
+/// Builds one tool per operation in `spec`.
+pub fn tools_from_spec(spec: &Value, service: &Service) -> Result<Vec<OpenApiTool>> {
+    let base_url = service.base_url.clone()
+        .or_else(|| spec["servers"][0]["url"].as_str().map(str::to_string))
+        .context("no base_url configured and the spec lists no servers")?;
+    let auth_header = match &service.auth {
+        Some(Auth::Bearer(token)) => Some(("Authorization".to_string(), format!("Bearer {}", token.resolve()?))),
+        Some(Auth::Header { name, value }) => Some((name.clone(), value.resolve()?)),
+        None => None,
+    };
+    let connection = Arc::new(Connection {
+        http: reqwest::Client::new(),
+        base_url: base_url.trim_end_matches('/').to_string(),
+        auth_header,
+    });
+
+    let mut tools = Vec::new();
+    let Some(paths) = spec["paths"].as_object() else {
+        return Ok(tools);
+    };
+    for (path, item) in paths {
+        for method in METHODS {
+            let Some(operation) = item.get(*method) else {
+                continue;
+            };
+            // Parameters may be declared on the path item and overridden per operation.
+            let mut declared: Vec<Value> = Vec::new();
+            for p in item["parameters"].as_array().into_iter().flatten()
+                .chain(operation["parameters"].as_array().into_iter().flatten())
+            {
+                declared.push(inline_refs(p, spec, 0));
+            }
+            tools.push(build_tool(spec, service, path, method, operation, declared, connection.clone()));
+        }
+    }
+    Ok(tools)
+}
+
+fn build_tool(
+    spec: &Value,
+    service: &Service,
+    path: &str,
+    method: &str,
+    operation: &Value,
+    declared: Vec<Value>,
+    connection: Arc<Connection>,
+) -> OpenApiTool {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut params: Vec<Param> = Vec::new();
+    for p in declared {
+        let (Some(name), Some(location)) = (p["name"].as_str(), p["in"].as_str()) else {
+            continue;
+        };
+        let location = match location {
+            "path" => Location::Path,
+            "query" => Location::Query,
+            "header" => Location::Header,
+            _ => continue,
+        };
+        let mut schema = p.get("schema").cloned().unwrap_or_else(|| json!({ "type": "string" }));
+        if let Some(description) = p["description"].as_str() {
+            schema["description"] = json!(description);
+        }
+        if location == Location::Path || p["required"].as_bool().unwrap_or(false) {
+            required.push(json!(name));
+        }
+        properties.insert(name.to_string(), schema);
+        params.retain(|existing| existing.name != name);
+        params.push(Param { name: name.to_string(), location });
+    }
+
+    let body_schema = &operation["requestBody"]["content"]["application/json"]["schema"];
+    let has_body = !body_schema.is_null();
+    if has_body {
+        properties.insert("body".to_string(), inline_refs(body_schema, spec, 0));
+        if operation["requestBody"]["required"].as_bool().unwrap_or(false) {
+            required.push(json!("body"));
+        }
+    }
+
+    let base_name = operation["operationId"].as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}_{}", method, path));
+    let name = tool_name(service.prefix.as_deref(), &base_name);
+    let description = operation["summary"].as_str()
+        .or_else(|| operation["description"].as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path));
+
+    OpenApiTool {
+        declaration: FunctionDeclaration {
+            name,
+            description,
+            parameters: Some(json!({ "type": "object", "properties": properties, "required": required })),
+        },
+        method: Method::from_bytes(method.to_uppercase().as_bytes()).unwrap_or(Method::GET),
+        path: path.to_string(),
+        params,
+        has_body,
+        connection,
+    }
+}
+
+/// Function names may only use letters, digits, `_` and `-`, up to 64 characters.
+fn tool_name(prefix: Option<&str>, base: &str) -> String {
+    let full = match prefix {
+        Some(prefix) => format!("{}_{}", prefix, base),
+        None => base.to_string(),
+    };
+    let mut name: String = full.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    while name.contains("__") {
+        name = name.replace("__", "_");
+    }
+    name.trim_matches('_').chars().take(64).collect()
+}
+
+/// Replaces local `$ref`s with the schemas they point to; function
+/// declarations can't reference shared definitions.
+fn inline_refs(value: &Value, spec: &Value, depth: usize) -> Value {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                if depth >= MAX_REF_DEPTH {
+                    return json!({ "type": "object" });
+                }
+                let target = reference.strip_prefix('#')
+                    .and_then(|pointer| spec.pointer(pointer))
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "type": "object" }));
+                return inline_refs(&target, spec, depth + 1);
+            }
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), inline_refs(v, spec, depth))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| inline_refs(v, spec, depth)).collect()),
+        other => other.clone(),
+    }
+}
+
+fn as_param_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn encode_segment(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl OpenApiTool {
+    fn request(&self, args: &HashMap<String, Value>) -> Result<reqwest::RequestBuilder> {
+        let mut path = self.path.clone();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        for param in &self.params {
+            let Some(value) = args.get(&param.name) else {
+                if param.location == Location::Path {
+                    anyhow::bail!("Missing path parameter '{}'", param.name);
+                }
+                continue;
+            };
+            let value = as_param_string(value);
+            match param.location {
+                Location::Path => {
+                    path = path.replace(&format!("{{{}}}", param.name), &encode_segment(&value));
+                }
+                Location::Query => query.push((param.name.clone(), value)),
+                Location::Header => headers.push((param.name.clone(), value)),
+            }
+        }
+
+        let conn = &self.connection;
+        let mut request = conn.http.request(self.method.clone(), format!("{}{}", conn.base_url, path));
+        if !query.is_empty() {
+            request = request.query(&query);
+        }
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some((name, value)) = &conn.auth_header {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if self.has_body {
+            if let Some(body) = args.get("body") {
+                request = request.json(body);
+            }
+        }
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for OpenApiTool {
+    fn name(&self) -> String {
+        self.declaration.name.clone()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        self.declaration.clone()
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        matches!(self.method, Method::GET | Method::HEAD | Method::OPTIONS)
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let response = self.request(&args)?.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        let body = serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text));
+        Ok(ToolResult {
+            output: json!({ "status": status.as_u16(), "body": body }),
+            is_error: !status.is_success(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+servers:
+  - url: https://pets.example.com/v1/
+paths:
+  /pets/{petId}:
+    parameters:
+      - { name: petId, in: path, schema: { type: integer } }
+    get:
+      operationId: getPet
+      summary: Fetch one pet
+      parameters:
+        - { name: verbose, in: query, schema: { type: boolean } }
+    put:
+      summary: Replace a pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name: { type: string }
+"##;
+
+    #[test]
+    fn test_operations_become_tools_with_http_requests() -> Result<()> {
+        let spec: Value = serde_yaml::from_str(SPEC)?;
+        let service = Service {
+            spec: "pets.yaml".to_string(),
+            prefix: Some("pets".to_string()),
+            base_url: None,
+            auth: Some(Auth::Header { name: "X-API-Key".to_string(), value: EnvValue::Literal("k123".to_string()) }),
+        };
+        let tools = tools_from_spec(&spec, &service)?;
+        let names: Vec<String> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["pets_getPet", "pets_put_pets_petId"]);
+
+        let get = &tools[0];
+        let params = get.definition().parameters.unwrap();
+        assert_eq!(params["required"], json!(["petId"]));
+        assert_eq!(params["properties"]["verbose"]["type"], "boolean");
+        assert!(get.read_only(&HashMap::new()));
+
+        assert_eq!(encode_segment("a b/c"), "a%20b%2Fc");
+        let args = HashMap::from([("petId".to_string(), json!(7)), ("verbose".to_string(), json!(true))]);
+        let request = get.request(&args)?.build()?;
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(request.url().as_str(), "https://pets.example.com/v1/pets/7?verbose=true");
+        assert_eq!(request.headers()["X-API-Key"], "k123");
+
+        let put = &tools[1];
+        let params = put.definition().parameters.unwrap();
+        assert_eq!(params["properties"]["body"]["properties"]["name"]["type"], "string");
+        assert_eq!(params["required"], json!(["petId", "body"]));
+        assert!(!put.read_only(&HashMap::new()));
+        assert!(put.request(&HashMap::new()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use serde_json::{json, Map, Value};
+
+/// Builds a tool's parameter schema:
+///
+/// ```ignore
+/// Params::object()
+///     .string("path", "File to read.")
+///     .integer("limit", "Maximum lines to return.")
+///     .required(&["path"])
+///     .build()
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    properties: Map<String, Value>,
+    required: Vec<String>,
+}
+
+#[allow(dead_code)]
+impl Params {
+    pub fn object() -> Self {
+        Self::default()
+    }
+
+    pub fn string(self, name: &str, description: &str) -> Self {
+        self.property(name, json!({ "type": "string", "description": description }))
+    }
+
+    pub fn integer(self, name: &str, description: &str) -> Self {
+        self.property(name, json!({ "type": "integer", "description": description }))
+    }
+
+    pub fn number(self, name: &str, description: &str) -> Self {
+        self.property(name, json!({ "type": "number", "description": description }))
+    }
+
+    pub fn boolean(self, name: &str, description: &str) -> Self {
+        self.property(name, json!({ "type": "boolean", "description": description }))
+    }
+
+    /// A string limited to `values`.
+    pub fn one_of(self, name: &str, description: &str, values: &[&str]) -> Self {
+        self.property(name, json!({ "type": "string", "enum": values, "description": description }))
+    }
+
+    /// An array whose items match `items` (e.g. `json!({ "type": "string" })`).
+    pub fn array(self, name: &str, description: &str, items: Value) -> Self {
+        self.property(name, json!({ "type": "array", "items": items, "description": description }))
+    }
+
+    /// Any other property schema, including nested `Params::build()` objects.
+    pub fn property(mut self, name: &str, schema: Value) -> Self {
+        self.properties.insert(name.to_string(), schema);
+        self
+    }
+
+    pub fn required(mut self, names: &[&str]) -> Self {
+        self.required.extend(names.iter().map(|n| n.to_string()));
+        self
+    }
+
+    pub fn build(self) -> Value {
+        debug_assert!(
+            self.required.iter().all(|r| self.properties.contains_key(r)),
+            "required parameters must be declared"
+        );
+        json!({ "type": "object", "properties": self.properties, "required": self.required })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_builder_matches_hand_written_schema() {
+        let built = Params::object()
+            .string("path", "File to read.")
+            .integer("limit", "Maximum lines.")
+            .one_of("mode", "How to read.", &["head", "tail"])
+            .array("tags", "Labels.", json!({ "type": "string" }))
+            .required(&["path"])
+            .build();
+        assert_eq!(built, json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "File to read." },
+                "limit": { "type": "integer", "description": "Maximum lines." },
+                "mode": { "type": "string", "enum": ["head", "tail"], "description": "How to read." },
+                "tags": { "type": "array", "items": { "type": "string" }, "description": "Labels." },
+            },
+            "required": ["path"],
+        }));
+    }
+}
@@ -0,0 +1,295 @@
+//! Tools provided by standalone executables speaking JSON-RPC 2.0 over stdio.
+//!
+//! Each plugin reads one request per line on stdin and writes one response per
+//! line on stdout. Two methods are used:
+//!
+//! - `describe` → `{"tools": [{"name", "description", "parameters", "read_only"?}]}`
+//! - `execute` with `{"name", "arguments"}` → `{"output", "is_error"?}`
+//!
+//! A plugin process is started on first use and restarted when it exits or
+//! stops answering sensibly. A failed call is retried on the new process only
+//! when its request never reached the old one, or when it is `describe`: an
+//! `execute` the plugin may already have acted on is never sent twice.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::{ToolExecutor, ToolResult};
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ToolDescription {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) parameters: Option<Value>,
+    #[serde(default)]
+    pub(crate) read_only: bool,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Describe {
+    pub(crate) tools: Vec<ToolDescription>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Executed {
+    #[serde(default)]
+    pub(crate) output: Value,
+    #[serde(default)]
+    pub(crate) is_error: bool,
+}
+
+struct Running {
+    // Held so the process is killed when the handle is dropped.
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// A supervised plugin process shared by all tools it provides.
+pub struct PluginProcess {
+    path: PathBuf,
+    running: Mutex<Option<Running>>,
+    next_id: AtomicU64,
+}
+
+impl PluginProcess {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, running: Mutex::new(None), next_id: AtomicU64::new(1) }
+    }
+
+    fn spawn(&self) -> Result<Running> {
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start plugin {}", self.path.display()))?;
+        let stdin = child.stdin.take().context("plugin stdin unavailable")?;
+        let stdout = BufReader::new(child.stdout.take().context("plugin stdout unavailable")?);
+        if let Some(stderr) = child.stderr.take() {
+            let name = self.path.display().to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!(plugin = %name, "{}", line);
+                }
+            });
+        }
+        Ok(Running { _child: child, stdin, stdout })
+    }
+
+    /// Calls `method`, restarting the process if it has died. See the module
+    /// docs for when the call is retried. Errors reported by the plugin itself
+    /// are returned without a restart.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let mut running = self.running.lock().await;
+        let mut last_error = None;
+        for _ in 0..2 {
+            if running.is_none() {
+                *running = Some(self.spawn()?);
+            }
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+            let process = running.as_mut().unwrap();
+            let sent = send(process, id, method, &params).await;
+            let written = sent.is_ok();
+            let result = match sent {
+                Ok(()) => receive(process, id).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(response) => return response,
+                Err(e) => {
+                    warn!(plugin = %self.path.display(), "Plugin failed ({:#}); restarting", e);
+                    *running = None;
+                    if written && method != "describe" {
+                        return Err(e.context(format!("Plugin {} failed", self.path.display())));
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap().context(format!("Plugin {} keeps failing", self.path.display())))
+    }
+}
+
+async fn send(running: &mut Running, id: u64, method: &str, params: &Value) -> Result<()> {
+    let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    running.stdin.write_all(format!("{}\n", request).as_bytes()).await?;
+    running.stdin.flush().await?;
+    Ok(())
+}
+
+/// Waits for the response with `id`. The outer error means the transport
+/// broke; the inner one is a JSON-RPC error.
+async fn receive(running: &mut Running, id: u64) -> Result<Result<Value>> {
+    loop {
+        let mut line = String::new();
+        if running.stdout.read_line(&mut line).await? == 0 {
+            anyhow::bail!("plugin exited");
+        }
+        let response: Value = serde_json::from_str(line.trim())
+            .with_context(|| format!("invalid JSON-RPC response: {}", line.trim()))?;
+        // Responses to calls abandoned earlier (e.g. on a deadline) are skipped.
+        if response["id"].as_u64() != Some(id) {
+            continue;
+        }
+        if let Some(error) = response.get("error") {
+            let message = error["message"].as_str().unwrap_or("unknown error").to_string();
+            return Ok(Err(anyhow::anyhow!(message)));
+        }
+        return Ok(Ok(response.get("result").cloned().unwrap_or(Value::Null)));
+    }
+}
+
+/// One tool exposed by a plugin.
+pub struct PluginTool {
+    description: ToolDescription,
+    process: Arc<PluginProcess>,
+}
+
+#[async_trait]
+impl ToolExecutor for PluginTool {
+    fn name(&self) -> String {
+        self.description.name.clone()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.description.name.clone(),
+            description: self.description.description.clone(),
+            parameters: self.description.parameters.clone(),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        self.description.read_only
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let result = self.process
+            .call("execute", json!({ "name": self.description.name, "arguments": args }))
+            .await?;
+        let executed: Executed = serde_json::from_value(result)
+            .context("plugin returned a malformed execute result")?;
+        Ok(ToolResult { output: executed.output, is_error: executed.is_error })
+    }
+}
+
+/// Starts a plugin and asks it which tools it provides.
+pub async fn load(path: &Path) -> Result<Vec<PluginTool>> {
+    let process = Arc::new(PluginProcess::new(path.to_path_buf()));
+    let described: Describe = serde_json::from_value(process.call("describe", json!({})).await?)
+        .with_context(|| format!("Plugin {} returned a malformed describe result", path.display()))?;
+    Ok(described.tools.into_iter()
+        .map(|description| PluginTool { description, process: process.clone() })
+        .collect())
+}
+
+/// Loads every executable in `dir`. Plugins that fail to start are skipped with a warning.
+pub async fn discover(dir: &Path) -> Vec<PluginTool> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_executable(p))
+        .collect();
+    paths.sort();
+
+    let mut tools = Vec::new();
+    for path in paths {
+        match load(&path).await {
+            Ok(loaded) => {
+                debug!(plugin = %path.display(), count = loaded.len(), "Loaded plugin tools");
+                tools.extend(loaded);
+            }
+            Err(e) => warn!(plugin = %path.display(), "Skipping plugin: {:#}", e),
+        }
+    }
+    tools
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.is_file() && meta.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        meta.is_file()
+    }
+}
+
+/// `~/.chitti/plugins`.
+pub fn default_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitti").join("plugins"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const PLUGIN: &str = r#"#!/bin/bash
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -E 's/.*"id":([0-9]+).*/\1/')
+  case "$line" in
+    *'"describe"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"tools":[{"name":"shout","description":"Upper-cases text","read_only":true,"parameters":{"type":"object","properties":{"text":{"type":"string"}}}}]}}' ;;
+    *crash*) echo "$id" >> "$0.crashes"; exit 1 ;;
+    *)
+      text=$(printf '%s' "$line" | sed -E 's/.*"text":"([^"]*)".*/\1/')
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"output":"'"${text^^}"'"}}' ;;
+  esac
+done
+"#;
+
+    #[tokio::test]
+    async fn test_plugin_tools_are_described_executed_and_restarted() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("chitti-plugins-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("shout");
+        std::fs::write(&path, PLUGIN)?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        std::fs::write(dir.join("README"), "not a plugin")?;
+
+        let tools = discover(&dir).await;
+        assert_eq!(tools.len(), 1);
+        let tool = &tools[0];
+        assert_eq!(tool.name(), "shout");
+        assert!(tool.read_only(&HashMap::new()));
+
+        let args = |text: &str| HashMap::from([("text".to_string(), json!(text))]);
+        assert_eq!(tool.execute(args("hello")).await?.output, json!("HELLO"));
+        assert!(tool.execute(args("crash")).await.is_err());
+        // A request the plugin received isn't sent again after it dies.
+        assert_eq!(std::fs::read_to_string(dir.join("shout.crashes"))?.lines().count(), 1);
+        // The crashed process is replaced on the next call.
+        assert_eq!(tool.execute(args("again")).await?.output, json!("AGAIN"));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// A machine that shell tools run on over SSH instead of locally.
+///
+/// Authentication is left to `ssh`: the key file if one is given, otherwise
+/// the agent and `~/.ssh/config`. Batch mode is forced so a password or host
+/// key prompt fails the call instead of hanging it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remote {
+    pub host: String,
+    pub user: Option<String>,
+    pub key: Option<PathBuf>,
+    pub port: Option<u16>,
+}
+
+impl Remote {
+    /// `user@host`, or just `host` when no user is configured.
+    pub fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    /// Arguments to `ssh` that start bash on the remote host, reading its
+    /// script from stdin.
+    pub fn ssh_args(&self) -> Vec<String> {
+        let mut args = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+        if let Some(key) = &self.key {
+            args.push("-i".to_string());
+            args.push(key.display().to_string());
+        }
+        if let Some(port) = self.port {
+            args.push("-p".to_string());
+            args.push(port.to_string());
+        }
+        args.push(self.destination());
+        args.push("--".to_string());
+        args.push("bash -s".to_string());
+        args
+    }
+
+    /// Runs `script` on the remote host with `env` exported, and waits for it.
+    /// ssh doesn't forward the local environment, so the variables travel on
+    /// ssh's stdin with the script rather than on any command line, where
+    /// `ps` would show them.
+    pub async fn output(&self, script: &str, env: &HashMap<String, String>) -> std::io::Result<Output> {
+        let mut child = Command::new("ssh")
+            .args(self.ssh_args())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // If ssh already gave up (no route, bad key), its stderr says why.
+            let _ = stdin.write_all(stdin_script(script, env).as_bytes()).await;
+        }
+        child.wait_with_output().await
+    }
+}
+
+/// What the remote `bash -s` reads: the exports, then `script` handed to a
+/// fresh bash so it is parsed whole and can't read the rest of stdin.
+fn stdin_script(script: &str, env: &HashMap<String, String>) -> String {
+    let mut names: Vec<&String> = env.keys().collect();
+    names.sort();
+    let mut full = String::new();
+    for name in names {
+        full.push_str(&format!("export {}={}\n", name, shell_quote(&env[name])));
+    }
+    full.push_str(&format!("exec bash -c {} < /dev/null\n", shell_quote(script)));
+    full
+}
+
+/// Quotes `s` as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_args_quote_the_script() {
+        let remote = Remote {
+            host: "homelab".to_string(),
+            user: Some("admin".to_string()),
+            key: Some(PathBuf::from("/home/me/.ssh/id_ed25519")),
+            port: Some(2222),
+        };
+        assert_eq!(
+            remote.ssh_args(),
+            vec![
+                "-o", "BatchMode=yes",
+                "-i", "/home/me/.ssh/id_ed25519",
+                "-p", "2222",
+                "admin@homelab",
+                "--",
+                "bash -s",
+            ]
+        );
+
+        let bare = Remote { host: "box".to_string(), user: None, key: None, port: None };
+        assert_eq!(bare.ssh_args(), vec!["-o", "BatchMode=yes", "box", "--", "bash -s"]);
+    }
+
+    #[test]
+    fn test_env_and_script_travel_on_stdin() {
+        let env = HashMap::from([
+            ("TOKEN".to_string(), "s3cr'et".to_string()),
+            ("API_URL".to_string(), "https://x".to_string()),
+        ]);
+        assert_eq!(
+            stdin_script("echo 'hi' && uptime", &env),
+            "export API_URL='https://x'\nexport TOKEN='s3cr'\\''et'\nexec bash -c 'echo '\\''hi'\\'' && uptime' < /dev/null\n"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::process::Command;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::redact;
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+
+/// Secret values looked up this session, by environment variable name.
+/// Shared with the tools that run commands; never sent to the model.
+#[derive(Debug, Clone, Default)]
+pub struct InjectedSecrets(Arc<RwLock<HashMap<String, String>>>);
+
+impl InjectedSecrets {
+    pub fn insert(&self, var: &str, value: String) {
+        self.0.write().unwrap().insert(var.to_string(), value);
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, String> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+/// Where a credential lives.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backend {
+    /// `keychain:<service>/<account>`: macOS Keychain, Windows Credential
+    /// Manager or the Linux secret service.
+    Keychain { service: String, account: String },
+    /// `pass:<path>`: the first line of a `pass` entry.
+    Pass(String),
+    /// `op:op://<vault>/<item>/<field>`: a 1Password CLI secret reference.
+    OnePassword(String),
+    /// `env:<VAR>`: a variable in Chitti's own environment.
+    Env(String),
+}
+
+impl Backend {
+    pub fn parse(name: &str) -> Result<Self> {
+        let (kind, rest) = name.split_once(':')
+            .context("Secret names look like keychain:<service>/<account>, pass:<path>, op:op://<vault>/<item>/<field> or env:<VAR>")?;
+        let rest = rest.trim();
+        if rest.is_empty() || rest.starts_with('-') {
+            anyhow::bail!("Invalid secret name '{}'", name);
+        }
+        Ok(match kind {
+            "keychain" => {
+                let (service, account) = rest.split_once('/').context("keychain secrets are keychain:<service>/<account>")?;
+                Backend::Keychain { service: service.to_string(), account: account.to_string() }
+            }
+            "pass" => Backend::Pass(rest.to_string()),
+            "op" if rest.starts_with("op://") => Backend::OnePassword(rest.to_string()),
+            "op" => anyhow::bail!("1Password secrets are op:op://<vault>/<item>/<field>"),
+            "env" => Backend::Env(rest.to_string()),
+            other => anyhow::bail!("Unknown secret backend '{}'", other),
+        })
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Backend::Keychain { .. } => "keychain",
+            Backend::Pass(_) => "pass",
+            Backend::OnePassword(_) => "1password",
+            Backend::Env(_) => "env",
+        }
+    }
+
+    /// The value and the names (not values) of any extra fields, or `None`
+    /// if the credential doesn't exist.
+    async fn fetch(&self) -> Result<Option<(String, Vec<String>)>> {
+        match self {
+            Backend::Keychain { service, account } => {
+                let entry = keyring::Entry::new(service, account).context("Failed to access the OS keychain")?;
+                match entry.get_password() {
+                    Ok(value) => Ok(Some((value, Vec::new()))),
+                    Err(keyring::Error::NoEntry) => Ok(None),
+                    Err(e) => Err(e).context("Failed to read from the OS keychain"),
+                }
+            }
+            Backend::Pass(path) => {
+                let Some(text) = run("pass", &["show", path]).await? else {
+                    return Ok(None);
+                };
+                let mut lines = text.lines();
+                let value = lines.next().unwrap_or_default().to_string();
+                let fields = lines.filter_map(|l| l.split_once(':').map(|(k, _)| k.trim().to_string())).collect();
+                Ok(Some((value, fields)))
+            }
+            Backend::OnePassword(reference) => {
+                Ok(run("op", &["read", "--no-newline", reference]).await?.map(|v| (v, Vec::new())))
+            }
+            Backend::Env(var) => Ok(std::env::var(var).ok().map(|v| (v, Vec::new()))),
+        }
+    }
+}
+
+/// Runs a password manager CLI; a failing command means "not found".
+async fn run(program: &str, args: &[&str]) -> Result<Option<String>> {
+    let output = Command::new(program).args(args).kill_on_drop(true).output().await
+        .with_context(|| format!("Failed to run {}", program))?;
+    Ok(output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string()))
+}
+
+/// The variable a secret is exposed as when the model doesn't choose one.
+pub fn default_var(name: &str) -> String {
+    let last = name.rsplit(['/', ':']).find(|s| !s.is_empty()).unwrap_or(name);
+    let cleaned: String = last.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("CHITTI_SECRET_{}", cleaned)
+}
+
+/// Looks up a named credential and makes it available to command-running
+/// tools as an environment variable. The model learns only whether the
+/// secret exists and where it can be used; the value is registered for
+/// redaction so it can't leak back through tool output.
+pub struct SecretsLookupTool {
+    injected: InjectedSecrets,
+}
+
+impl SecretsLookupTool {
+    pub fn new(injected: InjectedSecrets) -> Self {
+        Self { injected }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for SecretsLookupTool {
+    fn name(&self) -> String {
+        "secrets_lookup".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Check that a credential exists in the user's keychain or password manager and expose it to execute_bash as an environment variable. The value is never shown to you; refer to the variable (e.g. \"$CHITTI_SECRET_GITHUB\") in commands instead.".to_string(),
+            parameters: Some(Params::object()
+                .string("name", "The credential: keychain:<service>/<account>, pass:<path>, op:op://<vault>/<item>/<field> or env:<VAR>.")
+                .string("env_var", "Environment variable to expose it as (default CHITTI_SECRET_<NAME>).")
+                .required(&["name"])
+                .build()),
+        }
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let name = args.get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' argument"))?;
+        let backend = match Backend::parse(name) {
+            Ok(backend) => backend,
+            Err(e) => return Ok(ToolResult { output: json!({ "error": e.to_string() }), is_error: true }),
+        };
+        let var = args.get("env_var").and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            .map(str::to_string)
+            .unwrap_or_else(|| default_var(name));
+
+        let Some((value, fields)) = backend.fetch().await? else {
+            return Ok(ToolResult { output: json!({ "found": false, "backend": backend.label() }), is_error: false });
+        };
+        redact::register_secret(&value);
+        let length = value.chars().count();
+        self.injected.insert(&var, value);
+        Ok(ToolResult {
+            output: json!({
+                "found": true,
+                "backend": backend.label(),
+                "length": length,
+                "other_fields": fields,
+                "env_var": var,
+                "usage": format!("Available to execute_bash as ${}", var),
+            }),
+            is_error: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::bash::BashTool;
+
+    #[tokio::test]
+    async fn test_secret_reaches_bash_but_not_the_model() -> Result<()> {
+        assert_eq!(Backend::parse("keychain:github/me")?, Backend::Keychain { service: "github".into(), account: "me".into() });
+        assert_eq!(Backend::parse("op:op://Private/GitHub/token")?, Backend::OnePassword("op://Private/GitHub/token".into()));
+        assert!(Backend::parse("pass:--help").is_err());
+        assert_eq!(default_var("pass:work/github-token"), "CHITTI_SECRET_GITHUB_TOKEN");
+
+        std::env::set_var("CHITTI_TEST_LOOKUP_SECRET", "hunter2-very-secret");
+        let injected = InjectedSecrets::default();
+        let tool = SecretsLookupTool::new(injected.clone());
+        let result = tool.execute([("name".to_string(), json!("env:CHITTI_TEST_LOOKUP_SECRET"))].into()).await?;
+        assert_eq!(result.output["found"], true);
+        assert_eq!(result.output["env_var"], "CHITTI_SECRET_CHITTI_TEST_LOOKUP_SECRET");
+        assert!(!result.output.to_string().contains("hunter2"));
+
+        let bash = BashTool::new(None).with_secrets(injected);
+        let run = bash.execute([("command".to_string(), json!("printf %s \"$CHITTI_SECRET_CHITTI_TEST_LOOKUP_SECRET\" | wc -c"))].into()).await?;
+        assert_eq!(run.output["stdout"].as_str().unwrap().trim(), "19");
+
+        let missing = tool.execute([("name".to_string(), json!("env:CHITTI_TEST_LOOKUP_MISSING"))].into()).await?;
+        assert_eq!(missing.output["found"], false);
+        std::env::remove_var("CHITTI_TEST_LOOKUP_SECRET");
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Failure rate above which a tool is flagged; usually a schema or prompt bug.
+pub const FAILURE_WARN_RATE: f64 = 0.5;
+/// Calls needed before the failure rate is trusted enough to warn.
+pub const FAILURE_WARN_MIN_CALLS: u64 = 4;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolStat {
+    pub calls: u64,
+    pub failures: u64,
+    pub rejections: u64,
+    pub total_duration: Duration,
+    pub warned: bool,
+}
+
+impl ToolStat {
+    pub fn failure_rate(&self) -> f64 {
+        if self.calls == 0 { 0.0 } else { self.failures as f64 / self.calls as f64 }
+    }
+
+    /// Share of requested calls the user turned down.
+    pub fn rejection_rate(&self) -> f64 {
+        let requested = self.calls + self.rejections;
+        if requested == 0 { 0.0 } else { self.rejections as f64 / requested as f64 }
+    }
+
+    pub fn mean_duration(&self) -> Duration {
+        if self.calls == 0 { Duration::ZERO } else { self.total_duration / self.calls as u32 }
+    }
+}
+
+/// Per-session counters for tool calls, kept by the `ToolRegistry`.
+#[derive(Default)]
+pub struct ToolStats {
+    stats: Mutex<HashMap<String, ToolStat>>,
+    warnings: Mutex<Vec<String>>,
+}
+
+impl ToolStats {
+    /// Records a finished call. Returns true the first time the tool crosses
+    /// the failure warning threshold.
+    pub fn record_call(&self, name: &str, duration: Duration, failed: bool) -> bool {
+        let mut stats = self.stats.lock().unwrap();
+        let stat = stats.entry(name.to_string()).or_default();
+        stat.calls += 1;
+        stat.total_duration += duration;
+        if failed {
+            stat.failures += 1;
+        }
+        let crossed = !stat.warned
+            && stat.calls >= FAILURE_WARN_MIN_CALLS
+            && stat.failure_rate() > FAILURE_WARN_RATE;
+        stat.warned |= crossed;
+        if crossed {
+            self.warnings.lock().unwrap().push(format!(
+                "Tool '{}' has failed {} of {} calls; its schema or description may be wrong",
+                name, stat.failures, stat.calls
+            ));
+        }
+        crossed
+    }
+
+    /// Warnings raised since the last call, for the Conductor to show.
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    pub fn record_rejection(&self, name: &str) {
+        self.stats.lock().unwrap().entry(name.to_string()).or_default().rejections += 1;
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, name: &str) -> Option<ToolStat> {
+        self.stats.lock().unwrap().get(name).cloned()
+    }
+
+    /// A table of all tools that were called or rejected, sorted by name.
+    pub fn table(&self) -> String {
+        let stats = self.stats.lock().unwrap();
+        if stats.is_empty() {
+            return "No tool calls yet.".to_string();
+        }
+        let mut names: Vec<&String> = stats.keys().collect();
+        names.sort();
+        let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(4);
+        let mut out = format!(
+            "{:width$}  {:>5}  {:>7}  {:>8}  {:>8}\n",
+            "tool", "calls", "failed", "rejected", "avg ms",
+            width = width
+        );
+        for name in names {
+            let s = &stats[name];
+            out.push_str(&format!(
+                "{:width$}  {:>5}  {:>6.0}%  {:>7.0}%  {:>8}{}\n",
+                name,
+                s.calls,
+                s.failure_rate() * 100.0,
+                s.rejection_rate() * 100.0,
+                s.mean_duration().as_millis(),
+                if s.failure_rate() > FAILURE_WARN_RATE && s.calls >= FAILURE_WARN_MIN_CALLS { "  !" } else { "" },
+                width = width
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_rates_and_single_warning() {
+        let stats = ToolStats::default();
+        let ms = Duration::from_millis;
+        assert!(!stats.record_call("flaky", ms(10), true));
+        assert!(!stats.record_call("flaky", ms(30), true));
+        assert!(!stats.record_call("flaky", ms(20), false));
+        assert!(stats.record_call("flaky", ms(20), true));
+        assert!(!stats.record_call("flaky", ms(20), true));
+        assert_eq!(stats.take_warnings(), vec!["Tool 'flaky' has failed 3 of 4 calls; its schema or description may be wrong"]);
+        assert!(stats.take_warnings().is_empty());
+        stats.record_rejection("flaky");
+        stats.record_call("execute_bash", ms(5), false);
+
+        let flaky = stats.get("flaky").unwrap();
+        assert_eq!((flaky.calls, flaky.failures, flaky.rejections), (5, 4, 1));
+        assert_eq!(flaky.mean_duration(), ms(20));
+        assert!((flaky.rejection_rate() - 1.0 / 6.0).abs() < 1e-9);
+
+        let table = stats.table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "tool          calls   failed  rejected    avg ms");
+        assert_eq!(lines[1], "execute_bash      1       0%        0%         5");
+        assert_eq!(lines[2], "flaky             5      80%       17%        20  !");
+    }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use sysinfo::{Components, Disks, ProcessesToUpdate, System, MINIMUM_CPU_UPDATE_INTERVAL};
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+
+const MB: u64 = 1024 * 1024;
+
+/// Structured machine stats (CPU, memory, disks, temperatures) and the top
+/// processes, so questions like "why is my fan so loud?" get answered from
+/// data rather than guessed bash incantations.
+pub struct SysInfoTool;
+
+#[async_trait]
+impl ToolExecutor for SysInfoTool {
+    fn name(&self) -> String {
+        "system_info".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Report this machine's CPU load, memory, swap, disk space, temperatures and the processes using the most CPU or memory.".to_string(),
+            parameters: Some(Params::object()
+                .one_of("sort_by", "Rank processes by CPU (default) or memory use.", &["cpu", "memory"])
+                .integer("limit", "How many processes to list (default 10).")
+                .build()),
+        }
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let by_memory = args.get("sort_by").and_then(|v| v.as_str()) == Some("memory");
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+        // CPU usage is a delta between two samples, so this blocks briefly.
+        let output = tokio::task::spawn_blocking(move || snapshot(by_memory, limit)).await?;
+        Ok(ToolResult { output, is_error: false })
+    }
+}
+
+fn snapshot(by_memory: bool, limit: usize) -> Value {
+    let mut sys = System::new_all();
+    std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+
+    let mut processes: Vec<_> = sys.processes().values().collect();
+    if by_memory {
+        processes.sort_by_key(|p| std::cmp::Reverse(p.memory()));
+    } else {
+        processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage()));
+    }
+    let top: Vec<Value> = processes.into_iter().take(limit).map(|p| json!({
+        "pid": p.pid().as_u32(),
+        "name": p.name().to_string_lossy(),
+        // Per core, so a busy multi-threaded process can exceed 100.
+        "cpu_percent": round(p.cpu_usage()),
+        "memory_mb": p.memory() / MB,
+    })).collect();
+
+    let load = System::load_average();
+    let disks: Vec<Value> = Disks::new_with_refreshed_list().iter().map(|d| json!({
+        "mount_point": d.mount_point().display().to_string(),
+        "total_gb": d.total_space() / (1024 * MB),
+        "available_gb": d.available_space() / (1024 * MB),
+    })).collect();
+    let temperatures: Vec<Value> = Components::new_with_refreshed_list().iter()
+        .filter_map(|c| Some(json!({ "label": c.label(), "celsius": round(c.temperature()?) })))
+        .collect();
+
+    json!({
+        "host": System::host_name(),
+        "os": System::long_os_version(),
+        "uptime_secs": System::uptime(),
+        "cpu": {
+            "cores": sys.cpus().len(),
+            "usage_percent": round(sys.global_cpu_usage()),
+            "load_average": [load.one, load.five, load.fifteen],
+        },
+        "memory": {
+            "total_mb": sys.total_memory() / MB,
+            "used_mb": sys.used_memory() / MB,
+            "swap_total_mb": sys.total_swap() / MB,
+            "swap_used_mb": sys.used_swap() / MB,
+        },
+        "disks": disks,
+        "temperatures": temperatures,
+        "top_processes": top,
+    })
+}
+
+fn round(value: f32) -> f64 {
+    (value as f64 * 10.0).round() / 10.0
+}
+
+/// Kills every process descended from Chitti: tool commands, anything they
+/// left running in the background, the browser and plugins. Returns how
+/// many were signalled.
+pub fn kill_descendants() -> usize {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    let mut pending = vec![sysinfo::Pid::from_u32(std::process::id())];
+    let mut killed = 0;
+    while let Some(parent) = pending.pop() {
+        for (pid, process) in sys.processes() {
+            // On Linux threads are listed as children too; signalling one kills us.
+            if process.parent() == Some(parent) && process.thread_kind().is_none() {
+                pending.push(*pid);
+                killed += usize::from(process.kill());
+            }
+        }
+    }
+    killed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_system_info_reports_structured_stats() -> Result<()> {
+        let args: HashMap<String, Value> = [
+            ("sort_by".to_string(), json!("memory")),
+            ("limit".to_string(), json!(3)),
+        ].into();
+        let result = SysInfoTool.execute(args).await?;
+        let output = result.output;
+        assert!(output["cpu"]["cores"].as_u64().unwrap() >= 1);
+        assert!(output["memory"]["total_mb"].as_u64().unwrap() > 0);
+        let top = output["top_processes"].as_array().unwrap();
+        assert!(!top.is_empty() && top.len() <= 3);
+        let memory: Vec<u64> = top.iter().map(|p| p["memory_mb"].as_u64().unwrap()).collect();
+        assert!(memory.windows(2).all(|w| w[0] >= w[1]));
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::time::Instant;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::params::Params;
+use crate::tools::{FileAccess, ToolExecutor, ToolResult};
+use crate::workspace::PathResolver;
+
+const DEFAULT_SECONDS: u64 = 30;
+const MAX_SECONDS: u64 = 300;
+const DEFAULT_MAX_LINES: usize = 200;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Follows a local file like `tail -f` for a bounded time and returns the
+/// lines appended meanwhile, so the model can watch a log while the user
+/// reproduces a problem instead of polling with bash.
+pub struct TailLogTool;
+
+#[async_trait]
+impl ToolExecutor for TailLogTool {
+    fn name(&self) -> String {
+        "tail_log".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Follow a local log file (like `tail -f`) for a limited time and return the lines appended meanwhile. Use it to watch a log while the user reproduces a problem.".to_string(),
+            parameters: Some(Params::object()
+                .string("path", "Path of the file to follow.")
+                .integer("seconds", "How long to follow the file (default 30, at most 300).")
+                .string("pattern", "Only return lines matching this regular expression.")
+                .string("stop_on", "Stop as soon as a line matches this regular expression.")
+                .integer("max_lines", "Keep at most this many of the newest lines (default 200).")
+                .required(&["path"])
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    fn files_touched(&self, args: &HashMap<String, Value>) -> Vec<(String, FileAccess)> {
+        args.get("path").and_then(|v| v.as_str())
+            .map(|p| vec![(p.to_string(), FileAccess::Read)])
+            .unwrap_or_default()
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let path = args.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' argument"))?;
+        let seconds = args.get("seconds").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_SECONDS).min(MAX_SECONDS);
+        let max_lines = args.get("max_lines").and_then(|v| v.as_u64()).map(|n| n as usize).unwrap_or(DEFAULT_MAX_LINES);
+        let regex = |key: &str| -> Result<Option<Regex>> {
+            match args.get(key).and_then(|v| v.as_str()).filter(|p| !p.is_empty()) {
+                Some(p) => Ok(Some(Regex::new(p).map_err(|e| anyhow::anyhow!("Invalid '{}' regex: {}", key, e))?)),
+                None => Ok(None),
+            }
+        };
+        let (pattern, stop_on) = (regex("pattern")?, regex("stop_on")?);
+        let resolved = match PathResolver::current().resolve(path) {
+            Ok(resolved) => resolved,
+            Err(missing) => return Ok(ToolResult { output: missing.to_json(), is_error: true }),
+        };
+        let path = resolved.path.as_path();
+
+        let mut file = tokio::fs::File::open(path).await
+            .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", path.display(), e))?;
+        let mut offset = file.metadata().await?.len();
+        let started = Instant::now();
+        let deadline = started + Duration::from_secs(seconds);
+        let mut partial = String::new();
+        let mut lines: Vec<String> = Vec::new();
+        let (mut total, mut stopped_by) = (0usize, None);
+
+        while stopped_by.is_none() && Instant::now() < deadline {
+            let len = tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(offset);
+            if len < offset {
+                // Truncated or rotated: start again from the top.
+                file = tokio::fs::File::open(path).await?;
+                offset = 0;
+                partial.clear();
+            }
+            if len > offset {
+                file.seek(SeekFrom::Start(offset)).await?;
+                let mut chunk = Vec::new();
+                (&mut file).take(len - offset).read_to_end(&mut chunk).await?;
+                offset += chunk.len() as u64;
+                partial.push_str(&String::from_utf8_lossy(&chunk));
+                while let Some(end) = partial.find('\n') {
+                    let line: String = partial.drain(..=end).collect();
+                    let line = line.trim_end_matches(['\n', '\r']).to_string();
+                    if stop_on.as_ref().is_some_and(|r| r.is_match(&line)) {
+                        stopped_by = Some(line.clone());
+                    }
+                    if pattern.as_ref().is_none_or(|r| r.is_match(&line)) {
+                        total += 1;
+                        lines.push(line);
+                    }
+                    if stopped_by.is_some() {
+                        break;
+                    }
+                }
+            }
+            if stopped_by.is_none() {
+                tokio::time::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+            }
+        }
+
+        let dropped = lines.len().saturating_sub(max_lines);
+        lines.drain(..dropped);
+        Ok(ToolResult {
+            output: json!({
+                "lines": lines,
+                "matched_lines": total,
+                "dropped_lines": dropped,
+                "elapsed_secs": started.elapsed().as_secs_f64(),
+                "stopped_on": stopped_by,
+                "resolved_path": resolved.corrected.then(|| path.display().to_string()),
+            }),
+            is_error: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_tail_log_returns_only_new_lines() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("chitti-tail-{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old line\n")?;
+        let writer_path = path.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let mut f = std::fs::OpenOptions::new().append(true).open(&writer_path).unwrap();
+            writeln!(f, "INFO starting").unwrap();
+            writeln!(f, "ERROR disk full").unwrap();
+            writeln!(f, "INFO retrying").unwrap();
+            writeln!(f, "FATAL giving up").unwrap();
+            writeln!(f, "INFO never seen").unwrap();
+        });
+
+        let args: HashMap<String, Value> = [
+            ("path".to_string(), json!(path.to_str().unwrap())),
+            ("seconds".to_string(), json!(5)),
+            ("pattern".to_string(), json!("ERROR|FATAL")),
+            ("stop_on".to_string(), json!("^FATAL")),
+        ].into();
+        let result = TailLogTool.execute(args).await?;
+        assert_eq!(result.output["lines"], json!(["ERROR disk full", "FATAL giving up"]));
+        assert_eq!(result.output["stopped_on"], "FATAL giving up");
+        assert!(result.output["elapsed_secs"].as_f64().unwrap() < 5.0);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
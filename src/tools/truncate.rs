@@ -0,0 +1,325 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::tools::params::Params;
+use crate::tools::{ToolExecutor, ToolResult};
+use crate::brains::gemini::types::FunctionDeclaration;
+
+/// Default cap on the serialized size of a single tool result sent to the model.
+pub const DEFAULT_MAX_TOOL_RESULT_BYTES: usize = 32 * 1024;
+
+/// Lines from the omitted middle section that are kept because they look relevant.
+const RELEVANT_KEYWORDS: &[&str] = &["error", "fail", "panic", "exception", "fatal", "warn"];
+const MAX_RELEVANT_LINES: usize = 20;
+const MAX_READ_LINES: usize = 500;
+const MAX_READ_BYTES: usize = 16 * 1024;
+
+/// Keeps the full text of truncated outputs so the model can fetch omitted ranges.
+#[derive(Default)]
+pub struct OutputStore {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl OutputStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&self, text: String) -> String {
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        self.entries.lock().unwrap().insert(id.clone(), text);
+        id
+    }
+
+    /// Returns lines `start..=end` (1-based) and the total line count.
+    pub fn read_lines(&self, id: &str, start: usize, end: usize) -> Option<(String, usize)> {
+        let entries = self.entries.lock().unwrap();
+        let text = entries.get(id)?;
+        let total = text.lines().count();
+        let start = start.max(1);
+        let end = end.min(total).min(start + MAX_READ_LINES - 1);
+        if start > end {
+            return Some((String::new(), total));
+        }
+        let lines: Vec<&str> = text.lines().skip(start - 1).take(end - start + 1).collect();
+        Some((lines.join("\n"), total))
+    }
+
+    /// Returns up to `len` bytes starting at byte `start`, widened to whole
+    /// characters, together with the offset to continue from and the total size.
+    pub fn read_bytes(&self, id: &str, start: usize, len: usize) -> Option<(String, usize, usize)> {
+        let entries = self.entries.lock().unwrap();
+        let text = entries.get(id)?;
+        let start = floor_char_boundary(text, start);
+        let end = ceil_char_boundary(text, start + len.clamp(1, MAX_READ_BYTES));
+        Some((text[start..end].to_string(), end, text.len()))
+    }
+}
+
+/// Shrinks oversized string fields in a tool result to head + relevant lines + tail,
+/// storing the full text so it can be paged in with `read_tool_output`.
+pub fn truncate_output(output: Value, max_bytes: usize, store: &OutputStore) -> Value {
+    if output.to_string().len() <= max_bytes {
+        return output;
+    }
+    let large_fields = count_large_strings(&output, 1024).max(1);
+    let budget = (max_bytes / large_fields).max(256);
+    truncate_value(output, budget, store)
+}
+
+fn count_large_strings(value: &Value, threshold: usize) -> usize {
+    match value {
+        Value::String(s) => usize::from(s.len() > threshold),
+        Value::Array(items) => items.iter().map(|v| count_large_strings(v, threshold)).sum(),
+        Value::Object(map) => map.values().map(|v| count_large_strings(v, threshold)).sum(),
+        _ => 0,
+    }
+}
+
+fn truncate_value(value: Value, budget: usize, store: &OutputStore) -> Value {
+    match value {
+        Value::String(s) if s.len() > budget => Value::String(truncate_text(&s, budget, store)),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| truncate_value(v, budget, store)).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, truncate_value(v, budget, store))).collect()),
+        other => other,
+    }
+}
+
+fn truncate_text(text: &str, budget: usize, store: &OutputStore) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let head_budget = budget * 2 / 5;
+    let tail_budget = budget * 2 / 5;
+
+    let mut head_end = 0;
+    let mut used = 0;
+    while head_end < lines.len() && used + lines[head_end].len() < head_budget {
+        used += lines[head_end].len() + 1;
+        head_end += 1;
+    }
+    let mut tail_start = lines.len();
+    used = 0;
+    while tail_start > head_end && used + lines[tail_start - 1].len() < tail_budget {
+        used += lines[tail_start - 1].len() + 1;
+        tail_start -= 1;
+    }
+
+    let id = store.put(text.to_string());
+    let mut out = String::new();
+    if head_end == 0 && tail_start == lines.len() {
+        // A few huge lines: fall back to a byte-level cut that keeps both ends.
+        let head = floor_char_boundary(text, head_budget);
+        let tail = ceil_char_boundary(text, text.len() - tail_budget).max(head);
+        out.push_str(&text[..head]);
+        out.push_str(&format!(
+            "\n[... bytes {}-{} of {} omitted; full text stored as output_id={}. Call read_tool_output with start_byte to fetch byte ranges ...]\n",
+            head,
+            tail,
+            text.len(),
+            id
+        ));
+        out.push_str(&text[tail..]);
+        return out;
+    }
+
+    for line in &lines[..head_end] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    let omitted = &lines[head_end..tail_start];
+    let omitted_bytes: usize = omitted.iter().map(|l| l.len() + 1).sum();
+    out.push_str(&format!(
+        "[... {} lines ({} bytes) omitted: lines {}-{} of {}; full text stored as output_id={}. Call read_tool_output to fetch line ranges ...]\n",
+        omitted.len(),
+        omitted_bytes,
+        head_end + 1,
+        tail_start,
+        lines.len(),
+        id
+    ));
+    let relevant: Vec<(usize, &str)> = omitted
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| {
+            let lower = l.to_lowercase();
+            RELEVANT_KEYWORDS.iter().any(|k| lower.contains(k))
+        })
+        .take(MAX_RELEVANT_LINES)
+        .map(|(i, l)| (head_end + i + 1, *l))
+        .collect();
+    if !relevant.is_empty() {
+        out.push_str("[matching lines from the omitted section]\n");
+        for (n, line) in relevant {
+            let line = &line[..floor_char_boundary(line, 300)];
+            out.push_str(&format!("{}: {}\n", n, line));
+        }
+        out.push_str("[end of matching lines]\n");
+    }
+    for line in &lines[tail_start..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn floor_char_boundary(s: &str, mut idx: usize) -> usize {
+    if idx >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+fn ceil_char_boundary(s: &str, mut idx: usize) -> usize {
+    if idx >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(idx) {
+        idx += 1;
+    }
+    idx
+}
+
+/// Lets the model page through tool output that was truncated.
+pub struct ReadToolOutputTool {
+    store: Arc<OutputStore>,
+}
+
+impl ReadToolOutputTool {
+    pub fn new(store: Arc<OutputStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for ReadToolOutputTool {
+    fn name(&self) -> String {
+        "read_tool_output".to_string()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.name(),
+            description: "Read a line range, or with start_byte a byte range, from a tool output that was truncated. Use the output_id from the truncation note; byte ranges suit outputs made of a few very long lines.".to_string(),
+            parameters: Some(Params::object()
+                .string("output_id", "The output_id given in the truncation note.")
+                .integer("start_line", "First line to return (1-based).")
+                .integer("end_line", "Last line to return (inclusive). At most 500 lines are returned per call.")
+                .integer("start_byte", "Byte offset to read from (0-based) instead of a line range.")
+                .integer("max_bytes", "How many bytes to read from start_byte (default and maximum 16384).")
+                .required(&["output_id"])
+                .build()),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let id = args.get("output_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'output_id' argument"))?;
+        if let Some(start) = args.get("start_byte").and_then(|v| v.as_u64()) {
+            let len = args.get("max_bytes").and_then(|v| v.as_u64()).unwrap_or(MAX_READ_BYTES as u64) as usize;
+            return Ok(match self.store.read_bytes(id, start as usize, len) {
+                Some((text, next, total)) => ToolResult {
+                    output: json!({ "text": text, "next_byte": next, "total_bytes": total }),
+                    is_error: false,
+                },
+                None => ToolResult {
+                    output: json!({ "error": format!("Unknown output_id: {}", id) }),
+                    is_error: true,
+                },
+            });
+        }
+        let start = args.get("start_line").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+        let end = args.get("end_line").and_then(|v| v.as_u64()).unwrap_or(start as u64 + 100) as usize;
+
+        match self.store.read_lines(id, start, end) {
+            Some((lines, total)) => Ok(ToolResult {
+                output: json!({ "lines": lines, "total_lines": total }),
+                is_error: false,
+            }),
+            None => Ok(ToolResult {
+                output: json!({ "error": format!("Unknown output_id: {}", id) }),
+                is_error: true,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_output_is_untouched() {
+        let store = OutputStore::new();
+        let output = json!({ "stdout": "hello" });
+        assert_eq!(truncate_output(output.clone(), 1024, &store), output);
+    }
+
+    #[test]
+    fn test_truncates_head_tail_and_keeps_relevant_lines() {
+        let store = OutputStore::new();
+        let mut text = String::new();
+        for i in 1..=2000 {
+            if i == 1000 {
+                text.push_str("ERROR: disk full\n");
+            } else {
+                text.push_str(&format!("line {}\n", i));
+            }
+        }
+        let truncated = truncate_output(json!({ "stdout": text }), 2048, &store);
+        let stdout = truncated["stdout"].as_str().unwrap();
+        assert!(stdout.len() < 4096);
+        assert!(stdout.starts_with("line 1\n"));
+        assert!(stdout.contains("line 2000"));
+        assert!(stdout.contains("1000: ERROR: disk full"));
+
+        let id = stdout.split("output_id=").nth(1).unwrap().split('.').next().unwrap();
+        let (lines, total) = store.read_lines(id, 999, 1001).unwrap();
+        assert_eq!(total, 2000);
+        assert_eq!(lines, "line 999\nERROR: disk full\nline 1001");
+    }
+
+    #[tokio::test]
+    async fn test_huge_single_line_keeps_both_ends_and_pages_by_byte() -> Result<()> {
+        let store = Arc::new(OutputStore::new());
+        let text = format!("{{\"start\":1,{}\"end\":\"é\"}}", "\"k\":\"ü\",".repeat(5000));
+        let truncated = truncate_output(json!({ "stdout": text }), 2048, &store);
+        let stdout = truncated["stdout"].as_str().unwrap();
+        assert!(stdout.len() < 4096);
+        assert!(stdout.starts_with("{\"start\":1,"));
+        assert!(stdout.ends_with("\"end\":\"é\"}"));
+
+        let id = stdout.split("output_id=").nth(1).unwrap().split('.').next().unwrap();
+        let tool = ReadToolOutputTool::new(store.clone());
+        let mut paged = String::new();
+        let mut start = 0;
+        while start < text.len() {
+            let result = tool.execute(HashMap::from([
+                ("output_id".to_string(), json!(id)),
+                ("start_byte".to_string(), json!(start)),
+                ("max_bytes".to_string(), json!(1001)),
+            ])).await?;
+            assert_eq!(result.output["total_bytes"], text.len());
+            paged.push_str(result.output["text"].as_str().unwrap());
+            start = result.output["next_byte"].as_u64().unwrap() as usize;
+        }
+        assert_eq!(paged, text);
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Tools provided by WebAssembly modules (`*.wasm` in the plugin directory),
+//! run in wasmtime with a WASI context that grants nothing: no files,
+//! environment, arguments or sockets, and stdio goes nowhere.
+//!
+//! The guest speaks JSON through its exported `memory`, with the same
+//! messages as process plugins (see `tools::plugin`):
+//!
+//! - `alloc(len: i32) -> i32` reserves `len` bytes for the host to write into
+//! - `describe() -> i64` → `{"tools": [{"name", "description", "parameters", "read_only"?}]}`
+//! - `execute(ptr: i32, len: i32) -> i64` with `{"name", "arguments"}` → `{"output", "is_error"?}`
+//!
+//! Returned strings are packed as `ptr << 32 | len`. Every call runs in a
+//! fresh instance with a fuel budget, so a trap or an endless loop can't
+//! leave state behind or hang the turn.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::WasiCtxBuilder;
+use crate::brains::gemini::types::FunctionDeclaration;
+use crate::tools::plugin::{Describe, Executed, ToolDescription};
+use crate::tools::{ToolExecutor, ToolResult};
+
+/// Roughly how many wasm instructions one call may run.
+const FUEL: u64 = 500_000_000;
+
+/// A compiled plugin module shared by all tools it provides.
+pub struct WasmModule {
+    path: PathBuf,
+    engine: Engine,
+    module: Module,
+    linker: Linker<WasiP1Ctx>,
+}
+
+impl WasmModule {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, path)
+            .with_context(|| format!("Failed to compile {}", path.display()))?;
+        let mut linker = Linker::new(&engine);
+        preview1::add_to_linker_sync(&mut linker, |ctx| ctx)?;
+        Ok(Self { path: path.to_path_buf(), engine, module, linker })
+    }
+
+    /// Calls `export`, handing it `input` when given, and parses the JSON it returns.
+    fn call_blocking(&self, export: &str, input: Option<&[u8]>) -> Result<Value> {
+        let mut store = Store::new(&self.engine, WasiCtxBuilder::new().build_p1());
+        store.set_fuel(FUEL)?;
+        let instance = self.linker.instantiate(&mut store, &self.module)?;
+        let memory = instance.get_memory(&mut store, "memory").context("module exports no memory")?;
+        let packed = match input {
+            None => instance.get_typed_func::<(), i64>(&mut store, export)?.call(&mut store, ())?,
+            Some(bytes) => {
+                let len = i32::try_from(bytes.len()).context("input too large")?;
+                let ptr = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?.call(&mut store, len)?;
+                memory.write(&mut store, ptr as u32 as usize, bytes)?;
+                instance.get_typed_func::<(i32, i32), i64>(&mut store, export)?.call(&mut store, (ptr, len))?
+            }
+        };
+        let (ptr, len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize);
+        let mut out = vec![0; len];
+        memory.read(&store, ptr, &mut out)?;
+        serde_json::from_slice(&out).with_context(|| format!("{} returned invalid JSON", export))
+    }
+
+    /// Runs the call on a blocking thread: wasm execution never yields.
+    async fn call(self: &Arc<Self>, export: &'static str, input: Option<Vec<u8>>) -> Result<Value> {
+        let module = self.clone();
+        tokio::task::spawn_blocking(move || module.call_blocking(export, input.as_deref()))
+            .await?
+            .with_context(|| format!("WASM plugin {} failed", self.path.display()))
+    }
+}
+
+/// One tool exposed by a WASM module.
+pub struct WasmTool {
+    description: ToolDescription,
+    module: Arc<WasmModule>,
+}
+
+#[async_trait]
+impl ToolExecutor for WasmTool {
+    fn name(&self) -> String {
+        self.description.name.clone()
+    }
+
+    fn definition(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: self.description.name.clone(),
+            description: self.description.description.clone(),
+            parameters: self.description.parameters.clone(),
+        }
+    }
+
+    fn untrusted_output(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self, _args: &HashMap<String, Value>) -> bool {
+        self.description.read_only
+    }
+
+    async fn execute(&self, args: HashMap<String, Value>) -> Result<ToolResult> {
+        let input = json!({ "name": self.description.name, "arguments": args }).to_string();
+        let result = self.module.call("execute", Some(input.into_bytes())).await?;
+        let executed: Executed = serde_json::from_value(result)
+            .context("WASM plugin returned a malformed execute result")?;
+        Ok(ToolResult { output: executed.output, is_error: executed.is_error })
+    }
+}
+
+/// Compiles a module and asks it which tools it provides.
+pub async fn load(path: &Path) -> Result<Vec<WasmTool>> {
+    let module = Arc::new(WasmModule::load(path)?);
+    let described: Describe = serde_json::from_value(module.call("describe", None).await?)
+        .with_context(|| format!("WASM plugin {} returned a malformed describe result", path.display()))?;
+    Ok(described.tools.into_iter()
+        .map(|description| WasmTool { description, module: module.clone() })
+        .collect())
+}
+
+/// Loads every `*.wasm` file in `dir`. Modules that fail to load are skipped with a warning.
+pub async fn discover(dir: &Path) -> Vec<WasmTool> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    let mut tools = Vec::new();
+    for path in paths {
+        match load(&path).await {
+            Ok(loaded) => {
+                debug!(plugin = %path.display(), count = loaded.len(), "Loaded WASM plugin tools");
+                tools.extend(loaded);
+            }
+            Err(e) => warn!(plugin = %path.display(), "Skipping WASM plugin: {:#}", e),
+        }
+    }
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Describes one `echo` tool whose output is the call it received.
+    const ECHO: &str = r#"(module
+      (memory (export "memory") 1)
+      (global $next (mut i32) (i32.const 4096))
+      (data (i32.const 0) "{\"tools\":[{\"name\":\"echo\",\"description\":\"Echoes its call\",\"read_only\":true}]}")
+      (data (i32.const 512) "{\"output\":")
+      (func (export "alloc") (param $len i32) (result i32)
+        (global.get $next)
+        (global.set $next (i32.add (global.get $next) (local.get $len))))
+      (func (export "describe") (result i64)
+        (i64.const 76))
+      (func (export "execute") (param $ptr i32) (param $len i32) (result i64)
+        (memory.copy (i32.const 522) (local.get $ptr) (local.get $len))
+        (i32.store8 (i32.add (i32.const 522) (local.get $len)) (i32.const 125))
+        (i64.or
+          (i64.shl (i64.const 512) (i64.const 32))
+          (i64.extend_i32_u (i32.add (local.get $len) (i32.const 11))))))"#;
+
+    /// Describes `spin`, which never returns.
+    const SPIN: &str = r#"(module
+      (memory (export "memory") 1)
+      (data (i32.const 0) "{\"tools\":[{\"name\":\"spin\"}]}")
+      (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+      (func (export "describe") (result i64) (i64.const 27))
+      (func (export "execute") (param i32 i32) (result i64)
+        (loop $forever (br $forever))
+        (i64.const 0)))"#;
+
+    #[tokio::test]
+    async fn test_wasm_tools_are_described_and_executed_in_a_sandbox() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("chitti-wasm-plugins-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("echo.wasm"), wat::parse_str(ECHO)?)?;
+        std::fs::write(dir.join("spin.wasm"), wat::parse_str(SPIN)?)?;
+        std::fs::write(dir.join("broken.wasm"), "not wasm")?;
+        std::fs::write(dir.join("README"), "not a plugin")?;
+
+        let tools = discover(&dir).await;
+        let names: Vec<String> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(names, ["echo", "spin"]);
+        let echo = &tools[0];
+        assert_eq!(echo.definition().description, "Echoes its call");
+        assert!(echo.read_only(&HashMap::new()));
+
+        let result = echo.execute(HashMap::from([("text".to_string(), json!("hi"))])).await?;
+        assert_eq!(result.output, json!({ "name": "echo", "arguments": { "text": "hi" } }));
+        assert!(!result.is_error);
+
+        // Running out of fuel ends the call instead of hanging it.
+        assert!(tools[1].execute(HashMap::new()).await.is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// What Chitti's tools may do in a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Trust {
+    Full,
+    ReadOnly,
+    NoTools,
+}
+
+impl Trust {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "f" | "full" => Some(Trust::Full),
+            "r" | "read-only" | "readonly" => Some(Trust::ReadOnly),
+            "n" | "none" | "no-tools" => Some(Trust::NoTools),
+            _ => None,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Trust::Full => "full tools",
+            Trust::ReadOnly => "read-only tools",
+            Trust::NoTools => "no tools",
+        }
+    }
+}
+
+/// Trust decisions by directory, kept in `~/.chitti/trust.json`. A decision
+/// covers the directory and everything below it.
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    path: Option<PathBuf>,
+    dirs: BTreeMap<PathBuf, Trust>,
+}
+
+impl TrustStore {
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitti").join("trust.json"))
+    }
+
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let dirs = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self { path, dirs }
+    }
+
+    /// The decision for `dir`, or for its nearest ancestor with one.
+    pub fn get(&self, dir: &Path) -> Option<Trust> {
+        dir.ancestors().find_map(|d| self.dirs.get(d).copied())
+    }
+
+    pub fn set(&mut self, dir: &Path, trust: Trust) -> Result<()> {
+        self.dirs.insert(dir.to_path_buf(), trust);
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&self.dirs)?)
+                .with_context(|| format!("Failed to save {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// The trust level for the working directory, asking on the terminal the
+/// first time and remembering the answer. Without a terminal to ask on,
+/// an unknown directory gets read-only tools.
+pub fn for_current_dir() -> Result<Trust> {
+    let dir = std::env::current_dir()?.canonicalize()?;
+    let mut store = TrustStore::load(TrustStore::default_path());
+    if let Some(trust) = store.get(&dir) {
+        return Ok(trust);
+    }
+    if !std::io::stdin().is_terminal() {
+        tracing::warn!(dir = %dir.display(), "Untrusted directory and no terminal to ask on; using read-only tools");
+        return Ok(Trust::ReadOnly);
+    }
+    let trust = ask(&dir, &mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    store.set(&dir, trust)?;
+    Ok(trust)
+}
+
+fn ask(dir: &Path, input: &mut impl BufRead, output: &mut impl Write) -> Result<Trust> {
+    writeln!(output, "\x1b[33mChitti has not run in {} before.\x1b[0m", dir.display())?;
+    writeln!(output, "Its tools can run shell commands and change files here. Do you trust this directory?")?;
+    loop {
+        write!(output, "  [f]ull tools, [r]ead-only, [n]o tools: ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(Trust::ReadOnly);
+        }
+        if let Some(trust) = Trust::parse(&line) {
+            writeln!(output, "Using {} in {} (change it with `chitti trust full|read-only|none`)", trust.describe(), dir.display())?;
+            return Ok(trust);
+        }
+    }
+}
+
+/// `chitti trust [full|read-only|none]` shows or sets the working directory's trust.
+pub fn run_command(arg: Option<&str>) -> Result<()> {
+    let dir = std::env::current_dir()?.canonicalize()?;
+    let mut store = TrustStore::load(TrustStore::default_path());
+    match arg {
+        None => match store.get(&dir) {
+            Some(trust) => println!("{}: {}", dir.display(), trust.describe()),
+            None => println!("{}: not decided yet; Chitti will ask on its next start", dir.display()),
+        },
+        Some(level) => {
+            let trust = Trust::parse(level)
+                .ok_or_else(|| anyhow::anyhow!("Usage: chitti trust [full|read-only|none]"))?;
+            store.set(&dir, trust)?;
+            println!("{}: {}", dir.display(), trust.describe());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trust_store_and_prompt() -> Result<()> {
+        let file = std::env::temp_dir().join(format!("chitti-trust-{}.json", uuid::Uuid::new_v4()));
+        let mut store = TrustStore::load(Some(file.clone()));
+        assert_eq!(store.get(Path::new("/work/repo")), None);
+        store.set(Path::new("/work"), Trust::ReadOnly)?;
+        store.set(Path::new("/work/mine"), Trust::Full)?;
+
+        let store = TrustStore::load(Some(file.clone()));
+        assert_eq!(store.get(Path::new("/work/repo/src")), Some(Trust::ReadOnly));
+        assert_eq!(store.get(Path::new("/work/mine/app")), Some(Trust::Full));
+        assert_eq!(store.get(Path::new("/elsewhere")), None);
+        std::fs::remove_file(&file)?;
+
+        let mut out = Vec::new();
+        let trust = ask(Path::new("/work/repo"), &mut "maybe\nn\n".as_bytes(), &mut out)?;
+        assert_eq!(trust, Trust::NoTools);
+        assert_eq!(String::from_utf8_lossy(&out).matches("[f]ull tools").count(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+use crate::conductor::events::Usage;
+
+/// One completed turn, as delivered to `CHITTI_TURN_LOG`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnRecord {
+    pub timestamp: u64,
+    pub prompt: String,
+    pub response: String,
+    pub usage: Usage,
+    pub duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interaction_id: Option<String>,
+    /// `X-Request-ID`s of the turn's model requests, to quote in reports to the provider.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub request_ids: Vec<String>,
+}
+
+impl TurnRecord {
+    pub fn new(prompt: &str, response: &str, usage: Usage, elapsed: Duration, interaction_id: Option<String>, request_ids: Vec<String>) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+            usage,
+            duration_ms: elapsed.as_millis(),
+            interaction_id,
+            request_ids,
+        }
+    }
+}
+
+/// Where completed turns go so other tools (note importers, analytics) can
+/// follow along without a custom bridge.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TurnLog {
+    /// POSTs each record as JSON.
+    Webhook(String),
+    /// Appends each record as one JSON line.
+    Jsonl(PathBuf),
+}
+
+impl TurnLog {
+    /// An `http(s)://` URL is a webhook; anything else is a file path.
+    pub fn parse(target: &str) -> Option<Self> {
+        let target = target.trim();
+        if target.is_empty() {
+            None
+        } else if target.starts_with("http://") || target.starts_with("https://") {
+            Some(TurnLog::Webhook(target.to_string()))
+        } else {
+            Some(TurnLog::Jsonl(PathBuf::from(target)))
+        }
+    }
+
+    /// Delivers `record`. Webhook posts run in the background so a slow
+    /// endpoint never holds up the next turn; failures are only logged.
+    pub fn record(&self, record: &TurnRecord) {
+        match self {
+            TurnLog::Webhook(url) => {
+                let (url, body) = (url.clone(), record.clone());
+                tokio::spawn(async move {
+                    let result = reqwest::Client::new()
+                        .post(&url)
+                        .timeout(Duration::from_secs(10))
+                        .json(&body)
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status());
+                    if let Err(e) = result {
+                        warn!("Turn log webhook failed: {}", e);
+                    }
+                });
+            }
+            TurnLog::Jsonl(path) => {
+                if let Err(e) = append_line(path, record) {
+                    warn!("Failed to append turn to {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+fn append_line(path: &PathBuf, record: &TurnRecord) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_log_appends_jsonl() -> Result<()> {
+        assert_eq!(TurnLog::parse("https://hooks.example.com/chitti"), Some(TurnLog::Webhook("https://hooks.example.com/chitti".into())));
+        assert_eq!(TurnLog::parse("  "), None);
+
+        let path = std::env::temp_dir().join(format!("chitti-turns-{}.jsonl", uuid::Uuid::new_v4()));
+        let log = TurnLog::parse(path.to_str().unwrap()).unwrap();
+        let usage = Usage { input_tokens: 12, output_tokens: 5, thought_tokens: 0 };
+        log.record(&TurnRecord::new("hi", "hello", usage, Duration::from_millis(250), Some("int-1".into()), vec!["9f0c".into()]));
+        log.record(&TurnRecord::new("again", "sure", Usage::default(), Duration::from_millis(10), None, Vec::new()));
+
+        let text = std::fs::read_to_string(&path)?;
+        let lines: Vec<serde_json::Value> = text.lines().map(serde_json::from_str).collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["prompt"], "hi");
+        assert_eq!(lines[0]["usage"]["input_tokens"], 12);
+        assert_eq!(lines[0]["duration_ms"], 250);
+        assert_eq!(lines[0]["request_ids"][0], "9f0c");
+        assert!(lines[1].get("interaction_id").is_none());
+        assert!(lines[1].get("request_ids").is_none());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::path::{Path, PathBuf};
+
+/// Prefix of every encrypted file, so plaintext and ciphertext can coexist
+/// while a directory is being locked or unlocked.
+const MAGIC: &[u8] = b"CHITTI-VAULT1\n";
+const NONCE_LEN: usize = 12;
+const KEYCHAIN_SERVICE: &str = "chitti";
+const KEYCHAIN_USER: &str = "vault-key";
+const KEYCHAIN_API_KEY: &str = "gemini-api-key";
+
+/// Encrypts files Chitti keeps on disk with ChaCha20-Poly1305. The key lives
+/// in the OS keychain (macOS Keychain, Windows Credential Manager, Linux
+/// kernel keyring) and is created on first use.
+pub struct Vault {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Vault {
+    pub fn from_key(key: &[u8; 32]) -> Self {
+        Self { cipher: ChaCha20Poly1305::new(Key::from_slice(key)) }
+    }
+
+    /// Opens the vault with the keychain key, generating and storing one if needed.
+    pub fn open() -> Result<Self> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER)
+            .context("Failed to access the OS keychain")?;
+        let encoded = match entry.get_password() {
+            Ok(encoded) => encoded,
+            Err(keyring::Error::NoEntry) => {
+                let encoded = STANDARD.encode(ChaCha20Poly1305::generate_key(&mut OsRng));
+                entry.set_password(&encoded).context("Failed to store the vault key in the OS keychain")?;
+                encoded
+            }
+            Err(e) => return Err(e).context("Failed to read the vault key from the OS keychain"),
+        };
+        let key: [u8; 32] = STANDARD.decode(encoded.trim())?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("The vault key in the OS keychain has the wrong length"))?;
+        Ok(Self::from_key(&key))
+    }
+
+    pub fn is_encrypted(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher.encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+        Ok([MAGIC, nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let body = data.strip_prefix(MAGIC).context("Not an encrypted file")?;
+        anyhow::ensure!(body.len() > NONCE_LEN, "Encrypted file is truncated");
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Decryption failed; the file is damaged or was encrypted with another key"))
+    }
+
+    /// Encrypts `path`, or every plaintext file under it; returns how many were changed.
+    pub fn lock(&self, path: &Path) -> Result<usize> {
+        self.rewrite(path, &|data| !Self::is_encrypted(data), &|data| self.encrypt(data))
+    }
+
+    /// Decrypts `path`, or every encrypted file under it; returns how many were changed.
+    pub fn unlock(&self, path: &Path) -> Result<usize> {
+        self.rewrite(path, &Self::is_encrypted, &|data| self.decrypt(data))
+    }
+
+    /// Rewrites the file at `path`, or the files under it (recursively;
+    /// symlinks aren't followed), that `applies` picks.
+    fn rewrite(
+        &self,
+        path: &Path,
+        applies: &dyn Fn(&[u8]) -> bool,
+        transform: &dyn Fn(&[u8]) -> Result<Vec<u8>>,
+    ) -> Result<usize> {
+        let Ok(meta) = std::fs::symlink_metadata(path) else {
+            return Ok(0);
+        };
+        if meta.is_dir() {
+            let mut changed = 0;
+            for entry in std::fs::read_dir(path)?.filter_map(|e| e.ok()) {
+                changed += self.rewrite(&entry.path(), applies, transform)?;
+            }
+            return Ok(changed);
+        }
+        if !meta.is_file() {
+            return Ok(0);
+        }
+        let data = std::fs::read(path)?;
+        if !applies(&data) {
+            return Ok(0);
+        }
+        let output = transform(&data).with_context(|| format!("Failed on {}", path.display()))?;
+        // Write beside the original and rename so a crash never leaves half a file.
+        let tmp = path.with_extension("vault-tmp");
+        std::fs::write(&tmp, output)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(1)
+    }
+}
+
+/// Writes `data` to `path`, encrypted when a vault is given, creating the
+/// parent directory on the way.
+pub fn write(vault: Option<&Vault>, path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let data = match vault {
+        Some(vault) => vault.encrypt(data)?,
+        None => data.to_vec(),
+    };
+    std::fs::write(path, data).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Reads a file written by `write`; encrypted files need the vault.
+pub fn read(vault: Option<&Vault>, path: &Path) -> Result<Vec<u8>> {
+    let data = std::fs::read(path)?;
+    if !Vault::is_encrypted(&data) {
+        return Ok(data);
+    }
+    vault.with_context(|| format!("{} is encrypted; set CHITTI_VAULT=true to read it", path.display()))?
+        .decrypt(&data)
+}
+
+/// Saves the Gemini API key in the OS keychain, next to the vault key.
+pub fn store_api_key(api_key: &str) -> Result<()> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_API_KEY)
+        .and_then(|entry| entry.set_password(api_key))
+        .context("Failed to store the API key in the OS keychain")
+}
+
+/// The Gemini API key saved by the setup wizard, if the keychain has one.
+pub fn stored_api_key() -> Option<String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_API_KEY)
+        .and_then(|entry| entry.get_password())
+        .ok()
+        .filter(|key| !key.trim().is_empty())
+}
+
+/// Where conversation content is kept: the cached responses and saved
+/// sessions directories, and the knowledge graph file.
+pub fn data_dirs() -> Vec<PathBuf> {
+    [crate::brains::cache::CachedBrain::default_dir(), crate::conductor::artifacts::root()].into_iter()
+        .chain(crate::tools::graph::GraphStore::default_path())
+        .collect()
+}
+
+/// `chitti vault lock|unlock`.
+pub fn run_command(action: Option<&str>) -> Result<()> {
+    let vault = Vault::open()?;
+    for path in data_dirs() {
+        let (verb, count) = match action {
+            Some("lock") => ("Encrypted", vault.lock(&path)?),
+            Some("unlock") => ("Decrypted", vault.unlock(&path)?),
+            _ => anyhow::bail!("Usage: chitti vault <lock|unlock>"),
+        };
+        println!("{} {} file(s) in {}", verb, count, path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_directory_lock() -> Result<()> {
+        let vault = Vault::from_key(&[7u8; 32]);
+        let sealed = vault.encrypt(b"secret conversation")?;
+        assert!(Vault::is_encrypted(&sealed));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(vault.decrypt(&sealed)?, b"secret conversation");
+        assert!(Vault::from_key(&[8u8; 32]).decrypt(&sealed).is_err());
+
+        let dir = std::env::temp_dir().join(format!("chitti-vault-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("a.json"), "{\"a\":1}")?;
+        std::fs::create_dir_all(dir.join("session"))?;
+        std::fs::write(dir.join("session").join("session.json"), "{}")?;
+        assert_eq!(vault.lock(&dir)?, 2);
+        assert_eq!(vault.lock(&dir)?, 0);
+        assert!(Vault::is_encrypted(&std::fs::read(dir.join("a.json"))?));
+        assert!(Vault::is_encrypted(&std::fs::read(dir.join("session").join("session.json"))?));
+        assert_eq!(vault.unlock(&dir)?, 2);
+        assert_eq!(std::fs::read_to_string(dir.join("a.json"))?, "{\"a\":1}");
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const MAX_SUGGESTIONS: usize = 5;
+const MAX_DEPTH: usize = 6;
+const MAX_FILES: usize = 20_000;
+/// The index is rebuilt after this long, so new files become resolvable.
+const INDEX_TTL: Duration = Duration::from_secs(30);
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "vendor", "dist", "build", "__pycache__"];
+
+/// A path the resolver found, possibly after correcting a typo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub path: PathBuf,
+    /// Set when `path` differs from what was asked for.
+    pub corrected: bool,
+}
+
+/// A path that doesn't exist, with the closest existing ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFound {
+    pub path: String,
+    pub suggestions: Vec<PathBuf>,
+}
+
+impl NotFound {
+    /// The error payload returned to the model.
+    pub fn to_json(&self) -> Value {
+        let suggestions: Vec<String> = self.suggestions.iter().map(|p| p.display().to_string()).collect();
+        json!({
+            "error": format!("{} does not exist", self.path),
+            "did_you_mean": suggestions,
+        })
+    }
+}
+
+/// Resolves the paths tools receive against the working tree: `~` and
+/// relative paths as given, misspelled ones by fuzzy matching against an
+/// index of the files under the root.
+pub struct PathResolver {
+    root: PathBuf,
+    index: Mutex<Option<(Instant, Arc<Vec<PathBuf>>)>>,
+}
+
+impl PathResolver {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, index: Mutex::new(None) }
+    }
+
+    /// A resolver for the current working directory.
+    pub fn current() -> Self {
+        Self::new(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+    }
+
+    /// The existing path `input` means. A missing path is corrected only
+    /// when exactly one indexed file is a close match.
+    pub fn resolve(&self, input: &str) -> Result<Resolved, NotFound> {
+        let path = self.absolute(input);
+        if path.exists() {
+            return Ok(Resolved { path, corrected: false });
+        }
+        let suggestions = self.suggest(input);
+        if let [only] = suggestions.as_slice() {
+            let target = file_name(input);
+            if distance(&file_name(&only.to_string_lossy()), &target) <= 1 {
+                return Ok(Resolved { path: self.absolute(&only.to_string_lossy()), corrected: true });
+            }
+        }
+        Err(NotFound { path: input.to_string(), suggestions })
+    }
+
+    /// Existing paths close to `input`: similarly named siblings in the
+    /// nearest existing directory, then indexed files with a similar path or
+    /// the same name elsewhere in the tree.
+    pub fn suggest(&self, input: &str) -> Vec<PathBuf> {
+        let path = self.absolute(input);
+        let name = file_name(input);
+        if name.is_empty() {
+            return Vec::new();
+        }
+        let mut ranked: Vec<(usize, PathBuf)> = Vec::new();
+        if let Some(dir) = path.parent().filter(|d| d.is_dir()) {
+            for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+                let d = distance(&entry.file_name().to_string_lossy().to_lowercase(), &name);
+                if d <= threshold(&name) {
+                    ranked.push((d, self.display(&entry.path())));
+                }
+            }
+        }
+        let relative = self.display(&path).to_string_lossy().to_lowercase();
+        for file in self.files().iter() {
+            let d = distance(&file_name(&file.to_string_lossy()), &name)
+                .min(distance(&file.to_string_lossy().to_lowercase(), &relative));
+            if d <= threshold(&name) {
+                ranked.push((d, file.clone()));
+            }
+        }
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.as_os_str().len().cmp(&b.1.as_os_str().len())));
+        let mut out: Vec<PathBuf> = Vec::new();
+        for (_, p) in ranked {
+            if !out.contains(&p) {
+                out.push(p);
+            }
+        }
+        out.truncate(MAX_SUGGESTIONS);
+        out
+    }
+
+    fn absolute(&self, input: &str) -> PathBuf {
+        let path = crate::tools::env::expand_home(Path::new(input));
+        if path.is_absolute() { path } else { self.root.join(path) }
+    }
+
+    /// Paths under the root are shown relative to it, as the model usually
+    /// writes them.
+    fn display(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.root).map(Path::to_path_buf).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn files(&self) -> Arc<Vec<PathBuf>> {
+        let mut index = self.index.lock().unwrap();
+        match index.as_ref() {
+            Some((built, files)) if built.elapsed() < INDEX_TTL => files.clone(),
+            _ => {
+                let files = Arc::new(self.build_index());
+                *index = Some((Instant::now(), files.clone()));
+                files
+            }
+        }
+    }
+
+    fn build_index(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut stack = vec![(self.root.clone(), 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+                if files.len() >= MAX_FILES {
+                    return files;
+                }
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') {
+                    continue;
+                }
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    if depth < MAX_DEPTH && !SKIPPED_DIRS.contains(&name.as_str()) {
+                        stack.push((path, depth + 1));
+                    }
+                } else {
+                    files.push(self.display(&path));
+                }
+            }
+        }
+        files
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+fn threshold(name: &str) -> usize {
+    (name.chars().count() / 3).max(1)
+}
+
+/// Levenshtein distance.
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_resolver_corrects_and_suggests() {
+        let root = std::env::temp_dir().join(format!("chitti-workspace-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/conductor")).unwrap();
+        std::fs::write(root.join("src/conductor/mod.rs"), "").unwrap();
+        std::fs::write(root.join("src/config.rs"), "").unwrap();
+        std::fs::write(root.join("src/config.ts"), "").unwrap();
+        let resolver = PathResolver::new(root.clone());
+
+        assert_eq!(resolver.resolve("src/config.rs"), Ok(Resolved { path: root.join("src/config.rs"), corrected: false }));
+        let fixed = resolver.resolve("src/conductr/mod.rs").unwrap();
+        assert_eq!(fixed, Resolved { path: root.join("src/conductor/mod.rs"), corrected: true });
+        let missing = resolver.resolve("src/confi.rs").unwrap_err();
+        assert_eq!(missing.suggestions, vec![PathBuf::from("src/config.rs"), PathBuf::from("src/config.ts")]);
+        assert_eq!(missing.to_json()["did_you_mean"][0], "src/config.rs");
+        assert!(resolver.resolve("nothing/like/this.zip").unwrap_err().suggestions.is_empty());
+        assert_eq!(distance("kitten", "sitting"), 3);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -76,6 +76,7 @@ async fn test_exhaustive_checklist() -> anyhow::Result<()> {
             uri: Some(file.uri),
             data: None,
             mime_type: "text/plain".to_string(),
+            ..Default::default()
         })
     ])).send().await?;
     assert!(!r_file.outputs.is_empty());